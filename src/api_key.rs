@@ -0,0 +1,51 @@
+use std::fmt;
+
+/// A render api key. `Debug` prints `[redacted]` so the key can't end up in panics or logs, and
+/// there's no `Display`, [`ApiKey::expose`] is the only way to the key itself.
+#[derive(Clone, PartialEq, Eq)]
+pub struct ApiKey(String);
+
+impl ApiKey {
+    pub fn new(key: impl Into<String>) -> Self {
+        ApiKey(key.into())
+    }
+
+    /// The key itself, only for sending it to render or saving it somewhere safe
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Debug for ApiKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ApiKey([redacted])")
+    }
+}
+
+impl From<String> for ApiKey {
+    fn from(key: String) -> Self {
+        ApiKey(key)
+    }
+}
+
+impl From<&str> for ApiKey {
+    fn from(key: &str) -> Self {
+        ApiKey(key.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn debug_is_redacted() {
+        let key = ApiKey::new("rnd_secret");
+        assert_eq!(format!("{:?}", key), "ApiKey([redacted])");
+        assert_eq!(
+            format!("{:?}", Some(key.clone())),
+            "Some(ApiKey([redacted]))"
+        );
+        assert_eq!(key.expose(), "rnd_secret");
+    }
+}
@@ -0,0 +1,101 @@
+use crate::client::RenderClient;
+use crate::error::RenderError;
+use serde::{Deserialize, Serialize};
+
+/// Scale a service between `min` and `max` instances to keep cpu and memory around their
+/// targets
+#[derive(PartialEq, Deserialize, Serialize, Debug, Clone)]
+pub struct Autoscaling {
+    pub enabled: bool,
+    pub min: u32,
+    pub max: u32,
+    pub criteria: AutoscalingCriteria,
+}
+
+#[derive(PartialEq, Deserialize, Serialize, Debug, Clone, Default)]
+pub struct AutoscalingCriteria {
+    pub cpu: AutoscalingTarget,
+    pub memory: AutoscalingTarget,
+}
+
+/// Average utilization to scale towards
+#[derive(PartialEq, Deserialize, Serialize, Debug, Clone, Default)]
+pub struct AutoscalingTarget {
+    pub enabled: bool,
+    pub percentage: u32,
+}
+
+/// Just enough of a service to read its autoscaling settings
+#[derive(Deserialize)]
+struct ServiceWithDetails {
+    #[serde(rename = "serviceDetails", default)]
+    service_details: Option<ServiceDetails>,
+}
+
+#[derive(Deserialize)]
+struct ServiceDetails {
+    #[serde(default)]
+    autoscaling: Option<Autoscaling>,
+}
+
+impl RenderClient {
+    /// A service's autoscaling settings, `None` if it's never been set up
+    pub async fn get_autoscaling(
+        &self,
+        service_id: &str,
+    ) -> Result<Option<Autoscaling>, RenderError> {
+        let service: ServiceWithDetails =
+            self.get(&format!("/services/{}", service_id), &[]).await?;
+        Ok(service
+            .service_details
+            .and_then(|details| details.autoscaling))
+    }
+
+    pub async fn set_autoscaling(
+        &self,
+        service_id: &str,
+        autoscaling: &Autoscaling,
+    ) -> Result<Autoscaling, RenderError> {
+        let request = self
+            .client
+            .put(self.url(&format!("/services/{}/autoscaling", service_id)))
+            .json(autoscaling);
+        self.send(request).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_autoscaling() {
+        let sample = r#"
+            {
+                "id": "srv-1",
+                "serviceDetails": {
+                    "numInstances": 2,
+                    "autoscaling": {
+                        "enabled": true,
+                        "min": 1,
+                        "max": 3,
+                        "criteria": {
+                            "cpu": { "enabled": true, "percentage": 70 },
+                            "memory": { "enabled": false, "percentage": 80 }
+                        }
+                    }
+                }
+            }
+        "#;
+        let service: ServiceWithDetails = serde_json::from_str(sample).unwrap();
+        let autoscaling = service.service_details.unwrap().autoscaling.unwrap();
+        assert_eq!(autoscaling.max, 3);
+        assert!(autoscaling.criteria.cpu.enabled);
+        assert!(!autoscaling.criteria.memory.enabled);
+
+        let static_site: ServiceWithDetails =
+            serde_json::from_str(r#"{ "serviceDetails": { "url": "https://example.com" } }"#)
+                .unwrap();
+        assert!(static_site.service_details.unwrap().autoscaling.is_none());
+    }
+}
@@ -0,0 +1,745 @@
+use render_deploy::{Disk, EnvVar, RenderError, Service, ServiceSettings, ServiceType};
+use serde::{Deserialize, Serialize};
+use serde_yaml::{Mapping, Value};
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+/// Where `blueprint` commands look when they aren't given a file
+pub const DEFAULT_BLUEPRINT_FILE: &str = "render.yaml";
+
+const TOP_LEVEL_FIELDS: &[&str] = &[
+    "services",
+    "databases",
+    "envVarGroups",
+    "projects",
+    "previews",
+    "previewsEnabled",
+    "previewsExpireAfterDays",
+    "version",
+];
+const SERVICE_FIELDS: &[&str] = &[
+    "type",
+    "name",
+    "runtime",
+    "env",
+    "plan",
+    "region",
+    "repo",
+    "branch",
+    "rootDir",
+    "buildCommand",
+    "startCommand",
+    "preDeployCommand",
+    "dockerfilePath",
+    "dockerContext",
+    "dockerCommand",
+    "image",
+    "registryCredential",
+    "healthCheckPath",
+    "numInstances",
+    "scaling",
+    "autoDeploy",
+    "autoDeployTrigger",
+    "buildFilter",
+    "envVars",
+    "disk",
+    "domains",
+    "schedule",
+    "staticPublishPath",
+    "headers",
+    "routes",
+    "pullRequestPreviewsEnabled",
+    "previews",
+    "initialDeployHook",
+    "maxShutdownDelaySeconds",
+    "ipAllowList",
+    "maxmemoryPolicy",
+];
+const SERVICE_TYPES: &[&str] = &["web", "pserv", "worker", "cron", "keyvalue", "redis"];
+const RUNTIMES: &[&str] = &[
+    "docker", "elixir", "go", "image", "node", "python", "ruby", "rust", "static",
+];
+const REGIONS: &[&str] = &["oregon", "ohio", "virginia", "frankfurt", "singapore"];
+const DATABASE_FIELDS: &[&str] = &[
+    "name",
+    "databaseName",
+    "user",
+    "plan",
+    "region",
+    "postgresMajorVersion",
+    "diskSizeGB",
+    "ipAllowList",
+    "readReplicas",
+    "highAvailability",
+    "previewPlan",
+    "previewDiskSizeGB",
+];
+const ENV_VAR_GROUP_FIELDS: &[&str] = &["name", "envVars"];
+const ENV_VAR_FIELDS: &[&str] = &[
+    "key",
+    "value",
+    "generateValue",
+    "sync",
+    "fromDatabase",
+    "fromService",
+    "fromGroup",
+];
+/// An env var needs exactly one of these, `fromGroup` entries don't have a key
+const ENV_VAR_SOURCES: &[&str] = &[
+    "value",
+    "generateValue",
+    "sync",
+    "fromDatabase",
+    "fromService",
+];
+
+/// Something wrong with a blueprint
+#[derive(Serialize, Debug, PartialEq)]
+pub struct Problem {
+    /// 1 based, `None` when we can't tell where it is
+    pub line: Option<usize>,
+    /// where in the document, eg `services[1].plan`
+    pub path: String,
+    pub message: String,
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.path.as_str() {
+            "" => write!(f, "{}", self.message),
+            path => write!(f, "{}: {}", path, self.message),
+        }
+    }
+}
+
+/// The parts of a Blueprint we compare with live services or export them as, `lint` is what
+/// catches mistakes
+#[derive(Deserialize, Serialize, Debug, Default)]
+pub struct Blueprint {
+    #[serde(default)]
+    pub services: Vec<BlueprintService>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct BlueprintService {
+    #[serde(rename = "type")]
+    pub service_type: String,
+    pub name: String,
+    /// `env` is what older Blueprints call it
+    #[serde(alias = "env", skip_serializing_if = "Option::is_none")]
+    pub runtime: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub repo: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub branch: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub plan: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub region: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub build_command: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_command: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub static_publish_path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub schedule: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub num_instances: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auto_deploy: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub disk: Option<BlueprintDisk>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub env_vars: Vec<BlueprintEnvVar>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Default)]
+pub struct BlueprintEnvVar {
+    /// `fromGroup` entries don't have one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+    /// `false` asks for the value in the dashboard instead of keeping it in the file
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sync: Option<bool>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct BlueprintDisk {
+    pub name: String,
+    pub mount_path: String,
+    #[serde(rename = "sizeGB", skip_serializing_if = "Option::is_none")]
+    pub size_gb: Option<u32>,
+}
+
+impl BlueprintService {
+    /// What the api calls it, `None` for key value stores which aren't services there
+    pub fn api_type(&self) -> Option<ServiceType> {
+        match self.service_type.as_str() {
+            "web" if self.runtime.as_deref() == Some("static") => Some(ServiceType::StaticSite),
+            "web" => Some(ServiceType::WebService),
+            "pserv" => Some(ServiceType::PrivateService),
+            "worker" => Some(ServiceType::BackgroundWorker),
+            "cron" => Some(ServiceType::CronJob),
+            _ => None,
+        }
+    }
+
+    /// A live service as a Blueprint would declare it. Env var values stay out of it, they're
+    /// `sync: false` so render asks for them.
+    pub fn from_live(
+        service: &Service,
+        settings: ServiceSettings,
+        env_vars: &[EnvVar],
+        disk: Option<Disk>,
+    ) -> BlueprintService {
+        let (service_type, runtime) = match service.service_type {
+            ServiceType::StaticSite => ("web", Some("static".to_string())),
+            ServiceType::WebService => ("web", settings.runtime),
+            ServiceType::PrivateService => ("pserv", settings.runtime),
+            ServiceType::BackgroundWorker => ("worker", settings.runtime),
+            ServiceType::CronJob => ("cron", settings.runtime),
+            ServiceType::Unknown => ("unknown", settings.runtime),
+        };
+        BlueprintService {
+            service_type: service_type.to_string(),
+            name: service.name.clone(),
+            runtime,
+            repo: service.repo.clone(),
+            branch: settings.branch,
+            plan: settings.plan,
+            region: settings.region,
+            build_command: settings.build_command,
+            start_command: settings.start_command,
+            static_publish_path: settings.publish_path,
+            schedule: settings.schedule,
+            num_instances: settings.num_instances,
+            auto_deploy: Some(service.auto_deploy),
+            disk: disk.map(|disk| BlueprintDisk {
+                name: disk.name,
+                mount_path: disk.mount_path,
+                size_gb: Some(disk.size_gb),
+            }),
+            env_vars: env_vars
+                .iter()
+                .map(|env_var| BlueprintEnvVar {
+                    key: Some(env_var.key.clone()),
+                    sync: Some(false),
+                })
+                .collect(),
+        }
+    }
+
+    pub fn settings(&self) -> ServiceSettings {
+        ServiceSettings {
+            branch: self.branch.clone(),
+            build_command: self.build_command.clone(),
+            start_command: self.start_command.clone(),
+            plan: self.plan.clone(),
+            ..Default::default()
+        }
+    }
+
+    pub fn env_keys(&self) -> BTreeSet<&str> {
+        self.env_vars
+            .iter()
+            .filter_map(|env_var| env_var.key.as_deref())
+            .collect()
+    }
+}
+
+/// Read a Blueprint file
+pub fn load(path: &Path) -> Result<Blueprint, RenderError> {
+    let contents = fs::read_to_string(path)
+        .map_err(|e| RenderError::Config(format!("Unable to read {}: {}", path.display(), e)))?;
+    serde_yaml::from_str(&contents).map_err(|e| {
+        RenderError::Config(format!(
+            "Invalid Blueprint {}: {}, `blueprint lint` shows what's wrong",
+            path.display(),
+            e
+        ))
+    })
+}
+
+/// Check a render.yaml against what render accepts, an empty list means it looks fine
+pub fn lint(source: &str) -> Vec<Problem> {
+    let document: Value = match serde_yaml::from_str(source) {
+        Ok(document) => document,
+        Err(e) => {
+            return vec![Problem {
+                line: e.location().map(|location| location.line()),
+                path: String::new(),
+                message: e.to_string(),
+            }]
+        }
+    };
+    let mut linter = Linter {
+        lines: key_lines(source),
+        problems: vec![],
+    };
+    linter.document(&document);
+    linter.problems
+}
+
+struct Linter {
+    lines: HashMap<String, usize>,
+    problems: Vec<Problem>,
+}
+
+impl Linter {
+    fn problem(&mut self, path: &str, message: String) {
+        // a missing field is reported where its parent starts
+        let line = self.lines.get(path).copied().or_else(|| {
+            let parent = path.rsplit_once('.').map(|(parent, _)| parent)?;
+            self.lines.get(parent).copied()
+        });
+        self.problems.push(Problem {
+            line,
+            path: path.to_string(),
+            message,
+        });
+    }
+
+    /// The mapping at `path`, reporting fields that aren't in `fields`
+    fn mapping<'a>(
+        &mut self,
+        path: &str,
+        value: &'a Value,
+        fields: &[&str],
+    ) -> Option<&'a Mapping> {
+        let Some(mapping) = value.as_mapping() else {
+            self.problem(path, "should be a mapping of fields".into());
+            return None;
+        };
+        for key in mapping.keys() {
+            match key.as_str() {
+                Some(key) if fields.contains(&key) => {}
+                Some(key) => self.problem(&join(path, key), format!("unknown field {}", key)),
+                None => self.problem(path, format!("field names should be strings {:?}", key)),
+            }
+        }
+        Some(mapping)
+    }
+
+    /// The list at `path`, if it's there
+    fn sequence<'a>(&mut self, path: &str, value: Option<&'a Value>) -> &'a [Value] {
+        match value {
+            None | Some(Value::Null) => &[],
+            Some(Value::Sequence(items)) => items,
+            Some(_) => {
+                self.problem(path, "should be a list".into());
+                &[]
+            }
+        }
+    }
+
+    fn required_string<'a>(
+        &mut self,
+        path: &str,
+        mapping: &'a Mapping,
+        field: &str,
+    ) -> Option<&'a str> {
+        match mapping.get(field) {
+            Some(Value::String(value)) => Some(value),
+            Some(_) => {
+                self.problem(&join(path, field), "should be a string".into());
+                None
+            }
+            None => {
+                self.problem(&join(path, field), format!("{} is required", field));
+                None
+            }
+        }
+    }
+
+    fn one_of(&mut self, path: &str, mapping: &Mapping, field: &str, allowed: &[&str]) {
+        match mapping.get(field) {
+            None => {}
+            Some(Value::String(value)) if allowed.contains(&value.as_str()) => {}
+            Some(value) => self.problem(
+                &join(path, field),
+                format!(
+                    "{} isn't a {}, expected one of {}",
+                    describe(value),
+                    field,
+                    allowed.join(", ")
+                ),
+            ),
+        }
+    }
+
+    fn document(&mut self, document: &Value) {
+        let Some(root) = self.mapping("", document, TOP_LEVEL_FIELDS) else {
+            return;
+        };
+        let mut names = BTreeSet::new();
+        for (i, service) in self
+            .sequence("services", root.get("services"))
+            .iter()
+            .enumerate()
+        {
+            let path = format!("services[{}]", i);
+            if let Some(name) = self.service(&path, service) {
+                if !names.insert(name) {
+                    self.problem(
+                        &join(&path, "name"),
+                        format!("{} is used by more than one service", name),
+                    );
+                }
+            }
+        }
+        for (i, database) in self
+            .sequence("databases", root.get("databases"))
+            .iter()
+            .enumerate()
+        {
+            let path = format!("databases[{}]", i);
+            if let Some(database) = self.mapping(&path, database, DATABASE_FIELDS) {
+                self.required_string(&path, database, "name");
+                self.one_of(&path, database, "region", REGIONS);
+            }
+        }
+        for (i, group) in self
+            .sequence("envVarGroups", root.get("envVarGroups"))
+            .iter()
+            .enumerate()
+        {
+            let path = format!("envVarGroups[{}]", i);
+            if let Some(group) = self.mapping(&path, group, ENV_VAR_GROUP_FIELDS) {
+                self.required_string(&path, group, "name");
+                self.env_vars(&join(&path, "envVars"), group.get("envVars"));
+            }
+        }
+    }
+
+    /// Check a service, returning its name
+    fn service<'a>(&mut self, path: &str, service: &'a Value) -> Option<&'a str> {
+        let service = self.mapping(path, service, SERVICE_FIELDS)?;
+        let name = self.required_string(path, service, "name");
+        let service_type = self.required_string(path, service, "type");
+        self.one_of(path, service, "type", SERVICE_TYPES);
+        self.one_of(path, service, "runtime", RUNTIMES);
+        self.one_of(path, service, "env", RUNTIMES);
+        self.one_of(path, service, "region", REGIONS);
+
+        let runtime = service
+            .get("runtime")
+            .or_else(|| service.get("env"))
+            .and_then(Value::as_str);
+        let needs_runtime = matches!(service_type, Some("web" | "pserv" | "worker" | "cron"));
+        if needs_runtime && runtime.is_none() {
+            self.problem(&join(path, "runtime"), "runtime is required".into());
+        }
+        if service_type == Some("cron") && !service.contains_key("schedule") {
+            self.problem(&join(path, "schedule"), "cron jobs need a schedule".into());
+        }
+        if service.contains_key("staticPublishPath") && runtime != Some("static") {
+            self.problem(
+                &join(path, "staticPublishPath"),
+                "only static sites (runtime: static) publish a path".into(),
+            );
+        }
+        if let Some(instances) = service.get("numInstances") {
+            if instances.as_u64().is_none_or(|instances| instances < 1) {
+                self.problem(
+                    &join(path, "numInstances"),
+                    "should be a whole number, at least 1".into(),
+                );
+            }
+        }
+        if let Some(auto_deploy) = service.get("autoDeploy") {
+            if !auto_deploy.is_bool() {
+                self.problem(&join(path, "autoDeploy"), "should be true or false".into());
+            }
+        }
+        self.env_vars(&join(path, "envVars"), service.get("envVars"));
+        name
+    }
+
+    fn env_vars(&mut self, path: &str, env_vars: Option<&Value>) {
+        let mut keys = BTreeSet::new();
+        for (i, env_var) in self.sequence(path, env_vars).iter().enumerate() {
+            let path = format!("{}[{}]", path, i);
+            let Some(env_var) = self.mapping(&path, env_var, ENV_VAR_FIELDS) else {
+                continue;
+            };
+            if env_var.contains_key("fromGroup") {
+                continue;
+            }
+            if let Some(key) = self.required_string(&path, env_var, "key") {
+                if !keys.insert(key) {
+                    self.problem(
+                        &join(&path, "key"),
+                        format!("{} is set more than once", key),
+                    );
+                }
+            }
+            let sources = ENV_VAR_SOURCES
+                .iter()
+                .filter(|source| env_var.contains_key(**source))
+                .count();
+            if sources != 1 {
+                self.problem(
+                    &path,
+                    format!("needs exactly one of {}", ENV_VAR_SOURCES.join(", ")),
+                );
+            }
+        }
+    }
+}
+
+fn join(path: &str, field: &str) -> String {
+    if path.is_empty() {
+        field.to_string()
+    } else {
+        format!("{}.{}", path, field)
+    }
+}
+
+fn describe(value: &Value) -> String {
+    match value {
+        Value::String(value) => value.clone(),
+        other => serde_yaml::to_string(other)
+            .map(|yaml| yaml.trim().to_string())
+            .unwrap_or_default(),
+    }
+}
+
+/// The line each key and list item of a block style yaml document starts on, by paths like
+/// `services[1].plan`. Flow style (`{...}`) isn't looked inside, which is fine for blueprints.
+pub fn key_lines(source: &str) -> HashMap<String, usize> {
+    struct Open {
+        /// column of the key or `-` that opened it
+        column: usize,
+        path: String,
+        item: bool,
+    }
+    let mut lines = HashMap::new();
+    let mut open: Vec<Open> = vec![];
+    let mut items: HashMap<String, usize> = HashMap::new();
+    // the column a block scalar (`|` or `>`) started at, its lines aren't keys
+    let mut block_scalar: Option<usize> = None;
+
+    for (number, line) in source.lines().enumerate() {
+        let number = number + 1;
+        let trimmed = line.trim_start();
+        let mut column = line.len() - trimmed.len();
+        if trimmed.is_empty() || trimmed.starts_with('#') || trimmed.starts_with("---") {
+            continue;
+        }
+        match block_scalar {
+            Some(start) if column > start => continue,
+            _ => block_scalar = None,
+        }
+
+        let mut rest = trimmed;
+        while let Some(after_dash) = rest.strip_prefix("- ").or((rest == "-").then_some("")) {
+            while open
+                .last()
+                .is_some_and(|top| top.column > column || (top.column == column && top.item))
+            {
+                open.pop();
+            }
+            let parent = open.last().map(|top| top.path.clone()).unwrap_or_default();
+            let index = items.entry(parent.clone()).or_insert(0);
+            let path = format!("{}[{}]", parent, index);
+            *index += 1;
+            lines.insert(path.clone(), number);
+            open.push(Open {
+                column,
+                path,
+                item: true,
+            });
+            let content = after_dash.trim_start();
+            column += 2 + after_dash.len() - content.len();
+            rest = content;
+        }
+
+        let Some((key, value)) = rest.split_once(':') else {
+            continue;
+        };
+        if key.is_empty() || key.contains(' ') && !key.starts_with(['"', '\'']) {
+            continue;
+        }
+        if !(value.is_empty() || value.starts_with(' ')) {
+            continue;
+        }
+        let key = key.trim_matches(['"', '\'']);
+        while open.last().is_some_and(|top| top.column >= column) {
+            open.pop();
+        }
+        let parent = open.last().map(|top| top.path.as_str()).unwrap_or_default();
+        let path = join(parent, key);
+        lines.insert(path.clone(), number);
+
+        let value = value.split(" #").next().unwrap_or_default().trim();
+        if value.starts_with('|') || value.starts_with('>') {
+            block_scalar = Some(column);
+        } else if value.is_empty() {
+            items.remove(&path);
+            open.push(Open {
+                column,
+                path,
+                item: false,
+            });
+        }
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BLUEPRINT: &str = r#"
+services:
+  - type: web
+    name: api
+    runtime: node
+    buildCommand: |
+      npm ci
+      npm run build
+    startCommand: npm start
+    envVars:
+      - key: DATABASE_URL
+        fromDatabase:
+          name: db
+          property: connectionString
+  - type: worker
+    name: jobs
+    runtime: node
+databases:
+- name: db
+"#;
+
+    #[test]
+    fn parse_blueprint() {
+        let blueprint: Blueprint = serde_yaml::from_str(BLUEPRINT).unwrap();
+        let api = &blueprint.services[0];
+        assert_eq!(api.api_type(), Some(ServiceType::WebService));
+        assert_eq!(
+            api.build_command.as_deref(),
+            Some("npm ci\nnpm run build\n")
+        );
+        assert_eq!(api.env_keys(), BTreeSet::from(["DATABASE_URL"]));
+        assert_eq!(
+            blueprint.services[1].api_type(),
+            Some(ServiceType::BackgroundWorker)
+        );
+    }
+
+    #[test]
+    fn exported_services_lint_clean() {
+        let service: Service = serde_json::from_str(
+            r#"{
+                "id": "srv-1",
+                "name": "api",
+                "type": "web_service",
+                "ownerId": "tea-1",
+                "branch": "main",
+                "repo": "https://github.com/reconbot/render-deploy",
+                "dashboardUrl": "https://dashboard.render.com/web/srv-1",
+                "autoDeploy": "yes",
+                "createdAt": "2024-10-14T02:17:35Z",
+                "updatedAt": "2024-10-14T02:17:35Z"
+            }"#,
+        )
+        .unwrap();
+        let settings = ServiceSettings {
+            branch: Some("main".into()),
+            runtime: Some("node".into()),
+            start_command: Some("npm start".into()),
+            ..Default::default()
+        };
+        let env_vars = [EnvVar {
+            key: "API_KEY".into(),
+            value: "secret".into(),
+        }];
+        let disk = Disk {
+            id: "dsk-1".into(),
+            name: "data".into(),
+            size_gb: 10,
+            mount_path: "/var/data".into(),
+            service_id: None,
+        };
+        let blueprint = Blueprint {
+            services: vec![BlueprintService::from_live(
+                &service,
+                settings,
+                &env_vars,
+                Some(disk),
+            )],
+        };
+        let yaml = serde_yaml::to_string(&blueprint).unwrap();
+        assert!(!yaml.contains("secret"));
+        assert_eq!(lint(&yaml), vec![]);
+        let exported: Blueprint = serde_yaml::from_str(&yaml).unwrap();
+        assert_eq!(
+            exported.services[0].settings().start_command.as_deref(),
+            Some("npm start")
+        );
+        assert_eq!(
+            exported.services[0].disk.as_ref().unwrap().size_gb,
+            Some(10)
+        );
+    }
+
+    #[test]
+    fn find_key_lines() {
+        let lines = key_lines(BLUEPRINT);
+        assert_eq!(lines["services"], 2);
+        assert_eq!(lines["services[0]"], 3);
+        assert_eq!(lines["services[0].startCommand"], 9);
+        assert_eq!(lines["services[0].envVars[0].fromDatabase.property"], 14);
+        assert_eq!(lines["services[1].name"], 16);
+        assert_eq!(lines["databases[0].name"], 19);
+    }
+
+    #[test]
+    fn lint_a_good_blueprint() {
+        assert_eq!(lint(BLUEPRINT), vec![]);
+    }
+
+    #[test]
+    fn lint_reports_problems_where_they_are() {
+        let problems = lint(
+            r#"
+services:
+  - type: webservice
+    name: api
+    runtime: node
+    plann: starter
+  - type: cron
+    name: api
+    runtime: node
+    envVars:
+      - key: LOG_LEVEL
+"#,
+        );
+        let problems: Vec<String> = problems
+            .iter()
+            .map(|problem| format!("line {}: {}", problem.line.unwrap(), problem))
+            .collect();
+        assert_eq!(
+            problems,
+            vec![
+                "line 6: services[0].plann: unknown field plann",
+                "line 3: services[0].type: webservice isn't a type, expected one of web, pserv, worker, cron, keyvalue, redis",
+                "line 7: services[1].schedule: cron jobs need a schedule",
+                "line 11: services[1].envVars[0]: needs exactly one of value, generateValue, sync, fromDatabase, fromService",
+                "line 8: services[1].name: api is used by more than one service",
+            ]
+        );
+    }
+
+    #[test]
+    fn lint_reports_yaml_errors() {
+        let problems = lint("services:\n  - name: api\n   type: web\n");
+        assert_eq!(problems.len(), 1);
+        assert!(problems[0].line.is_some());
+    }
+}
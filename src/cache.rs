@@ -0,0 +1,171 @@
+use render_deploy::{ApiKey, Service, ServiceType};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::{env, fs};
+
+/// How long a service's id is trusted before it's looked up by name again
+const TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// What we remember about a service, enough to find it by name and tell what it is
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct CachedService {
+    id: String,
+    name: String,
+    #[serde(rename = "type")]
+    service_type: ServiceType,
+    repo: Option<String>,
+    branch: Option<String>,
+    /// unix seconds
+    cached_at: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+struct CacheFile {
+    services: Vec<CachedService>,
+}
+
+impl CacheFile {
+    /// The id of the service named exactly `name`, unless it's older than the TTL
+    fn find(&self, name: &str, now: u64) -> Option<&str> {
+        self.services
+            .iter()
+            .find(|service| service.name == name && now < service.cached_at + TTL.as_secs())
+            .map(|service| service.id.as_str())
+    }
+
+    /// Add or update these services, and drop ones that have expired. A name more than one of
+    /// them shares isn't kept, looking it up has to say it's ambiguous.
+    fn remember(&mut self, services: &[Service], now: u64) {
+        self.services.retain(|cached| {
+            now < cached.cached_at + TTL.as_secs()
+                && !services
+                    .iter()
+                    .any(|service| service.id == cached.id || service.name == cached.name)
+        });
+        let unique = |service: &&Service| {
+            services
+                .iter()
+                .filter(|other| other.name == service.name)
+                .count()
+                == 1
+        };
+        self.services
+            .extend(services.iter().filter(unique).map(|service| CachedService {
+                id: service.id.clone(),
+                name: service.name.clone(),
+                service_type: service.service_type,
+                repo: service.repo.clone(),
+                branch: service.branch.clone(),
+                cached_at: now,
+            }));
+    }
+}
+
+/// Service name to id lookups saved in `~/.cache/render-deploy`, so a service named on every
+/// run doesn't need searching for every time. It's only a shortcut, any trouble reading or
+/// writing it means looking the service up as if it wasn't there.
+#[derive(Debug)]
+pub struct ServiceCache {
+    /// `None` without a home directory to keep it in
+    path: Option<PathBuf>,
+    /// `--refresh-cache`, ignore what's saved and save what we find again
+    refresh: bool,
+}
+
+impl ServiceCache {
+    /// A cache for what one api key can see in one workspace, they each get their own file so
+    /// a name never resolves to a service from somewhere else
+    pub fn new(refresh: bool, api_key: &ApiKey, base_url: &str, owner_id: Option<&str>) -> Self {
+        let mut hasher = Sha256::new();
+        for part in [api_key.expose(), base_url, owner_id.unwrap_or_default()] {
+            hasher.update(part);
+            hasher.update([0]);
+        }
+        let scope: String = hasher
+            .finalize()
+            .iter()
+            .take(8)
+            .map(|byte| format!("{:02x}", byte))
+            .collect();
+        ServiceCache {
+            path: cache_dir().map(|dir| dir.join(format!("services-{}.json", scope))),
+            refresh,
+        }
+    }
+
+    /// The cached id of the service named `name`
+    pub fn id(&self, name: &str) -> Option<String> {
+        if self.refresh {
+            return None;
+        }
+        self.load()?.find(name, now()).map(String::from)
+    }
+
+    /// Save the ids of services we've looked up
+    pub fn remember(&self, services: &[Service]) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        let mut file = self.load().unwrap_or_default();
+        file.remember(services, now());
+        if let Ok(json) = serde_json::to_string_pretty(&file) {
+            path.parent().map(fs::create_dir_all);
+            fs::write(path, json).ok();
+        }
+    }
+
+    fn load(&self) -> Option<CacheFile> {
+        let json = fs::read_to_string(self.path.as_ref()?).ok()?;
+        serde_json::from_str(&json).ok()
+    }
+}
+
+/// `$XDG_CACHE_HOME/render-deploy` or `~/.cache/render-deploy`
+fn cache_dir() -> Option<PathBuf> {
+    env::var_os("XDG_CACHE_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".cache")))
+        .map(|cache_home| cache_home.join("render-deploy"))
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::service;
+
+    #[test]
+    fn find_until_expired() {
+        let mut file = CacheFile::default();
+        file.remember(&[service("srv-1", "api")], 1000);
+        assert_eq!(file.find("api", 1000), Some("srv-1"));
+        assert_eq!(file.find("ap", 1000), None);
+        assert_eq!(file.find("api", 1000 + TTL.as_secs()), None);
+    }
+
+    #[test]
+    fn forget_shared_names() {
+        let mut file = CacheFile::default();
+        file.remember(&[service("srv-1", "api")], 1000);
+        file.remember(&[service("srv-1", "api"), service("srv-2", "api")], 2000);
+        assert_eq!(file.find("api", 2000), None);
+    }
+
+    #[test]
+    fn remember_replaces_renamed_services() {
+        let mut file = CacheFile::default();
+        file.remember(&[service("srv-1", "api"), service("srv-2", "web")], 1000);
+        file.remember(&[service("srv-1", "api-v2")], 2000);
+        assert_eq!(file.find("api", 2000), None);
+        assert_eq!(file.find("api-v2", 2000), Some("srv-1"));
+        assert_eq!(file.find("web", 2000), Some("srv-2"));
+    }
+}
@@ -0,0 +1,1277 @@
+use crate::blueprint;
+use crate::logging::LogFormat;
+use crate::output::{ColorChoice, OutputFormat};
+use crate::release;
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use render_deploy::ApiKey;
+use serde::Deserialize;
+use std::path::PathBuf;
+use std::time::Duration;
+
+const EXIT_CODES: &str = "Exit codes:
+  0  the deploy is live (or the command succeeded)
+  1  usage or configuration error
+  2  the deploy failed, or --pre-deploy-cmd or --post-deploy-cmd did
+  3  timed out waiting, the deploy may still finish
+  4  the deploy was canceled
+  5  api, network or authentication error
+  130  interrupted while waiting, the deploy carries on";
+
+#[derive(Parser, Debug, Clone)]
+#[command(version, about = " I needed a cli for render.com and I wanted to play with rust so it's a rust cli for triggering deploys on render.com", long_about = None, after_help = EXIT_CODES)]
+pub struct Cli {
+    /// render api key, `-` reads it from stdin. Without one the key saved by `auth login` is used
+    #[arg(
+        short,
+        long,
+        env("RENDER_API_KEY"),
+        global = true,
+        hide_env_values = true
+    )]
+    pub api_key: Option<ApiKey>,
+
+    /// file holding the render api key, like a mounted secret
+    #[arg(long, env("RENDER_API_KEY_FILE"), global = true)]
+    pub api_key_file: Option<PathBuf>,
+
+    /// render api to talk to, eg a proxy or a mock server [default: https://api.render.com/v1]
+    #[arg(long, env("RENDER_API_BASE_URL"), global = true)]
+    pub api_base_url: Option<String>,
+
+    /// pem file of extra certificates to trust, for proxies that intercept tls
+    #[arg(long, global = true)]
+    pub cacert: Option<PathBuf>,
+
+    /// how to print results, non text formats move progress output to stderr
+    #[arg(short, long, value_enum, default_value_t, global = true)]
+    pub output: OutputFormat,
+
+    /// print results through a template instead, eg '{{.deploy.id}} {{.deploy.status}}', the
+    /// fields are the ones --output json has and lists print a line for each item
+    #[arg(long, value_name = "TEMPLATE", global = true)]
+    pub format_template: Option<String>,
+
+    /// only print these columns of tables, eg NAME,STATUS
+    #[arg(long, value_delimiter = ',', global = true)]
+    pub columns: Vec<String>,
+
+    /// when to color deploy statuses
+    #[arg(long, value_enum, default_value_t, global = true)]
+    pub color: ColorChoice,
+
+    /// same as --color never
+    #[arg(long, global = true, conflicts_with = "color")]
+    pub no_color: bool,
+
+    /// print times as Render sends them, in UTC, rather than in the local timezone with how
+    /// long ago they were
+    #[arg(long, global = true)]
+    pub utc: bool,
+
+    /// Only print what a command came to, eg just the id of a deploy it started
+    #[arg(short, long, global = true)]
+    pub quiet: bool,
+
+    /// Also print every api request with how long it took and any retries, on stderr
+    #[arg(short, long, global = true, conflicts_with = "quiet")]
+    pub verbose: bool,
+
+    /// Log api requests, retries and deploy status changes on stderr, json is for log
+    /// aggregators
+    #[arg(long, value_enum, global = true)]
+    pub log_format: Option<LogFormat>,
+
+    /// Print every api request and response with their headers on stderr, credentials redacted.
+    /// Responses that aren't the expected json are saved to a temp file.
+    #[arg(long, global = true)]
+    pub debug_http: bool,
+
+    /// Use the first service whose name starts with the given name instead of requiring an
+    /// exact match
+    #[arg(long, global = true)]
+    pub fuzzy: bool,
+
+    /// id of the service (srv-...), skips looking the service up by name
+    #[arg(long, global = true, conflicts_with = "fuzzy")]
+    pub service_id: Option<String>,
+
+    /// look services up by name again rather than trusting the ids saved in
+    /// ~/.cache/render-deploy, which are kept for a day
+    #[arg(long, global = true)]
+    pub refresh_cache: bool,
+
+    /// name or id (usr-... or tea-...) of the workspace to look services up in, see `owners`
+    #[arg(long, env("RENDER_OWNER"), global = true)]
+    pub owner: Option<String>,
+
+    /// profile from render-deploy.toml or ~/.config/render-deploy/config.toml to take defaults
+    /// from
+    #[arg(short, long, env("RENDER_DEPLOY_PROFILE"), global = true)]
+    pub profile: Option<String>,
+
+    #[command(subcommand)]
+    pub command: Commands,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum Commands {
+    /// Trigger a deploy of a service
+    Deploy(DeployArgs),
+    /// Cancel an in progress deploy
+    Cancel(CancelArgs),
+    /// Redeploy the commit that was live before the current deploy
+    Rollback(RollbackArgs),
+    /// Restart a service's instances without building or deploying
+    Restart(RestartArgs),
+    /// Stop a service's instances until it's resumed, eg to save money on staging overnight
+    Suspend(SuspendArgs),
+    /// Start a suspended service again
+    Resume(ResumeArgs),
+    /// Change how many instances of a service run
+    Scale(ScaleArgs),
+    /// Show or change a service's autoscaling
+    Autoscale(AutoscaleArgs),
+    /// Run one off commands like migrations with a service's build and environment
+    Job(JobArgs),
+    /// Kick off a cron job or see how its recent runs went
+    Cron(CronArgs),
+    /// Manage a service's custom domains
+    Domains(DomainsArgs),
+    /// Manage a static site's redirect and rewrite rules
+    Routes(RoutesArgs),
+    /// Manage the response headers a static site adds
+    Headers(HeadersArgs),
+    /// Look up render postgres databases and how to connect to them
+    Postgres(PostgresArgs),
+    /// Print a service's logs
+    Logs(LogsArgs),
+    /// Show a service and its latest deploy without triggering one
+    Status(StatusArgs),
+    /// Follow a service's deploys as they happen, whoever started them, until interrupted
+    Watch(WatchArgs),
+    /// List every service with its latest deploy
+    List,
+    /// List the user and team workspaces the api key can reach
+    Owners,
+    /// Keep the api key in the OS keyring instead of an env var
+    Auth(AuthArgs),
+    /// List the projects that group services into environments
+    Projects(ProjectsArgs),
+    /// List a project's environments
+    Environments(EnvironmentsArgs),
+    /// Show a service's recent deploys
+    Deploys(DeploysArgs),
+    /// Show how often a service deploys, how often deploys fail, how long they take and how
+    /// long it takes to recover from a failure
+    Stats(StatsArgs),
+    /// Show a timeline of what's happened to a service, deploys, failures, scaling etc
+    Events(EventsArgs),
+    /// Show a service's cpu and memory use
+    Metrics(MetricsArgs),
+    /// Full screen view of every service's latest deploy, following deploys in progress, with
+    /// keys to deploy, cancel and roll back
+    Dashboard(DashboardArgs),
+    /// Deploy the services in a release file in order, each stage once the last is live
+    Release(ReleaseArgs),
+    /// Manage a service's environment variables
+    Env(EnvArgs),
+    /// Manage the secret files render mounts in a service at /etc/secrets
+    SecretFiles(SecretFilesArgs),
+    /// Create and change services
+    Service(ServiceArgs),
+    /// Spin up copies of a service for branches and pull requests and tear them down again
+    Preview(PreviewArgs),
+    /// Make services match a yaml file of them, showing the plan before changing anything
+    Apply(ApplyArgs),
+    /// Work with render.yaml Blueprints
+    Blueprint(BlueprintArgs),
+    /// Receive render's webhooks and pass the events on to a command or stdout, one json per line
+    Listen(ListenArgs),
+    /// Print the man page, or write one for every subcommand with --out-dir
+    Man(ManArgs),
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct DeployArgs {
+    /// name, id (srv-...) or project/environment/service path of your service, deploy several at
+    /// once with a comma separated list or a glob like 'api-*'
+    pub name: Option<String>,
+    /// optional commit to deploy (otherwise head of the default branch), HEAD or @ deploys what's
+    /// checked out here
+    pub commit: Option<String>,
+
+    /// Deploy the head of this branch instead of the service's branch, without changing the
+    /// service's settings, for a one off test of a branch on staging
+    #[arg(long, conflicts_with_all = ["commit", "require_pushed"])]
+    pub branch: Option<String>,
+
+    /// Deploy an image backed service from this image, eg docker.io/library/nginx:1.27
+    #[arg(long, group = "image", conflicts_with_all = ["commit", "branch"])]
+    pub image_url: Option<String>,
+    /// Deploy this tag of the service's image
+    #[arg(long, group = "image", conflicts_with_all = ["commit", "branch"])]
+    pub image_tag: Option<String>,
+    /// Deploy this digest (sha256:...) of the service's image
+    #[arg(long, group = "image", conflicts_with_all = ["commit", "branch"])]
+    pub digest: Option<String>,
+
+    /// Clear the build cache before building
+    #[arg(long)]
+    pub clear_cache: bool,
+
+    /// Deploy with the service's Deploy Hook url instead of the api, for pipelines that only
+    /// have the hook. Give just the commit, if any. Without an api key the deploy is only
+    /// started, not waited on.
+    #[arg(long, env = "RENDER_DEPLOY_HOOK_URL", hide_env_values = true)]
+    pub hook_url: Option<String>,
+
+    /// Deploy to services the profile protects without asking
+    #[arg(short, long)]
+    pub yes: bool,
+
+    /// Hold a lock on the service while deploying it, so another pipeline with --lock fails, or
+    /// waits with --lock-wait, rather than deploying over this one, needs --wait. The lock is the
+    /// RENDER_DEPLOY_LOCK env var on the service, so the deploy sees it too, and env sync and apply
+    /// leave it alone. Render can't set it only if it's unset, so two pipelines taking it in the
+    /// same second can both get it. Stopping the wait with Ctrl-C leaves it in place.
+    #[arg(long)]
+    pub lock: bool,
+    /// How long to wait for another deploy's lock, eg 90s or 10m
+    #[arg(long, value_parser = parse_duration, requires = "lock")]
+    pub lock_wait: Option<Duration>,
+
+    /// Refuse to deploy a commit that isn't on the service's repo, going by the git remote here
+    /// that points at it as of its last fetch. Otherwise it's only a warning.
+    #[arg(long, requires = "commit")]
+    pub require_pushed: bool,
+
+    /// List the commits between the live deploy and the one being deployed, with a link to
+    /// compare them, from the git repo here or github or gitlab
+    #[arg(long)]
+    pub changes: bool,
+
+    /// Check with github or gitlab that the commit exists and is on the service's branch before
+    /// deploying it. Private repos need a token, see the profile's git_token_env.
+    #[arg(long, requires = "commit")]
+    pub verify_commit: bool,
+
+    /// Don't deploy if the commit (or head of the service's branch) is already live
+    #[arg(long)]
+    pub skip_if_current: bool,
+
+    /// What to do when the service already has a deploy in progress
+    #[arg(long, value_enum, default_value_t)]
+    pub if_in_progress: IfInProgress,
+
+    /// Turn off the service's AutoDeploy so only deploys like this one ship it
+    #[arg(long)]
+    pub disable_autodeploy: bool,
+
+    /// Run this with `sh -c` before triggering each deploy, failing stops the deploy.
+    /// RENDER_SERVICE_ID, RENDER_SERVICE_NAME and RENDER_DEPLOY_COMMIT (if given) are set.
+    #[arg(long, value_name = "COMMAND")]
+    pub pre_deploy_cmd: Option<String>,
+
+    /// Run this with `sh -c` once each deploy is live, like a smoke test, needs --wait. Failing
+    /// fails the command with exit code 2. RENDER_SERVICE_ID, RENDER_SERVICE_NAME,
+    /// RENDER_DEPLOY_ID, RENDER_DEPLOY_URL and RENDER_DEPLOY_COMMIT (for repo deploys) are set.
+    #[arg(long, value_name = "COMMAND")]
+    pub post_deploy_cmd: Option<String>,
+
+    /// When the deploy fails, or --post-deploy-cmd does, redeploy what was live before and wait
+    /// for it. Needs --wait and a single service, the command still fails with exit code 2.
+    #[arg(long)]
+    pub auto_rollback: bool,
+
+    /// Write a JUnit XML report of each deploy and deploy command, with how long they took and
+    /// why they failed, for the test report in Jenkins or GitLab. Needs --wait.
+    #[arg(long, value_name = "PATH")]
+    pub junit_output: Option<PathBuf>,
+
+    #[command(flatten)]
+    pub wait: WaitArgs,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum IfInProgress {
+    /// wait for it to finish then deploy
+    Wait,
+    /// don't deploy, wait on the deploy in progress instead
+    Attach,
+    /// cancel it then deploy
+    Cancel,
+    /// deploy anyway, render queues the new deploy behind it
+    #[default]
+    Force,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct WaitArgs {
+    /// Wait for the deploy to finish or fail
+    #[arg(short, long)]
+    pub wait: bool,
+
+    /// how long to wait for the deploy, eg 90s, 10m or 1h30m, doesn't cancel the deploy just
+    /// exits unless --cancel-on-timeout is given [default: 10m]
+    #[arg(short, long, value_parser = parse_duration)]
+    pub timeout: Option<Duration>,
+
+    /// Cancel the deploy if it hasn't finished by the timeout
+    #[arg(long)]
+    pub cancel_on_timeout: bool,
+
+    /// What Ctrl-C or SIGTERM does while waiting, asks at a terminal and detaches otherwise
+    #[arg(long, value_enum)]
+    pub on_interrupt: Option<OnInterrupt>,
+
+    /// time between checks on the deploy's status, eg 5s
+    #[arg(long, default_value = "5", value_parser = parse_poll_interval)]
+    pub poll_interval: Duration,
+
+    /// Print the service's logs while waiting
+    #[arg(short, long)]
+    pub logs: bool,
+
+    /// Mirror the deploy as a GitHub deployment while waiting, needs GITHUB_TOKEN and
+    /// GITHUB_REPOSITORY
+    #[arg(long)]
+    pub github_deployment: bool,
+
+    /// GitHub environment for --github-deployment [default: the service's name]
+    #[arg(long, requires = "github_deployment")]
+    pub github_environment: Option<String>,
+
+    /// Slack or Discord webhook to post to when the deploy goes live or fails, can be repeated
+    #[arg(long = "notify-url", value_name = "WEBHOOK")]
+    pub notify_urls: Vec<String>,
+
+    /// Prometheus pushgateway to push deploy_duration_seconds and deploy_result to when the
+    /// deploy goes live or fails, eg http://pushgateway:9091
+    #[arg(long, value_name = "URL")]
+    pub pushgateway_url: Option<String>,
+
+    /// StatsD server to send the same metrics to, with dogstatsd tags, eg localhost:8125
+    #[arg(long, value_name = "HOST:PORT")]
+    pub statsd: Option<String>,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OnInterrupt {
+    /// stop waiting and leave the deploy going
+    Detach,
+    /// cancel the deploy
+    Cancel,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct CancelArgs {
+    /// name or id (srv-...) of your service
+    pub name: Option<String>,
+    /// deploy to cancel (otherwise the most recent in progress deploy)
+    pub deploy_id: Option<String>,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct RollbackArgs {
+    /// name or id (srv-...) of your service
+    pub name: Option<String>,
+    /// deploy id (dep-...) or commit to roll back to (otherwise the previously live deploy)
+    #[arg(long)]
+    pub to: Option<String>,
+
+    #[command(flatten)]
+    pub wait: WaitArgs,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct RestartArgs {
+    /// name or id (srv-...) of your service
+    pub name: Option<String>,
+
+    #[command(flatten)]
+    pub wait: ServiceWaitArgs,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct SuspendArgs {
+    /// name or id (srv-...) of your service
+    pub name: Option<String>,
+    /// Suspend without asking
+    #[arg(short, long)]
+    pub yes: bool,
+
+    #[command(flatten)]
+    pub wait: ServiceWaitArgs,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct ResumeArgs {
+    /// name or id (srv-...) of your service
+    pub name: Option<String>,
+
+    #[command(flatten)]
+    pub wait: ServiceWaitArgs,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct ScaleArgs {
+    /// name or id (srv-...) of your service
+    pub name: Option<String>,
+    /// how many instances to run
+    #[arg(short = 'n', long, value_parser = clap::value_parser!(u32).range(1..))]
+    pub num_instances: u32,
+
+    #[command(flatten)]
+    pub wait: ServiceWaitArgs,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct AutoscaleArgs {
+    #[command(subcommand)]
+    pub command: AutoscaleCommands,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum AutoscaleCommands {
+    /// Print a service's autoscaling settings
+    Show {
+        /// name or id (srv-...) of your service
+        name: Option<String>,
+    },
+    /// Change a service's autoscaling settings, anything not given is left alone
+    Set(AutoscaleSetArgs),
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct AutoscaleSetArgs {
+    /// name or id (srv-...) of your service
+    pub name: Option<String>,
+    /// fewest instances to run
+    #[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
+    pub min: Option<u32>,
+    /// most instances to run
+    #[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
+    pub max: Option<u32>,
+    /// average cpu percentage to scale towards, 0 stops scaling on cpu
+    #[arg(long, value_name = "PERCENT", value_parser = clap::value_parser!(u32).range(0..=100))]
+    pub cpu_target: Option<u32>,
+    /// average memory percentage to scale towards, 0 stops scaling on memory
+    #[arg(long, value_name = "PERCENT", value_parser = clap::value_parser!(u32).range(0..=100))]
+    pub memory_target: Option<u32>,
+    /// Turn autoscaling on
+    #[arg(long, conflicts_with = "disable")]
+    pub enable: bool,
+    /// Turn autoscaling off, keeping the settings for next time
+    #[arg(long)]
+    pub disable: bool,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct JobArgs {
+    #[command(subcommand)]
+    pub command: JobCommands,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum JobCommands {
+    /// Start a job, with --wait the exit code follows the job's
+    Run(JobRunArgs),
+    /// Show a service's recent jobs
+    List(JobListArgs),
+    /// Show one job
+    Status {
+        /// job to show (job-...)
+        job_id: String,
+        /// name or id (srv-...) of the job's service
+        name: Option<String>,
+    },
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct JobListArgs {
+    /// name or id (srv-...) of your service
+    pub name: Option<String>,
+    /// how many jobs to show
+    #[arg(short = 'n', long, default_value_t = 20)]
+    pub limit: usize,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct JobRunArgs {
+    /// name or id (srv-...) of your service
+    pub name: Option<String>,
+    /// command to run, eg "rake db:migrate"
+    #[arg(short, long)]
+    pub command: String,
+    /// instance type to run on (plan-srv-...) [default: the service's]
+    #[arg(long)]
+    pub plan_id: Option<String>,
+
+    #[command(flatten)]
+    pub wait: ServiceWaitArgs,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct CronArgs {
+    #[command(subcommand)]
+    pub command: CronCommands,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum CronCommands {
+    /// Run a cron job now instead of waiting for its schedule
+    Run {
+        /// name or id (crn-...) of your cron job
+        name: Option<String>,
+    },
+    /// Show a cron job's recent runs and how they ended
+    Runs {
+        /// name or id (crn-...) of your cron job
+        name: Option<String>,
+        /// how many runs to show
+        #[arg(short = 'n', long, default_value_t = 20)]
+        limit: usize,
+    },
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct DomainsArgs {
+    #[command(subcommand)]
+    pub command: DomainsCommands,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum DomainsCommands {
+    /// List a service's custom domains and whether they're verified
+    List {
+        /// name or id (srv-...) of your service
+        name: Option<String>,
+    },
+    /// Add a custom domain, adding an apex domain adds its www subdomain too
+    Add {
+        /// name or id (srv-...) of your service
+        name: String,
+        /// eg example.com or api.example.com
+        domain: String,
+    },
+    /// Remove custom domains
+    Remove {
+        /// name or id (srv-...) of your service
+        name: String,
+        #[arg(required = true)]
+        domains: Vec<String>,
+    },
+    /// Check a domain's DNS now instead of waiting for render to
+    Verify {
+        /// name or id (srv-...) of your service
+        name: String,
+        domain: String,
+    },
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct RoutesArgs {
+    #[command(subcommand)]
+    pub command: RoutesCommands,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum RoutesCommands {
+    /// List a static site's routes in the order they're tried
+    List {
+        /// name or id (srv-...) of your static site
+        name: Option<String>,
+    },
+    /// Add a redirect, or a rewrite with --rewrite
+    Add {
+        /// name or id (srv-...) of your static site
+        name: String,
+        /// path to match, eg /blog/*
+        source: String,
+        /// where to send it, eg /posts/:splat or https://example.com
+        destination: String,
+        /// Serve the destination from the source's url instead of redirecting
+        #[arg(long)]
+        rewrite: bool,
+        /// where in the order to try this route [default: last]
+        #[arg(long)]
+        priority: Option<u32>,
+    },
+    /// Remove routes by id
+    Remove {
+        /// name or id (srv-...) of your static site
+        name: String,
+        #[arg(required = true, value_name = "ROUTE_ID")]
+        route_ids: Vec<String>,
+    },
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct HeadersArgs {
+    #[command(subcommand)]
+    pub command: HeadersCommands,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum HeadersCommands {
+    /// List a static site's header rules
+    List {
+        /// name or id (srv-...) of your static site
+        name: Option<String>,
+    },
+    /// Add a header to every response matching a path
+    Add {
+        /// name or id (srv-...) of your static site
+        name: String,
+        /// path to match, eg /assets/*
+        path: String,
+        /// eg Cache-Control
+        header: String,
+        value: String,
+    },
+    /// Remove header rules by id
+    Remove {
+        /// name or id (srv-...) of your static site
+        name: String,
+        #[arg(required = true, value_name = "HEADER_ID")]
+        header_ids: Vec<String>,
+    },
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct ProjectsArgs {
+    #[command(subcommand)]
+    pub command: ProjectsCommands,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum ProjectsCommands {
+    /// List every project and its environments
+    List,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct EnvironmentsArgs {
+    #[command(subcommand)]
+    pub command: EnvironmentsCommands,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum EnvironmentsCommands {
+    /// List a project's environments and how many services are in each
+    List {
+        /// name or id (prj-...) of the project
+        project: String,
+    },
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct PostgresArgs {
+    #[command(subcommand)]
+    pub command: PostgresCommands,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum PostgresCommands {
+    /// List every database
+    List,
+    /// Show a database and how to connect to it, passwords are masked unless --reveal is given
+    Info {
+        /// name or id (dpg-...) of your database
+        database: String,
+        /// Show the password instead of masking it
+        #[arg(long)]
+        reveal: bool,
+    },
+    /// Print a database's connection string, masked unless --reveal is given
+    ConnectionString {
+        /// name or id (dpg-...) of your database
+        database: String,
+        /// Print the string for connecting from outside render instead of from a service in the
+        /// same region
+        #[arg(long)]
+        external: bool,
+        /// Show the password instead of masking it
+        #[arg(long)]
+        reveal: bool,
+    },
+    /// Show how far back a database can be recovered to
+    Recovery {
+        /// name or id (dpg-...) of your database
+        database: String,
+    },
+    /// Recover a database to a point in time, into a new database
+    Recover(PostgresRecoverArgs),
+    /// Start a logical backup of a database
+    Export {
+        /// name or id (dpg-...) of your database
+        database: String,
+
+        #[command(flatten)]
+        wait: ServiceWaitArgs,
+    },
+    /// List a database's exports and where to download them
+    Exports {
+        /// name or id (dpg-...) of your database
+        database: String,
+    },
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct PostgresRecoverArgs {
+    /// name or id (dpg-...) of your database
+    pub database: String,
+    /// time to recover to, eg 2024-10-14T02:17:35Z
+    #[arg(long, value_parser = parse_time)]
+    pub to: String,
+    /// name of the new database [default: picked by render]
+    #[arg(long)]
+    pub name: Option<String>,
+
+    #[command(flatten)]
+    pub wait: ServiceWaitArgs,
+}
+
+/// Waiting on a change to a service rather than a deploy
+#[derive(Args, Debug, Clone)]
+pub struct ServiceWaitArgs {
+    /// Wait until it's done
+    #[arg(short, long)]
+    pub wait: bool,
+    /// how long to wait, eg 90s, 10m or 1h30m [default: 10m]
+    #[arg(short, long, value_parser = parse_duration)]
+    pub timeout: Option<Duration>,
+    /// time between checks, eg 5s
+    #[arg(long, default_value = "5", value_parser = parse_poll_interval)]
+    pub poll_interval: Duration,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct LogsArgs {
+    /// name or id (srv-...) of your service
+    pub name: Option<String>,
+    /// Keep printing new log lines until interrupted
+    #[arg(short, long)]
+    pub follow: bool,
+    /// only show logs newer than this, eg 30m, 1h, 2days
+    #[arg(short, long, value_parser = parse_duration)]
+    pub since: Option<Duration>,
+    /// how many of the most recent lines to show
+    #[arg(short = 'n', long, default_value_t = 100)]
+    pub limit: u32,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct DeploysArgs {
+    /// name or id (srv-...) of your service
+    pub name: Option<String>,
+    /// how many deploys to show
+    #[arg(short = 'n', long, default_value_t = 20)]
+    pub limit: usize,
+    /// only show deploys that ended this way
+    #[arg(long, value_enum)]
+    pub status: Option<DeployFilter>,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct StatsArgs {
+    /// name or id (srv-...) of your service
+    pub name: Option<String>,
+    /// count deploys from this far back, eg 7d, 30d, 12weeks
+    #[arg(short, long, default_value = "30d", value_parser = parse_duration)]
+    pub since: Duration,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct EventsArgs {
+    /// name or id (srv-...) of your service
+    pub name: Option<String>,
+    /// only show events newer than this, eg 30m, 1h, 2days
+    #[arg(short, long, default_value = "24h", value_parser = parse_duration)]
+    pub since: Duration,
+    /// how many events to show
+    #[arg(short = 'n', long, default_value_t = 100)]
+    pub limit: usize,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct WatchArgs {
+    /// name or id (srv-...) of your service
+    pub name: Option<String>,
+    /// Also print the service's events, scaling, restarts, failures etc
+    #[arg(long)]
+    pub events: bool,
+    /// Also print the service's logs
+    #[arg(long)]
+    pub logs: bool,
+    /// time between checks, eg 5s
+    #[arg(long, default_value = "5", value_parser = parse_poll_interval)]
+    pub poll_interval: Duration,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct DashboardArgs {
+    /// time between reloading every service, deploys in progress are checked more often
+    #[arg(long, default_value = "30", value_parser = parse_duration)]
+    pub refresh: Duration,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct MetricsArgs {
+    /// name or id (srv-...) of your service
+    pub name: Option<String>,
+    /// Show cpu use, both cpu and memory are shown if neither is picked
+    #[arg(long)]
+    pub cpu: bool,
+    /// Show memory use
+    #[arg(long)]
+    pub memory: bool,
+    /// how far back to go, eg 30m, 1h, 2days
+    #[arg(short, long, default_value = "1h", value_parser = parse_duration)]
+    pub since: Duration,
+    /// time between points, eg 1m, 5m [default: 60 points over --since]
+    #[arg(long, value_parser = parse_duration)]
+    pub resolution: Option<Duration>,
+    /// how to print the metrics [default: table, or json with --output json]
+    #[arg(long, value_enum)]
+    pub format: Option<MetricsFormat>,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MetricsFormat {
+    /// a row per instance per point in time
+    Table,
+    /// render's series as they came back, for feeding dashboards
+    Json,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeployFilter {
+    /// the deploy that's live now
+    Live,
+    /// deploys that went live, including ones since replaced
+    Succeeded,
+    /// deploys that failed to build, update or pre-deploy
+    Failed,
+    Canceled,
+    InProgress,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct ReleaseArgs {
+    /// release file listing the services to deploy and what each has to wait for
+    #[arg(short, long, default_value = release::DEFAULT_RELEASE_FILE)]
+    pub file: PathBuf,
+
+    // every stage waits on the one before it so --wait is implied
+    #[command(flatten)]
+    pub wait: WaitArgs,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct EnvArgs {
+    #[command(subcommand)]
+    pub command: EnvCommands,
+}
+
+// changing env vars doesn't deploy them, same as the dashboard's "save only"
+#[derive(Subcommand, Debug, Clone)]
+pub enum EnvCommands {
+    /// Print every environment variable of a service
+    List(EnvListArgs),
+    /// Print the value of one environment variable
+    Get {
+        /// name or id (srv-...) of your service
+        name: String,
+        key: String,
+    },
+    /// Add or update environment variables, leaving the others alone
+    Set(EnvSetArgs),
+    /// Remove environment variables
+    Unset {
+        /// name or id (srv-...) of your service
+        name: String,
+        #[arg(required = true)]
+        keys: Vec<String>,
+    },
+    /// Make a service's environment variables match a .env file, showing what will change first
+    Sync(EnvSyncArgs),
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct EnvListArgs {
+    /// name or id (srv-...) of your service
+    pub name: Option<String>,
+    /// how to print the variables [default: dotenv, or json with --output json]
+    #[arg(long, value_enum)]
+    pub format: Option<EnvFormat>,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EnvFormat {
+    /// KEY=value lines that can be read back with --from-file
+    Dotenv,
+    /// a json object of keys to values
+    Json,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct EnvSetArgs {
+    /// name or id (srv-...) of your service
+    pub name: String,
+    /// variables to set
+    #[arg(value_name = "KEY=VALUE")]
+    pub pairs: Vec<String>,
+    /// also set every variable in this .env file
+    #[arg(long, value_name = "FILE")]
+    pub from_file: Option<PathBuf>,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct EnvSyncArgs {
+    /// name or id (srv-...) of your service
+    pub name: Option<String>,
+    /// .env file to copy to the service
+    #[arg(short, long, default_value = ".env")]
+    pub file: PathBuf,
+    /// Also remove variables on the service that aren't in the file
+    #[arg(long)]
+    pub prune: bool,
+    /// Apply the changes without asking
+    #[arg(short, long)]
+    pub yes: bool,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct SecretFilesArgs {
+    #[command(subcommand)]
+    pub command: SecretFilesCommands,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum SecretFilesCommands {
+    /// List a service's secret files without their contents
+    List {
+        /// name or id (srv-...) of your service
+        name: Option<String>,
+    },
+    /// Upload a file as a secret file, replacing any with the same name
+    Put {
+        /// name or id (srv-...) of your service
+        name: String,
+        /// file to upload
+        path: PathBuf,
+        /// name of the secret file [default: the file's name]
+        #[arg(long = "as", value_name = "NAME")]
+        file_name: Option<String>,
+    },
+    /// Remove secret files
+    Delete {
+        /// name or id (srv-...) of your service
+        name: String,
+        #[arg(required = true, value_name = "FILE_NAME")]
+        file_names: Vec<String>,
+    },
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct ServiceArgs {
+    #[command(subcommand)]
+    pub command: ServiceCommands,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum ServiceCommands {
+    /// Create a service from a repo, eg a preview for a pull request, and print its id
+    Create(ServiceCreateArgs),
+    /// Change how a service is built and run, showing what will change first
+    Update(ServiceUpdateArgs),
+    /// Turn deploying on every push to the service's branch on or off, eg when CI deploys it
+    SetAutodeploy {
+        /// name or id (srv-...) of your service
+        name: String,
+        #[arg(value_enum)]
+        state: Toggle,
+    },
+    /// Delete a service, eg a preview made by `service create`, after typing its name to confirm
+    Delete {
+        /// name or id (srv-...) of your service
+        name: String,
+        /// Delete without asking, only with --really too
+        #[arg(short, long, requires = "really")]
+        yes: bool,
+        /// Yes, really delete without asking
+        #[arg(long, requires = "yes")]
+        really: bool,
+    },
+    /// Print a service as render.yaml would declare it, to move hand made services into a
+    /// Blueprint. Env var values are left out.
+    Export {
+        /// name, id (srv-...) or project/environment/service path of your service
+        name: Option<String>,
+        /// Export every service instead of one
+        #[arg(long, conflicts_with = "name")]
+        all: bool,
+    },
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Toggle {
+    On,
+    Off,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct ServiceCreateArgs {
+    /// name of the new service
+    pub name: Option<String>,
+    /// read the service's settings from a yaml file, flags override what's in it
+    #[arg(long, value_name = "FILE")]
+    pub from_file: Option<PathBuf>,
+    /// [default: web]
+    #[arg(long = "type", value_enum)]
+    pub service_type: Option<NewServiceType>,
+    /// url of the git repo to build from
+    #[arg(long)]
+    pub repo: Option<String>,
+    /// branch to deploy, render uses the repo's default branch if not given
+    #[arg(long)]
+    pub branch: Option<String>,
+    /// language the service is built with, eg node, python, ruby, go, rust or docker
+    #[arg(long)]
+    pub runtime: Option<String>,
+    #[arg(long)]
+    pub build_command: Option<String>,
+    #[arg(long)]
+    pub start_command: Option<String>,
+    /// directory a static site publishes, eg dist
+    #[arg(long)]
+    pub publish_path: Option<String>,
+    /// instance type, eg starter or standard
+    #[arg(long)]
+    pub plan: Option<String>,
+    /// eg oregon, ohio, virginia, frankfurt or singapore
+    #[arg(long)]
+    pub region: Option<String>,
+    /// environment variables to start with, can be given more than once
+    #[arg(short, long = "env", value_name = "KEY=VALUE")]
+    pub env_vars: Vec<String>,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct ServiceUpdateArgs {
+    /// name or id (srv-...) of your service
+    pub name: String,
+    /// branch to deploy from
+    #[arg(long)]
+    pub branch: Option<String>,
+    #[arg(long)]
+    pub build_command: Option<String>,
+    #[arg(long)]
+    pub start_command: Option<String>,
+    /// instance type, eg starter or standard
+    #[arg(long)]
+    pub plan: Option<String>,
+    /// Show what would change without changing it
+    #[arg(long)]
+    pub dry_run: bool,
+    /// Apply the changes without asking
+    #[arg(short, long, conflicts_with = "dry_run")]
+    pub yes: bool,
+}
+
+#[derive(ValueEnum, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum NewServiceType {
+    /// a web service with a public url
+    Web,
+    /// a background worker
+    Worker,
+    /// a web service only reachable from other services
+    Private,
+    /// a static site
+    Static,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct PreviewArgs {
+    #[command(subcommand)]
+    pub command: PreviewCommands,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum PreviewCommands {
+    /// List preview services, all of them or a service's
+    List {
+        /// name of the service the previews are copies of
+        base: Option<String>,
+    },
+    /// Create a copy of a service that deploys a branch, does nothing if it already exists
+    Create(PreviewCreateArgs),
+    /// Delete a service's preview of a branch or pull request
+    Delete {
+        /// name of the service the preview is a copy of
+        base: String,
+        /// the branch or pull request number the preview was created for
+        #[arg(value_name = "BRANCH_OR_PR")]
+        preview: String,
+        /// Delete without asking
+        #[arg(short, long)]
+        yes: bool,
+    },
+    /// Delete previews that haven't been deployed in a while
+    Prune {
+        /// name of the service the previews are copies of
+        base: Option<String>,
+        /// how long since the last deploy, eg 7d or 12h
+        #[arg(long, value_parser = parse_duration)]
+        older_than: Duration,
+        /// Show what would be deleted without deleting it
+        #[arg(long)]
+        dry_run: bool,
+        /// Delete without asking
+        #[arg(short, long, conflicts_with = "dry_run")]
+        yes: bool,
+    },
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct PreviewCreateArgs {
+    /// name or id (srv-...) of the service to copy
+    pub base: String,
+    /// branch the preview deploys
+    pub branch: String,
+    /// name the preview after this pull request instead of the branch
+    #[arg(long)]
+    pub pr: Option<u32>,
+    /// Don't copy the service's environment variables
+    #[arg(long)]
+    pub no_env: bool,
+
+    #[command(flatten)]
+    pub wait: WaitArgs,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct ApplyArgs {
+    /// yaml file with a list of `services`, each like `service create --from-file` takes
+    #[arg(short, long, default_value = "services.yaml")]
+    pub file: PathBuf,
+    /// Also remove env vars on the services that aren't in the file
+    #[arg(long)]
+    pub prune: bool,
+    /// Show the plan without changing anything
+    #[arg(long)]
+    pub dry_run: bool,
+    /// Apply the plan without asking
+    #[arg(short, long, conflicts_with = "dry_run")]
+    pub yes: bool,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct BlueprintArgs {
+    #[command(subcommand)]
+    pub command: BlueprintCommands,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum BlueprintCommands {
+    /// Check a Blueprint for mistakes render would reject, with the line they're on
+    Lint {
+        /// Blueprint file
+        #[arg(default_value = blueprint::DEFAULT_BLUEPRINT_FILE)]
+        path: PathBuf,
+    },
+    /// Show where live services have drifted from a Blueprint, failing if any have
+    Diff {
+        /// Blueprint file
+        #[arg(default_value = blueprint::DEFAULT_BLUEPRINT_FILE)]
+        path: PathBuf,
+    },
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct ListenArgs {
+    /// Port to listen on, point a webhook in the dashboard at it
+    #[arg(long, default_value_t = 8080)]
+    pub port: u16,
+    /// Address to listen on
+    #[arg(long, default_value = "0.0.0.0")]
+    pub bind: String,
+    /// The webhook's signing secret (whsec_...), deliveries that aren't signed with it are refused
+    #[arg(long, env = "RENDER_WEBHOOK_SECRET", hide_env_values = true)]
+    pub secret: String,
+    /// Run this with `sh -c` for each event instead of printing it, the event's json is on stdin
+    /// and RENDER_EVENT_TYPE, RENDER_SERVICE_ID and RENDER_SERVICE_NAME are set
+    #[arg(long)]
+    pub exec: Option<String>,
+    /// Only pass on events of this type, eg deploy_ended, can be repeated
+    #[arg(long = "event")]
+    pub events: Vec<String>,
+    /// Only pass on events for this service, can be repeated
+    #[arg(long = "service")]
+    pub services: Vec<String>,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct AuthArgs {
+    #[command(subcommand)]
+    pub command: AuthCommands,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum AuthCommands {
+    /// Check an api key works and save it in the OS keyring for the profile, asks for the key
+    /// unless --api-key or --api-key-file give it
+    Login,
+    /// Remove the profile's api key from the OS keyring
+    Logout,
+    /// Show who the api key belongs to and the workspaces it can reach, to check a key before
+    /// CI uses it
+    Whoami,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct ManArgs {
+    /// Write render-deploy.1, render-deploy-deploy.1 etc to this directory instead of printing
+    /// the main page
+    #[arg(long)]
+    pub out_dir: Option<PathBuf>,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct StatusArgs {
+    /// name or id (srv-...) of your service
+    pub name: Option<String>,
+
+    /// Show this deploy (dep-...) instead of the latest, with --wait to pick a deploy back up
+    /// after a CI retry or Ctrl-C
+    #[arg(long)]
+    pub deploy_id: Option<String>,
+
+    #[command(flatten)]
+    pub wait: WaitArgs,
+}
+
+/// An RFC3339 time, normalized to UTC
+fn parse_time(arg: &str) -> Result<String, humantime::TimestampError> {
+    let time = humantime::parse_rfc3339_weak(arg)?;
+    Ok(humantime::format_rfc3339_seconds(time).to_string())
+}
+
+/// `90s`, `10m`, `1h30m` or `2days`, a plain number is seconds
+fn parse_duration(arg: &str) -> Result<Duration, humantime::DurationError> {
+    match arg.parse::<u64>() {
+        Ok(seconds) => Ok(Duration::from_secs(seconds)),
+        Err(_) => humantime::parse_duration(arg),
+    }
+}
+
+/// A duration of at least a second, polling any faster only runs into the rate limit
+fn parse_poll_interval(arg: &str) -> Result<Duration, String> {
+    let interval = parse_duration(arg).map_err(|e| e.to_string())?;
+    if interval < Duration::from_secs(1) {
+        return Err("poll at most once a second".into());
+    }
+    Ok(interval)
+}
@@ -0,0 +1,524 @@
+use crate::api_key::ApiKey;
+use crate::error::RenderError;
+use crate::models::{
+    Deploy, DeployStatus, Instance, ListDeploysResponse, ListServiceResponse, ScaleRequest,
+    Service, TriggerDeployRequest,
+};
+use crate::observer::{Observer, RequestEvent};
+use crate::pagination::Pages;
+use crate::retry::{parse_retry_after, RetryPolicy};
+use futures::TryStreamExt;
+use reqwest::header;
+use reqwest::{Client, ClientBuilder, RequestBuilder};
+use serde::de::DeserializeOwned;
+use std::time::{Duration, Instant};
+use tokio::time::sleep;
+
+const API_BASE_URL: &str = "https://api.render.com/v1";
+
+/// How long and how often to poll a deploy in [`RenderClient::wait_for_deploy`]
+#[derive(Debug, Clone)]
+pub struct WaitOptions {
+    /// give up after this long, doesn't cancel the deploy
+    pub timeout: Duration,
+    pub poll_interval: Duration,
+}
+
+impl Default for WaitOptions {
+    fn default() -> Self {
+        WaitOptions {
+            timeout: Duration::from_secs(600),
+            poll_interval: Duration::from_secs(5),
+        }
+    }
+}
+
+/// An async client for the render.com api
+#[derive(Debug, Clone)]
+pub struct RenderClient {
+    pub(crate) client: Client,
+    base_url: String,
+    retry: RetryPolicy,
+    owner_id: Option<String>,
+    observer: Observer,
+    /// sent with every request, kept to show observers
+    headers: header::HeaderMap,
+}
+
+impl RenderClient {
+    pub fn new(api_key: impl Into<ApiKey>) -> Result<Self, RenderError> {
+        Self::from_builder(api_key, Client::builder())
+    }
+
+    /// Build on a [`reqwest::ClientBuilder`] you've configured, eg with extra root certificates
+    /// or an explicit proxy. Proxies from `HTTPS_PROXY`, `HTTP_PROXY`, `ALL_PROXY` and
+    /// `NO_PROXY` are used unless the builder turns them off.
+    pub fn from_builder(
+        api_key: impl Into<ApiKey>,
+        builder: ClientBuilder,
+    ) -> Result<Self, RenderError> {
+        let mut headers = header::HeaderMap::new();
+        let bearer = format!("Bearer {}", api_key.into().expose());
+        // the error is left out, it can quote the header
+        let mut authorization =
+            header::HeaderValue::from_str(&bearer).map_err(|_| RenderError::InvalidApiKey)?;
+        // keeps the key out of `Debug`, including the headers given to observers
+        authorization.set_sensitive(true);
+        headers.insert(header::AUTHORIZATION, authorization);
+        headers.insert(
+            header::ACCEPT,
+            header::HeaderValue::from_static("application/json"),
+        );
+
+        let client = builder
+            .user_agent("render-deploy: https://github.com/reconbot/render-deploy")
+            .default_headers(headers.clone())
+            .timeout(Duration::from_secs(30))
+            .gzip(true)
+            .build()?;
+
+        Ok(RenderClient {
+            client,
+            base_url: API_BASE_URL.to_string(),
+            retry: RetryPolicy::default(),
+            owner_id: None,
+            observer: Observer::default(),
+            headers,
+        })
+    }
+
+    /// Replace the default [`RetryPolicy`], use [`RetryPolicy::none`] to fail on the first error
+    pub fn with_retry(self, retry: RetryPolicy) -> Self {
+        RenderClient { retry, ..self }
+    }
+
+    /// Send requests somewhere other than `https://api.render.com/v1`, like a mock server
+    pub fn with_base_url(self, base_url: &str) -> Self {
+        RenderClient {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            ..self
+        }
+    }
+
+    /// Only list and find services in one workspace (`usr-...` or `tea-...`), names are only
+    /// unique within a workspace
+    pub fn with_owner(self, owner_id: &str) -> Self {
+        RenderClient {
+            owner_id: Some(owner_id.to_string()),
+            ..self
+        }
+    }
+
+    /// Call `observer` as requests are sent and retried, eg to log them
+    ///
+    /// ```no_run
+    /// use render_deploy::{RenderClient, RenderError, RequestEvent};
+    ///
+    /// # fn client() -> Result<RenderClient, RenderError> {
+    /// let client = RenderClient::new("rnd_xxxxx")?.with_observer(|event| {
+    ///     if let RequestEvent::Sent { method, url, status, elapsed, .. } = event {
+    ///         eprintln!("{} {} {:?} {:?}", method, url, status, elapsed);
+    ///     }
+    /// });
+    /// # Ok(client)
+    /// # }
+    /// ```
+    pub fn with_observer(self, observer: impl Fn(&RequestEvent) + Send + Sync + 'static) -> Self {
+        RenderClient {
+            observer: Observer::new(observer),
+            ..self
+        }
+    }
+
+    /// The workspace [`RenderClient::with_owner`] picked, if any
+    pub fn owner_id(&self) -> Option<&str> {
+        self.owner_id.as_deref()
+    }
+
+    /// The `ownerId` filter for service lookups, if [`RenderClient::with_owner`] set one
+    pub(crate) fn owner_query(&self) -> Vec<(String, String)> {
+        self.owner_id
+            .iter()
+            .map(|owner_id| ("ownerId".to_string(), owner_id.clone()))
+            .collect()
+    }
+
+    pub(crate) fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base_url, path)
+    }
+
+    pub(crate) async fn get<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        query: &[(String, String)],
+    ) -> Result<T, RenderError> {
+        self.send(self.client.get(self.url(path)).query(query))
+            .await
+    }
+
+    /// Send a request, retrying transient failures according to our [`RetryPolicy`]
+    pub(crate) async fn send<T: DeserializeOwned>(
+        &self,
+        mut request: RequestBuilder,
+    ) -> Result<T, RenderError> {
+        let mut attempt = 0;
+        loop {
+            // every request we make has a body that can be cloned
+            let retry = request.try_clone().expect("request can be cloned");
+            let (method, url, result) = self.send_once(request).await?;
+            let error = match result {
+                Ok(body) => return Ok(body),
+                Err(error) => error,
+            };
+            if !self.retry.should_retry(&method, &error, attempt) {
+                return Err(error);
+            }
+            let delay = self.retry.delay(&error, attempt);
+            self.observer.observe(RequestEvent::Retrying {
+                method: &method,
+                url: &url,
+                attempt: attempt + 1,
+                delay,
+                error: &error,
+            });
+            sleep(delay).await;
+            attempt += 1;
+            request = retry;
+        }
+    }
+
+    /// Send a request once, returning the method so we know if it's safe to retry
+    async fn send_once<T: DeserializeOwned>(
+        &self,
+        request: RequestBuilder,
+    ) -> Result<(reqwest::Method, reqwest::Url, Result<T, RenderError>), RenderError> {
+        let (client, request) = request.build_split();
+        let request = request?;
+        let method = request.method().clone();
+        let url = request.url().clone();
+        let mut request_headers = self.headers.clone();
+        request_headers.extend(request.headers().clone());
+        let start = Instant::now();
+        let mut response_meta = None;
+        let result = async {
+            let response = client.execute(request).await?;
+            response_meta = Some((response.status(), response.headers().clone()));
+            let status = response.status();
+            let retry_after = parse_retry_after(status, response.headers());
+            let body = response.text().await?;
+            if !status.is_success() {
+                return Err(RenderError::Api {
+                    status,
+                    body,
+                    retry_after,
+                    request: Some(format!("{} {}", method, url)),
+                });
+            }
+            // deletes come back 204 with nothing to parse
+            let json = if body.is_empty() { "null" } else { &body };
+            serde_json::from_str(json).map_err(|error| RenderError::Json { error, body })
+        }
+        .await;
+        self.observer.observe(RequestEvent::Sent {
+            method: &method,
+            url: &url,
+            status: response_meta.as_ref().map(|(status, _)| *status),
+            elapsed: start.elapsed(),
+            request_headers: &request_headers,
+            response_headers: response_meta.as_ref().map(|(_, headers)| headers),
+        });
+        if let Err(RenderError::Json { body, .. }) = &result {
+            self.observer.observe(RequestEvent::Unparseable {
+                method: &method,
+                url: &url,
+                body,
+            });
+        }
+        Ok((method, url, result))
+    }
+
+    pub async fn get_service(&self, service_id: &str) -> Result<Service, RenderError> {
+        self.get(&format!("/services/{}", service_id), &[]).await
+    }
+
+    /// Every service the api key can see, fetched a page at a time as you iterate
+    pub fn list_services(&self) -> Pages<'_, ListServiceResponse> {
+        Pages::new(self, "/services".to_string(), self.owner_query())
+    }
+
+    /// Every service whose name matches `name`, render matches names by prefix
+    pub fn find_services(&self, name: &str) -> Pages<'_, ListServiceResponse> {
+        let mut query = self.owner_query();
+        query.push(("name".into(), name.into()));
+        Pages::new(self, "/services".to_string(), query)
+    }
+
+    /// Find the service named exactly `name`. Similarly named services are listed in the
+    /// [`RenderError::ServiceNotFound`] or [`RenderError::AmbiguousService`] errors.
+    pub async fn find_service(&self, name: &str) -> Result<Service, RenderError> {
+        let services = self.find_services(name).try_collect().await?;
+        pick_service(name, services)
+    }
+
+    /// The first service whose name matches `name`, render matches names by prefix
+    pub async fn find_service_fuzzy(&self, name: &str) -> Result<Option<Service>, RenderError> {
+        let request = self
+            .client
+            .get(self.url("/services"))
+            .query(&[("name", name), ("limit", "1")])
+            .query(&self.owner_query());
+        let services: Vec<ListServiceResponse> = self.send(request).await?;
+        Ok(services.into_iter().next().map(|resp| resp.service))
+    }
+
+    /// Stop every instance of a service until it's resumed, render doesn't bill suspended
+    /// services
+    pub async fn suspend_service(&self, service_id: &str) -> Result<(), RenderError> {
+        let request = self
+            .client
+            .post(self.url(&format!("/services/{}/suspend", service_id)));
+        self.send::<serde_json::Value>(request).await?;
+        Ok(())
+    }
+
+    pub async fn resume_service(&self, service_id: &str) -> Result<(), RenderError> {
+        let request = self
+            .client
+            .post(self.url(&format!("/services/{}/resume", service_id)));
+        self.send::<serde_json::Value>(request).await?;
+        Ok(())
+    }
+
+    /// Poll a service until it's `suspended` or not
+    pub async fn wait_for_suspended(
+        &self,
+        service_id: &str,
+        suspended: bool,
+        options: &WaitOptions,
+    ) -> Result<Service, RenderError> {
+        let start = Instant::now();
+        loop {
+            if start.elapsed() > options.timeout {
+                return Err(RenderError::ServiceTimeout {
+                    service_id: service_id.to_string(),
+                    elapsed: start.elapsed(),
+                });
+            }
+            sleep(options.poll_interval).await;
+            match self.get_service(service_id).await {
+                Ok(service) if service.suspended == suspended => return Ok(service),
+                Ok(_) => {}
+                Err(e) if e.is_transient() => {}
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// The instances running a service right now
+    pub async fn list_instances(&self, service_id: &str) -> Result<Vec<Instance>, RenderError> {
+        self.get(&format!("/services/{}/instances", service_id), &[])
+            .await
+    }
+
+    /// Run this many instances of a service, render ignores this while autoscaling is on
+    pub async fn scale_service(
+        &self,
+        service_id: &str,
+        num_instances: u32,
+    ) -> Result<(), RenderError> {
+        let request = self
+            .client
+            .post(self.url(&format!("/services/{}/scale", service_id)))
+            .json(&ScaleRequest { num_instances });
+        self.send::<serde_json::Value>(request).await?;
+        Ok(())
+    }
+
+    /// Poll a service's instances until there are `num_instances` of them
+    pub async fn wait_for_instances(
+        &self,
+        service_id: &str,
+        num_instances: u32,
+        options: &WaitOptions,
+    ) -> Result<Vec<Instance>, RenderError> {
+        let start = Instant::now();
+        loop {
+            if start.elapsed() > options.timeout {
+                return Err(RenderError::ServiceTimeout {
+                    service_id: service_id.to_string(),
+                    elapsed: start.elapsed(),
+                });
+            }
+            sleep(options.poll_interval).await;
+            match self.list_instances(service_id).await {
+                Ok(instances) if instances.len() == num_instances as usize => return Ok(instances),
+                Ok(_) => {}
+                Err(e) if e.is_transient() => {}
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    pub async fn trigger_deploy(
+        &self,
+        service_id: &str,
+        request: &TriggerDeployRequest,
+    ) -> Result<Deploy, RenderError> {
+        let request = self
+            .client
+            .post(self.url(&format!("/services/{}/deploys", service_id)))
+            .json(request);
+        self.send(request).await
+    }
+
+    pub async fn latest_deploy(&self, service_id: &str) -> Result<Option<Deploy>, RenderError> {
+        let request = self
+            .client
+            .get(self.url(&format!("/services/{}/deploys", service_id)))
+            .query(&[("limit", "1")]);
+        let deploys: Vec<ListDeploysResponse> = self.send(request).await?;
+        Ok(deploys.into_iter().next().map(|resp| resp.deploy))
+    }
+
+    /// The most recent deploys of a service, newest first
+    pub async fn list_deploys(
+        &self,
+        service_id: &str,
+        limit: u32,
+    ) -> Result<Vec<Deploy>, RenderError> {
+        let request = self
+            .client
+            .get(self.url(&format!("/services/{}/deploys", service_id)))
+            .query(&[("limit", limit.to_string())]);
+        let deploys: Vec<ListDeploysResponse> = self.send(request).await?;
+        Ok(deploys.into_iter().map(|resp| resp.deploy).collect())
+    }
+
+    /// Every deploy of a service, newest first, fetched a page at a time as you iterate
+    pub fn deploy_history(&self, service_id: &str) -> Pages<'_, ListDeploysResponse> {
+        Pages::new(self, format!("/services/{}/deploys", service_id), vec![])
+    }
+
+    pub async fn cancel_deploy(
+        &self,
+        service_id: &str,
+        deploy_id: &str,
+    ) -> Result<Deploy, RenderError> {
+        let request = self.client.post(self.url(&format!(
+            "/services/{service_id}/deploys/{deploy_id}/cancel",
+            service_id = service_id,
+            deploy_id = deploy_id
+        )));
+        self.send(request).await
+    }
+
+    pub async fn get_deploy(
+        &self,
+        service_id: &str,
+        deploy_id: &str,
+    ) -> Result<Deploy, RenderError> {
+        let request = self.client.get(self.url(&format!(
+            "/services/{service_id}/deploys/{deploy_id}",
+            service_id = service_id,
+            deploy_id = deploy_id
+        )));
+        self.send(request).await
+    }
+
+    /// Poll a deploy until it's live, calling `on_poll` with every status we see. A deploy that
+    /// stops without going live is a [`RenderError::DeployFailed`]. Rate limits and other
+    /// transient failures that outlast the [`RetryPolicy`] don't end the wait, we back off and
+    /// keep polling until the timeout.
+    pub async fn wait_for_deploy<F>(
+        &self,
+        service_id: &str,
+        deploy_id: &str,
+        options: &WaitOptions,
+        mut on_poll: F,
+    ) -> Result<Deploy, RenderError>
+    where
+        F: FnMut(&Deploy),
+    {
+        let start = Instant::now();
+        loop {
+            if start.elapsed() > options.timeout {
+                return Err(RenderError::Timeout {
+                    deploy_id: deploy_id.to_string(),
+                    elapsed: start.elapsed(),
+                });
+            }
+            sleep(options.poll_interval).await;
+            let deploy = match self.get_deploy(service_id, deploy_id).await {
+                Ok(deploy) => deploy,
+                Err(RenderError::Api {
+                    retry_after: Some(retry_after),
+                    ..
+                }) => {
+                    let remaining = options.timeout.saturating_sub(start.elapsed());
+                    sleep(retry_after.min(remaining)).await;
+                    continue;
+                }
+                Err(e) if e.is_transient() => continue,
+                Err(e) => return Err(e),
+            };
+            on_poll(&deploy);
+            if deploy.status == DeployStatus::Live {
+                return Ok(deploy);
+            }
+            if deploy.status.is_failed() {
+                return Err(RenderError::DeployFailed(Box::new(deploy)));
+            }
+        }
+    }
+}
+
+/// The one service named exactly `name`
+pub(crate) fn pick_service(name: &str, services: Vec<Service>) -> Result<Service, RenderError> {
+    let (exact, similar): (Vec<Service>, Vec<Service>) = services
+        .into_iter()
+        .partition(|service| service.name == name);
+    match exact.len() {
+        1 => Ok(exact.into_iter().next().expect("one service")),
+        0 => Err(RenderError::ServiceNotFound {
+            name: name.to_string(),
+            candidates: similar.iter().map(describe_service).collect(),
+        }),
+        _ => Err(RenderError::AmbiguousService {
+            name: name.to_string(),
+            candidates: exact.iter().map(describe_service).collect(),
+        }),
+    }
+}
+
+fn describe_service(service: &Service) -> String {
+    format!("{} ({})", service.name, service.id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::service;
+
+    #[test]
+    fn pick_service_requires_an_exact_name() {
+        let services = vec![service("srv-2", "api-staging"), service("srv-1", "api")];
+        assert_eq!(pick_service("api", services).unwrap().id, "srv-1");
+
+        let services = vec![service("srv-2", "api-staging")];
+        match pick_service("api", services) {
+            Err(RenderError::ServiceNotFound { candidates, .. }) => {
+                assert_eq!(candidates, vec!["api-staging (srv-2)"])
+            }
+            other => panic!("expected not found {:?}", other),
+        }
+    }
+
+    #[test]
+    fn pick_service_rejects_duplicate_names() {
+        let services = vec![service("srv-1", "api"), service("srv-2", "api")];
+        assert!(matches!(
+            pick_service("api", services),
+            Err(RenderError::AmbiguousService { .. })
+        ));
+    }
+}
@@ -0,0 +1,298 @@
+use super::env::EnvDiff;
+use super::service::{
+    create_request, load, only_owner, setting_changes, update_request, ServiceFile, SettingChange,
+};
+use super::{confirm, Context};
+use crate::cli::ApplyArgs;
+use futures::TryStreamExt;
+use render_deploy::{EnvVar, RenderError, Service, ServiceType};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// The file `apply` reads
+///
+/// ```yaml
+/// services:
+///   - name: api
+///     repo: https://github.com/reconbot/render-deploy
+///     runtime: node
+///     startCommand: npm start
+///     numInstances: 2
+///     envVars:
+///       - key: LOG_LEVEL
+///         value: info
+/// ```
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+struct ApplyFile {
+    services: Vec<ServiceFile>,
+}
+
+#[derive(Serialize, Debug, PartialEq, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+enum Action {
+    Create,
+    Update,
+    Unchanged,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+struct InstancesChange {
+    before: u32,
+    after: u32,
+}
+
+/// What applying the file does to one service
+#[derive(Serialize, Debug)]
+struct ServicePlan {
+    name: String,
+    action: Action,
+    settings: Vec<SettingChange>,
+    env: EnvDiff,
+    instances: Option<InstancesChange>,
+    /// the live service, `None` when it's to be created
+    #[serde(skip)]
+    service: Option<Service>,
+    #[serde(skip)]
+    spec: ServiceFile,
+}
+
+/// What `--output json` prints
+#[derive(Serialize, Debug)]
+struct ApplyResult {
+    plan: Vec<ServicePlan>,
+    /// false for --dry-run or when everything was up to date
+    applied: bool,
+}
+
+pub async fn run(ctx: &Context, args: &ApplyArgs) -> Result<(), RenderError> {
+    let file: ApplyFile = load(&args.file)?;
+    let mut names = BTreeSet::new();
+    for spec in &file.services {
+        let name = spec.name.as_deref().ok_or_else(|| {
+            RenderError::Config(format!(
+                "Every service in {} needs a name",
+                args.file.display()
+            ))
+        })?;
+        if !names.insert(name) {
+            return Err(RenderError::Config(format!(
+                "{} is in {} more than once",
+                name,
+                args.file.display()
+            )));
+        }
+    }
+
+    let mut plan = vec![];
+    for spec in file.services {
+        plan.push(plan_service(ctx, spec, args.prune).await?);
+    }
+    for service in &plan {
+        print_plan(ctx, service);
+    }
+    let count = |action: Action| plan.iter().filter(|plan| plan.action == action).count();
+    ctx.output.info(format!(
+        "Plan: {} to create, {} to update, {} unchanged",
+        count(Action::Create),
+        count(Action::Update),
+        count(Action::Unchanged)
+    ));
+
+    if plan.iter().all(|plan| plan.action == Action::Unchanged) || args.dry_run {
+        if args.dry_run {
+            ctx.output.info("Dry run, nothing was changed");
+        }
+        return ctx.output.result(&ApplyResult {
+            plan,
+            applied: false,
+        });
+    }
+    if !args.yes && !confirm("Apply this plan?")? {
+        return Err(RenderError::Config(
+            "Apply canceled, nothing was changed".into(),
+        ));
+    }
+    for service in &plan {
+        apply_service(ctx, service).await?;
+    }
+    ctx.output.result(&ApplyResult {
+        plan,
+        applied: true,
+    })
+}
+
+/// Compare one service in the file with the live one
+async fn plan_service(
+    ctx: &Context,
+    spec: ServiceFile,
+    prune: bool,
+) -> Result<ServicePlan, RenderError> {
+    let name = spec.name.clone().unwrap_or_default();
+    let local: BTreeMap<String, String> = spec
+        .env_vars
+        .iter()
+        .map(|env_var| (env_var.key.clone(), env_var.value.clone()))
+        .collect();
+    let service = match ctx.client.find_service(&name).await {
+        Ok(service) => service,
+        Err(RenderError::ServiceNotFound { .. }) => {
+            // catch a missing repo or runtime before anything is changed
+            create_request(spec.clone(), String::new())?;
+            return Ok(ServicePlan {
+                name,
+                action: Action::Create,
+                settings: vec![],
+                env: EnvDiff::new(&local, &[], prune),
+                instances: None,
+                service: None,
+                spec,
+            });
+        }
+        Err(e) => return Err(e),
+    };
+    if let Some(service_type) = spec.service_type {
+        if ServiceType::from(service_type) != service.service_type {
+            return Err(RenderError::Config(format!(
+                "{} is a {}, delete it to make it a {}",
+                name,
+                service.service_type,
+                ServiceType::from(service_type)
+            )));
+        }
+    }
+
+    let current = ctx.client.get_service_settings(&service.id).await?;
+    let settings = setting_changes(&current, &spec.settings());
+    let remote: Vec<EnvVar> = ctx.client.list_env_vars(&service.id).try_collect().await?;
+    let env = EnvDiff::new(&local, &remote, prune);
+    let instances = match spec.num_instances {
+        Some(after) => {
+            let before = ctx.client.list_instances(&service.id).await?.len() as u32;
+            (before != after).then_some(InstancesChange { before, after })
+        }
+        None => None,
+    };
+    let action = if settings.is_empty() && env.is_empty() && instances.is_none() {
+        Action::Unchanged
+    } else {
+        Action::Update
+    };
+    Ok(ServicePlan {
+        name,
+        action,
+        settings,
+        env,
+        instances,
+        service: Some(service),
+        spec,
+    })
+}
+
+/// Terraform style, `+` for what's added, `~` for what's changed and `-` for what's removed
+fn print_plan(ctx: &Context, plan: &ServicePlan) {
+    match plan.action {
+        Action::Create => ctx.output.info(format!("+ {} will be created", plan.name)),
+        Action::Update => ctx.output.info(format!("~ {} will be updated", plan.name)),
+        Action::Unchanged => {
+            ctx.output.info(format!("  {} is up to date", plan.name));
+            return;
+        }
+    }
+    for change in &plan.settings {
+        ctx.output.info(format!(
+            "    ~ {}: {} -> {}",
+            change.setting,
+            change.before.as_deref().unwrap_or("(none)"),
+            change.after
+        ));
+    }
+    for key in &plan.env.added {
+        ctx.output.info(format!("    + env {}", key));
+    }
+    for key in &plan.env.changed {
+        ctx.output.info(format!("    ~ env {}", key));
+    }
+    for key in &plan.env.removed {
+        ctx.output.info(format!("    - env {}", key));
+    }
+    if let Some(instances) = &plan.instances {
+        ctx.output.info(format!(
+            "    ~ instances: {} -> {}",
+            instances.before, instances.after
+        ));
+    }
+}
+
+async fn apply_service(ctx: &Context, plan: &ServicePlan) -> Result<(), RenderError> {
+    let Some(service) = &plan.service else {
+        let owner_id = match ctx.client.owner_id() {
+            Some(owner_id) => owner_id.to_string(),
+            None => only_owner(ctx).await?.id,
+        };
+        let request = create_request(plan.spec.clone(), owner_id)?;
+        let created = ctx.client.create_service(&request).await?;
+        ctx.output.info(format!(
+            "Created {} ({}) {}",
+            created.service.name, created.service.id, created.service.dashboard_url
+        ));
+        return Ok(());
+    };
+    if plan.action == Action::Unchanged {
+        return Ok(());
+    }
+
+    if !plan.settings.is_empty() {
+        let request = update_request(service, &plan.settings);
+        ctx.client.update_service(&service.id, &request).await?;
+    }
+    for env_var in &plan.spec.env_vars {
+        if plan.env.added.contains(&env_var.key) || plan.env.changed.contains(&env_var.key) {
+            ctx.client
+                .set_env_var(&service.id, &env_var.key, &env_var.value)
+                .await?;
+        }
+    }
+    for key in &plan.env.removed {
+        ctx.client.delete_env_var(&service.id, key).await?;
+    }
+    if let Some(instances) = &plan.instances {
+        ctx.client
+            .scale_service(&service.id, instances.after)
+            .await?;
+    }
+    ctx.output.info(format!(
+        "Updated {}, deploy it for setting and env var changes to take effect",
+        service.name
+    ));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_apply_file() {
+        let file: ApplyFile = serde_yaml::from_str(
+            r#"
+                services:
+                  - name: api
+                    runtime: node
+                    numInstances: 2
+                    envVars:
+                      - key: LOG_LEVEL
+                        value: info
+                  - name: site
+                    type: static
+                    publishPath: dist
+            "#,
+        )
+        .unwrap();
+        assert_eq!(file.services.len(), 2);
+        assert_eq!(file.services[0].num_instances, Some(2));
+        assert!(
+            serde_yaml::from_str::<ApplyFile>("services:\n  - name: api\n    plans: pro").is_err()
+        );
+    }
+}
@@ -0,0 +1,83 @@
+use super::{owners, Context};
+use crate::cli::{AuthArgs, AuthCommands};
+use crate::credentials;
+use crate::output::Output;
+use dialoguer::console::Term;
+use dialoguer::Password;
+use futures::TryStreamExt;
+use render_deploy::{ApiKey, Owner, RenderError, User};
+use serde::Serialize;
+use std::io::{self, IsTerminal};
+
+/// What `--output json` prints for `auth whoami`
+#[derive(Serialize, Debug)]
+struct Whoami {
+    user: User,
+    /// the workspaces the key can reach
+    owners: Vec<Owner>,
+}
+
+/// `profile` is the profile the key is saved for, `api_key` is the key the client is using
+pub async fn run(
+    ctx: &Context,
+    args: &AuthArgs,
+    profile: &str,
+    api_key: &ApiKey,
+) -> Result<(), RenderError> {
+    match args.command {
+        AuthCommands::Login => login(ctx, profile, api_key).await,
+        AuthCommands::Logout => unreachable!("handled before the client is needed"),
+        AuthCommands::Whoami => whoami(ctx).await,
+    }
+}
+
+async fn login(ctx: &Context, profile: &str, api_key: &ApiKey) -> Result<(), RenderError> {
+    // a key that can't list its workspaces can't do anything else either
+    let owners: Vec<Owner> = ctx.client.list_owners().try_collect().await?;
+    credentials::store(profile, api_key)?;
+    let names: Vec<&str> = owners.iter().map(|owner| owner.name.as_str()).collect();
+    ctx.output.info(format!(
+        "Saved the api key for the {} profile, it can reach {}",
+        profile,
+        names.join(", ")
+    ));
+    Ok(())
+}
+
+/// Doesn't need an api key, so it's run before there's a client
+pub fn logout(output: &Output, profile: &str) -> Result<(), RenderError> {
+    if credentials::delete(profile)? {
+        output.info(format!("Removed the api key for the {} profile", profile));
+    } else {
+        output.info(format!(
+            "There's no api key saved for the {} profile",
+            profile
+        ));
+    }
+    Ok(())
+}
+
+async fn whoami(ctx: &Context) -> Result<(), RenderError> {
+    let user = ctx.client.get_user().await?;
+    let owners: Vec<Owner> = ctx.client.list_owners().try_collect().await?;
+    ctx.output.summary(format!(
+        "{} <{}>, the key can reach:",
+        user.name, user.email
+    ));
+    owners::table(&ctx.output, &owners);
+    ctx.output.result(&Whoami { user, owners })
+}
+
+/// Ask for the key `auth login` is to save, without echoing it
+pub fn prompt() -> Result<ApiKey, RenderError> {
+    if !io::stdin().is_terminal() {
+        return Err(RenderError::Config(
+            "Not a terminal to ask for the api key on, pipe it in with --api-key -".into(),
+        ));
+    }
+    Password::new()
+        .with_prompt("Render api key (from Account Settings > API Keys)")
+        .interact_on(&Term::stderr())
+        .map(ApiKey::new)
+        .map_err(|e| RenderError::Config(format!("Unable to read the api key {}", e)))
+}
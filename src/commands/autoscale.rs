@@ -0,0 +1,197 @@
+use super::Context;
+use crate::cli::{AutoscaleArgs, AutoscaleCommands, AutoscaleSetArgs};
+use render_deploy::{Autoscaling, AutoscalingTarget, RenderError, Service};
+use serde::Serialize;
+
+/// What `--output json` prints
+#[derive(Serialize, Debug)]
+struct AutoscaleResult<'a> {
+    service: &'a Service,
+    autoscaling: Option<&'a Autoscaling>,
+}
+
+pub async fn run(ctx: &Context, args: &AutoscaleArgs) -> Result<(), RenderError> {
+    match &args.command {
+        AutoscaleCommands::Show { name } => {
+            let service = ctx.service(name.as_deref()).await?;
+            let autoscaling = ctx.client.get_autoscaling(&service.id).await?;
+            print_autoscaling(ctx, &service, autoscaling.as_ref());
+            ctx.output.result(&AutoscaleResult {
+                service: &service,
+                autoscaling: autoscaling.as_ref(),
+            })
+        }
+        AutoscaleCommands::Set(args) => {
+            let service = ctx.service(args.name.as_deref()).await?;
+            let current = ctx.client.get_autoscaling(&service.id).await?;
+            let autoscaling = update(current, args).map_err(RenderError::Config)?;
+            let autoscaling = ctx
+                .client
+                .set_autoscaling(&service.id, &autoscaling)
+                .await?;
+            print_autoscaling(ctx, &service, Some(&autoscaling));
+            ctx.output.result(&AutoscaleResult {
+                service: &service,
+                autoscaling: Some(&autoscaling),
+            })
+        }
+    }
+}
+
+fn print_autoscaling(ctx: &Context, service: &Service, autoscaling: Option<&Autoscaling>) {
+    let Some(autoscaling) = autoscaling else {
+        ctx.output
+            .info(format!("{} doesn't autoscale", service.name));
+        return;
+    };
+    let target = |target: &AutoscalingTarget| match target.enabled {
+        true => format!("{}%", target.percentage),
+        false => "off".to_string(),
+    };
+    ctx.output.info(format!(
+        "Autoscaling: {}",
+        if autoscaling.enabled { "on" } else { "off" }
+    ));
+    ctx.output.info(format!(
+        "Instances: {} to {}",
+        autoscaling.min, autoscaling.max
+    ));
+    ctx.output
+        .info(format!("CPU target: {}", target(&autoscaling.criteria.cpu)));
+    ctx.output.info(format!(
+        "Memory target: {}",
+        target(&autoscaling.criteria.memory)
+    ));
+}
+
+/// Apply the flags to a service's current settings
+fn update(current: Option<Autoscaling>, args: &AutoscaleSetArgs) -> Result<Autoscaling, String> {
+    let mut autoscaling = match current {
+        Some(current) => current,
+        None => Autoscaling {
+            enabled: true,
+            min: args
+                .min
+                .ok_or("--min is needed to set up autoscaling for the first time")?,
+            max: args
+                .max
+                .ok_or("--max is needed to set up autoscaling for the first time")?,
+            criteria: Default::default(),
+        },
+    };
+    if args.enable {
+        autoscaling.enabled = true;
+    }
+    if args.disable {
+        autoscaling.enabled = false;
+    }
+    autoscaling.min = args.min.unwrap_or(autoscaling.min);
+    autoscaling.max = args.max.unwrap_or(autoscaling.max);
+    for (target, percentage) in [
+        (&mut autoscaling.criteria.cpu, args.cpu_target),
+        (&mut autoscaling.criteria.memory, args.memory_target),
+    ] {
+        match percentage {
+            Some(0) => target.enabled = false,
+            Some(percentage) => {
+                target.enabled = true;
+                target.percentage = percentage;
+            }
+            None => {}
+        }
+    }
+
+    if autoscaling.min > autoscaling.max {
+        return Err(format!(
+            "--min {} is more than --max {}",
+            autoscaling.min, autoscaling.max
+        ));
+    }
+    if autoscaling.enabled
+        && !autoscaling.criteria.cpu.enabled
+        && !autoscaling.criteria.memory.enabled
+    {
+        return Err("Autoscaling needs a --cpu-target or --memory-target to scale on".into());
+    }
+    Ok(autoscaling)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use render_deploy::AutoscalingCriteria;
+
+    fn args() -> AutoscaleSetArgs {
+        AutoscaleSetArgs {
+            name: None,
+            min: None,
+            max: None,
+            cpu_target: None,
+            memory_target: None,
+            enable: false,
+            disable: false,
+        }
+    }
+
+    #[test]
+    fn update_autoscaling() {
+        assert!(update(None, &args()).is_err());
+        let created = update(
+            None,
+            &AutoscaleSetArgs {
+                min: Some(1),
+                max: Some(3),
+                cpu_target: Some(70),
+                ..args()
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            created,
+            Autoscaling {
+                enabled: true,
+                min: 1,
+                max: 3,
+                criteria: AutoscalingCriteria {
+                    cpu: AutoscalingTarget {
+                        enabled: true,
+                        percentage: 70
+                    },
+                    memory: AutoscalingTarget::default(),
+                },
+            }
+        );
+
+        // only what's given changes
+        let raised = update(
+            Some(created.clone()),
+            &AutoscaleSetArgs {
+                max: Some(10),
+                ..args()
+            },
+        )
+        .unwrap();
+        assert_eq!(raised.max, 10);
+        assert_eq!(raised.criteria, created.criteria);
+
+        let disabled = update(
+            Some(created.clone()),
+            &AutoscaleSetArgs {
+                disable: true,
+                cpu_target: Some(0),
+                ..args()
+            },
+        )
+        .unwrap();
+        assert!(!disabled.enabled);
+        assert!(update(
+            Some(created),
+            &AutoscaleSetArgs {
+                min: Some(5),
+                max: Some(2),
+                ..args()
+            }
+        )
+        .is_err());
+    }
+}
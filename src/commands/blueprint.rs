@@ -0,0 +1,193 @@
+use super::service::{setting_changes, SettingChange};
+use super::Context;
+use crate::blueprint::{self, BlueprintService};
+use crate::cli::{BlueprintArgs, BlueprintCommands};
+use futures::TryStreamExt;
+use render_deploy::{EnvVar, RenderError};
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+
+#[derive(Serialize, Debug, PartialEq, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+enum Drift {
+    /// in the Blueprint but not on render
+    Missing,
+    Changed,
+    InSync,
+    /// key value stores, which the api doesn't list as services
+    Skipped,
+}
+
+/// How one Blueprint service differs from the live one. Setting changes go from live to declared.
+#[derive(Serialize, Debug)]
+struct ServiceDrift {
+    name: String,
+    drift: Drift,
+    settings: Vec<SettingChange>,
+    /// declared but not set on the service
+    missing_env: Vec<String>,
+    /// set on the service but not declared
+    extra_env: Vec<String>,
+}
+
+/// What `--output json` prints for `blueprint diff`
+#[derive(Serialize, Debug)]
+struct DiffResult {
+    services: Vec<ServiceDrift>,
+    drifted: bool,
+}
+
+pub async fn run(ctx: &Context, args: &BlueprintArgs) -> Result<(), RenderError> {
+    match &args.command {
+        BlueprintCommands::Lint { path } => lint(ctx, path),
+        BlueprintCommands::Diff { path } => diff(ctx, path).await,
+    }
+}
+
+fn lint(ctx: &Context, path: &Path) -> Result<(), RenderError> {
+    let source = fs::read_to_string(path)
+        .map_err(|e| RenderError::Config(format!("Unable to read {}: {}", path.display(), e)))?;
+    let problems = blueprint::lint(&source);
+    for problem in &problems {
+        // `file:line:` like compilers do so editors can jump to it
+        let at = match problem.line {
+            Some(line) => format!("{}:{}", path.display(), line),
+            None => path.display().to_string(),
+        };
+        ctx.output.info(format!("{}: {}", at, problem));
+    }
+    ctx.output.result(&problems)?;
+    if problems.is_empty() {
+        ctx.output.info(format!("{} looks good", path.display()));
+        return Ok(());
+    }
+    Err(RenderError::Config(format!(
+        "{} has {} problem{}",
+        path.display(),
+        problems.len(),
+        if problems.len() == 1 { "" } else { "s" }
+    )))
+}
+
+/// Compare the Blueprint with the live services, failing when they've drifted so CI can gate on it
+async fn diff(ctx: &Context, path: &Path) -> Result<(), RenderError> {
+    let file = blueprint::load(path)?;
+    let mut services = vec![];
+    for declared in &file.services {
+        let drift = service_drift(ctx, declared).await?;
+        print_drift(ctx, &drift);
+        services.push(drift);
+    }
+    let drifted = services
+        .iter()
+        .filter(|service| matches!(service.drift, Drift::Missing | Drift::Changed))
+        .count();
+    ctx.output.result(&DiffResult {
+        services,
+        drifted: drifted > 0,
+    })?;
+    if drifted == 0 {
+        ctx.output
+            .info(format!("Live services match {}", path.display()));
+        return Ok(());
+    }
+    Err(RenderError::Config(format!(
+        "{} service{} drifted from {}",
+        drifted,
+        if drifted == 1 { " has" } else { "s have" },
+        path.display()
+    )))
+}
+
+async fn service_drift(
+    ctx: &Context,
+    declared: &BlueprintService,
+) -> Result<ServiceDrift, RenderError> {
+    let mut drift = ServiceDrift {
+        name: declared.name.clone(),
+        drift: Drift::InSync,
+        settings: vec![],
+        missing_env: vec![],
+        extra_env: vec![],
+    };
+    let Some(service_type) = declared.api_type() else {
+        drift.drift = Drift::Skipped;
+        return Ok(drift);
+    };
+    let service = match ctx.client.find_service(&declared.name).await {
+        Ok(service) => service,
+        Err(RenderError::ServiceNotFound { .. }) => {
+            drift.drift = Drift::Missing;
+            return Ok(drift);
+        }
+        Err(e) => return Err(e),
+    };
+
+    if service.service_type != service_type {
+        drift.settings.push(SettingChange {
+            setting: "type",
+            before: Some(service.service_type.to_string()),
+            after: service_type.to_string(),
+        });
+    }
+    let live = ctx.client.get_service_settings(&service.id).await?;
+    drift
+        .settings
+        .extend(setting_changes(&live, &declared.settings()));
+
+    // values can be generated or synced by hand so only keys are compared
+    let live_env: Vec<EnvVar> = ctx.client.list_env_vars(&service.id).try_collect().await?;
+    let declared_env = declared.env_keys();
+    drift.missing_env = declared_env
+        .iter()
+        .filter(|key| !live_env.iter().any(|env_var| env_var.key == **key))
+        .map(|key| key.to_string())
+        .collect();
+    drift.extra_env = live_env
+        .into_iter()
+        .map(|env_var| env_var.key)
+        .filter(|key| !declared_env.contains(key.as_str()))
+        .collect();
+
+    if !drift.settings.is_empty() || !drift.missing_env.is_empty() || !drift.extra_env.is_empty() {
+        drift.drift = Drift::Changed;
+    }
+    Ok(drift)
+}
+
+/// `+` for what the Blueprint has that render doesn't, `-` the other way round
+fn print_drift(ctx: &Context, drift: &ServiceDrift) {
+    match drift.drift {
+        Drift::Missing => {
+            ctx.output.info(format!("+ {} doesn't exist", drift.name));
+            return;
+        }
+        Drift::Changed => ctx.output.info(format!("~ {} has drifted", drift.name)),
+        Drift::InSync => {
+            ctx.output.info(format!("  {} matches", drift.name));
+            return;
+        }
+        Drift::Skipped => {
+            ctx.output.info(format!(
+                "  {} skipped, key value stores aren't compared",
+                drift.name
+            ));
+            return;
+        }
+    }
+    for change in &drift.settings {
+        ctx.output.info(format!(
+            "    ~ {}: {} -> {}",
+            change.setting,
+            change.before.as_deref().unwrap_or("(none)"),
+            change.after
+        ));
+    }
+    for key in &drift.missing_env {
+        ctx.output.info(format!("    + env {}", key));
+    }
+    for key in &drift.extra_env {
+        ctx.output.info(format!("    - env {}", key));
+    }
+}
@@ -0,0 +1,32 @@
+use super::{report_deploy, Context};
+use crate::cli::CancelArgs;
+use render_deploy::{deploy_url, RenderError};
+
+/// How far back to look for a deploy that's still running
+const IN_PROGRESS_SEARCH_LIMIT: u32 = 20;
+
+pub async fn run(ctx: &Context, args: &CancelArgs) -> Result<(), RenderError> {
+    let service = ctx.service(args.name.as_deref()).await?;
+
+    let deploy_id = match &args.deploy_id {
+        Some(deploy_id) => deploy_id.clone(),
+        None => ctx
+            .client
+            .list_deploys(&service.id, IN_PROGRESS_SEARCH_LIMIT)
+            .await?
+            .into_iter()
+            .find(|deploy| deploy.status.is_in_progress())
+            .map(|deploy| deploy.id)
+            .ok_or_else(|| {
+                RenderError::Config(format!("{} has no deploys in progress", service.name))
+            })?,
+    };
+
+    let deploy = ctx.client.cancel_deploy(&service.id, &deploy_id).await?;
+    ctx.output
+        .info(format!("Canceled Deploy {}", deploy.describe()));
+    ctx.output.info(deploy_url(&service, &deploy));
+    ctx.output
+        .info(format!("Status: {}", ctx.output.status(&deploy.status)));
+    report_deploy(ctx, &service, &deploy)
+}
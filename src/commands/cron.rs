@@ -0,0 +1,103 @@
+use super::Context;
+use crate::cli::{CronArgs, CronCommands};
+use futures::{future, StreamExt, TryStreamExt};
+use render_deploy::{CronRunStatus, Event, EventType, RenderError, Service, ServiceType};
+use serde::{Deserialize, Serialize};
+
+/// A finished run, pieced together from its `cron_job_run_ended` event
+#[derive(Serialize, Debug)]
+struct CronRun {
+    id: String,
+    /// `None` for a status render added after we were written
+    status: Option<CronRunStatus>,
+    finished_at: String,
+}
+
+#[derive(Deserialize)]
+struct CronRunEnded {
+    #[serde(rename = "cronJobRunId")]
+    cron_job_run_id: String,
+    status: Option<serde_json::Value>,
+}
+
+impl CronRun {
+    fn from_event(event: Event) -> Option<CronRun> {
+        let details: CronRunEnded = serde_json::from_value(event.details).ok()?;
+        Some(CronRun {
+            id: details.cron_job_run_id,
+            status: details
+                .status
+                .and_then(|status| serde_json::from_value(status).ok()),
+            finished_at: event.timestamp,
+        })
+    }
+}
+
+pub async fn run(ctx: &Context, args: &CronArgs) -> Result<(), RenderError> {
+    match &args.command {
+        CronCommands::Run { name } => {
+            let service = cron_job(ctx, name.as_deref()).await?;
+            let run = ctx.client.trigger_cron_run(&service.id).await?;
+            ctx.output
+                .info(format!("Started run {} of {}", run.id, service.name));
+            ctx.output.info(&service.dashboard_url);
+            ctx.output.result(&run)
+        }
+        CronCommands::Runs { name, limit } => {
+            let service = cron_job(ctx, name.as_deref()).await?;
+            let runs: Vec<CronRun> = ctx
+                .client
+                .list_events(&service.id, None)
+                .try_filter(|event| future::ready(event.event_type == EventType::CronJobRunEnded))
+                .try_filter_map(|event| future::ready(Ok(CronRun::from_event(event))))
+                .take(*limit)
+                .try_collect()
+                .await?;
+            let rows: Vec<Vec<String>> = runs
+                .iter()
+                .map(|run| {
+                    vec![
+                        run.id.clone(),
+                        run.status
+                            .map(|status| status.to_string())
+                            .unwrap_or_else(|| "-".into()),
+                        ctx.output.time(&run.finished_at),
+                    ]
+                })
+                .collect();
+            ctx.output.table(&["RUN", "STATUS", "FINISHED"], &rows);
+            ctx.output.result(&runs)
+        }
+    }
+}
+
+async fn cron_job(ctx: &Context, name: Option<&str>) -> Result<Service, RenderError> {
+    let service = ctx.service(name).await?;
+    if service.service_type != ServiceType::CronJob {
+        return Err(RenderError::Config(format!(
+            "{} is a {}, not a cron job",
+            service.name, service.service_type
+        )));
+    }
+    Ok(service)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn cron_run_from_event() {
+        let event = Event {
+            id: "evt-1".into(),
+            timestamp: "2024-10-14T02:17:35Z".into(),
+            service_id: "crn-1".into(),
+            event_type: EventType::CronJobRunEnded,
+            details: json!({ "cronJobRunId": "crn-run-1", "status": "unsuccessful" }),
+        };
+        let run = CronRun::from_event(event).unwrap();
+        assert_eq!(run.id, "crn-run-1");
+        assert_eq!(run.status, Some(CronRunStatus::Unsuccessful));
+    }
+}
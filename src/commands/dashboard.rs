@@ -0,0 +1,340 @@
+use super::rollback::{previous_live_deploy, redeploy_request};
+use super::Context;
+use crate::cli::DashboardArgs;
+use futures::TryStreamExt;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::widgets::{Row, Table, TableState};
+use ratatui::{DefaultTerminal, Frame};
+use render_deploy::{
+    format_duration, Deploy, DeployStatus, RenderError, Service, TriggerDeployRequest,
+};
+use std::io::{self, IsTerminal};
+use std::time::{Duration, Instant, SystemTime};
+
+/// How often deploys in progress are checked on
+const POLL_INTERVAL: Duration = Duration::from_secs(3);
+/// How long to wait for a key before redrawing, it keeps the timers ticking
+const TICK: Duration = Duration::from_millis(250);
+
+struct ServiceRow {
+    service: Service,
+    latest_deploy: Option<Deploy>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Action {
+    Deploy,
+    Cancel,
+    Rollback,
+}
+
+struct Dashboard {
+    rows: Vec<ServiceRow>,
+    table: TableState,
+    /// the last thing that happened, or the question being asked
+    message: String,
+    /// an action waiting on y to confirm it
+    pending: Option<Action>,
+}
+
+pub async fn run(ctx: &Context, args: &DashboardArgs) -> Result<(), RenderError> {
+    if !io::stdout().is_terminal() {
+        return Err(RenderError::Config(
+            "The dashboard needs a terminal, `list` prints the same for scripts".into(),
+        ));
+    }
+    let mut dashboard = Dashboard {
+        rows: vec![],
+        table: TableState::default().with_selected(0),
+        message: String::new(),
+        pending: None,
+    };
+    // before taking over the screen so a bad api key is reported like anywhere else
+    dashboard.reload(ctx).await?;
+    let mut terminal = ratatui::init();
+    let result = dashboard.run(ctx, args, &mut terminal).await;
+    ratatui::restore();
+    result
+}
+
+impl Dashboard {
+    async fn run(
+        &mut self,
+        ctx: &Context,
+        args: &DashboardArgs,
+        terminal: &mut DefaultTerminal,
+    ) -> Result<(), RenderError> {
+        let mut reloaded = Instant::now();
+        let mut polled = Instant::now();
+        loop {
+            terminal
+                .draw(|frame| self.draw(frame))
+                .map_err(terminal_error)?;
+            if event::poll(TICK).map_err(terminal_error)? {
+                if let Event::Key(key) = event::read().map_err(terminal_error)? {
+                    if key.kind == KeyEventKind::Press && !self.key(ctx, key.code).await {
+                        return Ok(());
+                    }
+                }
+            }
+            // api errors are shown rather than closing the dashboard, they're often blips
+            if reloaded.elapsed() >= args.refresh {
+                if let Err(e) = self.reload(ctx).await {
+                    self.message = e.to_string();
+                }
+                reloaded = Instant::now();
+                polled = Instant::now();
+            } else if polled.elapsed() >= POLL_INTERVAL {
+                if let Err(e) = self.poll_in_progress(ctx).await {
+                    self.message = e.to_string();
+                }
+                polled = Instant::now();
+            }
+        }
+    }
+
+    /// Every service and its latest deploy
+    async fn reload(&mut self, ctx: &Context) -> Result<(), RenderError> {
+        let services: Vec<Service> = ctx.client.list_services().try_collect().await?;
+        let mut rows = vec![];
+        for service in services {
+            let latest_deploy = ctx.client.latest_deploy(&service.id).await?;
+            rows.push(ServiceRow {
+                service,
+                latest_deploy,
+            });
+        }
+        self.rows = rows;
+        if self.table.selected().is_some_and(|i| i >= self.rows.len()) {
+            self.table.select(Some(self.rows.len().saturating_sub(1)));
+        }
+        Ok(())
+    }
+
+    /// Catch up on deploys that haven't finished
+    async fn poll_in_progress(&mut self, ctx: &Context) -> Result<(), RenderError> {
+        for row in &mut self.rows {
+            let Some(deploy) = &row.latest_deploy else {
+                continue;
+            };
+            if deploy.status.is_in_progress() {
+                row.latest_deploy = Some(ctx.client.get_deploy(&row.service.id, &deploy.id).await?);
+            }
+        }
+        Ok(())
+    }
+
+    /// Handle a key press, false to quit
+    async fn key(&mut self, ctx: &Context, key: KeyCode) -> bool {
+        if let Some(action) = self.pending.take() {
+            self.message = match key {
+                KeyCode::Char('y') => match self.act(ctx, action).await {
+                    Ok(message) => message,
+                    Err(e) => e.to_string(),
+                },
+                _ => "Nothing was changed".into(),
+            };
+            return true;
+        }
+        match key {
+            KeyCode::Char('q') | KeyCode::Esc => return false,
+            KeyCode::Down | KeyCode::Char('j') => self.table.select_next(),
+            KeyCode::Up | KeyCode::Char('k') => self.table.select_previous(),
+            KeyCode::Char('g') => {
+                self.message = match self.reload(ctx).await {
+                    Ok(()) => "Reloaded".into(),
+                    Err(e) => e.to_string(),
+                }
+            }
+            KeyCode::Char('d') => self.ask(Action::Deploy),
+            KeyCode::Char('c') => self.ask(Action::Cancel),
+            KeyCode::Char('r') => self.ask(Action::Rollback),
+            _ => {}
+        }
+        true
+    }
+
+    fn selected(&self) -> Option<&ServiceRow> {
+        self.rows.get(self.table.selected()?)
+    }
+
+    /// Ask before doing anything to a service
+    fn ask(&mut self, action: Action) {
+        let Some(row) = self.selected() else {
+            return;
+        };
+        let in_progress = row
+            .latest_deploy
+            .as_ref()
+            .is_some_and(|deploy| deploy.status.is_in_progress());
+        self.message = match action {
+            Action::Cancel if !in_progress => {
+                format!("{} has no deploy in progress", row.service.name)
+            }
+            Action::Deploy => format!("Deploy {}? (y/n)", row.service.name),
+            Action::Cancel => format!("Cancel {}'s deploy? (y/n)", row.service.name),
+            Action::Rollback => format!("Roll {} back? (y/n)", row.service.name),
+        };
+        if action != Action::Cancel || in_progress {
+            self.pending = Some(action);
+        }
+    }
+
+    async fn act(&mut self, ctx: &Context, action: Action) -> Result<String, RenderError> {
+        let Some(index) = self.table.selected().filter(|i| *i < self.rows.len()) else {
+            return Ok(String::new());
+        };
+        let row = &mut self.rows[index];
+        let service = &row.service;
+        let (deploy, message) = match action {
+            Action::Deploy => {
+                let deploy = ctx
+                    .client
+                    .trigger_deploy(&service.id, &TriggerDeployRequest::default())
+                    .await?;
+                let message = format!("Deploying {}", service.name);
+                (deploy, message)
+            }
+            Action::Cancel => {
+                let Some(deploy) = &row.latest_deploy else {
+                    return Ok(String::new());
+                };
+                let deploy = ctx.client.cancel_deploy(&service.id, &deploy.id).await?;
+                let message = format!("Canceled {}'s deploy", service.name);
+                (deploy, message)
+            }
+            Action::Rollback => {
+                let previous = previous_live_deploy(ctx.client.deploy_history(&service.id))
+                    .await?
+                    .ok_or_else(|| {
+                        RenderError::Config(format!(
+                            "{} has no previously live deploy to roll back to",
+                            service.name
+                        ))
+                    })?;
+                let request = redeploy_request(&previous)?;
+                let deploy = ctx.client.trigger_deploy(&service.id, &request).await?;
+                let message = format!("Rolling {} back to {}", service.name, previous.describe());
+                (deploy, message)
+            }
+        };
+        row.latest_deploy = Some(deploy);
+        Ok(message)
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [table_area, message_area, help_area] = Layout::vertical([
+            Constraint::Min(3),
+            Constraint::Length(1),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+
+        let now = SystemTime::now();
+        let rows = self.rows.iter().map(|row| {
+            let (status, deployed, time, style) = match &row.latest_deploy {
+                Some(deploy) => (
+                    deploy.status.to_string(),
+                    deploy
+                        .describe()
+                        .lines()
+                        .next()
+                        .unwrap_or_default()
+                        .to_string(),
+                    deploy_time(deploy, now),
+                    status_style(&deploy.status),
+                ),
+                None => ("-".into(), "-".into(), "-".into(), Style::default()),
+            };
+            Row::new(vec![
+                row.service.name.clone(),
+                row.service.service_type.to_string(),
+                row.service.branch.clone().unwrap_or_else(|| "-".into()),
+                status,
+                time,
+                deployed,
+            ])
+            .style(style)
+        });
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Fill(2),
+                Constraint::Length(17),
+                Constraint::Fill(1),
+                Constraint::Length(20),
+                Constraint::Length(10),
+                Constraint::Fill(4),
+            ],
+        )
+        .header(
+            Row::new(["NAME", "TYPE", "BRANCH", "STATUS", "TIME", "DEPLOY"])
+                .style(Style::default().add_modifier(Modifier::BOLD)),
+        )
+        .row_highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(table, table_area, &mut self.table);
+        frame.render_widget(self.message.as_str(), message_area);
+        frame.render_widget(
+            "↑/↓ pick  d deploy  c cancel  r roll back  g reload  q quit",
+            help_area,
+        );
+    }
+}
+
+/// How long a deploy has been going, or how long it took
+fn deploy_time(deploy: &Deploy, now: SystemTime) -> String {
+    let took = if deploy.status.is_in_progress() {
+        humantime::parse_rfc3339_weak(&deploy.created_at)
+            .ok()
+            .and_then(|created_at| now.duration_since(created_at).ok())
+    } else {
+        deploy.duration()
+    };
+    match took {
+        Some(took) => format_duration(took),
+        None => "-".into(),
+    }
+}
+
+fn status_style(status: &DeployStatus) -> Style {
+    if status.is_in_progress() {
+        Style::default().fg(Color::Yellow)
+    } else if status.is_failed() {
+        Style::default().fg(Color::Red)
+    } else if *status == DeployStatus::Live {
+        Style::default().fg(Color::Green)
+    } else {
+        Style::default()
+    }
+}
+
+fn terminal_error(e: io::Error) -> RenderError {
+    RenderError::Config(format!("Unable to draw the dashboard {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deploy_times() {
+        let mut deploy: Deploy = serde_json::from_str(
+            r#"{
+                "id": "dep-1",
+                "status": "build_in_progress",
+                "createdAt": "2024-10-14T02:17:35Z",
+                "updatedAt": "2024-10-14T02:17:35Z",
+                "finishedAt": null
+            }"#,
+        )
+        .unwrap();
+        let now = humantime::parse_rfc3339("2024-10-14T02:18:40Z").unwrap();
+        assert_eq!(deploy_time(&deploy, now), "1m5s");
+
+        deploy.status = DeployStatus::Live;
+        deploy.finished_at = Some("2024-10-14T02:19:38Z".into());
+        assert_eq!(deploy_time(&deploy, now), "2m3s");
+    }
+}
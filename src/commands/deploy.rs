@@ -0,0 +1,967 @@
+use super::rollback::redeploy_request;
+use super::{
+    await_deploys, confirm, follow_deploy, glob_match, live_deploy, poll_deploy, report_deploy,
+    report_deploys, watch_deploy, Context,
+};
+use crate::cli::{DeployArgs, IfInProgress};
+use crate::config::Profile;
+use crate::git;
+use crate::git_host;
+use crate::lock::DeployLock;
+use crate::output::{Output, OutputFormat};
+use crate::progress::{typical_deploy_duration, Progress};
+use render_deploy::{
+    deploy_url, image_repository, ClearCache, Deploy, DeployHook, DeployStatus, RenderError,
+    Service, TriggerDeployRequest,
+};
+use serde::Serialize;
+use std::borrow::Cow;
+use std::io;
+use std::time::Instant;
+use tokio::process::Command;
+
+/// How we got a deploy of a service going
+enum Started {
+    /// we triggered a new deploy
+    Triggered(Deploy),
+    /// `--if-in-progress attach` picked up the deploy that was already running
+    Attached(Deploy),
+    /// `--skip-if-current` found what we'd deploy already live
+    Current(Deploy),
+}
+
+pub async fn run(
+    ctx: &Context,
+    args: &DeployArgs,
+    hook: Option<&DeployHook>,
+) -> Result<(), RenderError> {
+    let args = lone_commit(args, hook);
+    let args = &*resolve_commit(&ctx.output, &args)?;
+    if args.post_deploy_cmd.is_some() && ctx.wait_timeout(&args.wait).is_none() {
+        return Err(RenderError::Config(
+            "--post-deploy-cmd runs once the deploy is live, it needs --wait".into(),
+        ));
+    }
+    if args.auto_rollback && ctx.wait_timeout(&args.wait).is_none() {
+        return Err(RenderError::Config(
+            "--auto-rollback needs --wait to know the deploy failed".into(),
+        ));
+    }
+    if args.junit_output.is_some() && ctx.wait_timeout(&args.wait).is_none() {
+        return Err(RenderError::Config(
+            "--junit-output needs --wait to know how the deploy went".into(),
+        ));
+    }
+    if args.lock && ctx.wait_timeout(&args.wait).is_none() {
+        return Err(RenderError::Config(
+            "--lock is let go of once the deploy finishes, it needs --wait".into(),
+        ));
+    }
+    let result = deploy(ctx, args, hook).await;
+    if let Err(e) = ctx.trace.export(&ctx.http, &result).await {
+        ctx.output
+            .info(format!("Unable to export the deploy's trace: {}", e));
+    }
+    match &args.junit_output {
+        Some(path) => match ctx.junit.write(path, &result) {
+            Err(e) if result.is_err() => {
+                ctx.output.info(format!("Warning: {}", e));
+                result
+            }
+            written => result.and(written),
+        },
+        None => result,
+    }
+}
+
+/// Find the services, check we're allowed to deploy them and hold their locks while we do
+async fn deploy(
+    ctx: &Context,
+    args: &DeployArgs,
+    hook: Option<&DeployHook>,
+) -> Result<(), RenderError> {
+    let services = async {
+        match hook {
+            Some(hook) => Ok(vec![ctx.client.get_service(hook.service_id()).await?]),
+            None => ctx.services(args.name.as_deref()).await,
+        }
+    };
+    let services = ctx.trace.time("service lookup", None, services).await?;
+    guard(ctx, args, &services)?;
+
+    let mut locks = vec![];
+    if args.lock {
+        for service in &services {
+            match DeployLock::acquire(&ctx.client, &ctx.output, service, args.lock_wait).await {
+                Ok(lock) => locks.push(lock),
+                Err(e) => {
+                    release(ctx, locks).await;
+                    return Err(e);
+                }
+            }
+        }
+    }
+    let result = match hook {
+        Some(hook) => deploy_with_hook(ctx, args, hook, &services[0]).await,
+        None => deploy_services(ctx, args, &services).await,
+    };
+    match result {
+        // the deploy carries on without us, so it's still holding the service
+        Err(RenderError::Interrupted { .. }) => {
+            locks.into_iter().for_each(|lock| lock.keep(&ctx.output))
+        }
+        _ => release(ctx, locks).await,
+    }
+    result
+}
+
+async fn release(ctx: &Context, locks: Vec<DeployLock>) {
+    for lock in locks {
+        lock.release(&ctx.client, &ctx.output).await;
+    }
+}
+
+/// Deploy with a deploy hook but follow the deploy with the api
+async fn deploy_with_hook(
+    ctx: &Context,
+    args: &DeployArgs,
+    hook: &DeployHook,
+    service: &Service,
+) -> Result<(), RenderError> {
+    let previous = rollback_target(ctx, args, service).await?;
+    let args = &*branch_commit(ctx, service, args).await?;
+    check_pushed(ctx, args, service)?;
+    verify_commit(ctx, args, service).await?;
+    show_changes(ctx, args, service).await?;
+    pre_deploy_service(ctx, args, service).await?;
+    let trigger = trigger_hook(&ctx.output, hook, args);
+    let deploy_id = ctx.trace.time("trigger", Some(service), trigger).await?;
+    let deploy = ctx.client.get_deploy(&service.id, &deploy_id).await?;
+    let result = async {
+        follow_deploy(ctx, service, deploy.clone(), &args.wait).await?;
+        post_deploy(ctx, args, service, &deploy).await
+    }
+    .await;
+    roll_back_on_failure(ctx, args, service, previous, result).await
+}
+
+async fn deploy_services(
+    ctx: &Context,
+    args: &DeployArgs,
+    services: &[Service],
+) -> Result<(), RenderError> {
+    if let [service] = services {
+        let previous = rollback_target(ctx, args, service).await?;
+        let result = match start(ctx, service, args).await? {
+            Started::Triggered(deploy) => {
+                async {
+                    follow_deploy(ctx, service, deploy.clone(), &args.wait).await?;
+                    post_deploy(ctx, args, service, &deploy).await
+                }
+                .await
+            }
+            Started::Attached(deploy) => {
+                async {
+                    watch_deploy(ctx, service, deploy.clone(), &args.wait).await?;
+                    post_deploy(ctx, args, service, &deploy).await
+                }
+                .await
+            }
+            Started::Current(deploy) => return report_deploy(ctx, service, &deploy),
+        };
+        return roll_back_on_failure(ctx, args, service, previous, result).await;
+    }
+    if args.wait.logs {
+        return Err(RenderError::Config(
+            "--logs only works when deploying a single service".into(),
+        ));
+    }
+    if args.auto_rollback {
+        return Err(RenderError::Config(
+            "--auto-rollback only works when deploying a single service".into(),
+        ));
+    }
+
+    let mut started = vec![];
+    // the deploys we started or attached to rather than found already live
+    let mut deployed = vec![];
+    for service in services {
+        let deploy = match start(ctx, service, args).await? {
+            Started::Triggered(deploy) => {
+                ctx.output
+                    .info(format!("Created Deploy {}", deploy.describe()));
+                ctx.output.info(deploy_url(service, &deploy));
+                deployed.push(deploy.id.clone());
+                deploy
+            }
+            Started::Attached(deploy) => {
+                deployed.push(deploy.id.clone());
+                deploy
+            }
+            Started::Current(deploy) => deploy,
+        };
+        ctx.output.info("");
+        started.push((service, deploy));
+    }
+    let Some(timeout) = ctx.wait_timeout(&args.wait) else {
+        return report_deploys(ctx, &started);
+    };
+    let (finished, failure) =
+        await_deploys(ctx, started, &args.wait, Instant::now() + timeout).await;
+    report_deploys(ctx, &finished)?;
+    if let Some(failure) = failure {
+        return Err(failure);
+    }
+    for (service, deploy) in &finished {
+        if deployed.contains(&deploy.id) {
+            post_deploy(ctx, args, service, deploy).await?;
+        }
+    }
+    Ok(())
+}
+
+/// Refuse branches the profile's `deploy_branches` doesn't allow, and ask before deploying to
+/// services it protects
+fn guard(ctx: &Context, args: &DeployArgs, services: &[Service]) -> Result<(), RenderError> {
+    let profile = &ctx.profile;
+    if !profile.deploy_branches.is_empty() {
+        for service in services {
+            let Some(branch) = args.branch.as_ref().or(service.branch.as_ref()) else {
+                continue;
+            };
+            if !branch_allowed(&profile.deploy_branches, branch) {
+                return Err(RenderError::Config(format!(
+                    "The profile only deploys {} to {}, not {}",
+                    profile.deploy_branches.join(", "),
+                    service.name,
+                    branch
+                )));
+            }
+        }
+    }
+    let protected = protected(profile, services);
+    if protected.is_empty() || args.yes {
+        return Ok(());
+    }
+    let question = format!("Deploy {}? The profile protects it.", protected.join(", "));
+    if !confirm(&question)? {
+        return Err(RenderError::Config("Nothing was deployed".into()));
+    }
+    Ok(())
+}
+
+fn branch_allowed(allowed: &[String], branch: &str) -> bool {
+    allowed.iter().any(|pattern| glob_match(pattern, branch))
+}
+
+/// The names of the services the profile protects, all of them when the profile is protected
+fn protected<'a>(profile: &Profile, services: &'a [Service]) -> Vec<&'a str> {
+    services
+        .iter()
+        .filter(|service| {
+            profile.protected == Some(true)
+                || profile
+                    .protected_services
+                    .iter()
+                    .any(|pattern| pattern == &service.id || glob_match(pattern, &service.name))
+        })
+        .map(|service| service.name.as_str())
+        .collect()
+}
+
+/// The deploy `--auto-rollback` goes back to, whatever is live before we deploy
+async fn rollback_target(
+    ctx: &Context,
+    args: &DeployArgs,
+    service: &Service,
+) -> Result<Option<Deploy>, RenderError> {
+    if !args.auto_rollback {
+        return Ok(None);
+    }
+    live_deploy(ctx, service).await
+}
+
+/// With `--auto-rollback`, redeploy `previous` when the deploy failed or `--post-deploy-cmd`
+/// did. The deploy's failure is still the result, unless the rollback fails too.
+async fn roll_back_on_failure(
+    ctx: &Context,
+    args: &DeployArgs,
+    service: &Service,
+    previous: Option<Deploy>,
+    result: Result<(), RenderError>,
+) -> Result<(), RenderError> {
+    let failed = match &result {
+        Err(RenderError::DeployFailed(deploy)) => deploy.status != DeployStatus::Canceled,
+        Err(RenderError::CommandFailed { .. }) => true,
+        _ => false,
+    };
+    if !args.auto_rollback || !failed {
+        return result;
+    }
+    let Some(previous) = previous else {
+        ctx.output.info(format!(
+            "{} had no live deploy to roll back to",
+            service.name
+        ));
+        return result;
+    };
+    ctx.output.info("");
+    ctx.output.info(format!(
+        "Rolling back {name} to {description}",
+        name = service.name,
+        description = previous.describe()
+    ));
+    let request = redeploy_request(&previous)?;
+    let deploy = ctx.client.trigger_deploy(&service.id, &request).await?;
+    follow_deploy(ctx, service, deploy, &args.wait).await?;
+    result
+}
+
+/// `args` with a lone argument moved to the commit when there's a deploy hook, the hook picks the
+/// service
+fn lone_commit<'a>(args: &'a DeployArgs, hook: Option<&DeployHook>) -> Cow<'a, DeployArgs> {
+    match (hook, &args.name, &args.commit) {
+        (Some(_), Some(name), None) => Cow::Owned(DeployArgs {
+            name: None,
+            commit: Some(name.clone()),
+            ..args.clone()
+        }),
+        _ => Cow::Borrowed(args),
+    }
+}
+
+/// `args` with a local commit like `HEAD` or `@` swapped for its sha
+fn resolve_commit<'a>(
+    output: &Output,
+    args: &'a DeployArgs,
+) -> Result<Cow<'a, DeployArgs>, RenderError> {
+    let Some(commit) = args
+        .commit
+        .as_deref()
+        .filter(|commit| git::is_local_rev(commit))
+    else {
+        return Ok(Cow::Borrowed(args));
+    };
+    let sha = git::resolve(commit)
+        .ok_or_else(|| RenderError::Config(format!("Cannot find {} in a git repo here", commit)))?;
+    output.info(format!("Resolved {} to {}", commit, sha));
+    Ok(Cow::Owned(DeployArgs {
+        commit: Some(sha),
+        ..args.clone()
+    }))
+}
+
+/// `args` with `--branch` swapped for the commit at the head of that branch of the service's repo
+async fn branch_commit<'a>(
+    ctx: &Context,
+    service: &Service,
+    args: &'a DeployArgs,
+) -> Result<Cow<'a, DeployArgs>, RenderError> {
+    let Some(branch) = &args.branch else {
+        return Ok(Cow::Borrowed(args));
+    };
+    let Some(repo) = &service.repo else {
+        return Err(RenderError::Config(format!(
+            "{} isn't built from a repo, there's no branch to deploy",
+            service.name
+        )));
+    };
+    let head = match git::remote_head(repo, branch) {
+        Some(head) => head,
+        None => git_host::branch_head(&ctx.http, repo, branch, ctx.profile.git_token())
+            .await?
+            .ok_or_else(|| {
+                RenderError::Config(format!("Cannot find the {} branch in {}", branch, repo))
+            })?,
+    };
+    ctx.output
+        .info(format!("The head of {} is #{}", branch, head));
+    Ok(Cow::Owned(DeployArgs {
+        commit: Some(head),
+        ..args.clone()
+    }))
+}
+
+/// Warn, or with `--require-pushed` fail, when the commit isn't on the service's repo yet
+fn check_pushed(ctx: &Context, args: &DeployArgs, service: &Service) -> Result<(), RenderError> {
+    let (Some(commit), Some(repo), None) = (&args.commit, &service.repo, &args.branch) else {
+        return Ok(());
+    };
+    match git::is_pushed(commit, repo) {
+        Some(true) => Ok(()),
+        Some(false) if args.require_pushed => Err(RenderError::Config(format!(
+            "#{} hasn't been pushed to {}, push it first",
+            commit, repo
+        ))),
+        Some(false) => {
+            ctx.output.info(format!(
+                "Warning: #{} hasn't been pushed to {}, render can't build it until it is",
+                commit, repo
+            ));
+            Ok(())
+        }
+        None if args.require_pushed => Err(RenderError::Config(format!(
+            "Unable to tell if #{} has been pushed, no git remote here points at {}",
+            commit, repo
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// With `--verify-commit`, fail before deploying a commit github or gitlab doesn't have on the
+/// service's branch
+async fn verify_commit(
+    ctx: &Context,
+    args: &DeployArgs,
+    service: &Service,
+) -> Result<(), RenderError> {
+    let (true, Some(commit)) = (args.verify_commit, &args.commit) else {
+        return Ok(());
+    };
+    let (Some(repo), Some(branch)) = (&service.repo, &service.branch) else {
+        return Err(RenderError::Config(format!(
+            "{} isn't built from a repo, there's no commit to verify",
+            service.name
+        )));
+    };
+    git_host::verify_commit(&ctx.http, repo, branch, commit, ctx.profile.git_token()).await?;
+    ctx.output
+        .info(format!("Verified #{} is on {}", commit, branch));
+    Ok(())
+}
+
+/// With `--changes`, list the commits that are shipping. Not being able to is only a warning.
+async fn show_changes(
+    ctx: &Context,
+    args: &DeployArgs,
+    service: &Service,
+) -> Result<(), RenderError> {
+    let (true, Some(repo), Some(branch)) = (args.changes, &service.repo, &service.branch) else {
+        return Ok(());
+    };
+    let Some(live) = live_deploy(ctx, service)
+        .await?
+        .and_then(|live| live.commit)
+    else {
+        ctx.output
+            .info("Nothing is live yet, every commit is shipping");
+        return Ok(());
+    };
+    let token = ctx.profile.git_token();
+    let target = match &args.commit {
+        Some(commit) => Some(commit.clone()),
+        None => match git::remote_head(repo, branch) {
+            Some(head) => Some(head),
+            None => git_host::branch_head(&ctx.http, repo, branch, token.clone())
+                .await
+                .ok()
+                .flatten(),
+        },
+    };
+    let Some(target) = target else {
+        ctx.output.info(format!(
+            "Warning: Unable to find the head of {} to list the commits shipping",
+            service.source()
+        ));
+        return Ok(());
+    };
+    // allow abbreviated shas
+    if live.id.starts_with(&target) || target.starts_with(&live.id) {
+        ctx.output.info(format!(
+            "#{} is already live, nothing new is shipping",
+            target
+        ));
+        return Ok(());
+    }
+    let host = git_host::GitHost::parse(repo);
+    let commits = match git::log(&live.id, &target) {
+        Some(commits) => Some(commits),
+        None => match &host {
+            Some(host) => git_host::commits_between(&ctx.http, host, &live.id, &target, token)
+                .await
+                .unwrap_or_else(|e| {
+                    ctx.output
+                        .info(format!("Warning: Unable to compare commits: {}", e));
+                    None
+                }),
+            None => None,
+        },
+    };
+    match commits {
+        Some(commits) if commits.is_empty() => ctx
+            .output
+            .info(format!("No new commits since the live deploy #{}", live.id)),
+        Some(commits) => {
+            ctx.output.info(format!(
+                "Shipping {} commits since the live deploy #{}:",
+                commits.len(),
+                live.id
+            ));
+            for commit in commits {
+                ctx.output.info(format!("  {}", commit));
+            }
+        }
+        None => ctx.output.info(format!(
+            "Warning: Unable to list the commits from #{} to #{}",
+            live.id, target
+        )),
+    }
+    if let Some(host) = host {
+        ctx.output.info(host.compare_url(&live.id, &target));
+    }
+    Ok(())
+}
+
+/// Run `--pre-deploy-cmd`, if given, for a service we're about to deploy
+async fn pre_deploy(
+    output: &Output,
+    args: &DeployArgs,
+    service_id: &str,
+    service_name: Option<&str>,
+) -> Result<(), RenderError> {
+    let Some(command) = &args.pre_deploy_cmd else {
+        return Ok(());
+    };
+    let mut env = vec![("RENDER_SERVICE_ID", service_id)];
+    env.extend(service_name.map(|name| ("RENDER_SERVICE_NAME", name)));
+    env.extend(
+        args.commit
+            .as_deref()
+            .map(|commit| ("RENDER_DEPLOY_COMMIT", commit)),
+    );
+    run_command(output, command, &env).await
+}
+
+/// [`pre_deploy`] for a service we found with the api, it's part of the junit report
+async fn pre_deploy_service(
+    ctx: &Context,
+    args: &DeployArgs,
+    service: &Service,
+) -> Result<(), RenderError> {
+    if args.pre_deploy_cmd.is_none() {
+        return Ok(());
+    }
+    let pre_deploy = pre_deploy(&ctx.output, args, &service.id, Some(&service.name));
+    ctx.junit
+        .time(&service.name, "pre-deploy-cmd", pre_deploy)
+        .await
+}
+
+/// Run `--post-deploy-cmd`, if given, for a deploy that's gone live
+async fn post_deploy(
+    ctx: &Context,
+    args: &DeployArgs,
+    service: &Service,
+    deploy: &Deploy,
+) -> Result<(), RenderError> {
+    let Some(command) = &args.post_deploy_cmd else {
+        return Ok(());
+    };
+    let url = deploy_url(service, deploy);
+    let mut env = vec![
+        ("RENDER_SERVICE_ID", service.id.as_str()),
+        ("RENDER_SERVICE_NAME", service.name.as_str()),
+        ("RENDER_DEPLOY_ID", deploy.id.as_str()),
+        ("RENDER_DEPLOY_URL", url.as_str()),
+    ];
+    env.extend(
+        deploy
+            .commit
+            .as_ref()
+            .map(|commit| ("RENDER_DEPLOY_COMMIT", commit.id.as_str())),
+    );
+    let command = run_command(&ctx.output, command, &env);
+    ctx.junit
+        .time(&service.name, "post-deploy-cmd", command)
+        .await
+}
+
+/// Run a deploy command with `sh -c`. Its output goes where our progress does so it can't mix
+/// with `--output json` results.
+async fn run_command(
+    output: &Output,
+    command: &str,
+    env: &[(&str, &str)],
+) -> Result<(), RenderError> {
+    output.info(format!("Running {}", command));
+    let mut child = Command::new("sh");
+    child.arg("-c").arg(command).envs(env.iter().copied());
+    if output.format() != OutputFormat::Text {
+        child.stdout(io::stderr());
+    }
+    let status = child
+        .status()
+        .await
+        .map_err(|e| RenderError::Config(format!("Unable to run {}: {}", command, e)))?;
+    if !status.success() {
+        return Err(RenderError::CommandFailed {
+            command: command.to_string(),
+            status: status.to_string(),
+        });
+    }
+    Ok(())
+}
+
+/// What `--output json` prints for a deploy started by a hook without an api key
+#[derive(Serialize, Debug)]
+struct HookResult<'a> {
+    service_id: &'a str,
+    deploy_id: String,
+}
+
+/// Start a deploy with only a deploy hook, there's no api key to follow it with
+pub async fn run_hook(
+    output: &Output,
+    profile: &Profile,
+    hook: &DeployHook,
+    args: &DeployArgs,
+) -> Result<(), RenderError> {
+    if args.wait.wait
+        || profile.wait.unwrap_or(false)
+        || args.post_deploy_cmd.is_some()
+        || args.junit_output.is_some()
+        || args.lock
+    {
+        return Err(RenderError::Config(
+            "Waiting for the deploy needs an api key, a deploy hook can only start it".into(),
+        ));
+    }
+    if !profile.deploy_branches.is_empty() {
+        return Err(RenderError::Config(
+            "The profile's deploy_branches needs an api key to find the service's branch".into(),
+        ));
+    }
+    if profile.protected == Some(true)
+        && !args.yes
+        && !confirm(&format!(
+            "Deploy {}? The profile is protected.",
+            hook.service_id()
+        ))?
+    {
+        return Err(RenderError::Config("Nothing was deployed".into()));
+    }
+    if args.require_pushed || args.verify_commit || args.branch.is_some() {
+        return Err(RenderError::Config(
+            "Checking the commit or deploying a branch needs an api key to find the service's repo"
+                .into(),
+        ));
+    }
+    if args.lock {
+        return Err(RenderError::Config(
+            "--lock needs an api key to keep the lock on the service".into(),
+        ));
+    }
+    let args = lone_commit(args, Some(hook));
+    let args = &*resolve_commit(output, &args)?;
+    pre_deploy(output, args, hook.service_id(), None).await?;
+    let deploy_id = trigger_hook(output, hook, args).await?;
+    output.brief(&deploy_id);
+    output.result(&HookResult {
+        service_id: hook.service_id(),
+        deploy_id,
+    })
+}
+
+/// Trigger the hook, refusing options it can't do without the api
+async fn trigger_hook(
+    output: &Output,
+    hook: &DeployHook,
+    args: &DeployArgs,
+) -> Result<String, RenderError> {
+    let unsupported = [
+        (
+            args.name.is_some() && args.commit.is_some(),
+            "a service name",
+        ),
+        (args.image_tag.is_some(), "--image-tag"),
+        (args.digest.is_some(), "--digest"),
+        (args.skip_if_current, "--skip-if-current"),
+        (args.disable_autodeploy, "--disable-autodeploy"),
+        (
+            args.if_in_progress != IfInProgress::Force,
+            "--if-in-progress",
+        ),
+    ];
+    if let Some((_, option)) = unsupported.iter().find(|(given, _)| *given) {
+        return Err(RenderError::Config(format!(
+            "A deploy hook deploys its own service as is, it can't be used with {}",
+            option
+        )));
+    }
+    let request = TriggerDeployRequest {
+        commit_id: args.commit.clone(),
+        image_url: args.image_url.clone(),
+        clear_cache: args.clear_cache.then_some(ClearCache::Clear),
+    };
+    let deploy_id = hook.trigger(&request).await?;
+    output.info(format!(
+        "Triggered Deploy {} of {} with its deploy hook",
+        deploy_id,
+        hook.service_id()
+    ));
+    Ok(deploy_id)
+}
+
+/// Get a deploy of one service going, honoring `--skip-if-current` and `--if-in-progress`
+async fn start(
+    ctx: &Context,
+    service: &Service,
+    args: &DeployArgs,
+) -> Result<Started, RenderError> {
+    ctx.output.info(format!(
+        "Found {name} {dashboard}",
+        name = service.name,
+        dashboard = service.dashboard_url
+    ));
+    let args = &*branch_commit(ctx, service, args).await?;
+    if service.auto_deploy && args.disable_autodeploy {
+        ctx.client.set_auto_deploy(&service.id, false).await?;
+        ctx.output.info("Turned off AutoDeploy");
+    } else if service.auto_deploy {
+        ctx.output
+            .info("Warning: AutoDeploy is true, --disable-autodeploy turns it off");
+    }
+
+    let image_url = image_url(service, args)?;
+    if let Some(commit) = &args.commit {
+        ctx.output.info(format!(
+            "Deploying {repo} #{commit}",
+            repo = service.repo.as_deref().unwrap_or_default(),
+            commit = commit
+        ));
+    } else if let Some(image_url) = &image_url {
+        ctx.output.info(format!("Deploying {}", image_url));
+    } else {
+        ctx.output.info(format!("Deploying {}", service.source()));
+    }
+    check_pushed(ctx, args, service)?;
+    verify_commit(ctx, args, service).await?;
+    show_changes(ctx, args, service).await?;
+    ctx.output.info("");
+
+    if args.skip_if_current {
+        if let Some(live) = current_deploy(ctx, service, args.commit.as_deref(), &image_url).await?
+        {
+            ctx.output
+                .info(format!("Already deployed {}", live.describe()));
+            return Ok(Started::Current(live));
+        }
+    }
+
+    let previous_deploy = ctx.client.latest_deploy(&service.id).await?;
+    if let Some(deploy) = &previous_deploy {
+        ctx.output
+            .info(format!("Previous Deploy {}", deploy.describe()));
+        ctx.output.info(format!(
+            "Status: {status} on {finished_at}",
+            status = ctx.output.status(&deploy.status),
+            finished_at = deploy
+                .finished_at
+                .as_deref()
+                .map(|finished_at| ctx.output.time(finished_at))
+                .unwrap_or_default()
+        ));
+        ctx.output.info("");
+    }
+
+    if let Some(in_progress) = previous_deploy.filter(|deploy| deploy.status.is_in_progress()) {
+        match args.if_in_progress {
+            IfInProgress::Force => {
+                ctx.output.info(format!(
+                    "Warning: Deploy {} is still in progress",
+                    in_progress.id
+                ));
+            }
+            IfInProgress::Attach => {
+                ctx.output
+                    .info(format!("Attaching to Deploy {}", in_progress.id));
+                return Ok(Started::Attached(in_progress));
+            }
+            IfInProgress::Cancel => {
+                let canceled = ctx
+                    .client
+                    .cancel_deploy(&service.id, &in_progress.id)
+                    .await?;
+                ctx.output.info(format!(
+                    "Canceled Deploy {id}, status: {status}",
+                    id = canceled.id,
+                    status = ctx.output.status(&canceled.status)
+                ));
+            }
+            IfInProgress::Wait => {
+                ctx.output
+                    .info(format!("Waiting for Deploy {} to finish", in_progress.id));
+                let typical =
+                    typical_deploy_duration(&ctx.client, &service.id, &in_progress.id).await;
+                let progress =
+                    Progress::new(ctx.output.clone(), !args.wait.logs, &in_progress, typical);
+                let (_, finished) = poll_deploy(
+                    ctx,
+                    service,
+                    &in_progress,
+                    &args.wait,
+                    ctx.timeout(&args.wait),
+                    Some(&progress),
+                    |deploy| progress.update(deploy),
+                )
+                .await;
+                match finished {
+                    // canceled because we were interrupted, which stops us deploying too
+                    Err(e) if ctx.interrupt.interrupted() => return Err(e),
+                    // it doesn't matter how the other deploy ended, only that it did
+                    Ok(_) | Err(RenderError::DeployFailed(_)) => ctx.output.info(""),
+                    Err(e) => return Err(e),
+                }
+            }
+        }
+    }
+
+    pre_deploy_service(ctx, args, service).await?;
+    // trigger deploy
+    let request = TriggerDeployRequest {
+        commit_id: args.commit.clone(),
+        image_url,
+        clear_cache: args.clear_cache.then_some(ClearCache::Clear),
+    };
+    let trigger = ctx.client.trigger_deploy(&service.id, &request);
+    let deploy = ctx.trace.time("trigger", Some(service), trigger).await?;
+    Ok(Started::Triggered(deploy))
+}
+
+/// The image to deploy from `--image-url`, or the service's image with `--image-tag` or
+/// `--digest` swapped in
+fn image_url(service: &Service, args: &DeployArgs) -> Result<Option<String>, RenderError> {
+    if let Some(image_url) = &args.image_url {
+        return Ok(Some(image_url.clone()));
+    }
+    if args.image_tag.is_none() && args.digest.is_none() {
+        return Ok(None);
+    }
+    let image_path = service.image_path.as_deref().ok_or_else(|| {
+        RenderError::Config(format!(
+            "{} isn't deployed from an image, use --image-url",
+            service.name
+        ))
+    })?;
+    let repository = image_repository(image_path);
+    Ok(match (&args.image_tag, &args.digest) {
+        (Some(tag), _) => Some(format!("{}:{}", repository, tag)),
+        (_, Some(digest)) => Some(format!("{}@{}", repository, digest)),
+        (None, None) => None,
+    })
+}
+
+/// The live deploy if it's already running `commit` or `image_url`, or the head of the
+/// service's branch when there's neither
+async fn current_deploy(
+    ctx: &Context,
+    service: &Service,
+    commit: Option<&str>,
+    image_url: &Option<String>,
+) -> Result<Option<Deploy>, RenderError> {
+    let Some(live) = live_deploy(ctx, service).await? else {
+        return Ok(None);
+    };
+    if let Some(image_url) = image_url {
+        let current = live.image.as_ref().map(|image| &image.image_ref) == Some(image_url);
+        return Ok(current.then_some(live));
+    }
+    let wanted = match (commit, &service.repo, &service.branch) {
+        (Some(commit), _, _) => commit.to_string(),
+        (None, Some(repo), Some(branch)) => match git::remote_head(repo, branch) {
+            Some(head) => head,
+            None => {
+                ctx.output.info(format!(
+                    "Warning: Unable to find the head of {}, deploying anyway",
+                    service.source()
+                ));
+                return Ok(None);
+            }
+        },
+        (None, _, _) => return Ok(None),
+    };
+    // allow abbreviated shas
+    let current = live
+        .commit
+        .as_ref()
+        .is_some_and(|live| live.id.starts_with(&wanted));
+    Ok(current.then_some(live))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::{Cli, Commands};
+    use crate::fixtures::service;
+    use clap::Parser;
+
+    fn deploy_args(args: &[&str]) -> DeployArgs {
+        match Cli::parse_from([&["render-deploy", "deploy"], args].concat()).command {
+            Commands::Deploy(args) => args,
+            other => panic!("expected deploy {:?}", other),
+        }
+    }
+
+    #[test]
+    fn protected_services() {
+        let services = [
+            service("srv-1", "api-prod"),
+            service("srv-2", "worker-prod"),
+            service("srv-3", "api-staging"),
+        ];
+        let profile = Profile {
+            protected_services: vec!["*-prod".into(), "srv-3".into()],
+            ..Profile::default()
+        };
+        assert_eq!(
+            protected(&profile, &services[1..]),
+            ["worker-prod", "api-staging"]
+        );
+        assert!(protected(&Profile::default(), &services).is_empty());
+        let profile = Profile {
+            protected: Some(true),
+            ..Profile::default()
+        };
+        assert_eq!(protected(&profile, &services).len(), 3);
+    }
+
+    #[test]
+    fn allowed_branches() {
+        let allowed = ["main".to_string(), "release/*".to_string()];
+        assert!(branch_allowed(&allowed, "main"));
+        assert!(branch_allowed(&allowed, "release/1.2"));
+        assert!(!branch_allowed(&allowed, "feature/foo"));
+    }
+
+    #[test]
+    fn lone_argument_to_a_hook() {
+        let hook = DeployHook::new("https://api.render.com/deploy/srv-1?key=abc").unwrap();
+        let args = deploy_args(&["HEAD"]);
+        let args = lone_commit(&args, Some(&hook));
+        assert_eq!(
+            (args.name.as_deref(), args.commit.as_deref()),
+            (None, Some("HEAD"))
+        );
+        let args = deploy_args(&["api", "b2be9cf"]);
+        assert_eq!(lone_commit(&args, Some(&hook)).name.as_deref(), Some("api"));
+        let args = deploy_args(&["api"]);
+        assert_eq!(lone_commit(&args, None).name.as_deref(), Some("api"));
+    }
+
+    #[test]
+    fn poll_at_most_once_a_second() {
+        assert_eq!(
+            deploy_args(&["--poll-interval", "1"])
+                .wait
+                .poll_interval
+                .as_secs(),
+            1
+        );
+        for interval in ["0", "0s", "500ms"] {
+            assert!(
+                Cli::try_parse_from(["render-deploy", "deploy", "--poll-interval", interval])
+                    .is_err()
+            );
+        }
+    }
+}
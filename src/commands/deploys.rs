@@ -0,0 +1,76 @@
+use super::Context;
+use crate::cli::{DeployFilter, DeploysArgs};
+use futures::TryStreamExt;
+use render_deploy::{format_duration, Deploy, DeployStatus, RenderError};
+
+pub async fn run(ctx: &Context, args: &DeploysArgs) -> Result<(), RenderError> {
+    let service = ctx.service(args.name.as_deref()).await?;
+    ctx.output.info(format!(
+        "{name} ({id}) {dashboard}",
+        name = service.name,
+        id = service.id,
+        dashboard = service.dashboard_url
+    ));
+    ctx.output.info("");
+
+    let mut deploys = vec![];
+    let mut history = ctx.client.deploy_history(&service.id);
+    while deploys.len() < args.limit {
+        let Some(deploy) = history.try_next().await? else {
+            break;
+        };
+        if args
+            .status
+            .is_none_or(|filter| matches(filter, &deploy.status))
+        {
+            deploys.push(deploy);
+        }
+    }
+
+    let rows: Vec<Vec<String>> = deploys
+        .iter()
+        .map(|deploy| {
+            vec![
+                deploy.id.clone(),
+                ctx.output.time(&deploy.created_at),
+                ctx.output.status(&deploy.status),
+                deploy
+                    .duration()
+                    .map(format_duration)
+                    .unwrap_or_else(|| "-".into()),
+                deploy.trigger.clone().unwrap_or_else(|| "-".into()),
+                summary(deploy),
+            ]
+        })
+        .collect();
+    ctx.output.table(
+        &["ID", "CREATED", "STATUS", "DURATION", "TRIGGER", "DEPLOYED"],
+        &rows,
+    );
+    ctx.output.result(&deploys)
+}
+
+fn matches(filter: DeployFilter, status: &DeployStatus) -> bool {
+    match filter {
+        DeployFilter::Live => *status == DeployStatus::Live,
+        DeployFilter::Succeeded => {
+            matches!(status, DeployStatus::Live | DeployStatus::Deactivated)
+        }
+        DeployFilter::Failed => matches!(
+            status,
+            DeployStatus::BuildFailed | DeployStatus::UpdateFailed | DeployStatus::PreDeployFailed
+        ),
+        DeployFilter::Canceled => *status == DeployStatus::Canceled,
+        DeployFilter::InProgress => status.is_in_progress(),
+    }
+}
+
+/// The first line of what was deployed, commit messages can go on a while
+fn summary(deploy: &Deploy) -> String {
+    deploy
+        .describe()
+        .lines()
+        .next()
+        .unwrap_or_default()
+        .to_string()
+}
@@ -0,0 +1,72 @@
+use super::Context;
+use crate::cli::{DomainsArgs, DomainsCommands};
+use futures::TryStreamExt;
+use render_deploy::{CustomDomain, RenderError, VerificationStatus};
+
+pub async fn run(ctx: &Context, args: &DomainsArgs) -> Result<(), RenderError> {
+    match &args.command {
+        DomainsCommands::List { name } => {
+            let service = ctx.service(name.as_deref()).await?;
+            let domains: Vec<CustomDomain> = ctx
+                .client
+                .list_custom_domains(&service.id)
+                .try_collect()
+                .await?;
+            print_domains(ctx, &domains);
+            ctx.output.result(&domains)
+        }
+        DomainsCommands::Add { name, domain } => {
+            let service = ctx.service(Some(name)).await?;
+            let domains = ctx.client.add_custom_domain(&service.id, domain).await?;
+            print_domains(ctx, &domains);
+            ctx.output.info(format!(
+                "Point the DNS records shown on {} at render, then run `render-deploy domains verify {} {}`",
+                service.dashboard_url, service.name, domain
+            ));
+            ctx.output.result(&domains)
+        }
+        DomainsCommands::Remove { name, domains } => {
+            let service = ctx.service(Some(name)).await?;
+            for domain in domains {
+                ctx.client.delete_custom_domain(&service.id, domain).await?;
+                ctx.output.info(format!("Removed {}", domain));
+            }
+            Ok(())
+        }
+        DomainsCommands::Verify { name, domain } => {
+            let service = ctx.service(Some(name)).await?;
+            ctx.client.verify_custom_domain(&service.id, domain).await?;
+            let domain = ctx.client.get_custom_domain(&service.id, domain).await?;
+            match domain.verification_status {
+                VerificationStatus::Verified => ctx.output.info(format!(
+                    "{} is verified, render will issue its certificate shortly",
+                    domain.name
+                )),
+                VerificationStatus::Unverified => ctx.output.info(format!(
+                    "{} isn't verified yet, DNS changes can take a while to show up",
+                    domain.name
+                )),
+            }
+            ctx.output.result(&domain)
+        }
+    }
+}
+
+fn print_domains(ctx: &Context, domains: &[CustomDomain]) {
+    let rows: Vec<Vec<String>> = domains
+        .iter()
+        .map(|domain| {
+            vec![
+                domain.name.clone(),
+                domain.verification_status.to_string(),
+                domain
+                    .redirect_for_name
+                    .clone()
+                    .unwrap_or_else(|| "-".into()),
+                ctx.output.time(&domain.created_at),
+            ]
+        })
+        .collect();
+    ctx.output
+        .table(&["DOMAIN", "STATUS", "REDIRECTS", "CREATED"], &rows);
+}
@@ -0,0 +1,221 @@
+use super::{confirm, Context};
+use crate::cli::{EnvArgs, EnvCommands, EnvFormat, EnvListArgs, EnvSetArgs, EnvSyncArgs};
+use crate::dotenv;
+use crate::lock::LOCK_KEY;
+use crate::output::OutputFormat;
+use futures::TryStreamExt;
+use render_deploy::{EnvVar, RenderError};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+pub async fn run(ctx: &Context, args: &EnvArgs) -> Result<(), RenderError> {
+    match &args.command {
+        EnvCommands::List(args) => list(ctx, args).await,
+        EnvCommands::Get { name, key } => {
+            let service = ctx.service(Some(name)).await?;
+            let env_var = ctx.client.get_env_var(&service.id, key).await?;
+            ctx.output.item(&env_var, &env_var.value)
+        }
+        EnvCommands::Set(args) => set(ctx, args).await,
+        EnvCommands::Sync(args) => sync(ctx, args).await,
+        EnvCommands::Unset { name, keys } => {
+            let service = ctx.service(Some(name)).await?;
+            for key in keys {
+                ctx.client.delete_env_var(&service.id, key).await?;
+                ctx.output.info(format!("Removed {}", key));
+            }
+            ctx.output
+                .info("Deploy the service for the change to take effect");
+            Ok(())
+        }
+    }
+}
+
+async fn list(ctx: &Context, args: &EnvListArgs) -> Result<(), RenderError> {
+    let service = ctx.service(args.name.as_deref()).await?;
+    let env_vars: Vec<EnvVar> = ctx.client.list_env_vars(&service.id).try_collect().await?;
+    let format = args.format.unwrap_or(match ctx.output.format() {
+        OutputFormat::Json | OutputFormat::Ndjson => EnvFormat::Json,
+        _ => EnvFormat::Dotenv,
+    });
+    match format {
+        EnvFormat::Dotenv => {
+            for env_var in &env_vars {
+                println!("{}", dotenv::format_line(&env_var.key, &env_var.value));
+            }
+        }
+        EnvFormat::Json => {
+            let env_vars: BTreeMap<&str, &str> = env_vars
+                .iter()
+                .map(|env_var| (env_var.key.as_str(), env_var.value.as_str()))
+                .collect();
+            let json = serde_json::to_string_pretty(&env_vars)
+                .map_err(|e| RenderError::Config(format!("Unable to write json {}", e)))?;
+            println!("{}", json);
+        }
+    }
+    Ok(())
+}
+
+async fn set(ctx: &Context, args: &EnvSetArgs) -> Result<(), RenderError> {
+    let mut pairs = match &args.from_file {
+        Some(path) => dotenv::load(path)?,
+        None => vec![],
+    };
+    for pair in &args.pairs {
+        let (key, value) = pair
+            .split_once('=')
+            .ok_or_else(|| RenderError::Config(format!("{} isn't KEY=value", pair)))?;
+        pairs.push((key.to_string(), value.to_string()));
+    }
+    if pairs.is_empty() {
+        return Err(RenderError::Config(
+            "Nothing to set, pass KEY=value or --from-file".into(),
+        ));
+    }
+
+    let service = ctx.service(Some(&args.name)).await?;
+    for (key, value) in &pairs {
+        ctx.client.set_env_var(&service.id, key, value).await?;
+        ctx.output.info(format!("Set {}", key));
+    }
+    ctx.output
+        .info("Deploy the service for the change to take effect");
+    Ok(())
+}
+
+/// What syncing a .env file will do to a service, by key. Values are left out so secrets don't
+/// end up in CI logs.
+#[derive(Serialize, Debug, Default, PartialEq)]
+pub struct EnvDiff {
+    pub added: Vec<String>,
+    pub changed: Vec<String>,
+    pub removed: Vec<String>,
+    /// on the service but not in the file, kept because --prune wasn't given
+    pub kept: Vec<String>,
+}
+
+impl EnvDiff {
+    pub fn new(local: &BTreeMap<String, String>, remote: &[EnvVar], prune: bool) -> EnvDiff {
+        // the --lock env var belongs to whichever deploy holds it
+        let remote: BTreeMap<&str, &str> = remote
+            .iter()
+            .filter(|env_var| env_var.key != LOCK_KEY)
+            .map(|env_var| (env_var.key.as_str(), env_var.value.as_str()))
+            .collect();
+        let mut diff = EnvDiff::default();
+        for (key, value) in local {
+            match remote.get(key.as_str()) {
+                None => diff.added.push(key.clone()),
+                Some(remote_value) if remote_value != value => diff.changed.push(key.clone()),
+                Some(_) => {}
+            }
+        }
+        for key in remote.keys().filter(|key| !local.contains_key(**key)) {
+            match prune {
+                true => diff.removed.push(key.to_string()),
+                false => diff.kept.push(key.to_string()),
+            }
+        }
+        diff
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.changed.is_empty() && self.removed.is_empty()
+    }
+}
+
+async fn sync(ctx: &Context, args: &EnvSyncArgs) -> Result<(), RenderError> {
+    // later lines win, like every other .env reader
+    let local: BTreeMap<String, String> = dotenv::load(&args.file)?.into_iter().collect();
+    let service = ctx.service(args.name.as_deref()).await?;
+    let remote: Vec<EnvVar> = ctx.client.list_env_vars(&service.id).try_collect().await?;
+    let diff = EnvDiff::new(&local, &remote, args.prune);
+
+    for key in &diff.added {
+        ctx.output.info(format!("+ {}", key));
+    }
+    for key in &diff.changed {
+        ctx.output.info(format!("~ {}", key));
+    }
+    for key in &diff.removed {
+        ctx.output.info(format!("- {}", key));
+    }
+    if !diff.kept.is_empty() {
+        ctx.output.info(format!(
+            "Keeping {} which aren't in {}, use --prune to remove them",
+            diff.kept.join(", "),
+            args.file.display()
+        ));
+    }
+    if diff.is_empty() {
+        ctx.output
+            .info(format!("{} is already up to date", service.name));
+        return ctx.output.result(&diff);
+    }
+
+    if !args.yes && !confirm(&format!("Apply these changes to {}?", service.name))? {
+        return Err(RenderError::Config(
+            "Sync canceled, nothing was changed".into(),
+        ));
+    }
+    for key in diff.added.iter().chain(&diff.changed) {
+        ctx.client
+            .set_env_var(&service.id, key, &local[key])
+            .await?;
+    }
+    for key in &diff.removed {
+        ctx.client.delete_env_var(&service.id, key).await?;
+    }
+    ctx.output.info(format!(
+        "Updated {}, deploy the service for the change to take effect",
+        service.name
+    ));
+    ctx.output.result(&diff)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn env_var(key: &str, value: &str) -> EnvVar {
+        EnvVar {
+            key: key.into(),
+            value: value.into(),
+        }
+    }
+
+    #[test]
+    fn diff_against_the_service() {
+        let local = BTreeMap::from([
+            ("NEW".to_string(), "1".to_string()),
+            ("CHANGED".to_string(), "2".to_string()),
+            ("SAME".to_string(), "3".to_string()),
+        ]);
+        let remote = vec![
+            env_var("CHANGED", "old"),
+            env_var("SAME", "3"),
+            env_var("EXTRA", "4"),
+        ];
+        assert_eq!(
+            EnvDiff::new(&local, &remote, false),
+            EnvDiff {
+                added: vec!["NEW".into()],
+                changed: vec!["CHANGED".into()],
+                removed: vec![],
+                kept: vec!["EXTRA".into()],
+            }
+        );
+        let pruned = EnvDiff::new(&local, &remote, true);
+        assert_eq!(pruned.removed, vec!["EXTRA".to_string()]);
+        assert!(pruned.kept.is_empty());
+    }
+
+    #[test]
+    fn leave_the_lock_alone() {
+        let local = BTreeMap::from([("SAME".to_string(), "3".to_string())]);
+        let remote = vec![env_var("SAME", "3"), env_var(LOCK_KEY, "taken")];
+        assert!(EnvDiff::new(&local, &remote, true).is_empty());
+        assert!(EnvDiff::new(&local, &remote, false).kept.is_empty());
+    }
+}
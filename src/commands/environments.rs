@@ -0,0 +1,62 @@
+use super::projects::find_project;
+use super::Context;
+use crate::cli::{EnvironmentsArgs, EnvironmentsCommands};
+use futures::TryStreamExt;
+use render_deploy::{Environment, Project, RenderClient, RenderError};
+
+pub async fn run(ctx: &Context, args: &EnvironmentsArgs) -> Result<(), RenderError> {
+    match &args.command {
+        EnvironmentsCommands::List { project } => {
+            let project = find_project(&ctx.client, project).await?;
+            let environments: Vec<Environment> = ctx
+                .client
+                .list_environments(&project.id)
+                .try_collect()
+                .await?;
+            let rows: Vec<Vec<String>> = environments
+                .iter()
+                .map(|environment| {
+                    vec![
+                        environment.name.clone(),
+                        environment.id.clone(),
+                        environment.service_ids.len().to_string(),
+                        environment.database_ids.len().to_string(),
+                        if environment.is_protected() {
+                            "yes"
+                        } else {
+                            "no"
+                        }
+                        .into(),
+                    ]
+                })
+                .collect();
+            ctx.output
+                .table(&["NAME", "ID", "SERVICES", "DATABASES", "PROTECTED"], &rows);
+            ctx.output.result(&environments)
+        }
+    }
+}
+
+/// The environment of `project` named `name`, or with the id `name`
+pub async fn find_environment(
+    client: &RenderClient,
+    project: &Project,
+    name: &str,
+) -> Result<Environment, RenderError> {
+    let environments: Vec<Environment> =
+        client.list_environments(&project.id).try_collect().await?;
+    let names: Vec<&str> = environments
+        .iter()
+        .map(|environment| environment.name.as_str())
+        .collect();
+    let names = names.join(", ");
+    environments
+        .into_iter()
+        .find(|environment| environment.name == name || environment.id == name)
+        .ok_or_else(|| {
+            RenderError::Config(format!(
+                "{} has no environment named {}, it has: {}",
+                project.name, name, names
+            ))
+        })
+}
@@ -0,0 +1,73 @@
+use super::Context;
+use crate::cli::EventsArgs;
+use futures::{StreamExt, TryStreamExt};
+use render_deploy::{Event, RenderError};
+use std::time::SystemTime;
+
+pub async fn run(ctx: &Context, args: &EventsArgs) -> Result<(), RenderError> {
+    let service = ctx.service(args.name.as_deref()).await?;
+    let since = SystemTime::now()
+        .checked_sub(args.since)
+        .unwrap_or(SystemTime::UNIX_EPOCH);
+    let since = humantime::format_rfc3339_seconds(since).to_string();
+    let events: Vec<Event> = ctx
+        .client
+        .list_events(&service.id, Some(&since))
+        .take(args.limit)
+        .try_collect()
+        .await?;
+    if events.is_empty() {
+        ctx.output.info(format!(
+            "Nothing has happened to {} since {}",
+            service.name,
+            ctx.output.time(&since)
+        ));
+    }
+    let rows: Vec<Vec<String>> = events
+        .iter()
+        .map(|event| {
+            vec![
+                ctx.output.time(&event.timestamp),
+                event.event_type.to_string(),
+                details(event),
+            ]
+        })
+        .collect();
+    ctx.output.table(&["TIME", "EVENT", "DETAILS"], &rows);
+    ctx.output.result(&events)
+}
+
+/// The event's details as `key=value` pairs, they're different for every type of event
+pub fn details(event: &Event) -> String {
+    let Some(details) = event.details.as_object() else {
+        return String::new();
+    };
+    details
+        .iter()
+        .filter(|(_, value)| !value.is_null())
+        .map(|(key, value)| match value {
+            serde_json::Value::String(value) => format!("{}={}", key, value),
+            value => format!("{}={}", key, value),
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use render_deploy::EventType;
+    use serde_json::json;
+
+    #[test]
+    fn event_details() {
+        let event = Event {
+            id: "evt-1".into(),
+            timestamp: "2024-10-14T02:17:35Z".into(),
+            service_id: "srv-1".into(),
+            event_type: EventType::InstanceCountChanged,
+            details: json!({ "fromInstances": 1, "toInstances": 3, "reason": null }),
+        };
+        assert_eq!(details(&event), "fromInstances=1 toInstances=3");
+    }
+}
@@ -0,0 +1,57 @@
+use super::{static_site, Context};
+use crate::cli::{HeadersArgs, HeadersCommands};
+use futures::TryStreamExt;
+use render_deploy::{HeaderRule, RenderError};
+
+pub async fn run(ctx: &Context, args: &HeadersArgs) -> Result<(), RenderError> {
+    match &args.command {
+        HeadersCommands::List { name } => {
+            let service = static_site(ctx, name.as_deref()).await?;
+            let headers: Vec<HeaderRule> = ctx
+                .client
+                .list_header_rules(&service.id)
+                .try_collect()
+                .await?;
+            let rows: Vec<Vec<String>> = headers
+                .iter()
+                .map(|header| {
+                    vec![
+                        header.id.clone(),
+                        header.path.clone(),
+                        header.name.clone(),
+                        header.value.clone(),
+                    ]
+                })
+                .collect();
+            ctx.output.table(&["ID", "PATH", "HEADER", "VALUE"], &rows);
+            ctx.output.result(&headers)
+        }
+        HeadersCommands::Add {
+            name,
+            path,
+            header,
+            value,
+        } => {
+            let service = static_site(ctx, Some(name)).await?;
+            let header = ctx
+                .client
+                .add_header_rule(&service.id, path, header, value)
+                .await?;
+            ctx.output.info(format!(
+                "Added {} {}: {} on {}",
+                header.id, header.name, header.value, header.path
+            ));
+            ctx.output.result(&header)
+        }
+        HeadersCommands::Remove { name, header_ids } => {
+            let service = static_site(ctx, Some(name)).await?;
+            for header_id in header_ids {
+                ctx.client
+                    .delete_header_rule(&service.id, header_id)
+                    .await?;
+                ctx.output.info(format!("Removed {}", header_id));
+            }
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,122 @@
+use super::Context;
+use crate::cli::{JobArgs, JobCommands, JobListArgs, JobRunArgs};
+use crate::output::Output;
+use futures::{StreamExt, TryStreamExt};
+use render_deploy::{format_duration, Job, RenderError, RunJobRequest};
+
+pub async fn run(ctx: &Context, args: &JobArgs) -> Result<(), RenderError> {
+    match &args.command {
+        JobCommands::Run(args) => run_job(ctx, args).await,
+        JobCommands::List(args) => list(ctx, args).await,
+        JobCommands::Status { job_id, name } => {
+            let service = ctx.service(name.as_deref()).await?;
+            let job = ctx.client.get_job(&service.id, job_id).await?;
+            ctx.output
+                .info(format!("Job {} on {}", job.id, service.name));
+            ctx.output.info(format!("Command: {}", job.start_command));
+            ctx.output.info(format!("Status: {}", job.status));
+            ctx.output
+                .info(format!("Created: {}", ctx.output.time(&job.created_at)));
+            ctx.output
+                .info(format!("Started: {}", time(&ctx.output, &job.started_at)));
+            ctx.output
+                .info(format!("Finished: {}", time(&ctx.output, &job.finished_at)));
+            ctx.output.info(format!("Duration: {}", duration(&job)));
+            ctx.output.result(&job)
+        }
+    }
+}
+
+async fn list(ctx: &Context, args: &JobListArgs) -> Result<(), RenderError> {
+    let service = ctx.service(args.name.as_deref()).await?;
+    let jobs: Vec<Job> = ctx
+        .client
+        .job_history(&service.id)
+        .take(args.limit)
+        .try_collect()
+        .await?;
+    let rows: Vec<Vec<String>> = jobs
+        .iter()
+        .map(|job| {
+            vec![
+                job.id.clone(),
+                job.status.to_string(),
+                time(&ctx.output, &job.started_at),
+                time(&ctx.output, &job.finished_at),
+                duration(job),
+                job.start_command.clone(),
+            ]
+        })
+        .collect();
+    ctx.output.table(
+        &["ID", "STATUS", "STARTED", "FINISHED", "DURATION", "COMMAND"],
+        &rows,
+    );
+    ctx.output.result(&jobs)
+}
+
+fn duration(job: &Job) -> String {
+    job.duration()
+        .map(format_duration)
+        .unwrap_or_else(|| "-".into())
+}
+
+async fn run_job(ctx: &Context, args: &JobRunArgs) -> Result<(), RenderError> {
+    let service = ctx.service(args.name.as_deref()).await?;
+    let request = RunJobRequest {
+        start_command: args.command.clone(),
+        plan_id: args.plan_id.clone(),
+    };
+    let job = ctx.client.run_job(&service.id, &request).await?;
+    ctx.output.info(format!(
+        "Started Job {id} on {name}: {command}",
+        id = job.id,
+        name = service.name,
+        command = job.start_command
+    ));
+
+    let Some(options) = ctx.service_wait(&args.wait) else {
+        return ctx.output.result(&job);
+    };
+    let mut last_status = job.status;
+    let waited = ctx
+        .client
+        .wait_for_job(&service.id, &job.id, &options, |job| {
+            if job.status != last_status {
+                ctx.output.info(format!("Status: {}", job.status));
+                last_status = job.status;
+            }
+        })
+        .await;
+    match waited {
+        Ok(job) => {
+            ctx.output.info(describe_finished(&ctx.output, &job));
+            ctx.output.result(&job)
+        }
+        Err(RenderError::JobFailed(job)) => {
+            ctx.output.info(describe_finished(&ctx.output, &job));
+            ctx.output.result(&job)?;
+            Err(RenderError::JobFailed(job))
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// When a job started or finished, if it has
+fn time(output: &Output, time: &Option<String>) -> String {
+    time.as_deref()
+        .map(|time| output.time(time))
+        .unwrap_or_else(|| "-".into())
+}
+
+fn describe_finished(output: &Output, job: &Job) -> String {
+    match &job.finished_at {
+        Some(finished_at) => format!(
+            "Job {} {} on {}",
+            job.id,
+            job.status,
+            output.time(finished_at)
+        ),
+        None => format!("Job {} {}", job.id, job.status),
+    }
+}
@@ -0,0 +1,52 @@
+use super::Context;
+use futures::TryStreamExt;
+use render_deploy::{Deploy, RenderError, Service};
+use serde::Serialize;
+
+/// What `--output json` prints for each service
+#[derive(Serialize, Debug)]
+struct ServiceSummary {
+    service: Service,
+    latest_deploy: Option<Deploy>,
+}
+
+pub async fn run(ctx: &Context) -> Result<(), RenderError> {
+    let mut summaries = vec![];
+    let mut services = ctx.client.list_services();
+    while let Some(service) = services.try_next().await? {
+        let latest_deploy = ctx.client.latest_deploy(&service.id).await?;
+        summaries.push(ServiceSummary {
+            service,
+            latest_deploy,
+        });
+    }
+    let services: Vec<Service> = summaries
+        .iter()
+        .map(|summary| summary.service.clone())
+        .collect();
+    ctx.cache.remember(&services);
+
+    let rows: Vec<Vec<String>> = summaries
+        .iter()
+        .map(|summary| {
+            let service = &summary.service;
+            vec![
+                service.name.clone(),
+                service.id.clone(),
+                service.service_type.to_string(),
+                service.branch.clone().unwrap_or_else(|| "-".into()),
+                if service.auto_deploy { "yes" } else { "no" }.into(),
+                summary
+                    .latest_deploy
+                    .as_ref()
+                    .map(|deploy| ctx.output.status(&deploy.status))
+                    .unwrap_or_else(|| "-".into()),
+            ]
+        })
+        .collect();
+    ctx.output.table(
+        &["NAME", "ID", "TYPE", "BRANCH", "AUTODEPLOY", "LAST DEPLOY"],
+        &rows,
+    );
+    ctx.output.result(&summaries)
+}
@@ -0,0 +1,158 @@
+use super::Context;
+use crate::cli::ListenArgs;
+use crate::webhooks::{self, Deliveries, WebhookEvent};
+use render_deploy::RenderError;
+use std::process::Stdio;
+use std::time::{Duration, SystemTime};
+use tokio::io::AsyncWriteExt;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::process::Command;
+use tokio::time::timeout;
+
+/// How long a delivery has to arrive in full
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Serve until killed. Deliveries are handled one at a time so `--exec` sees events in the order
+/// they came, progress goes to stderr to keep stdout for the events.
+pub async fn run(ctx: &Context, args: &ListenArgs) -> Result<(), RenderError> {
+    let mut service_ids = vec![];
+    for name in &args.services {
+        service_ids.push(ctx.service(Some(name)).await?.id);
+    }
+    let listener = TcpListener::bind((args.bind.as_str(), args.port))
+        .await
+        .map_err(|e| {
+            RenderError::Config(format!(
+                "Unable to listen on {}:{}: {}",
+                args.bind, args.port, e
+            ))
+        })?;
+    eprintln!(
+        "Listening for render webhooks on {}:{}",
+        args.bind, args.port
+    );
+
+    let mut deliveries = Deliveries::default();
+    loop {
+        let (mut stream, peer) = match listener.accept().await {
+            Ok(connection) => connection,
+            Err(e) => {
+                eprintln!("Unable to accept a connection: {}", e);
+                continue;
+            }
+        };
+        // a client that never finishes its request shouldn't hold up everyone else's
+        let event = match timeout(
+            READ_TIMEOUT,
+            receive(&mut stream, &args.secret, &mut deliveries),
+        )
+        .await
+        {
+            Ok(Ok(event)) => event,
+            Ok(Err(e)) => {
+                eprintln!("Refused a delivery from {}: {}", peer, e);
+                continue;
+            }
+            Err(_) => {
+                eprintln!("Gave up on a delivery from {}, it took too long", peer);
+                continue;
+            }
+        };
+        let wanted = (args.events.is_empty() || args.events.contains(&event.event_type))
+            && (service_ids.is_empty()
+                || event
+                    .service_id()
+                    .is_some_and(|id| service_ids.iter().any(|wanted| wanted == id)));
+        if wanted {
+            dispatch(args, &event).await?;
+        }
+    }
+}
+
+/// Read and check one delivery, answering render straight away so it doesn't retry while
+/// `--exec` runs
+async fn receive(
+    stream: &mut TcpStream,
+    secret: &str,
+    deliveries: &mut Deliveries,
+) -> Result<WebhookEvent, String> {
+    let request = match webhooks::read_request(stream).await {
+        Ok(request) => request,
+        Err(e) => {
+            let _ = webhooks::respond(stream, 400, "Bad Request").await;
+            return Err(e);
+        }
+    };
+    if request.method != "POST" {
+        let _ = webhooks::respond(stream, 405, "Method Not Allowed").await;
+        return Err(format!("{} {}", request.method, request.path));
+    }
+    let (Some(id), Some(timestamp), Some(signature)) = (
+        request.header("webhook-id"),
+        request.header("webhook-timestamp"),
+        request.header("webhook-signature"),
+    ) else {
+        let _ = webhooks::respond(stream, 401, "Unauthorized").await;
+        return Err("it isn't signed".into());
+    };
+    let now = SystemTime::now();
+    if let Err(e) = webhooks::verify(secret, id, timestamp, signature, &request.body, now) {
+        let _ = webhooks::respond(stream, 401, "Unauthorized").await;
+        return Err(e);
+    }
+    // render retries with the same id, so it's told it arrived, but it's only handled once
+    if !deliveries.first(id, now) {
+        let _ = webhooks::respond(stream, 200, "OK").await;
+        return Err(format!("webhook-id {} was already delivered", id));
+    }
+    match serde_json::from_slice(&request.body) {
+        Ok(event) => {
+            let _ = webhooks::respond(stream, 200, "OK").await;
+            Ok(event)
+        }
+        Err(e) => {
+            let _ = webhooks::respond(stream, 400, "Bad Request").await;
+            Err(format!("it isn't a webhook event: {}", e))
+        }
+    }
+}
+
+/// Print the event as a line of json or hand it to `--exec`. A failing command is reported and
+/// listening carries on.
+async fn dispatch(args: &ListenArgs, event: &WebhookEvent) -> Result<(), RenderError> {
+    let json = serde_json::to_string(event)
+        .map_err(|e| RenderError::Config(format!("Unable to write json {}", e)))?;
+    let Some(exec) = &args.exec else {
+        println!("{}", json);
+        return Ok(());
+    };
+    let mut command = Command::new("sh");
+    command
+        .arg("-c")
+        .arg(exec)
+        .stdin(Stdio::piped())
+        .env("RENDER_EVENT_TYPE", &event.event_type);
+    if let Some(id) = event.service_id() {
+        command.env("RENDER_SERVICE_ID", id);
+    }
+    if let Some(name) = event.service_name() {
+        command.env("RENDER_SERVICE_NAME", name);
+    }
+    let mut child = match command.spawn() {
+        Ok(child) => child,
+        Err(e) => {
+            eprintln!("Unable to run {}: {}", exec, e);
+            return Ok(());
+        }
+    };
+    if let Some(mut stdin) = child.stdin.take() {
+        // the command may not read it
+        let _ = stdin.write_all(format!("{}\n", json).as_bytes()).await;
+    }
+    match child.wait().await {
+        Ok(status) if status.success() => {}
+        Ok(status) => eprintln!("{} {} for {}", exec, status, event.event_type),
+        Err(e) => eprintln!("{} failed for {}: {}", exec, event.event_type, e),
+    }
+    Ok(())
+}
@@ -0,0 +1,70 @@
+use super::Context;
+use crate::cli::LogsArgs;
+use crate::output::Output;
+use render_deploy::{LogEntry, LogsQuery, RenderError};
+use std::time::{Duration, SystemTime};
+use tokio::time::sleep;
+
+/// How often to check for new log lines when following
+const FOLLOW_INTERVAL: Duration = Duration::from_secs(2);
+/// Give up following after this many failed polls in a row
+pub const MAX_FOLLOW_FAILURES: u32 = 10;
+
+pub async fn run(ctx: &Context, args: &LogsArgs) -> Result<(), RenderError> {
+    let service = ctx.service(args.name.as_deref()).await?;
+    let query = LogsQuery {
+        owner_id: service.owner_id.clone(),
+        resources: vec![service.id.clone()],
+        start_time: args
+            .since
+            .map(|since| humantime::format_rfc3339(SystemTime::now() - since).to_string()),
+        limit: Some(args.limit),
+        ..LogsQuery::default()
+    };
+
+    // newest first so the limit keeps the most recent lines
+    let mut recent = ctx.client.list_logs(&query).await?.logs;
+    recent.reverse();
+    for entry in &recent {
+        print_entry(&ctx.output, entry)?;
+    }
+    if !args.follow {
+        return Ok(());
+    }
+
+    let mut tail = ctx.client.tail_logs(LogsQuery {
+        start_time: Some(match recent.last() {
+            Some(entry) => entry.timestamp.clone(),
+            None => humantime::format_rfc3339(SystemTime::now()).to_string(),
+        }),
+        limit: None,
+        ..query
+    });
+    tail.skip(&recent);
+    let mut failures = 0;
+    loop {
+        match tail.poll().await {
+            Ok(entries) => {
+                failures = 0;
+                for entry in &entries {
+                    print_entry(&ctx.output, entry)?;
+                }
+                sleep(FOLLOW_INTERVAL).await;
+            }
+            Err(e) if e.is_transient() && failures < MAX_FOLLOW_FAILURES => {
+                failures += 1;
+                ctx.output
+                    .info(format!("Unable to fetch logs, reconnecting: {}", e));
+                sleep(FOLLOW_INTERVAL * 2u32.pow(failures.min(4))).await;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+fn print_entry(output: &Output, entry: &LogEntry) -> Result<(), RenderError> {
+    output.item(
+        entry,
+        format!("{} {}", entry.timestamp, entry.message.trim_end()),
+    )
+}
@@ -0,0 +1,90 @@
+use crate::cli::{Cli, ManArgs};
+use clap::{Command, CommandFactory};
+use clap_mangen::roff::{bold, roman, Roff};
+use clap_mangen::Man;
+use render_deploy::RenderError;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::Path;
+
+/// Environment variables read outside of options, the ones options read are in their
+/// descriptions
+const ENVIRONMENT: [(&str, &str); 10] = [
+    (
+        "NO_COLOR",
+        "turns colors off when set to anything, like --color never",
+    ),
+    (
+        "XDG_CONFIG_HOME",
+        "where to find render-deploy/config.toml, ~/.config by default",
+    ),
+    (
+        "XDG_CACHE_HOME",
+        "where render-deploy/ keeps the ids of services found by name, ~/.cache by default",
+    ),
+    (
+        "HTTPS_PROXY, HTTP_PROXY, ALL_PROXY, NO_PROXY",
+        "proxy to reach render through",
+    ),
+    (
+        "GITHUB_ACTIONS",
+        "set by Actions, deploys are written to the step's outputs and summary",
+    ),
+    (
+        "GITHUB_OUTPUT",
+        "the step outputs file to write deploy-id, deploy-url, status and duration to",
+    ),
+    (
+        "GITHUB_STEP_SUMMARY",
+        "the job summary file to describe the deploy in",
+    ),
+    (
+        "GITHUB_TOKEN",
+        "for --github-deployment, needs deployments: write",
+    ),
+    ("GITHUB_REPOSITORY", "owner/repo for --github-deployment"),
+    (
+        "GITHUB_API_URL",
+        "github api for --github-deployment, for GitHub Enterprise",
+    ),
+];
+
+pub fn run(args: &ManArgs) -> Result<(), RenderError> {
+    let mut command = Cli::command();
+    // gives subcommands their full `render-deploy deploy` style names
+    command.build();
+    let written = match &args.out_dir {
+        Some(dir) => fs::create_dir_all(dir).and_then(|()| write_pages(&command, dir)),
+        None => render(&command, &mut io::stdout(), true),
+    };
+    written.map_err(|e| RenderError::Config(format!("Unable to write the man page {}", e)))
+}
+
+/// A page for the command and each of its subcommands, `render-deploy-deploy.1` etc
+fn write_pages(command: &Command, dir: &Path) -> io::Result<()> {
+    let name = command.get_display_name().unwrap_or(command.get_name());
+    let mut file = File::create(dir.join(format!("{}.1", name)))?;
+    render(command, &mut file, command.get_display_name().is_none())?;
+    for subcommand in command.get_subcommands() {
+        if subcommand.get_name() != "help" {
+            write_pages(subcommand, dir)?;
+        }
+    }
+    Ok(())
+}
+
+/// The page for a command, the main one also gets an ENVIRONMENT section
+fn render(command: &Command, out: &mut dyn Write, environment: bool) -> io::Result<()> {
+    Man::new(command.clone()).render(out)?;
+    if !environment {
+        return Ok(());
+    }
+    let mut roff = Roff::new();
+    roff.control("SH", ["ENVIRONMENT"]);
+    for (name, description) in ENVIRONMENT {
+        roff.control("TP", []);
+        roff.text([bold(name)]);
+        roff.text([roman(description)]);
+    }
+    roff.to_writer(out)
+}
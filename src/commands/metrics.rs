@@ -0,0 +1,113 @@
+use super::Context;
+use crate::cli::{MetricsArgs, MetricsFormat};
+use crate::output::{format_table, Output, OutputFormat};
+use render_deploy::{MetricKind, MetricSeries, MetricsQuery, RenderError};
+use serde::Serialize;
+use std::time::{Duration, SystemTime};
+
+/// How many points to ask for when --resolution isn't given
+const POINTS: u32 = 60;
+/// render doesn't go finer than this
+const MIN_RESOLUTION: Duration = Duration::from_secs(30);
+
+/// What `--format json` prints for each metric
+#[derive(Serialize, Debug)]
+struct Metrics {
+    metric: MetricKind,
+    series: Vec<MetricSeries>,
+}
+
+pub async fn run(ctx: &Context, args: &MetricsArgs) -> Result<(), RenderError> {
+    let service = ctx.service(args.name.as_deref()).await?;
+    let kinds = match (args.cpu, args.memory) {
+        (true, false) => vec![MetricKind::Cpu],
+        (false, true) => vec![MetricKind::Memory],
+        _ => vec![MetricKind::Cpu, MetricKind::Memory],
+    };
+    let now = SystemTime::now();
+    let start = now
+        .checked_sub(args.since)
+        .unwrap_or(SystemTime::UNIX_EPOCH);
+    let resolution = args
+        .resolution
+        .unwrap_or(args.since / POINTS)
+        .max(MIN_RESOLUTION);
+    let query = MetricsQuery {
+        resources: vec![service.id.clone()],
+        start_time: Some(humantime::format_rfc3339_seconds(start).to_string()),
+        end_time: Some(humantime::format_rfc3339_seconds(now).to_string()),
+        resolution_seconds: Some(resolution.as_secs()),
+    };
+
+    let mut metrics = vec![];
+    for kind in kinds {
+        let series = ctx.client.metrics(kind, &query).await?;
+        metrics.push(Metrics {
+            metric: kind,
+            series,
+        });
+    }
+
+    let format = args.format.unwrap_or(match ctx.output.format() {
+        OutputFormat::Json | OutputFormat::Ndjson => MetricsFormat::Json,
+        _ => MetricsFormat::Table,
+    });
+    match format {
+        MetricsFormat::Table => {
+            let header = ["TIME", "METRIC", "INSTANCE", "VALUE"];
+            let rows: Vec<Vec<String>> = metrics
+                .iter()
+                .flat_map(|metrics| rows(&ctx.output, metrics))
+                .collect();
+            match ctx.output.format() {
+                OutputFormat::Text | OutputFormat::Table | OutputFormat::Csv => {
+                    ctx.output.table(&header, &rows)
+                }
+                _ => print!("{}", format_table(&header, &rows)),
+            }
+        }
+        MetricsFormat::Json => {
+            let json = serde_json::to_string_pretty(&metrics)
+                .map_err(|e| RenderError::Config(format!("Unable to write json {}", e)))?;
+            println!("{}", json);
+        }
+    }
+    Ok(())
+}
+
+fn rows(output: &Output, metrics: &Metrics) -> Vec<Vec<String>> {
+    let mut rows = vec![];
+    for series in &metrics.series {
+        let instance = series.label("instance").unwrap_or("-");
+        for point in &series.values {
+            rows.push(vec![
+                output.time(&point.timestamp),
+                match metrics.metric {
+                    MetricKind::Cpu => "cpu".to_string(),
+                    MetricKind::Memory => "memory".to_string(),
+                },
+                instance.to_string(),
+                format_value(metrics.metric, point.value),
+            ]);
+        }
+    }
+    rows
+}
+
+fn format_value(kind: MetricKind, value: f64) -> String {
+    match kind {
+        MetricKind::Cpu => format!("{:.3} cpu", value),
+        MetricKind::Memory => format!("{:.1} MB", value / 1_000_000.0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_values() {
+        assert_eq!(format_value(MetricKind::Cpu, 0.0214), "0.021 cpu");
+        assert_eq!(format_value(MetricKind::Memory, 268_435_456.0), "268.4 MB");
+    }
+}
@@ -0,0 +1,958 @@
+use crate::cache::ServiceCache;
+use crate::cli::{OnInterrupt, ServiceWaitArgs, WaitArgs};
+use crate::config::Profile;
+use crate::deploy_metrics;
+use crate::git;
+use crate::github::{self, DeploymentState};
+use crate::interrupt::{self, Interrupt};
+use crate::junit::Report;
+use crate::logging;
+use crate::notify;
+use crate::otel::Trace;
+use crate::output::{format_table, Output};
+use crate::progress::{suggested_timeout, typical_deploy_duration, Progress};
+use crate::timeline;
+use dialoguer::console::Term;
+use dialoguer::FuzzySelect;
+use futures::{future, stream, StreamExt, TryStreamExt};
+use render_deploy::{
+    deploy_url, format_duration, Deploy, DeployStatus, LogsQuery, RenderClient, RenderError,
+    Service, ServiceType, WaitOptions,
+};
+use reqwest::StatusCode;
+use serde::Serialize;
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime};
+use tokio::time::sleep;
+
+/// How long to wait for a deploy when neither `--timeout` nor the profile say
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(600);
+/// How often to check for new log lines while waiting
+const LOG_POLL_INTERVAL: Duration = Duration::from_secs(2);
+/// How many services to look up the last deploy of at once when picking, so a big team doesn't
+/// run into the rate limit
+const PICK_CONCURRENCY: usize = 8;
+
+pub mod apply;
+pub mod auth;
+pub mod autoscale;
+pub mod blueprint;
+pub mod cancel;
+pub mod cron;
+pub mod dashboard;
+pub mod deploy;
+pub mod deploys;
+pub mod domains;
+pub mod env;
+pub mod environments;
+pub mod events;
+pub mod headers;
+pub mod job;
+pub mod list;
+pub mod listen;
+pub mod logs;
+pub mod man;
+pub mod metrics;
+pub mod owners;
+pub mod postgres;
+pub mod preview;
+pub mod projects;
+pub mod release;
+pub mod restart;
+pub mod resume;
+pub mod rollback;
+pub mod routes;
+pub mod scale;
+pub mod secret_files;
+pub mod service;
+pub mod stats;
+pub mod status;
+pub mod suspend;
+pub mod watch;
+
+/// What `--output json` prints for a deploy
+#[derive(Serialize, Debug)]
+pub struct DeployResult<'a> {
+    pub service: &'a Service,
+    pub deploy: &'a Deploy,
+    pub url: String,
+}
+
+/// What `--output ndjson` prints for each change in a deploy's status while we wait
+#[derive(Serialize, Debug)]
+pub struct StatusChange<'a> {
+    pub timestamp: String,
+    pub service_id: &'a str,
+    pub service: &'a str,
+    pub deploy_id: &'a str,
+    pub previous_status: &'a DeployStatus,
+    pub status: &'a DeployStatus,
+    /// seconds since we started waiting
+    pub elapsed: u64,
+}
+
+/// Everything a command needs from the global options
+pub struct Context {
+    pub client: RenderClient,
+    /// for webhooks and anything else that isn't render, with `--cacert` and a short timeout
+    pub http: reqwest::Client,
+    pub output: Output,
+    /// take the first service whose name starts with the one we're given
+    pub fuzzy: bool,
+    /// `--service-id`, used when a command isn't given a service
+    pub service_id: Option<String>,
+    /// defaults from the config file
+    pub profile: Profile,
+    /// ids of services we've found by name before
+    pub cache: ServiceCache,
+    /// what to do about deploys we're waiting on when interrupted
+    pub interrupt: Interrupt,
+    /// how deploys and their commands went, for `--junit-output`
+    pub junit: Report,
+    /// the deploy's phases, for OTEL_EXPORTER_OTLP_ENDPOINT
+    pub trace: Trace,
+}
+
+impl Context {
+    /// The service a command was pointed at, by name, id, `--service-id` or the profile, or the
+    /// one built from the git repo we're in, or picked from a list when none of those say and
+    /// there's a terminal to ask on
+    pub async fn service(&self, name: Option<&str>) -> Result<Service, RenderError> {
+        let Some(name) = name
+            .or(self.service_id.as_deref())
+            .or(self.profile.service.as_deref())
+        else {
+            return self.pick_service().await;
+        };
+        if is_service_id(name) {
+            self.client.get_service(name).await
+        } else if let Some((project, environment, name)) = service_path(name) {
+            let project = projects::find_project(&self.client, project).await?;
+            let environment =
+                environments::find_environment(&self.client, &project, environment).await?;
+            self.client
+                .find_service_in_environment(&environment.id, name)
+                .await
+        } else {
+            self.find_service(name).await
+        }
+    }
+
+    /// The service whose repo is this git repo's origin, otherwise fuzzy search every service.
+    /// Scripts without a terminal get an error asking for a name.
+    async fn pick_service(&self) -> Result<Service, RenderError> {
+        let interactive = io::stdin().is_terminal() && io::stderr().is_terminal();
+        let origin = git::origin_url();
+        if !interactive && origin.is_none() {
+            return Err(RenderError::Config(
+                "A service name, --service-id or a profile with a service is required".into(),
+            ));
+        }
+        let services: Vec<Service> = self.client.list_services().try_collect().await?;
+        self.cache.remember(&services);
+        if let Some(origin) = &origin {
+            let from_repo: Vec<Service> = services
+                .iter()
+                .filter(|service| {
+                    (service.repo.as_deref()).is_some_and(|repo| git::same_repo(repo, origin))
+                })
+                .cloned()
+                .collect();
+            match from_repo.as_slice() {
+                [] => {}
+                [service] => {
+                    self.output.info(format!(
+                        "Found {} built from this repo's origin {}",
+                        service.name, origin
+                    ));
+                    return Ok(service.clone());
+                }
+                _ if interactive => return self.pick_from(from_repo).await,
+                _ => {
+                    let names: Vec<&str> = from_repo
+                        .iter()
+                        .map(|service| service.name.as_str())
+                        .collect();
+                    return Err(RenderError::Config(format!(
+                        "More than one service is built from {}, name one of them:\n  {}",
+                        origin,
+                        names.join("\n  ")
+                    )));
+                }
+            }
+        }
+        if !interactive {
+            return Err(RenderError::Config(format!(
+                "No service is built from {}, a service name, --service-id or a profile with a \
+                 service is required",
+                origin.unwrap_or_default()
+            )));
+        }
+        self.pick_from(services).await
+    }
+
+    /// Fuzzy search `services` on the terminal
+    async fn pick_from(&self, services: Vec<Service>) -> Result<Service, RenderError> {
+        if services.is_empty() {
+            return Err(RenderError::Config(
+                "There aren't any services to pick".into(),
+            ));
+        }
+        // the last deploy is only a hint, one we can't look up shouldn't stop the picking
+        let latest_deploys: Vec<_> = stream::iter(&services)
+            .map(|service| self.client.latest_deploy(&service.id))
+            .buffered(PICK_CONCURRENCY)
+            .collect()
+            .await;
+        let rows: Vec<Vec<String>> = services
+            .iter()
+            .zip(latest_deploys)
+            .map(|(service, latest_deploy)| {
+                vec![
+                    service.name.clone(),
+                    service.service_type.to_string(),
+                    service.branch.clone().unwrap_or_else(|| "-".into()),
+                    latest_deploy
+                        .ok()
+                        .flatten()
+                        .map(|deploy| deploy.status.to_string())
+                        .unwrap_or_else(|| "-".into()),
+                ]
+            })
+            .collect();
+        let table = format_table(&["NAME", "TYPE", "BRANCH", "LAST DEPLOY"], &rows);
+        let mut lines = table.lines();
+        // lined up with the items, which are indented by the selection marker
+        eprintln!("  {}", lines.next().unwrap_or_default());
+        let items: Vec<&str> = lines.collect();
+        let picked = FuzzySelect::new()
+            .with_prompt("Service")
+            .items(&items)
+            .default(0)
+            .interact_on_opt(&Term::stderr())
+            .map_err(|e| RenderError::Config(format!("Unable to pick a service {}", e)))?;
+        match picked {
+            Some(index) => Ok(services[index].clone()),
+            None => Err(RenderError::Config("No service picked".into())),
+        }
+    }
+
+    /// Every service a command was pointed at, `name` can be a comma separated list of names,
+    /// ids or globs like `api-*`
+    pub async fn services(&self, name: Option<&str>) -> Result<Vec<Service>, RenderError> {
+        let Some(names) = name else {
+            return Ok(vec![self.service(None).await?]);
+        };
+        let mut services: Vec<Service> = vec![];
+        for name in names
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+        {
+            let found = if is_glob(name) {
+                let matched: Vec<Service> = self
+                    .client
+                    .list_services()
+                    .try_filter(|service| future::ready(glob_match(name, &service.name)))
+                    .try_collect()
+                    .await?;
+                if matched.is_empty() {
+                    return Err(RenderError::ServiceNotFound {
+                        name: name.to_string(),
+                        candidates: vec![],
+                    });
+                }
+                matched
+            } else {
+                vec![self.service(Some(name)).await?]
+            };
+            for service in found {
+                if !services.iter().any(|seen| seen.id == service.id) {
+                    services.push(service);
+                }
+            }
+        }
+        Ok(services)
+    }
+
+    /// How long to wait for a deploy, `None` if we shouldn't wait
+    pub fn wait_timeout(&self, args: &WaitArgs) -> Option<Duration> {
+        if !args.wait && !self.profile.wait.unwrap_or(false) {
+            return None;
+        }
+        Some(self.timeout(args))
+    }
+
+    /// How long to wait for a deploy when we do wait
+    pub fn timeout(&self, args: &WaitArgs) -> Duration {
+        self.timeout_or_default(args.timeout)
+    }
+
+    /// How long and how often to poll a service we're waiting on, `None` if we shouldn't wait
+    pub fn service_wait(&self, args: &ServiceWaitArgs) -> Option<WaitOptions> {
+        args.wait.then(|| WaitOptions {
+            timeout: self.timeout_or_default(args.timeout),
+            poll_interval: args.poll_interval,
+        })
+    }
+
+    /// Point out when the default timeout is likely to run out before a service's deploys
+    /// usually finish, a timeout that's been set is left alone
+    fn suggest_timeout(&self, args: &WaitArgs, service: &Service, typical: Duration) {
+        let suggested = suggested_timeout(typical);
+        if args.timeout.is_some()
+            || self.profile.timeout().is_some()
+            || suggested <= DEFAULT_TIMEOUT
+        {
+            return;
+        }
+        self.output.info(format!(
+            "{}'s deploys typically take {}, longer than the default timeout allows for, try --timeout {}",
+            service.name,
+            format_duration(typical),
+            format_duration(suggested)
+        ));
+    }
+
+    /// A `--timeout`, falling back on the profile's
+    fn timeout_or_default(&self, timeout: Option<Duration>) -> Duration {
+        timeout
+            .or(self.profile.timeout())
+            .unwrap_or(DEFAULT_TIMEOUT)
+    }
+
+    async fn find_service(&self, name: &str) -> Result<Service, RenderError> {
+        if self.fuzzy {
+            return self.client.find_service_fuzzy(name).await?.ok_or_else(|| {
+                RenderError::ServiceNotFound {
+                    name: name.to_string(),
+                    candidates: vec![],
+                }
+            });
+        }
+        if let Some(id) = self.cache.id(name) {
+            match self.client.get_service(&id).await {
+                Ok(service) if service.name == name => return Ok(service),
+                // renamed or deleted since, search for the name again
+                Ok(_)
+                | Err(RenderError::Api {
+                    status: StatusCode::NOT_FOUND,
+                    ..
+                }) => {}
+                Err(e) => return Err(e),
+            }
+        }
+        let service = self.client.find_service(name).await?;
+        self.cache.remember(std::slice::from_ref(&service));
+        Ok(service)
+    }
+}
+
+/// Ask a yes or no question on the terminal, stdout may be piped so the question goes to stderr
+pub fn confirm(question: &str) -> Result<bool, RenderError> {
+    let answer = ask(&format!("{} [y/N]", question), "--yes")?;
+    Ok(matches!(answer.as_str(), "y" | "Y" | "yes"))
+}
+
+/// Make someone type `name` out before doing something that can't be undone
+pub fn confirm_by_typing(name: &str, action: &str) -> Result<bool, RenderError> {
+    let answer = ask(
+        &format!("This {}. Type {} to go ahead:", action, name),
+        "--yes --really",
+    )?;
+    Ok(answer == name)
+}
+
+/// Print `question` on stderr and read the answer, `flags` are how to go ahead without a terminal
+fn ask(question: &str, flags: &str) -> Result<String, RenderError> {
+    if !io::stdin().is_terminal() {
+        return Err(RenderError::Config(format!(
+            "Not a terminal to ask on, pass {} to go ahead",
+            flags
+        )));
+    }
+    eprint!("{} ", question);
+    io::stderr().flush().ok();
+    let mut answer = String::new();
+    io::stdin()
+        .lock()
+        .read_line(&mut answer)
+        .map_err(|e| RenderError::Config(format!("Unable to read the answer {}", e)))?;
+    Ok(answer.trim().to_string())
+}
+
+/// The deploy that's live now, if any deploy has gone live
+pub async fn live_deploy(ctx: &Context, service: &Service) -> Result<Option<Deploy>, RenderError> {
+    let mut history = ctx.client.deploy_history(&service.id);
+    while let Some(deploy) = history.try_next().await? {
+        if deploy.status == DeployStatus::Live {
+            return Ok(Some(deploy));
+        }
+    }
+    Ok(None)
+}
+
+/// The static site a command was pointed at, routes and headers only apply to static sites
+pub async fn static_site(ctx: &Context, name: Option<&str>) -> Result<Service, RenderError> {
+    let service = ctx.service(name).await?;
+    if service.service_type != ServiceType::StaticSite {
+        return Err(RenderError::Config(format!(
+            "{} is a {}, not a static site",
+            service.name, service.service_type
+        )));
+    }
+    Ok(service)
+}
+
+/// Web services, workers and static sites have `srv-` ids, cron jobs have `crn-` ids
+fn is_service_id(name: &str) -> bool {
+    name.starts_with("srv-") || name.starts_with("crn-")
+}
+
+/// Split a `project/environment/service` path
+fn service_path(name: &str) -> Option<(&str, &str, &str)> {
+    let mut parts = name.split('/');
+    match (parts.next(), parts.next(), parts.next(), parts.next()) {
+        (Some(project), Some(environment), Some(service), None) => {
+            Some((project, environment, service))
+        }
+        _ => None,
+    }
+}
+
+fn is_glob(name: &str) -> bool {
+    name.contains(['*', '?'])
+}
+
+/// Match a service name against a pattern where `*` is any run of characters and `?` is any one
+fn glob_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    // where to resume after the last `*` if the rest doesn't match
+    let mut star: Option<(usize, usize)> = None;
+    let (mut p, mut n) = (0, 0);
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match star {
+                Some((star_p, star_n)) => {
+                    p = star_p + 1;
+                    n = star_n + 1;
+                    star = Some((star_p, star_n + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Report a deploy we just triggered and, if asked, wait for it to go live
+pub async fn follow_deploy(
+    ctx: &Context,
+    service: &Service,
+    deploy: Deploy,
+    wait: &WaitArgs,
+) -> Result<(), RenderError> {
+    logging::deploy_status(service, &deploy, None);
+    ctx.output.brief(&deploy.id);
+    ctx.output
+        .info(format!("Created Deploy {}", deploy.describe()));
+    ctx.output.info(deploy_url(service, &deploy));
+    ctx.output
+        .info(format!("Status: {}", ctx.output.status(&deploy.status)));
+    watch_deploy(ctx, service, deploy, wait).await
+}
+
+/// Wait for a deploy to go live if asked to, then print the result
+pub async fn watch_deploy(
+    ctx: &Context,
+    service: &Service,
+    deploy: Deploy,
+    wait: &WaitArgs,
+) -> Result<(), RenderError> {
+    let Some(timeout) = ctx.wait_timeout(wait) else {
+        return report_deploy(ctx, service, &deploy);
+    };
+
+    let start = Instant::now();
+    let typical = typical_deploy_duration(&ctx.client, &service.id, &deploy.id).await;
+    if let Some(typical) = typical {
+        ctx.suggest_timeout(wait, service, typical);
+    }
+    let progress = Progress::new(ctx.output.clone(), !wait.logs, &deploy, typical);
+    let (last_seen, waited) = await_deploy(
+        ctx,
+        service,
+        &deploy,
+        wait,
+        timeout,
+        Some(&progress),
+        |deploy| progress.update(deploy),
+    )
+    .await;
+    report_deploy(ctx, service, &last_seen)?;
+    if matches!(waited, Ok(_) | Err(RenderError::DeployFailed(_))) {
+        print_phases(ctx, service, &last_seen).await;
+    }
+    let deploy = waited?;
+    ctx.output.summary(format!(
+        "Deploy is live on {} in {}",
+        deploy
+            .finished_at
+            .map(|finished_at| ctx.output.time(&finished_at))
+            .unwrap_or("unknown".into()),
+        format_duration(start.elapsed())
+    ));
+    Ok(())
+}
+
+/// Print how long each part of a finished deploy took, it's only progress so not being able to
+/// is left to `--verbose`
+async fn print_phases(ctx: &Context, service: &Service, deploy: &Deploy) {
+    if ctx.output.is_quiet() {
+        return;
+    }
+    match timeline::phases(&ctx.client, &service.id, deploy).await {
+        Ok(phases) if !phases.is_empty() => ctx
+            .output
+            .info(format!("Time spent: {}", timeline::describe(&phases))),
+        Ok(_) => {}
+        Err(e) => ctx.output.verbose(format!(
+            "Unable to find how long each part of the deploy took: {}",
+            e
+        )),
+    }
+}
+
+/// Print a deploy as the result of a command, and hand it to github actions when we're running
+/// in a workflow
+pub fn report_deploy(ctx: &Context, service: &Service, deploy: &Deploy) -> Result<(), RenderError> {
+    if let Err(e) = github::report(service, deploy) {
+        ctx.output
+            .info(format!("Unable to write github actions outputs: {}", e));
+    }
+    ctx.output.result(&DeployResult {
+        service,
+        url: deploy_url(service, deploy),
+        deploy,
+    })
+}
+
+/// Poll a deploy until it finishes or `timeout` passes, calling `on_poll` with every status we
+/// see, spinning `progress` meanwhile, and printing logs and canceling it on timeout if asked to.
+/// Returns the last state we saw of the deploy along with how the wait ended.
+pub async fn await_deploy(
+    ctx: &Context,
+    service: &Service,
+    deploy: &Deploy,
+    wait: &WaitArgs,
+    timeout: Duration,
+    progress: Option<&Progress>,
+    on_poll: impl FnMut(&Deploy),
+) -> (Deploy, Result<Deploy, RenderError>) {
+    let github = github_deployment(ctx, service, deploy, wait).await;
+    let started = Instant::now();
+    let (mut last_seen, mut waited) =
+        poll_deploy(ctx, service, deploy, wait, timeout, progress, on_poll).await;
+    if let (Err(RenderError::Timeout { .. }), true) = (&waited, wait.cancel_on_timeout) {
+        if let Err(e) = cancel_deploy(ctx, service, &mut last_seen, started).await {
+            waited = Err(e);
+        }
+    }
+    ctx.junit.record(
+        &service.name,
+        &format!("deploy {}", last_seen.id),
+        last_seen.duration().unwrap_or(started.elapsed()),
+        waited.as_ref().err().map(ToString::to_string),
+    );
+    ctx.trace.deploy_finished(service, &last_seen, &waited);
+    match &waited {
+        Ok(finished) => finished_deploy(ctx, service, finished, wait).await,
+        Err(RenderError::DeployFailed(finished)) => {
+            finished_deploy(ctx, service, finished, wait).await
+        }
+        Err(_) => (),
+    }
+    if let Some(github) = github {
+        let state = match &waited {
+            Ok(_) => DeploymentState::Success,
+            Err(RenderError::DeployFailed(_)) => DeploymentState::Failure,
+            // still in progress
+            Err(RenderError::Interrupted { .. }) => return (last_seen, waited),
+            Err(_) => DeploymentState::Error,
+        };
+        let url = deploy_url(service, &last_seen);
+        let description = last_seen.status.to_string();
+        if let Err(e) = github.set_state(state, &url, &description).await {
+            ctx.output
+                .info(format!("Unable to update the github deployment: {}", e));
+        }
+    }
+    (last_seen, waited)
+}
+
+/// Poll a deploy until it finishes or `timeout` passes, calling `on_poll` with every status we
+/// see, spinning `progress` and printing logs meanwhile. When we're interrupted the deploy is
+/// canceled or left to carry on as `--on-interrupt` says. Returns the last state we saw of the
+/// deploy along with how the wait ended.
+pub async fn poll_deploy(
+    ctx: &Context,
+    service: &Service,
+    deploy: &Deploy,
+    wait: &WaitArgs,
+    timeout: Duration,
+    progress: Option<&Progress>,
+    mut on_poll: impl FnMut(&Deploy),
+) -> (Deploy, Result<Deploy, RenderError>) {
+    let options = WaitOptions {
+        timeout,
+        poll_interval: wait.poll_interval,
+    };
+    let started = Instant::now();
+    let mut last_seen = deploy.clone();
+    let finished = AtomicBool::new(false);
+    let waiting = async {
+        let waited = ctx
+            .client
+            .wait_for_deploy(&service.id, &deploy.id, &options, |deploy| {
+                on_poll(deploy);
+                if deploy.status != last_seen.status {
+                    status_changed(ctx, service, deploy, &last_seen.status, started);
+                }
+                last_seen = deploy.clone();
+            })
+            .await;
+        finished.store(true, Ordering::Relaxed);
+        waited
+    };
+    let logs = async {
+        if wait.logs {
+            print_logs(&ctx.client, &ctx.output, service, deploy, &finished).await;
+        }
+    };
+    let spinner = async {
+        if let Some(progress) = progress {
+            progress.spin(&finished).await;
+        }
+    };
+    let waited = tokio::select! {
+        (waited, _, _) = async { tokio::join!(waiting, logs, spinner) } => waited,
+        _ = interrupt::signalled() => {
+            if let Some(progress) = progress {
+                progress.clear();
+            }
+            match ctx.interrupt.choose(wait.on_interrupt).await {
+                OnInterrupt::Cancel => {
+                    cancel_deploy(ctx, service, &mut last_seen, started).await.and_then(|()| {
+                        Err(RenderError::DeployFailed(Box::new(last_seen.clone())))
+                    })
+                }
+                OnInterrupt::Detach => {
+                    ctx.output.info(format!(
+                        "Wait on it again with: render-deploy status -w --deploy-id {} {}",
+                        deploy.id, service.id
+                    ));
+                    Err(RenderError::Interrupted {
+                        deploy_id: deploy.id.clone(),
+                    })
+                }
+            }
+        }
+    };
+    (last_seen, waited)
+}
+
+/// Cancel a deploy we've been waiting on since `started`, `last_seen` becomes the canceled deploy
+async fn cancel_deploy(
+    ctx: &Context,
+    service: &Service,
+    last_seen: &mut Deploy,
+    started: Instant,
+) -> Result<(), RenderError> {
+    let canceled = ctx.client.cancel_deploy(&service.id, &last_seen.id).await?;
+    status_changed(ctx, service, &canceled, &last_seen.status, started);
+    *last_seen = canceled;
+    ctx.output.info(format!(
+        "Canceled Deploy {id}, status: {status}",
+        id = last_seen.id,
+        status = ctx.output.status(&last_seen.status)
+    ));
+    Ok(())
+}
+
+/// A deploy we've been waiting on since `started` moved on from `previous`, for
+/// `--log-format json` and `--output ndjson`
+fn status_changed(
+    ctx: &Context,
+    service: &Service,
+    deploy: &Deploy,
+    previous: &DeployStatus,
+    started: Instant,
+) {
+    logging::deploy_status(service, deploy, Some(previous));
+    ctx.trace.status_changed(deploy);
+    ctx.output.event(&StatusChange {
+        timestamp: humantime::format_rfc3339_seconds(SystemTime::now()).to_string(),
+        service_id: &service.id,
+        service: &service.name,
+        deploy_id: &deploy.id,
+        previous_status: previous,
+        status: &deploy.status,
+        elapsed: started.elapsed().as_secs(),
+    });
+}
+
+/// A deploy we waited on went live or failed
+async fn finished_deploy(ctx: &Context, service: &Service, deploy: &Deploy, wait: &WaitArgs) {
+    notify(ctx, service, deploy, wait).await;
+    push_metrics(ctx, service, deploy, wait).await;
+}
+
+/// Tell `--notify-url` and the profile's webhooks how a deploy ended, they're only told so
+/// failing to reach one is a warning
+async fn notify(ctx: &Context, service: &Service, deploy: &Deploy, wait: &WaitArgs) {
+    let webhooks = wait.notify_urls.iter().chain(&ctx.profile.notify);
+    for webhook in webhooks {
+        if let Err(e) = notify::notify(&ctx.http, webhook, service, deploy).await {
+            ctx.output
+                .info(format!("Unable to send a notification: {}", e));
+        }
+    }
+}
+
+/// Send how a deploy ended to `--pushgateway-url` and `--statsd`, failing to is a warning like
+/// it is for notifications
+async fn push_metrics(ctx: &Context, service: &Service, deploy: &Deploy, wait: &WaitArgs) {
+    if let Some(gateway) = &wait.pushgateway_url {
+        if let Err(e) = deploy_metrics::push(&ctx.http, gateway, service, deploy).await {
+            ctx.output
+                .info(format!("Unable to push metrics to {}: {}", gateway, e));
+        }
+    }
+    if let Some(address) = &wait.statsd {
+        if let Err(e) = deploy_metrics::send_statsd(address, service, deploy).await {
+            ctx.output.info(format!("Unable to send metrics: {}", e));
+        }
+    }
+}
+
+/// The github deployment for `--github-deployment`. The render deploy is already underway so
+/// not being able to create it is only a warning.
+async fn github_deployment(
+    ctx: &Context,
+    service: &Service,
+    deploy: &Deploy,
+    wait: &WaitArgs,
+) -> Option<github::Deployment> {
+    if !wait.github_deployment {
+        return None;
+    }
+    let environment = wait.github_environment.as_deref().unwrap_or(&service.name);
+    let git_ref = deploy.commit.as_ref().map(|commit| commit.id.as_str());
+    let url = deploy_url(service, deploy);
+    let description = format!("Render deploy {}", deploy.id);
+    let created = async {
+        let deployment =
+            github::Deployment::create(&ctx.http, environment, git_ref, &description).await?;
+        deployment
+            .set_state(DeploymentState::InProgress, &url, &description)
+            .await?;
+        Ok::<_, RenderError>(deployment)
+    }
+    .await;
+    match created {
+        Ok(deployment) => Some(deployment),
+        Err(e) => {
+            ctx.output
+                .info(format!("Unable to create a github deployment: {}", e));
+            None
+        }
+    }
+}
+
+/// Wait on several deploys at once until they finish or `deadline` passes, printing their
+/// statuses together whenever one changes. Returns the last state we saw of each deploy and the
+/// first failure, if any of them didn't go live.
+pub async fn await_deploys<'a>(
+    ctx: &Context,
+    started: Vec<(&'a Service, Deploy)>,
+    wait: &WaitArgs,
+    deadline: Instant,
+) -> (Vec<(&'a Service, Deploy)>, Option<RenderError>) {
+    let progress = Mutex::new(
+        started
+            .iter()
+            .map(|(_, deploy)| deploy.status.clone())
+            .collect::<Vec<_>>(),
+    );
+    ctx.output.info(describe_progress(
+        &ctx.output,
+        &started,
+        &progress.lock().expect("progress"),
+    ));
+    let waits = started
+        .iter()
+        .enumerate()
+        .filter(|(_, (_, deploy))| deploy.status.is_in_progress())
+        .map(|(index, (service, deploy))| {
+            let (started, progress) = (&started, &progress);
+            async move {
+                let remaining = deadline.saturating_duration_since(Instant::now());
+                let waited = await_deploy(ctx, service, deploy, wait, remaining, None, |deploy| {
+                    let mut progress = progress.lock().expect("progress");
+                    if progress[index] != deploy.status {
+                        progress[index] = deploy.status.clone();
+                        ctx.output
+                            .info(describe_progress(&ctx.output, started, &progress));
+                    }
+                })
+                .await;
+                (index, waited)
+            }
+        });
+    let waited = future::join_all(waits).await;
+
+    let mut finished = started;
+    let mut failure = None;
+    for (index, (last_seen, waited)) in waited {
+        finished[index].1 = last_seen;
+        if let Err(e) = waited {
+            ctx.output
+                .info(format!("{}: {}", finished[index].0.name, e));
+            failure.get_or_insert(e);
+        }
+    }
+    (finished, failure)
+}
+
+/// `api: Build In Progress, worker: Live`
+fn describe_progress(
+    output: &Output,
+    deploys: &[(&Service, Deploy)],
+    statuses: &[DeployStatus],
+) -> String {
+    deploys
+        .iter()
+        .zip(statuses)
+        .map(|((service, _), status)| format!("{}: {}", service.name, output.status(status)))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Print a table of where several deploys ended up
+pub fn report_deploys(ctx: &Context, deploys: &[(&Service, Deploy)]) -> Result<(), RenderError> {
+    let rows: Vec<Vec<String>> = deploys
+        .iter()
+        .map(|(service, deploy)| {
+            vec![
+                service.name.clone(),
+                deploy.id.clone(),
+                ctx.output.status(&deploy.status),
+            ]
+        })
+        .collect();
+    ctx.output.info("");
+    ctx.output.table(&["SERVICE", "DEPLOY", "STATUS"], &rows);
+    for (service, deploy) in deploys {
+        if let Err(e) = github::summarize(service, deploy) {
+            ctx.output
+                .info(format!("Unable to write github actions summary: {}", e));
+        }
+    }
+    let results: Vec<DeployResult> = deploys
+        .iter()
+        .map(|(service, deploy)| DeployResult {
+            service,
+            url: deploy_url(service, deploy),
+            deploy,
+        })
+        .collect();
+    ctx.output.result(&results)
+}
+
+/// Print the service's logs from the start of the deploy until `stop` is set, retrying
+/// transient errors like `logs --follow` does
+async fn print_logs(
+    client: &RenderClient,
+    output: &Output,
+    service: &Service,
+    deploy: &Deploy,
+    stop: &AtomicBool,
+) {
+    let mut tail = client.tail_logs(LogsQuery {
+        owner_id: service.owner_id.clone(),
+        resources: vec![service.id.clone()],
+        start_time: Some(deploy.created_at.clone()),
+        ..LogsQuery::default()
+    });
+    let mut failures = 0;
+    loop {
+        // read stop first so the last poll catches anything logged as the deploy finished
+        let stopping = stop.load(Ordering::Relaxed);
+        match tail.poll().await {
+            Ok(entries) => {
+                failures = 0;
+                for entry in entries {
+                    output.info(entry.message);
+                }
+            }
+            Err(e) if e.is_transient() && failures < logs::MAX_FOLLOW_FAILURES => {
+                failures += 1;
+                output.info(format!("Unable to fetch logs, reconnecting: {}", e));
+            }
+            Err(e) => {
+                output.info(format!("Unable to fetch logs: {}", e));
+                return;
+            }
+        }
+        if stopping {
+            return;
+        }
+        // backing off is cut short when the deploy finishes, for the last poll
+        for _ in 0..2u32.pow(failures.min(4)) {
+            if stop.load(Ordering::Relaxed) {
+                break;
+            }
+            sleep(LOG_POLL_INTERVAL).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn glob_match_services() {
+        assert!(glob_match("api-*", "api-web"));
+        assert!(glob_match("api-*", "api-"));
+        assert!(!glob_match("api-*", "web-api"));
+        assert!(glob_match("*-worker", "api-worker"));
+        assert!(glob_match("api-?", "api-1"));
+        assert!(!glob_match("api-?", "api-12"));
+        assert!(glob_match("a*b*c", "aXXbYYbc"));
+        assert!(glob_match("api", "api"));
+        assert!(!glob_match("api", "api-web"));
+    }
+
+    #[test]
+    fn split_service_paths() {
+        assert_eq!(
+            service_path("shop/production/api"),
+            Some(("shop", "production", "api"))
+        );
+        assert_eq!(service_path("api"), None);
+        assert_eq!(service_path("shop/api"), None);
+        assert_eq!(service_path("a/b/c/d"), None);
+    }
+}
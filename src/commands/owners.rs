@@ -0,0 +1,92 @@
+use super::Context;
+use crate::output::Output;
+use futures::TryStreamExt;
+use render_deploy::{Owner, RenderClient, RenderError};
+
+pub async fn run(ctx: &Context) -> Result<(), RenderError> {
+    let owners: Vec<Owner> = ctx.client.list_owners().try_collect().await?;
+    table(&ctx.output, &owners);
+    ctx.output.result(&owners)
+}
+
+/// The workspaces lined up in a table, for text mode
+pub fn table(output: &Output, owners: &[Owner]) {
+    let rows: Vec<Vec<String>> = owners
+        .iter()
+        .map(|owner| {
+            vec![
+                owner.name.clone(),
+                owner.id.clone(),
+                owner.owner_type.to_string(),
+                owner.email.clone().unwrap_or_else(|| "-".into()),
+            ]
+        })
+        .collect();
+    output.table(&["NAME", "ID", "TYPE", "EMAIL"], &rows);
+}
+
+/// The workspace `--owner` or the profile points at, by id, name or email
+pub async fn find_owner(client: &RenderClient, name: &str) -> Result<Owner, RenderError> {
+    if name.starts_with("usr-") || name.starts_with("tea-") {
+        return client.get_owner(name).await;
+    }
+    let owners: Vec<Owner> = client.list_owners().try_collect().await?;
+    pick_owner(name, owners)
+}
+
+fn pick_owner(name: &str, owners: Vec<Owner>) -> Result<Owner, RenderError> {
+    let describe = |owners: &[Owner]| {
+        owners
+            .iter()
+            .map(|owner| format!("{} ({})", owner.name, owner.id))
+            .collect::<Vec<_>>()
+            .join("\n  ")
+    };
+    let (exact, others): (Vec<Owner>, Vec<Owner>) = owners
+        .into_iter()
+        .partition(|owner| owner.name == name || owner.email.as_deref() == Some(name));
+    match exact.len() {
+        1 => Ok(exact.into_iter().next().expect("one owner")),
+        0 => Err(RenderError::Config(format!(
+            "Cannot find a workspace named {}, the api key can reach:\n  {}",
+            name,
+            describe(&others)
+        ))),
+        _ => Err(RenderError::Config(format!(
+            "More than one workspace is named {}, pass its id instead:\n  {}",
+            name,
+            describe(&exact)
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use render_deploy::OwnerType;
+
+    fn owner(id: &str, name: &str, email: &str) -> Owner {
+        Owner {
+            id: id.into(),
+            name: name.into(),
+            email: Some(email.into()),
+            owner_type: OwnerType::Team,
+        }
+    }
+
+    #[test]
+    fn pick_owner_by_name_or_email() {
+        let owners = || {
+            vec![
+                owner("usr-1", "Francis", "francis@example.com"),
+                owner("tea-1", "Platform", "platform@example.com"),
+            ]
+        };
+        assert_eq!(pick_owner("Platform", owners()).unwrap().id, "tea-1");
+        assert_eq!(
+            pick_owner("francis@example.com", owners()).unwrap().id,
+            "usr-1"
+        );
+        assert!(pick_owner("plat", owners()).is_err());
+    }
+}
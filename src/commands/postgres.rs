@@ -0,0 +1,211 @@
+use super::Context;
+use crate::cli::{PostgresArgs, PostgresCommands, PostgresRecoverArgs};
+use futures::TryStreamExt;
+use render_deploy::{ConnectionInfo, Postgres, RecoverRequest, RecoveryStatus, RenderError};
+use serde::Serialize;
+
+/// What `--output json` prints for `postgres info`
+#[derive(Serialize, Debug)]
+struct PostgresInfo<'a> {
+    postgres: &'a Postgres,
+    connection: &'a ConnectionInfo,
+}
+
+pub async fn run(ctx: &Context, args: &PostgresArgs) -> Result<(), RenderError> {
+    match &args.command {
+        PostgresCommands::List => {
+            let databases: Vec<Postgres> = ctx.client.list_postgres().try_collect().await?;
+            let rows: Vec<Vec<String>> = databases
+                .iter()
+                .map(|postgres| {
+                    vec![
+                        postgres.name.clone(),
+                        postgres.id.clone(),
+                        postgres.status.to_string(),
+                        postgres.version.clone(),
+                        postgres.plan.clone(),
+                        postgres.region.clone(),
+                    ]
+                })
+                .collect();
+            ctx.output.table(
+                &["NAME", "ID", "STATUS", "VERSION", "PLAN", "REGION"],
+                &rows,
+            );
+            ctx.output.result(&databases)
+        }
+        PostgresCommands::Info { database, reveal } => {
+            let postgres = find_database(ctx, database).await?;
+            let connection = connection_info(ctx, &postgres, *reveal).await?;
+            ctx.output.info(format!(
+                "{name} ({id}) {dashboard}",
+                name = postgres.name,
+                id = postgres.id,
+                dashboard = postgres.dashboard_url
+            ));
+            ctx.output.info(format!("Status: {}", postgres.status));
+            ctx.output.info(format!(
+                "Postgres {} on {} in {}",
+                postgres.version, postgres.plan, postgres.region
+            ));
+            ctx.output
+                .info(format!("Database: {}", postgres.database_name));
+            ctx.output.info(format!("User: {}", postgres.database_user));
+            ctx.output
+                .info(format!("Password: {}", connection.password));
+            ctx.output.info(format!(
+                "Internal: {}",
+                connection.internal_connection_string
+            ));
+            ctx.output.info(format!(
+                "External: {}",
+                connection.external_connection_string
+            ));
+            ctx.output
+                .info(format!("psql: {}", connection.psql_command));
+            ctx.output.result(&PostgresInfo {
+                postgres: &postgres,
+                connection: &connection,
+            })
+        }
+        PostgresCommands::Recovery { database } => {
+            let postgres = find_database(ctx, database).await?;
+            let recovery = ctx.client.postgres_recovery_info(&postgres.id).await?;
+            match (recovery.recovery_status, &recovery.starts_at) {
+                (RecoveryStatus::Available, Some(starts_at)) => ctx.output.info(format!(
+                    "{} can be recovered to any time since {}",
+                    postgres.name, starts_at
+                )),
+                (RecoveryStatus::Available, None) => ctx
+                    .output
+                    .info(format!("{} can be recovered", postgres.name)),
+                (RecoveryStatus::BackupNotReady, _) => ctx.output.info(format!(
+                    "{} can't be recovered until its first backup finishes",
+                    postgres.name
+                )),
+                (RecoveryStatus::NotAvailable, _) => ctx.output.info(format!(
+                    "{}'s plan doesn't include point in time recovery",
+                    postgres.name
+                )),
+            }
+            ctx.output.result(&recovery)
+        }
+        PostgresCommands::Recover(args) => recover(ctx, args).await,
+        PostgresCommands::Export { database, wait } => {
+            let postgres = find_database(ctx, database).await?;
+            let export = ctx.client.export_postgres(&postgres.id).await?;
+            ctx.output
+                .info(format!("Exporting {} as {}", postgres.name, export.id));
+            let Some(options) = ctx.service_wait(wait) else {
+                return ctx.output.result(&export);
+            };
+            let export = ctx
+                .client
+                .wait_for_postgres_export(&postgres.id, &export.id, &options)
+                .await?;
+            if let Some(url) = &export.url {
+                ctx.output.info(format!("Download it from {}", url));
+            }
+            ctx.output.result(&export)
+        }
+        PostgresCommands::Exports { database } => {
+            let postgres = find_database(ctx, database).await?;
+            let exports = ctx.client.list_postgres_exports(&postgres.id).await?;
+            let rows: Vec<Vec<String>> = exports
+                .iter()
+                .map(|export| {
+                    vec![
+                        export.id.clone(),
+                        ctx.output.time(&export.created_at),
+                        export.url.clone().unwrap_or_else(|| "in progress".into()),
+                    ]
+                })
+                .collect();
+            ctx.output.table(&["ID", "CREATED", "URL"], &rows);
+            ctx.output.result(&exports)
+        }
+        PostgresCommands::ConnectionString {
+            database,
+            external,
+            reveal,
+        } => {
+            let postgres = find_database(ctx, database).await?;
+            let connection = connection_info(ctx, &postgres, *reveal).await?;
+            let connection_string = match external {
+                true => &connection.external_connection_string,
+                false => &connection.internal_connection_string,
+            };
+            ctx.output.item(connection_string, connection_string)
+        }
+    }
+}
+
+async fn recover(ctx: &Context, args: &PostgresRecoverArgs) -> Result<(), RenderError> {
+    let postgres = find_database(ctx, &args.database).await?;
+    let request = RecoverRequest {
+        restore_time: args.to.clone(),
+        datastore_name: args.name.clone(),
+    };
+    let recovered = ctx.client.recover_postgres(&postgres.id, &request).await?;
+    ctx.output.info(format!(
+        "Recovering {} to {} into {} ({})",
+        postgres.name, args.to, recovered.name, recovered.id
+    ));
+    ctx.output.info(&recovered.dashboard_url);
+    let Some(options) = ctx.service_wait(&args.wait) else {
+        return ctx.output.result(&recovered);
+    };
+    let recovered = ctx
+        .client
+        .wait_for_postgres(&recovered.id, &options)
+        .await?;
+    ctx.output.info(format!("{} is available", recovered.name));
+    ctx.output.result(&recovered)
+}
+
+async fn connection_info(
+    ctx: &Context,
+    postgres: &Postgres,
+    reveal: bool,
+) -> Result<ConnectionInfo, RenderError> {
+    let connection = ctx.client.postgres_connection_info(&postgres.id).await?;
+    Ok(match reveal {
+        true => connection,
+        false => connection.masked(),
+    })
+}
+
+/// The database named exactly `name`, or with the id `name`
+async fn find_database(ctx: &Context, name: &str) -> Result<Postgres, RenderError> {
+    if name.starts_with("dpg-") {
+        return ctx.client.get_postgres(name).await;
+    }
+    let databases: Vec<Postgres> = ctx.client.find_postgres(name).try_collect().await?;
+    let (exact, similar): (Vec<Postgres>, Vec<Postgres>) = databases
+        .into_iter()
+        .partition(|postgres| postgres.name == name);
+    let describe = |databases: &[Postgres]| {
+        databases
+            .iter()
+            .map(|postgres| format!("{} ({})", postgres.name, postgres.id))
+            .collect::<Vec<_>>()
+            .join("\n  ")
+    };
+    match exact.len() {
+        1 => Ok(exact.into_iter().next().expect("one database")),
+        0 if similar.is_empty() => Err(RenderError::Config(format!(
+            "Cannot find a database named {}",
+            name
+        ))),
+        0 => Err(RenderError::Config(format!(
+            "Cannot find a database named {}, did you mean:\n  {}",
+            name,
+            describe(&similar)
+        ))),
+        _ => Err(RenderError::Config(format!(
+            "More than one database is named {}:\n  {}",
+            name,
+            describe(&exact)
+        ))),
+    }
+}
@@ -0,0 +1,242 @@
+use super::{confirm, follow_deploy, Context};
+use crate::cli::{PreviewArgs, PreviewCommands, PreviewCreateArgs};
+use futures::{future, TryStreamExt};
+use render_deploy::{
+    format_duration, CreateServiceRequest, EnvSpecificDetails, EnvVar, RenderError, Service,
+    ServiceDetailsRequest, ServiceType,
+};
+use serde::Serialize;
+use std::time::{Duration, SystemTime};
+
+/// Previews are named `{base}-preview-{branch}` or `{base}-preview-pr-{number}`, render matches
+/// names by prefix so searching for `{base}-preview-` finds them all
+const PREVIEW: &str = "-preview-";
+
+/// A preview service and when it last deployed
+#[derive(Serialize, Debug)]
+struct Preview {
+    service: Service,
+    /// RFC3339, when it was created if it's never deployed
+    last_deployed_at: String,
+}
+
+impl Preview {
+    fn older_than(&self, age: Duration) -> bool {
+        let Ok(last_deployed_at) = humantime::parse_rfc3339_weak(&self.last_deployed_at) else {
+            return false;
+        };
+        SystemTime::now()
+            .duration_since(last_deployed_at)
+            .is_ok_and(|since| since > age)
+    }
+}
+
+pub async fn run(ctx: &Context, args: &PreviewArgs) -> Result<(), RenderError> {
+    match &args.command {
+        PreviewCommands::List { base } => {
+            let previews = previews(ctx, base.as_deref()).await?;
+            print_previews(ctx, &previews);
+            ctx.output.result(&previews)
+        }
+        PreviewCommands::Create(args) => create(ctx, args).await,
+        PreviewCommands::Delete { base, preview, yes } => {
+            let slug = match preview.parse::<u32>() {
+                Ok(pr) => format!("pr-{}", pr),
+                Err(_) => slug(preview),
+            };
+            let service = ctx
+                .client
+                .find_service(&format!("{}{}{}", base, PREVIEW, slug))
+                .await?;
+            if !yes && !confirm(&format!("Delete {} ({})?", service.name, service.id))? {
+                return Err(RenderError::Config(
+                    "Delete canceled, nothing was deleted".into(),
+                ));
+            }
+            ctx.client.delete_service(&service.id).await?;
+            ctx.output
+                .info(format!("Deleted {} ({})", service.name, service.id));
+            ctx.output.result(&service)
+        }
+        PreviewCommands::Prune {
+            base,
+            older_than,
+            dry_run,
+            yes,
+        } => {
+            let stale: Vec<Preview> = previews(ctx, base.as_deref())
+                .await?
+                .into_iter()
+                .filter(|preview| preview.older_than(*older_than))
+                .collect();
+            if stale.is_empty() {
+                ctx.output.info(format!(
+                    "No previews older than {}",
+                    format_duration(*older_than)
+                ));
+                return ctx.output.result(&stale);
+            }
+            print_previews(ctx, &stale);
+            if *dry_run {
+                ctx.output.info("Dry run, nothing was deleted");
+                return ctx.output.result(&stale);
+            }
+            if !yes && !confirm(&format!("Delete these {} previews?", stale.len()))? {
+                return Err(RenderError::Config(
+                    "Prune canceled, nothing was deleted".into(),
+                ));
+            }
+            for preview in &stale {
+                ctx.client.delete_service(&preview.service.id).await?;
+                ctx.output.info(format!("Deleted {}", preview.service.name));
+            }
+            ctx.output.result(&stale)
+        }
+    }
+}
+
+/// Copy `base` to a service that deploys the branch, reusing the copy if it's already there
+async fn create(ctx: &Context, args: &PreviewCreateArgs) -> Result<(), RenderError> {
+    let base = ctx.service(Some(&args.base)).await?;
+    let name = format!(
+        "{}{}{}",
+        base.name,
+        PREVIEW,
+        args.pr
+            .map(|pr| format!("pr-{}", pr))
+            .unwrap_or_else(|| slug(&args.branch))
+    );
+    match ctx.client.find_service(&name).await {
+        Ok(existing) => {
+            ctx.output.info(format!(
+                "{} ({}) already exists {}",
+                existing.name, existing.id, existing.dashboard_url
+            ));
+            return ctx.output.result(&existing);
+        }
+        Err(RenderError::ServiceNotFound { .. }) => {}
+        Err(e) => return Err(e),
+    }
+
+    let settings = ctx.client.get_service_settings(&base.id).await?;
+    let env_vars: Vec<EnvVar> = if args.no_env {
+        vec![]
+    } else {
+        ctx.client.list_env_vars(&base.id).try_collect().await?
+    };
+    let service_details = if base.service_type == ServiceType::StaticSite {
+        ServiceDetailsRequest {
+            build_command: settings.build_command,
+            publish_path: settings.publish_path,
+            ..Default::default()
+        }
+    } else {
+        ServiceDetailsRequest {
+            runtime: settings.runtime,
+            env_specific_details: Some(EnvSpecificDetails {
+                build_command: settings.build_command,
+                start_command: settings.start_command,
+            }),
+            ..Default::default()
+        }
+    };
+    let request = CreateServiceRequest {
+        service_type: base.service_type,
+        name,
+        owner_id: base.owner_id.clone(),
+        repo: base.repo.clone(),
+        branch: Some(args.branch.clone()),
+        // pushes to the branch keep the preview up to date
+        auto_deploy: true,
+        env_vars,
+        service_details: ServiceDetailsRequest {
+            plan: settings.plan,
+            region: settings.region,
+            ..service_details
+        },
+    };
+    let created = ctx.client.create_service(&request).await?;
+    let service = created.service;
+    ctx.output.info(format!(
+        "Created {} ({}) {}",
+        service.name, service.id, service.dashboard_url
+    ));
+    match created.deploy_id {
+        Some(deploy_id) => {
+            let deploy = ctx.client.get_deploy(&service.id, &deploy_id).await?;
+            follow_deploy(ctx, &service, deploy, &args.wait).await
+        }
+        None => ctx.output.result(&service),
+    }
+}
+
+/// Every preview, or just `base`'s
+async fn previews(ctx: &Context, base: Option<&str>) -> Result<Vec<Preview>, RenderError> {
+    let services: Vec<Service> = match base {
+        Some(base) => {
+            let prefix = format!("{}{}", base, PREVIEW);
+            ctx.client
+                .find_services(&prefix)
+                .try_filter(|service| future::ready(service.name.starts_with(&prefix)))
+                .try_collect()
+                .await?
+        }
+        None => {
+            ctx.client
+                .list_services()
+                .try_filter(|service| future::ready(service.name.contains(PREVIEW)))
+                .try_collect()
+                .await?
+        }
+    };
+    let mut previews = vec![];
+    for service in services {
+        let last_deployed_at = match ctx.client.latest_deploy(&service.id).await? {
+            Some(deploy) => deploy.created_at,
+            None => service.created_at.clone(),
+        };
+        previews.push(Preview {
+            service,
+            last_deployed_at,
+        });
+    }
+    Ok(previews)
+}
+
+fn print_previews(ctx: &Context, previews: &[Preview]) {
+    let rows: Vec<Vec<String>> = previews
+        .iter()
+        .map(|preview| {
+            vec![
+                preview.service.name.clone(),
+                preview.service.id.clone(),
+                preview.service.branch.clone().unwrap_or_else(|| "-".into()),
+                ctx.output.time(&preview.last_deployed_at),
+            ]
+        })
+        .collect();
+    ctx.output
+        .table(&["NAME", "ID", "BRANCH", "LAST DEPLOY"], &rows);
+}
+
+/// A branch name made fit for a service name, `feature/Login_Page` becomes `feature-login-page`
+fn slug(branch: &str) -> String {
+    branch
+        .to_lowercase()
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slug_branch_names() {
+        assert_eq!(slug("feature/Login_Page"), "feature-login-page");
+        assert_eq!(slug("fix--double//slash-"), "fix-double-slash");
+        assert_eq!(slug("pr-42"), "pr-42");
+    }
+}
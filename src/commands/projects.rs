@@ -0,0 +1,84 @@
+use super::Context;
+use crate::cli::{ProjectsArgs, ProjectsCommands};
+use futures::TryStreamExt;
+use render_deploy::{Environment, Project, RenderClient, RenderError};
+use serde::Serialize;
+
+/// What `--output json` prints for each project
+#[derive(Serialize, Debug)]
+struct ProjectSummary {
+    project: Project,
+    environments: Vec<Environment>,
+}
+
+pub async fn run(ctx: &Context, args: &ProjectsArgs) -> Result<(), RenderError> {
+    match &args.command {
+        ProjectsCommands::List => {
+            let mut summaries = vec![];
+            let mut projects = ctx.client.list_projects();
+            while let Some(project) = projects.try_next().await? {
+                let environments = ctx
+                    .client
+                    .list_environments(&project.id)
+                    .try_collect()
+                    .await?;
+                summaries.push(ProjectSummary {
+                    project,
+                    environments,
+                });
+            }
+            let rows: Vec<Vec<String>> = summaries
+                .iter()
+                .map(|summary| {
+                    let environments: Vec<&str> = summary
+                        .environments
+                        .iter()
+                        .map(|environment| environment.name.as_str())
+                        .collect();
+                    vec![
+                        summary.project.name.clone(),
+                        summary.project.id.clone(),
+                        environments.join(", "),
+                    ]
+                })
+                .collect();
+            ctx.output.table(&["NAME", "ID", "ENVIRONMENTS"], &rows);
+            ctx.output.result(&summaries)
+        }
+    }
+}
+
+/// The project named exactly `name`, or with the id `name`
+pub async fn find_project(client: &RenderClient, name: &str) -> Result<Project, RenderError> {
+    if name.starts_with("prj-") {
+        return client.get_project(name).await;
+    }
+    let projects: Vec<Project> = client.list_projects().try_collect().await?;
+    let (exact, others): (Vec<Project>, Vec<Project>) = projects
+        .into_iter()
+        .partition(|project| project.name == name);
+    let describe = |projects: &[Project]| {
+        projects
+            .iter()
+            .map(|project| format!("{} ({})", project.name, project.id))
+            .collect::<Vec<_>>()
+            .join("\n  ")
+    };
+    match exact.len() {
+        1 => Ok(exact.into_iter().next().expect("one project")),
+        0 if others.is_empty() => Err(RenderError::Config(format!(
+            "Cannot find a project named {}",
+            name
+        ))),
+        0 => Err(RenderError::Config(format!(
+            "Cannot find a project named {}, there's:\n  {}",
+            name,
+            describe(&others)
+        ))),
+        _ => Err(RenderError::Config(format!(
+            "More than one project is named {}, pass --owner or its id instead:\n  {}",
+            name,
+            describe(&exact)
+        ))),
+    }
+}
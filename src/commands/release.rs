@@ -0,0 +1,63 @@
+use super::{await_deploys, report_deploys, Context};
+use crate::cli::ReleaseArgs;
+use crate::release;
+use render_deploy::{RenderError, TriggerDeployRequest};
+use std::time::Instant;
+
+pub async fn run(ctx: &Context, args: &ReleaseArgs) -> Result<(), RenderError> {
+    if args.wait.logs {
+        return Err(RenderError::Config(
+            "--logs only works when deploying a single service".into(),
+        ));
+    }
+    let release = release::load(&args.file)?;
+    let stages = release.stages()?;
+
+    // look every service up front so a typo fails before anything deploys
+    let mut services = vec![];
+    for stage in &stages {
+        let mut stage_services = vec![];
+        for (name, step) in stage {
+            stage_services.push((ctx.service(Some(step.service(name))).await?, *step));
+        }
+        services.push(stage_services);
+    }
+
+    let deadline = Instant::now() + ctx.timeout(&args.wait);
+    let mut finished = vec![];
+    for (number, stage) in services.iter().enumerate() {
+        let names: Vec<&str> = stage
+            .iter()
+            .map(|(service, _)| service.name.as_str())
+            .collect();
+        ctx.output.info(format!(
+            "Stage {number}: {names}",
+            number = number + 1,
+            names = names.join(", ")
+        ));
+        let mut started = vec![];
+        for (service, step) in stage {
+            let request = TriggerDeployRequest {
+                commit_id: step.commit.clone(),
+                ..TriggerDeployRequest::default()
+            };
+            let deploy = ctx.client.trigger_deploy(&service.id, &request).await?;
+            ctx.output.info(format!(
+                "Created {name} Deploy {description}",
+                name = service.name,
+                description = deploy.describe()
+            ));
+            started.push((service, deploy));
+        }
+        let (stage_finished, failure) = await_deploys(ctx, started, &args.wait, deadline).await;
+        finished.extend(stage_finished);
+        if let Some(e) = failure {
+            ctx.output
+                .info("Stopping the release, later stages weren't deployed");
+            report_deploys(ctx, &finished)?;
+            return Err(e);
+        }
+        ctx.output.info("");
+    }
+    report_deploys(ctx, &finished)
+}
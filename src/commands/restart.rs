@@ -0,0 +1,38 @@
+use super::Context;
+use crate::cli::RestartArgs;
+use render_deploy::{Event, RenderError, Service};
+use serde::Serialize;
+use std::time::SystemTime;
+
+/// What `--output json` prints
+#[derive(Serialize, Debug)]
+struct RestartResult<'a> {
+    service: &'a Service,
+    restarted_at: String,
+    /// the instance coming back, only when waiting
+    available: Option<Event>,
+}
+
+pub async fn run(ctx: &Context, args: &RestartArgs) -> Result<(), RenderError> {
+    let service = ctx.service(args.name.as_deref()).await?;
+    let restarted_at = humantime::format_rfc3339_seconds(SystemTime::now()).to_string();
+    ctx.client.restart_service(&service.id).await?;
+    ctx.output.info(format!("Restarting {}", service.name));
+    ctx.output.info(&service.dashboard_url);
+
+    let mut result = RestartResult {
+        service: &service,
+        restarted_at,
+        available: None,
+    };
+    if let Some(options) = ctx.service_wait(&args.wait) {
+        let event = ctx
+            .client
+            .wait_for_restart(&service.id, &result.restarted_at, &options)
+            .await?;
+        ctx.output
+            .info(format!("{} is running again", service.name));
+        result.available = Some(event);
+    }
+    ctx.output.result(&result)
+}
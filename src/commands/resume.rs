@@ -0,0 +1,23 @@
+use super::Context;
+use crate::cli::ResumeArgs;
+use render_deploy::RenderError;
+
+pub async fn run(ctx: &Context, args: &ResumeArgs) -> Result<(), RenderError> {
+    let service = ctx.service(args.name.as_deref()).await?;
+    if !service.suspended {
+        ctx.output.info(format!("{} isn't suspended", service.name));
+        return ctx.output.result(&service);
+    }
+
+    ctx.client.resume_service(&service.id).await?;
+    ctx.output.info(format!("Resuming {}", service.name));
+    let Some(options) = ctx.service_wait(&args.wait) else {
+        return ctx.output.result(&service);
+    };
+    let service = ctx
+        .client
+        .wait_for_suspended(&service.id, false, &options)
+        .await?;
+    ctx.output.info(format!("{} is running", service.name));
+    ctx.output.result(&service)
+}
@@ -0,0 +1,121 @@
+use super::{follow_deploy, Context};
+use crate::cli::RollbackArgs;
+use futures::{Stream, TryStreamExt};
+use render_deploy::{image_repository, Deploy, DeployStatus, RenderError, TriggerDeployRequest};
+
+pub async fn run(ctx: &Context, args: &RollbackArgs) -> Result<(), RenderError> {
+    let service = ctx.service(args.name.as_deref()).await?;
+
+    let request = match &args.to {
+        Some(to) if to.starts_with("dep-") => {
+            redeploy_request(&ctx.client.get_deploy(&service.id, to).await?)?
+        }
+        Some(commit) => TriggerDeployRequest {
+            commit_id: Some(commit.clone()),
+            ..TriggerDeployRequest::default()
+        },
+        None => {
+            let deploy = previous_live_deploy(ctx.client.deploy_history(&service.id))
+                .await?
+                .ok_or_else(|| {
+                    RenderError::Config(format!(
+                        "{} has no previously live deploy to roll back to",
+                        service.name
+                    ))
+                })?;
+            ctx.output.info(format!(
+                "Previous Live Deploy {id} {description}",
+                id = deploy.id,
+                description = deploy.describe()
+            ));
+            redeploy_request(&deploy)?
+        }
+    };
+    let target = request
+        .commit_id
+        .as_ref()
+        .map(|commit| format!("#{}", commit))
+        .or(request.image_url.clone())
+        .unwrap_or_default();
+    ctx.output.info(format!(
+        "Rolling back {name} to {target}",
+        name = service.name,
+        target = target
+    ));
+    ctx.output.info("");
+
+    let deploy = ctx.client.trigger_deploy(&service.id, &request).await?;
+    follow_deploy(ctx, &service, deploy, &args.wait).await
+}
+
+/// A request that deploys the same commit or image as `deploy`
+pub fn redeploy_request(deploy: &Deploy) -> Result<TriggerDeployRequest, RenderError> {
+    match (&deploy.commit, &deploy.image) {
+        (Some(commit), _) => Ok(TriggerDeployRequest {
+            commit_id: Some(commit.id.clone()),
+            ..TriggerDeployRequest::default()
+        }),
+        // pin the digest, the tag may have moved since
+        (None, Some(image)) => Ok(TriggerDeployRequest {
+            image_url: Some(match &image.sha {
+                Some(sha) => format!("{}@{}", image_repository(&image.image_ref), sha),
+                None => image.image_ref.clone(),
+            }),
+            ..TriggerDeployRequest::default()
+        }),
+        (None, None) => Err(RenderError::Config(format!(
+            "Deploy {} has no commit or image to redeploy",
+            deploy.id
+        ))),
+    }
+}
+
+/// Render marks the deploy that's serving traffic as `live` and every deploy it replaced as
+/// `deactivated`, so the deploy to roll back to is the first deactivated one older than the live
+/// one. Failed and canceled deploys never went live and are skipped.
+pub async fn previous_live_deploy(
+    mut history: impl Stream<Item = Result<Deploy, RenderError>> + Unpin,
+) -> Result<Option<Deploy>, RenderError> {
+    let mut seen_live = false;
+    while let Some(deploy) = history.try_next().await? {
+        match deploy.status {
+            DeployStatus::Live => seen_live = true,
+            DeployStatus::Deactivated if seen_live => return Ok(Some(deploy)),
+            _ => (),
+        }
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures;
+    use futures::executor::block_on;
+    use futures::stream;
+
+    fn deploy(id: &str, status: DeployStatus) -> Result<Deploy, RenderError> {
+        Ok(fixtures::deploy(id, status))
+    }
+
+    #[test]
+    fn skips_failed_deploys() {
+        let history = vec![
+            deploy("dep-4", DeployStatus::BuildFailed),
+            deploy("dep-3", DeployStatus::Live),
+            deploy("dep-2", DeployStatus::UpdateFailed),
+            deploy("dep-1", DeployStatus::Deactivated),
+        ];
+        let previous = block_on(previous_live_deploy(stream::iter(history))).unwrap();
+        assert_eq!(previous.unwrap().id, "dep-1");
+    }
+
+    #[test]
+    fn nothing_to_roll_back_to() {
+        let history = vec![deploy("dep-1", DeployStatus::Live)];
+        assert_eq!(
+            block_on(previous_live_deploy(stream::iter(history))).unwrap(),
+            None
+        );
+    }
+}
@@ -0,0 +1,61 @@
+use super::{static_site, Context};
+use crate::cli::{RoutesArgs, RoutesCommands};
+use futures::TryStreamExt;
+use render_deploy::{AddRouteRequest, RenderError, Route, RouteType};
+
+pub async fn run(ctx: &Context, args: &RoutesArgs) -> Result<(), RenderError> {
+    match &args.command {
+        RoutesCommands::List { name } => {
+            let service = static_site(ctx, name.as_deref()).await?;
+            let mut routes: Vec<Route> = ctx.client.list_routes(&service.id).try_collect().await?;
+            routes.sort_by_key(|route| route.priority);
+            let rows: Vec<Vec<String>> = routes
+                .iter()
+                .map(|route| {
+                    vec![
+                        route.id.clone(),
+                        route.priority.to_string(),
+                        route.route_type.to_string(),
+                        route.source.clone(),
+                        route.destination.clone(),
+                    ]
+                })
+                .collect();
+            ctx.output
+                .table(&["ID", "PRIORITY", "TYPE", "SOURCE", "DESTINATION"], &rows);
+            ctx.output.result(&routes)
+        }
+        RoutesCommands::Add {
+            name,
+            source,
+            destination,
+            rewrite,
+            priority,
+        } => {
+            let service = static_site(ctx, Some(name)).await?;
+            let request = AddRouteRequest {
+                route_type: match rewrite {
+                    true => RouteType::Rewrite,
+                    false => RouteType::Redirect,
+                },
+                source: source.clone(),
+                destination: destination.clone(),
+                priority: *priority,
+            };
+            let route = ctx.client.add_route(&service.id, &request).await?;
+            ctx.output.info(format!(
+                "Added {} {} {} -> {}",
+                route.id, route.route_type, route.source, route.destination
+            ));
+            ctx.output.result(&route)
+        }
+        RoutesCommands::Remove { name, route_ids } => {
+            let service = static_site(ctx, Some(name)).await?;
+            for route_id in route_ids {
+                ctx.client.delete_route(&service.id, route_id).await?;
+                ctx.output.info(format!("Removed {}", route_id));
+            }
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,46 @@
+use super::Context;
+use crate::cli::ScaleArgs;
+use render_deploy::{RenderError, Service};
+use serde::Serialize;
+
+/// What `--output json` prints
+#[derive(Serialize, Debug)]
+struct ScaleResult<'a> {
+    service: &'a Service,
+    before: usize,
+    after: u32,
+    /// how many instances were running when we stopped waiting, only when waiting
+    running: Option<usize>,
+}
+
+pub async fn run(ctx: &Context, args: &ScaleArgs) -> Result<(), RenderError> {
+    let service = ctx.service(args.name.as_deref()).await?;
+    let before = ctx.client.list_instances(&service.id).await?.len();
+    ctx.client
+        .scale_service(&service.id, args.num_instances)
+        .await?;
+    ctx.output.info(format!(
+        "Scaling {} from {} to {} instances",
+        service.name, before, args.num_instances
+    ));
+
+    let mut result = ScaleResult {
+        service: &service,
+        before,
+        after: args.num_instances,
+        running: None,
+    };
+    if let Some(options) = ctx.service_wait(&args.wait) {
+        let instances = ctx
+            .client
+            .wait_for_instances(&service.id, args.num_instances, &options)
+            .await?;
+        ctx.output.info(format!(
+            "{} has {} instances running",
+            service.name,
+            instances.len()
+        ));
+        result.running = Some(instances.len());
+    }
+    ctx.output.result(&result)
+}
@@ -0,0 +1,78 @@
+use super::Context;
+use crate::cli::{SecretFilesArgs, SecretFilesCommands};
+use futures::TryStreamExt;
+use render_deploy::{RenderError, SecretFile};
+use serde::Serialize;
+use std::fs;
+
+/// A secret file without its contents, so listing them doesn't leak into CI logs
+#[derive(Serialize)]
+struct SecretFileSummary {
+    name: String,
+    size: usize,
+}
+
+pub async fn run(ctx: &Context, args: &SecretFilesArgs) -> Result<(), RenderError> {
+    match &args.command {
+        SecretFilesCommands::List { name } => {
+            let service = ctx.service(name.as_deref()).await?;
+            let files: Vec<SecretFile> = ctx
+                .client
+                .list_secret_files(&service.id)
+                .try_collect()
+                .await?;
+            let summaries: Vec<SecretFileSummary> = files
+                .into_iter()
+                .map(|file| SecretFileSummary {
+                    size: file.content.len(),
+                    name: file.name,
+                })
+                .collect();
+            let rows: Vec<Vec<String>> = summaries
+                .iter()
+                .map(|file| vec![file.name.clone(), file.size.to_string()])
+                .collect();
+            ctx.output.table(&["NAME", "BYTES"], &rows);
+            ctx.output.result(&summaries)
+        }
+        SecretFilesCommands::Put {
+            name,
+            path,
+            file_name,
+        } => {
+            let content = fs::read_to_string(path).map_err(|e| {
+                RenderError::Config(format!("Unable to read {}: {}", path.display(), e))
+            })?;
+            let file_name = match file_name {
+                Some(file_name) => file_name.clone(),
+                None => path
+                    .file_name()
+                    .map(|file_name| file_name.to_string_lossy().into_owned())
+                    .ok_or_else(|| {
+                        RenderError::Config(format!("{} isn't a file", path.display()))
+                    })?,
+            };
+            let service = ctx.service(Some(name)).await?;
+            ctx.client
+                .put_secret_file(&service.id, &file_name, &content)
+                .await?;
+            ctx.output.info(format!(
+                "Uploaded /etc/secrets/{}, deploy the service for the change to take effect",
+                file_name
+            ));
+            Ok(())
+        }
+        SecretFilesCommands::Delete { name, file_names } => {
+            let service = ctx.service(Some(name)).await?;
+            for file_name in file_names {
+                ctx.client
+                    .delete_secret_file(&service.id, file_name)
+                    .await?;
+                ctx.output.info(format!("Removed {}", file_name));
+            }
+            ctx.output
+                .info("Deploy the service for the change to take effect");
+            Ok(())
+        }
+    }
+}
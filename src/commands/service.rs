@@ -0,0 +1,529 @@
+use super::{confirm, confirm_by_typing, Context};
+use crate::blueprint::{Blueprint, BlueprintService};
+use crate::cli::{
+    NewServiceType, ServiceArgs, ServiceCommands, ServiceCreateArgs, ServiceUpdateArgs, Toggle,
+};
+use crate::output::OutputFormat;
+use futures::TryStreamExt;
+use render_deploy::{
+    CreateServiceRequest, EnvSpecificDetails, EnvVar, Owner, RenderError, Service,
+    ServiceDetailsRequest, ServiceSettings, ServiceType, UpdateServiceRequest,
+};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+pub async fn run(ctx: &Context, args: &ServiceArgs) -> Result<(), RenderError> {
+    match &args.command {
+        ServiceCommands::Create(args) => create(ctx, args).await,
+        ServiceCommands::Update(args) => update(ctx, args).await,
+        ServiceCommands::SetAutodeploy { name, state } => {
+            let service = ctx.service(Some(name)).await?;
+            let auto_deploy = *state == Toggle::On;
+            let service = if service.auto_deploy == auto_deploy {
+                service
+            } else {
+                ctx.client.set_auto_deploy(&service.id, auto_deploy).await?
+            };
+            ctx.output.info(format!(
+                "AutoDeploy is {} for {}",
+                if service.auto_deploy { "on" } else { "off" },
+                service.name
+            ));
+            ctx.output.result(&service)
+        }
+        // --yes can't be given without --really
+        ServiceCommands::Delete { name, yes, .. } => {
+            let service = ctx.service(Some(name)).await?;
+            ctx.output.info(format!(
+                "Found {} ({}) {}",
+                service.name, service.id, service.dashboard_url
+            ));
+            let action = "deletes the service, its deploys and any disks for good";
+            if !*yes && !confirm_by_typing(&service.name, action)? {
+                return Err(RenderError::Config(
+                    "That isn't the service's name, nothing was deleted".into(),
+                ));
+            }
+            ctx.client.delete_service(&service.id).await?;
+            ctx.output
+                .info(format!("Deleted {} ({})", service.name, service.id));
+            ctx.output.result(&service)
+        }
+        ServiceCommands::Export { name, all } => export(ctx, name.as_deref(), *all).await,
+    }
+}
+
+/// Print services as a Blueprint, yaml unless json was asked for
+async fn export(ctx: &Context, name: Option<&str>, all: bool) -> Result<(), RenderError> {
+    let services: Vec<Service> = if all {
+        ctx.client.list_services().try_collect().await?
+    } else {
+        vec![ctx.service(name).await?]
+    };
+    let mut blueprint = Blueprint::default();
+    for service in &services {
+        let settings = ctx.client.get_service_settings(&service.id).await?;
+        let env_vars: Vec<EnvVar> = ctx.client.list_env_vars(&service.id).try_collect().await?;
+        let disk = match &settings.disk_id {
+            Some(disk_id) => Some(ctx.client.get_disk(disk_id).await?),
+            None => None,
+        };
+        blueprint.services.push(BlueprintService::from_live(
+            service, settings, &env_vars, disk,
+        ));
+    }
+    if matches!(
+        ctx.output.format(),
+        OutputFormat::Json | OutputFormat::Ndjson
+    ) {
+        return ctx.output.result(&blueprint);
+    }
+    let yaml = serde_yaml::to_string(&blueprint)
+        .map_err(|e| RenderError::Config(format!("Unable to write yaml {}", e)))?;
+    print!("{}", yaml);
+    Ok(())
+}
+
+/// A `service.yaml` for `service create --from-file`, keys are named like render.yaml's
+///
+/// ```yaml
+/// name: api-pr-42
+/// type: web
+/// repo: https://github.com/reconbot/render-deploy
+/// branch: pr-42
+/// runtime: node
+/// buildCommand: npm ci
+/// startCommand: npm start
+/// plan: starter
+/// numInstances: 2
+/// envVars:
+///   - key: LOG_LEVEL
+///     value: debug
+/// ```
+#[derive(Deserialize, Debug, Default, PartialEq, Clone)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct ServiceFile {
+    pub name: Option<String>,
+    #[serde(rename = "type")]
+    pub service_type: Option<NewServiceType>,
+    pub repo: Option<String>,
+    pub branch: Option<String>,
+    pub runtime: Option<String>,
+    pub build_command: Option<String>,
+    pub start_command: Option<String>,
+    pub publish_path: Option<String>,
+    pub plan: Option<String>,
+    pub region: Option<String>,
+    pub num_instances: Option<u32>,
+    pub auto_deploy: Option<bool>,
+    #[serde(default)]
+    pub env_vars: Vec<EnvVar>,
+}
+
+impl ServiceFile {
+    /// The settings `service update` can change, `None` where the file doesn't say
+    pub fn settings(&self) -> ServiceSettings {
+        ServiceSettings {
+            branch: self.branch.clone(),
+            build_command: self.build_command.clone(),
+            start_command: self.start_command.clone(),
+            plan: self.plan.clone(),
+            ..Default::default()
+        }
+    }
+}
+
+/// A yaml file of services, one like [`ServiceFile`] or a list of them
+pub fn load<T: DeserializeOwned>(path: &Path) -> Result<T, RenderError> {
+    let contents = fs::read_to_string(path)
+        .map_err(|e| RenderError::Config(format!("Unable to read {}: {}", path.display(), e)))?;
+    serde_yaml::from_str(&contents)
+        .map_err(|e| RenderError::Config(format!("Invalid service file {}: {}", path.display(), e)))
+}
+
+async fn create(ctx: &Context, args: &ServiceCreateArgs) -> Result<(), RenderError> {
+    let file = match &args.from_file {
+        Some(path) => load(path)?,
+        None => ServiceFile::default(),
+    };
+    let owner_id = match ctx.client.owner_id() {
+        Some(owner_id) => owner_id.to_string(),
+        None => only_owner(ctx).await?.id,
+    };
+    let request = create_request(with_flags(args, file)?, owner_id)?;
+    let created = ctx.client.create_service(&request).await?;
+    ctx.output.info(format!(
+        "Created {} ({}) {}",
+        created.service.name, created.service.id, created.service.dashboard_url
+    ));
+    if let Some(deploy_id) = &created.deploy_id {
+        ctx.output.info(format!(
+            "Its first deploy {} has started, `render-deploy status -w {}` waits for it",
+            deploy_id, created.service.id
+        ));
+    }
+    ctx.output.result(&created)
+}
+
+impl From<NewServiceType> for ServiceType {
+    fn from(service_type: NewServiceType) -> Self {
+        match service_type {
+            NewServiceType::Web => ServiceType::WebService,
+            NewServiceType::Worker => ServiceType::BackgroundWorker,
+            NewServiceType::Private => ServiceType::PrivateService,
+            NewServiceType::Static => ServiceType::StaticSite,
+        }
+    }
+}
+
+/// The workspace to create a service in when `--owner` isn't given, only guessed when the api
+/// key can reach just one
+pub async fn only_owner(ctx: &Context) -> Result<Owner, RenderError> {
+    let mut owners: Vec<Owner> = ctx.client.list_owners().try_collect().await?;
+    if owners.len() != 1 {
+        return Err(RenderError::Config(
+            "Pick the workspace to create the service in with --owner, see `render-deploy owners`"
+                .into(),
+        ));
+    }
+    Ok(owners.remove(0))
+}
+
+/// The flags on top of the file
+fn with_flags(args: &ServiceCreateArgs, file: ServiceFile) -> Result<ServiceFile, RenderError> {
+    let mut env_vars = file.env_vars;
+    for pair in &args.env_vars {
+        let (key, value) = pair
+            .split_once('=')
+            .ok_or_else(|| RenderError::Config(format!("{} isn't KEY=value", pair)))?;
+        env_vars.retain(|env_var| env_var.key != key);
+        env_vars.push(EnvVar {
+            key: key.to_string(),
+            value: value.to_string(),
+        });
+    }
+    Ok(ServiceFile {
+        name: args.name.clone().or(file.name),
+        service_type: args.service_type.or(file.service_type),
+        repo: args.repo.clone().or(file.repo),
+        branch: args.branch.clone().or(file.branch),
+        runtime: args.runtime.clone().or(file.runtime),
+        build_command: args.build_command.clone().or(file.build_command),
+        start_command: args.start_command.clone().or(file.start_command),
+        publish_path: args.publish_path.clone().or(file.publish_path),
+        plan: args.plan.clone().or(file.plan),
+        region: args.region.clone().or(file.region),
+        env_vars,
+        ..file
+    })
+}
+
+pub fn create_request(
+    file: ServiceFile,
+    owner_id: String,
+) -> Result<CreateServiceRequest, RenderError> {
+    let name = file
+        .name
+        .ok_or_else(|| RenderError::Config("A name for the service is required".into()))?;
+    let repo = file
+        .repo
+        .ok_or_else(|| RenderError::Config(format!("A repo for {} is required", name)))?;
+    let build_command = file.build_command;
+    let start_command = file.start_command;
+    let runtime = file.runtime;
+    let publish_path = file.publish_path;
+
+    let service_type = file.service_type.unwrap_or(NewServiceType::Web);
+    let service_details = match service_type {
+        NewServiceType::Static => {
+            if start_command.is_some() || runtime.is_some() {
+                return Err(RenderError::Config(format!(
+                    "{} is a static site, they don't take a runtime or start command",
+                    name
+                )));
+            }
+            ServiceDetailsRequest {
+                build_command,
+                publish_path,
+                ..Default::default()
+            }
+        }
+        _ => {
+            if runtime.is_none() {
+                return Err(RenderError::Config(format!(
+                    "A runtime for {} is required, eg node, python or docker",
+                    name
+                )));
+            }
+            if publish_path.is_some() {
+                return Err(RenderError::Config(format!(
+                    "{} isn't a static site, only they take a publish path",
+                    name
+                )));
+            }
+            ServiceDetailsRequest {
+                runtime,
+                env_specific_details: Some(EnvSpecificDetails {
+                    build_command,
+                    start_command,
+                }),
+                ..Default::default()
+            }
+        }
+    };
+
+    Ok(CreateServiceRequest {
+        service_type: service_type.into(),
+        name,
+        owner_id,
+        repo: Some(repo),
+        branch: file.branch,
+        auto_deploy: file.auto_deploy.unwrap_or(true),
+        env_vars: file.env_vars,
+        service_details: ServiceDetailsRequest {
+            plan: file.plan,
+            region: file.region,
+            num_instances: file.num_instances,
+            ..service_details
+        },
+    })
+}
+
+/// One setting `service update` changes
+#[derive(Serialize, Debug, PartialEq)]
+pub struct SettingChange {
+    pub setting: &'static str,
+    pub before: Option<String>,
+    pub after: String,
+}
+
+/// What `--output json` prints for `service update`
+#[derive(Serialize, Debug)]
+struct UpdateResult {
+    service: Service,
+    changes: Vec<SettingChange>,
+    /// false for --dry-run or when nothing needed changing
+    applied: bool,
+}
+
+async fn update(ctx: &Context, args: &ServiceUpdateArgs) -> Result<(), RenderError> {
+    if args.branch.is_none()
+        && args.build_command.is_none()
+        && args.start_command.is_none()
+        && args.plan.is_none()
+    {
+        return Err(RenderError::Config(
+            "Nothing to change, pass --branch, --build-command, --start-command or --plan".into(),
+        ));
+    }
+    let service = ctx.service(Some(&args.name)).await?;
+    if service.service_type == ServiceType::StaticSite && args.start_command.is_some() {
+        return Err(RenderError::Config(format!(
+            "{} is a static site, it doesn't have a start command",
+            service.name
+        )));
+    }
+    let current = ctx.client.get_service_settings(&service.id).await?;
+    let desired = ServiceSettings {
+        branch: args.branch.clone(),
+        build_command: args.build_command.clone(),
+        start_command: args.start_command.clone(),
+        plan: args.plan.clone(),
+        ..Default::default()
+    };
+    let changes = setting_changes(&current, &desired);
+
+    for change in &changes {
+        ctx.output.info(format!(
+            "~ {}: {} -> {}",
+            change.setting,
+            change.before.as_deref().unwrap_or("(none)"),
+            change.after
+        ));
+    }
+    if changes.is_empty() || args.dry_run {
+        ctx.output.info(if changes.is_empty() {
+            format!("{} already has these settings", service.name)
+        } else {
+            "Dry run, nothing was changed".to_string()
+        });
+        return ctx.output.result(&UpdateResult {
+            service,
+            changes,
+            applied: false,
+        });
+    }
+
+    if !args.yes && !confirm(&format!("Apply these changes to {}?", service.name))? {
+        return Err(RenderError::Config(
+            "Update canceled, nothing was changed".into(),
+        ));
+    }
+    let request = update_request(&service, &changes);
+    let service = ctx.client.update_service(&service.id, &request).await?;
+    ctx.output.info(format!(
+        "Updated {}, the next deploy uses the new settings",
+        service.name
+    ));
+    ctx.output.result(&UpdateResult {
+        service,
+        changes,
+        applied: true,
+    })
+}
+
+/// The `desired` settings that differ from the service's, those left `None` aren't changed
+pub fn setting_changes(current: &ServiceSettings, desired: &ServiceSettings) -> Vec<SettingChange> {
+    [
+        ("branch", &current.branch, &desired.branch),
+        (
+            "build command",
+            &current.build_command,
+            &desired.build_command,
+        ),
+        (
+            "start command",
+            &current.start_command,
+            &desired.start_command,
+        ),
+        ("plan", &current.plan, &desired.plan),
+    ]
+    .into_iter()
+    .filter_map(|(setting, before, after)| match after {
+        Some(after) if before.as_ref() != Some(after) => Some(SettingChange {
+            setting,
+            before: before.clone(),
+            after: after.clone(),
+        }),
+        _ => None,
+    })
+    .collect()
+}
+
+pub fn update_request(service: &Service, changes: &[SettingChange]) -> UpdateServiceRequest {
+    let changed = |setting: &str| {
+        changes
+            .iter()
+            .find(|change| change.setting == setting)
+            .map(|change| change.after.clone())
+    };
+    let build_command = changed("build command");
+    let start_command = changed("start command");
+    let mut details = ServiceDetailsRequest {
+        plan: changed("plan"),
+        ..Default::default()
+    };
+    if service.service_type == ServiceType::StaticSite {
+        details.build_command = build_command;
+    } else if build_command.is_some() || start_command.is_some() {
+        details.env_specific_details = Some(EnvSpecificDetails {
+            build_command,
+            start_command,
+        });
+    }
+    let has_details = details.plan.is_some()
+        || details.build_command.is_some()
+        || details.env_specific_details.is_some();
+    UpdateServiceRequest {
+        branch: changed("branch"),
+        service_details: has_details.then_some(details),
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::{Cli, Commands};
+    use clap::Parser;
+
+    fn service_command(args: &[&str]) -> ServiceCommands {
+        match Cli::parse_from([&["render-deploy", "service"], args].concat()).command {
+            Commands::Service(args) => args.command,
+            other => panic!("expected a service command {:?}", other),
+        }
+    }
+
+    fn create_args(args: &[&str]) -> ServiceCreateArgs {
+        match service_command(&[&["create"], args].concat()) {
+            ServiceCommands::Create(args) => args,
+            other => panic!("expected service create {:?}", other),
+        }
+    }
+
+    #[test]
+    fn flags_override_the_file() {
+        let file: ServiceFile = serde_yaml::from_str(
+            r#"
+                name: api-pr-42
+                repo: https://github.com/reconbot/render-deploy
+                branch: main
+                runtime: node
+                startCommand: npm start
+                envVars:
+                  - key: LOG_LEVEL
+                    value: info
+            "#,
+        )
+        .unwrap();
+        let args = create_args(&["--branch", "pr-42", "-e", "LOG_LEVEL=debug"]);
+        let request = create_request(with_flags(&args, file).unwrap(), "tea-1".into()).unwrap();
+        assert_eq!(request.name, "api-pr-42");
+        assert_eq!(request.service_type, ServiceType::WebService);
+        assert_eq!(request.branch.as_deref(), Some("pr-42"));
+        assert_eq!(request.env_vars.len(), 1);
+        assert_eq!(request.env_vars[0].value, "debug");
+        let details = request.service_details.env_specific_details.unwrap();
+        assert_eq!(details.start_command.as_deref(), Some("npm start"));
+    }
+
+    #[test]
+    fn only_changed_settings_are_sent() {
+        let current = ServiceSettings {
+            branch: Some("main".into()),
+            build_command: Some("npm ci".into()),
+            start_command: Some("npm start".into()),
+            plan: None,
+            ..Default::default()
+        };
+        let desired = ServiceSettings {
+            branch: Some("main".into()),
+            start_command: Some("node server.js".into()),
+            plan: Some("starter".into()),
+            ..Default::default()
+        };
+        let changes = setting_changes(&current, &desired);
+        assert_eq!(
+            changes,
+            vec![
+                SettingChange {
+                    setting: "start command",
+                    before: Some("npm start".into()),
+                    after: "node server.js".into(),
+                },
+                SettingChange {
+                    setting: "plan",
+                    before: None,
+                    after: "starter".into(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn static_sites_have_no_runtime() {
+        let args = create_args(&[
+            "site",
+            "--type",
+            "static",
+            "--repo",
+            "https://github.com/reconbot/site",
+            "--runtime",
+            "node",
+        ]);
+        let file = with_flags(&args, ServiceFile::default()).unwrap();
+        assert!(create_request(file, "tea-1".into()).is_err());
+    }
+}
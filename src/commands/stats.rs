@@ -0,0 +1,229 @@
+use super::Context;
+use crate::cli::StatsArgs;
+use futures::TryStreamExt;
+use render_deploy::{format_duration, Deploy, DeployStatus, RenderError};
+use serde::Serialize;
+use std::time::{Duration, SystemTime};
+
+/// DORA style numbers for a service's deploys, what `--output json` prints. Durations are in
+/// seconds.
+#[derive(Serialize, Debug, PartialEq)]
+struct Stats {
+    service_id: String,
+    service: String,
+    since: String,
+    deploys: usize,
+    live: usize,
+    failed: usize,
+    canceled: usize,
+    deploys_per_day: f64,
+    /// failed and canceled deploys out of those that finished
+    change_failure_rate: Option<f64>,
+    median_duration: Option<u64>,
+    p95_duration: Option<u64>,
+    /// from a deploy failing to the next one going live
+    mean_time_to_recovery: Option<u64>,
+}
+
+pub async fn run(ctx: &Context, args: &StatsArgs) -> Result<(), RenderError> {
+    let service = ctx.service(args.name.as_deref()).await?;
+    let since = SystemTime::now()
+        .checked_sub(args.since)
+        .unwrap_or(SystemTime::UNIX_EPOCH);
+    let mut deploys = vec![];
+    let mut history = ctx.client.deploy_history(&service.id);
+    // newest first, so stop at the first one from before --since
+    while let Some(deploy) = history.try_next().await? {
+        match humantime::parse_rfc3339(&deploy.created_at) {
+            Ok(created) if created < since => break,
+            _ => deploys.push(deploy),
+        }
+    }
+    deploys.reverse();
+
+    let stats = Stats {
+        service_id: service.id.clone(),
+        service: service.name.clone(),
+        since: humantime::format_rfc3339_seconds(since).to_string(),
+        ..stats(&deploys, args.since)
+    };
+    let seconds = |seconds: Option<u64>| {
+        seconds
+            .map(|seconds| format_duration(Duration::from_secs(seconds)))
+            .unwrap_or_else(|| "-".into())
+    };
+    let rows = vec![
+        vec!["Deploys".into(), stats.deploys.to_string()],
+        vec!["Live".into(), stats.live.to_string()],
+        vec!["Failed".into(), stats.failed.to_string()],
+        vec!["Canceled".into(), stats.canceled.to_string()],
+        vec![
+            "Deploys per day".into(),
+            format!("{:.2}", stats.deploys_per_day),
+        ],
+        vec![
+            "Change failure rate".into(),
+            stats
+                .change_failure_rate
+                .map(|rate| format!("{:.1}%", rate * 100.0))
+                .unwrap_or_else(|| "-".into()),
+        ],
+        vec!["Median duration".into(), seconds(stats.median_duration)],
+        vec!["p95 duration".into(), seconds(stats.p95_duration)],
+        vec![
+            "Mean time to recovery".into(),
+            seconds(stats.mean_time_to_recovery),
+        ],
+    ];
+    ctx.output.info(format!(
+        "{} deploys since {}",
+        service.name,
+        ctx.output.time(&stats.since)
+    ));
+    ctx.output.info("");
+    ctx.output.table(&["METRIC", "VALUE"], &rows);
+    ctx.output.result(&stats)
+}
+
+/// The numbers for `deploys`, oldest first, made over `window`
+fn stats(deploys: &[Deploy], window: Duration) -> Stats {
+    let count = |wanted: fn(&DeployStatus) -> bool| {
+        deploys
+            .iter()
+            .filter(|deploy| wanted(&deploy.status))
+            .count()
+    };
+    let live = count(went_live);
+    let failed = count(is_failure);
+    let canceled = count(|status| *status == DeployStatus::Canceled);
+    let finished = live + failed + canceled;
+
+    let mut durations: Vec<u64> = deploys
+        .iter()
+        .filter(|deploy| went_live(&deploy.status))
+        .filter_map(|deploy| deploy.duration())
+        .map(|duration| duration.as_secs())
+        .collect();
+    durations.sort_unstable();
+
+    // a run of failures is one outage, it's over once something goes live again
+    let mut recoveries = vec![];
+    let mut failing_since = None;
+    for deploy in deploys {
+        let Some(finished_at) = deploy
+            .finished_at
+            .as_deref()
+            .and_then(|finished_at| humantime::parse_rfc3339(finished_at).ok())
+        else {
+            continue;
+        };
+        if is_failure(&deploy.status) {
+            failing_since.get_or_insert(finished_at);
+        } else if went_live(&deploy.status) {
+            if let Some(since) = failing_since.take() {
+                recoveries.push(finished_at.duration_since(since).unwrap_or_default());
+            }
+        }
+    }
+
+    let days = (window.as_secs_f64() / 86_400.0).max(1.0 / 24.0);
+    Stats {
+        service_id: String::new(),
+        service: String::new(),
+        since: String::new(),
+        deploys: deploys.len(),
+        live,
+        failed,
+        canceled,
+        deploys_per_day: live as f64 / days,
+        change_failure_rate: (finished > 0).then(|| (failed + canceled) as f64 / finished as f64),
+        median_duration: percentile(&durations, 50),
+        p95_duration: percentile(&durations, 95),
+        mean_time_to_recovery: (!recoveries.is_empty()).then(|| {
+            recoveries.iter().map(Duration::as_secs).sum::<u64>() / recoveries.len() as u64
+        }),
+    }
+}
+
+/// Deactivated deploys went live and were replaced later
+fn went_live(status: &DeployStatus) -> bool {
+    matches!(status, DeployStatus::Live | DeployStatus::Deactivated)
+}
+
+/// Failed on its own rather than canceled or replaced
+fn is_failure(status: &DeployStatus) -> bool {
+    matches!(
+        status,
+        DeployStatus::BuildFailed | DeployStatus::UpdateFailed | DeployStatus::PreDeployFailed
+    )
+}
+
+/// The nearest rank percentile of sorted `values`
+fn percentile(values: &[u64], percent: usize) -> Option<u64> {
+    let rank = (values.len() * percent).div_ceil(100);
+    values.get(rank.saturating_sub(1)).copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures;
+
+    fn deploy(status: DeployStatus, created_at: &str, finished_at: &str) -> Deploy {
+        Deploy {
+            created_at: created_at.into(),
+            updated_at: finished_at.into(),
+            finished_at: Some(finished_at.into()),
+            ..fixtures::deploy("dep-1", status)
+        }
+    }
+
+    #[test]
+    fn percentiles() {
+        let values: Vec<u64> = (1..=20).collect();
+        assert_eq!(percentile(&values, 50), Some(10));
+        assert_eq!(percentile(&values, 95), Some(19));
+        assert_eq!(percentile(&[7], 95), Some(7));
+        assert_eq!(percentile(&[], 50), None);
+    }
+
+    #[test]
+    fn deploy_stats() {
+        let deploys = vec![
+            deploy(
+                DeployStatus::Deactivated,
+                "2024-10-01T10:00:00Z",
+                "2024-10-01T10:02:00Z",
+            ),
+            deploy(
+                DeployStatus::BuildFailed,
+                "2024-10-02T10:00:00Z",
+                "2024-10-02T10:01:00Z",
+            ),
+            deploy(
+                DeployStatus::UpdateFailed,
+                "2024-10-02T10:30:00Z",
+                "2024-10-02T10:35:00Z",
+            ),
+            deploy(
+                DeployStatus::Canceled,
+                "2024-10-02T11:00:00Z",
+                "2024-10-02T11:00:30Z",
+            ),
+            deploy(
+                DeployStatus::Live,
+                "2024-10-02T11:00:00Z",
+                "2024-10-02T11:04:00Z",
+            ),
+        ];
+        let stats = stats(&deploys, Duration::from_secs(2 * 86_400));
+        assert_eq!(stats.deploys, 5);
+        assert_eq!((stats.live, stats.failed, stats.canceled), (2, 2, 1));
+        assert_eq!(stats.deploys_per_day, 1.0);
+        assert_eq!(stats.change_failure_rate, Some(0.6));
+        assert_eq!(stats.median_duration, Some(120));
+        assert_eq!(stats.p95_duration, Some(240));
+        // from the build failing at 10:01 to going live at 11:04
+        assert_eq!(stats.mean_time_to_recovery, Some(63 * 60));
+    }
+}
@@ -0,0 +1,87 @@
+use super::{live_deploy, report_deploy, watch_deploy, Context};
+use crate::cli::StatusArgs;
+use render_deploy::{deploy_url, Deploy, DeployStatus, RenderError, Service, ServiceType};
+use serde::Serialize;
+
+/// What `--output json` prints for a service that's never been deployed
+#[derive(Serialize, Debug)]
+struct NoDeploys<'a> {
+    service: &'a Service,
+    deploy: Option<&'a Deploy>,
+}
+
+pub async fn run(ctx: &Context, args: &StatusArgs) -> Result<(), RenderError> {
+    let service = ctx.service(args.name.as_deref()).await?;
+    ctx.output.info(format!(
+        "{name} ({id}) {dashboard}",
+        name = service.name,
+        id = service.id,
+        dashboard = service.dashboard_url
+    ));
+    ctx.output.info(format!("Source: {}", service.source()));
+    ctx.output.info(format!(
+        "AutoDeploy: {}",
+        if service.auto_deploy { "yes" } else { "no" }
+    ));
+    if service.suspended {
+        ctx.output.info("Suspended: yes");
+    }
+    ctx.output.info("");
+
+    let deploy = match &args.deploy_id {
+        Some(deploy_id) => Some(ctx.client.get_deploy(&service.id, deploy_id).await?),
+        None => ctx.client.latest_deploy(&service.id).await?,
+    };
+    let Some(deploy) = deploy else {
+        ctx.output.info("No deploys yet");
+        return ctx.output.result(&NoDeploys {
+            service: &service,
+            deploy: None,
+        });
+    };
+    ctx.output.info(format!(
+        "{label} {id} {description}",
+        label = if args.deploy_id.is_some() {
+            "Deploy"
+        } else {
+            "Latest Deploy"
+        },
+        id = deploy.id,
+        description = deploy.describe()
+    ));
+    ctx.output.info(deploy_url(&service, &deploy));
+    match &deploy.finished_at {
+        Some(finished_at) => ctx.output.info(format!(
+            "Status: {status} on {finished_at}",
+            status = ctx.output.status(&deploy.status),
+            finished_at = ctx.output.time(finished_at)
+        )),
+        None => ctx
+            .output
+            .info(format!("Status: {}", ctx.output.status(&deploy.status))),
+    }
+
+    // a static site keeps serving what it last published while a new deploy builds
+    if service.service_type == ServiceType::StaticSite {
+        let published = match deploy.status {
+            DeployStatus::Live => Some(deploy.clone()),
+            _ => live_deploy(ctx, &service).await?,
+        };
+        match published {
+            Some(published) => ctx
+                .output
+                .info(format!("Published: {}", published.describe())),
+            None => ctx.output.info("Published: nothing yet"),
+        }
+    }
+
+    if deploy.status.is_in_progress() {
+        return watch_deploy(ctx, &service, deploy, &args.wait).await;
+    }
+    report_deploy(ctx, &service, &deploy)?;
+    // waiting on a deploy that's already failed fails the same way waiting on it would have
+    if ctx.wait_timeout(&args.wait).is_some() && deploy.status.is_failed() {
+        return Err(RenderError::DeployFailed(Box::new(deploy)));
+    }
+    Ok(())
+}
@@ -0,0 +1,34 @@
+use super::{confirm, Context};
+use crate::cli::SuspendArgs;
+use render_deploy::RenderError;
+
+pub async fn run(ctx: &Context, args: &SuspendArgs) -> Result<(), RenderError> {
+    let service = ctx.service(args.name.as_deref()).await?;
+    if service.suspended {
+        ctx.output
+            .info(format!("{} is already suspended", service.name));
+        return ctx.output.result(&service);
+    }
+    let question = format!(
+        "Suspend {}? It won't serve anything until it's resumed.",
+        service.name
+    );
+    if !args.yes && !confirm(&question)? {
+        return Err(RenderError::Config(format!(
+            "{} wasn't suspended",
+            service.name
+        )));
+    }
+
+    ctx.client.suspend_service(&service.id).await?;
+    ctx.output.info(format!("Suspending {}", service.name));
+    let Some(options) = ctx.service_wait(&args.wait) else {
+        return ctx.output.result(&service);
+    };
+    let service = ctx
+        .client
+        .wait_for_suspended(&service.id, true, &options)
+        .await?;
+    ctx.output.info(format!("{} is suspended", service.name));
+    ctx.output.result(&service)
+}
@@ -0,0 +1,162 @@
+use super::events::details;
+use super::Context;
+use crate::cli::WatchArgs;
+use crate::logging;
+use futures::TryStreamExt;
+use render_deploy::{
+    Deploy, DeployStatus, Event, LogEntry, LogTail, LogsQuery, RenderError, Service,
+};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::time::SystemTime;
+use tokio::time::sleep;
+
+/// Give up after this many failed checks in a row
+const MAX_FAILURES: u32 = 10;
+
+/// A line of `watch`'s output, `--output json` prints one per line
+#[derive(Serialize, Debug)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum Change<'a> {
+    Deploy {
+        deploy: &'a Deploy,
+        /// what it was at the last check, missing for a deploy we haven't seen before
+        previous_status: Option<&'a DeployStatus>,
+    },
+    Event(&'a Event),
+    Log(&'a LogEntry),
+}
+
+struct Watcher<'a> {
+    service: &'a Service,
+    deploy: Option<Deploy>,
+    /// `None` unless `--events`
+    events_since: Option<String>,
+    seen_events: HashSet<String>,
+    /// `None` unless `--logs`
+    logs: Option<LogTail<'a>>,
+}
+
+/// Print every change to the service's latest deploy until interrupted, so deploys started by
+/// pushes or the dashboard can be followed too
+pub async fn run(ctx: &Context, args: &WatchArgs) -> Result<(), RenderError> {
+    let service = ctx.service(args.name.as_deref()).await?;
+    let now = humantime::format_rfc3339_seconds(SystemTime::now()).to_string();
+    let mut watcher = Watcher {
+        service: &service,
+        deploy: None,
+        events_since: args.events.then(|| now.clone()),
+        seen_events: HashSet::new(),
+        logs: args.logs.then(|| {
+            ctx.client.tail_logs(LogsQuery {
+                owner_id: service.owner_id.clone(),
+                resources: vec![service.id.clone()],
+                start_time: Some(now.clone()),
+                ..LogsQuery::default()
+            })
+        }),
+    };
+    ctx.output
+        .info(format!("Watching {}, interrupt to stop", service.name));
+
+    let mut failures = 0;
+    loop {
+        match watcher.check(ctx).await {
+            Ok(()) => failures = 0,
+            // an incident is when render is most likely to be flaky, so keep going through blips
+            Err(e) if e.is_transient() && failures < MAX_FAILURES => {
+                failures += 1;
+                ctx.output
+                    .info(format!("Unable to check on {}: {}", service.name, e));
+            }
+            Err(e) => return Err(e),
+        }
+        sleep(args.poll_interval).await;
+    }
+}
+
+impl Watcher<'_> {
+    async fn check(&mut self, ctx: &Context) -> Result<(), RenderError> {
+        self.check_deploy(ctx).await?;
+        self.check_events(ctx).await?;
+        if let Some(logs) = &mut self.logs {
+            for entry in logs.poll().await? {
+                ctx.output.item(
+                    &Change::Log(&entry),
+                    format!("{} {}", entry.timestamp, entry.message.trim_end()),
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Print the latest deploy when it's new or its status has changed
+    async fn check_deploy(&mut self, ctx: &Context) -> Result<(), RenderError> {
+        let Some(deploy) = ctx.client.latest_deploy(&self.service.id).await? else {
+            return Ok(());
+        };
+        let previous_status = match &self.deploy {
+            Some(previous) if previous.id == deploy.id && previous.status == deploy.status => {
+                return Ok(());
+            }
+            Some(previous) if previous.id == deploy.id => Some(&previous.status),
+            _ => None,
+        };
+        let text = match previous_status {
+            Some(previous_status) => format!(
+                "{} Deploy {} {} -> {}",
+                deploy.updated_at,
+                deploy.id,
+                ctx.output.status(previous_status),
+                ctx.output.status(&deploy.status)
+            ),
+            None => format!(
+                "{} Deploy {} {}: {}",
+                deploy.updated_at,
+                deploy.id,
+                ctx.output.status(&deploy.status),
+                deploy.describe().lines().next().unwrap_or_default()
+            ),
+        };
+        logging::deploy_status(self.service, &deploy, previous_status);
+        ctx.output.item(
+            &Change::Deploy {
+                deploy: &deploy,
+                previous_status,
+            },
+            text,
+        )?;
+        self.deploy = Some(deploy);
+        Ok(())
+    }
+
+    /// Print events that have happened since the last check, oldest first
+    async fn check_events(&mut self, ctx: &Context) -> Result<(), RenderError> {
+        let Some(since) = &self.events_since else {
+            return Ok(());
+        };
+        let mut events: Vec<Event> = ctx
+            .client
+            .list_events(&self.service.id, Some(since))
+            .try_collect()
+            .await?;
+        // the window starts at the newest event we've seen, so that one comes back every time
+        events.retain(|event| self.seen_events.insert(event.id.clone()));
+        events.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+        for event in &events {
+            ctx.output.item(
+                &Change::Event(event),
+                format!(
+                    "{} {} {}",
+                    event.timestamp,
+                    event.event_type,
+                    details(event)
+                ),
+            )?;
+        }
+        if let Some(newest) = events.last() {
+            self.events_since = Some(newest.timestamp.clone());
+        }
+        Ok(())
+    }
+}
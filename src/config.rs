@@ -0,0 +1,207 @@
+use render_deploy::RenderError;
+use serde::{Deserialize, Deserializer};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use std::{env, fs};
+
+/// Project config, checked before the user config
+const PROJECT_CONFIG: &str = "render-deploy.toml";
+/// Profile used when `--profile` isn't given
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// A `render-deploy.toml` or `~/.config/render-deploy/config.toml`
+///
+/// ```toml
+/// [profiles.staging]
+/// service = "api-staging"
+/// owner = "Platform"
+/// api_key_env = "RENDER_API_KEY_STAGING"
+/// timeout = "15m"
+/// wait = true
+/// notify = ["https://hooks.slack.com/services/..."]
+/// protected = true
+/// deploy_branches = ["main"]
+/// ```
+#[derive(Deserialize, Debug, Default)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigFile {
+    #[serde(default)]
+    pub profiles: BTreeMap<String, Profile>,
+}
+
+#[derive(Deserialize, Debug, Default, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Profile {
+    /// name or id of the service commands act on when one isn't given
+    pub service: Option<String>,
+    /// name or id of the workspace services are looked up in
+    pub owner: Option<String>,
+    /// env var holding the api key for this profile
+    pub api_key_env: Option<String>,
+    /// env var holding the service's deploy hook url, to deploy without an api key
+    pub deploy_hook_env: Option<String>,
+    /// how long to wait for deploys, seconds or a duration like `15m`
+    #[serde(default, deserialize_with = "duration")]
+    pub timeout: Option<Duration>,
+    /// always wait for deploys to finish
+    pub wait: Option<bool>,
+    /// env var holding the github or gitlab token `--verify-commit` uses, instead of
+    /// GITHUB_TOKEN or GITLAB_TOKEN
+    pub git_token_env: Option<String>,
+    /// slack or discord webhooks to tell when a deploy goes live or fails
+    #[serde(default)]
+    pub notify: Vec<String>,
+    /// deploys with this profile ask first, or need --yes
+    pub protected: Option<bool>,
+    /// names, ids or globs of services whose deploys ask first, or need --yes
+    #[serde(default)]
+    pub protected_services: Vec<String>,
+    /// branches, or globs of them, that may be deployed with this profile, any when empty
+    #[serde(default)]
+    pub deploy_branches: Vec<String>,
+}
+
+impl Profile {
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    /// The api key from this profile's env var, if it names one that's set
+    pub fn api_key(&self) -> Option<String> {
+        self.api_key_env
+            .as_ref()
+            .and_then(|name| env::var(name).ok())
+    }
+
+    /// The github or gitlab token from this profile's env var, if it names one that's set
+    pub fn git_token(&self) -> Option<String> {
+        self.git_token_env
+            .as_ref()
+            .and_then(|name| env::var(name).ok())
+    }
+
+    /// The deploy hook url from this profile's env var, if it names one that's set
+    pub fn deploy_hook_url(&self) -> Option<String> {
+        self.deploy_hook_env
+            .as_ref()
+            .and_then(|name| env::var(name).ok())
+    }
+}
+
+/// Where to look for config files, most specific first
+fn config_paths() -> Vec<PathBuf> {
+    let mut paths = vec![PathBuf::from(PROJECT_CONFIG)];
+    let config_home = env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".config")));
+    if let Some(config_home) = config_home {
+        paths.push(config_home.join("render-deploy").join("config.toml"));
+    }
+    paths
+}
+
+fn read_config(path: &Path) -> Result<Option<ConfigFile>, RenderError> {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => {
+            return Err(RenderError::Config(format!(
+                "Unable to read {}: {}",
+                path.display(),
+                e
+            )))
+        }
+    };
+    toml::from_str(&contents)
+        .map(Some)
+        .map_err(|e| RenderError::Config(format!("Invalid config {}: {}", path.display(), e)))
+}
+
+/// Load the named profile from the first config file that defines it. Without a name the
+/// `default` profile is used if there is one.
+pub fn load_profile(name: Option<&str>) -> Result<Profile, RenderError> {
+    let wanted = name.unwrap_or(DEFAULT_PROFILE);
+    for path in config_paths() {
+        if let Some(mut config) = read_config(&path)? {
+            if let Some(profile) = config.profiles.remove(wanted) {
+                return Ok(profile);
+            }
+        }
+    }
+    match name {
+        Some(name) => Err(RenderError::Config(format!(
+            "No profile named {} in {}",
+            name,
+            config_paths()
+                .iter()
+                .map(|path| path.display().to_string())
+                .collect::<Vec<_>>()
+                .join(" or ")
+        ))),
+        None => Ok(Profile::default()),
+    }
+}
+
+/// Seconds, or a duration like `15m` or `1h30m`
+fn duration<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Duration>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Timeout {
+        Seconds(u64),
+        Text(String),
+    }
+    match Option::<Timeout>::deserialize(deserializer)? {
+        Some(Timeout::Seconds(seconds)) => Ok(Some(Duration::from_secs(seconds))),
+        Some(Timeout::Text(text)) => humantime::parse_duration(&text)
+            .map(Some)
+            .map_err(serde::de::Error::custom),
+        None => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_profiles() {
+        let config: ConfigFile = toml::from_str(
+            r#"
+                [profiles.staging]
+                service = "api-staging"
+                owner = "Platform"
+                api_key_env = "RENDER_API_KEY_STAGING"
+                timeout = 900
+                wait = true
+                notify = ["https://hooks.slack.com/services/T0/B0/abc"]
+
+                [profiles.production]
+                service = "srv-cs67ufi3esus73b74a70"
+                timeout = "1h30m"
+                protected = true
+                deploy_branches = ["main", "release/*"]
+            "#,
+        )
+        .unwrap();
+        let staging = &config.profiles["staging"];
+        assert_eq!(staging.service.as_deref(), Some("api-staging"));
+        assert_eq!(staging.owner.as_deref(), Some("Platform"));
+        assert_eq!(staging.timeout(), Some(Duration::from_secs(900)));
+        assert_eq!(staging.wait, Some(true));
+        assert_eq!(staging.notify.len(), 1);
+        let production = &config.profiles["production"];
+        assert_eq!(production.wait, None);
+        assert_eq!(production.timeout(), Some(Duration::from_secs(5_400)));
+        assert_eq!(production.protected, Some(true));
+        assert_eq!(production.deploy_branches, ["main", "release/*"]);
+        assert!(staging.protected_services.is_empty());
+    }
+
+    #[test]
+    fn reject_unknown_fields() {
+        let config: Result<ConfigFile, _> =
+            toml::from_str("[profiles.staging]\nservices = \"api\"");
+        assert!(config.is_err());
+    }
+}
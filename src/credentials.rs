@@ -0,0 +1,75 @@
+use render_deploy::{ApiKey, RenderError};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// The keyring service our entries are saved under, one entry per profile
+const KEYRING_SERVICE: &str = "render-deploy";
+
+/// The key in an `--api-key-file`, surrounding whitespace like a trailing newline is dropped
+pub fn read_file(path: &Path) -> Result<ApiKey, RenderError> {
+    let contents = fs::read_to_string(path)
+        .map_err(|e| RenderError::Config(format!("Unable to read {}: {}", path.display(), e)))?;
+    non_empty(&contents, &path.display().to_string())
+}
+
+/// The key piped in for `--api-key -`
+pub fn read_stdin() -> Result<ApiKey, RenderError> {
+    let contents = io::read_to_string(io::stdin())
+        .map_err(|e| RenderError::Config(format!("Unable to read the api key from stdin {}", e)))?;
+    non_empty(&contents, "stdin")
+}
+
+fn non_empty(contents: &str, source: &str) -> Result<ApiKey, RenderError> {
+    match contents.trim() {
+        "" => Err(RenderError::Config(format!(
+            "There's no api key in {}",
+            source
+        ))),
+        key => Ok(ApiKey::new(key)),
+    }
+}
+
+/// The key `auth login` saved for this profile. Having no keyring, like in CI, is the same as
+/// having nothing saved.
+pub fn load(profile: &str) -> Option<ApiKey> {
+    keyring::Entry::new(KEYRING_SERVICE, profile)
+        .and_then(|entry| entry.get_password())
+        .ok()
+        .map(ApiKey::new)
+}
+
+pub fn store(profile: &str, api_key: &ApiKey) -> Result<(), RenderError> {
+    keyring::Entry::new(KEYRING_SERVICE, profile)
+        .and_then(|entry| entry.set_password(api_key.expose()))
+        .map_err(|e| {
+            RenderError::Config(format!("Unable to save the api key in the keyring {}", e))
+        })
+}
+
+/// Forget the key saved for this profile, false if there wasn't one
+pub fn delete(profile: &str) -> Result<bool, RenderError> {
+    match keyring::Entry::new(KEYRING_SERVICE, profile).and_then(|entry| entry.delete_credential())
+    {
+        Ok(()) => Ok(true),
+        Err(keyring::Error::NoEntry) => Ok(false),
+        Err(e) => Err(RenderError::Config(format!(
+            "Unable to remove the api key from the keyring {}",
+            e
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trim_keys() {
+        assert_eq!(
+            non_empty("rnd_abc\n", "stdin").unwrap(),
+            ApiKey::new("rnd_abc")
+        );
+        assert!(non_empty(" \n", "stdin").is_err());
+    }
+}
@@ -0,0 +1,72 @@
+use crate::client::RenderClient;
+use crate::error::RenderError;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+#[derive(PartialEq, Deserialize, Serialize, Debug, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum CronRunStatus {
+    Pending,
+    Running,
+    Successful,
+    Unsuccessful,
+    Canceled,
+}
+
+impl fmt::Display for CronRunStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let status_str = match self {
+            CronRunStatus::Pending => "Pending",
+            CronRunStatus::Running => "Running",
+            CronRunStatus::Successful => "Successful",
+            CronRunStatus::Unsuccessful => "Unsuccessful",
+            CronRunStatus::Canceled => "Canceled",
+        };
+        write!(f, "{}", status_str)
+    }
+}
+
+/// One run of a cron job, scheduled or triggered by hand
+#[derive(PartialEq, Deserialize, Serialize, Debug, Clone)]
+pub struct CronJobRun {
+    pub id: String,
+    pub status: CronRunStatus,
+    #[serde(rename = "startedAt", default)]
+    pub started_at: Option<String>,
+    #[serde(rename = "finishedAt", default)]
+    pub finished_at: Option<String>,
+    /// who or what started the run
+    #[serde(rename = "triggeredBy", default)]
+    pub triggered_by: Option<String>,
+}
+
+impl RenderClient {
+    /// Run a cron job now instead of waiting for its schedule. Render's history of runs is in
+    /// the service's events, see [`RenderClient::list_events`].
+    pub async fn trigger_cron_run(&self, cron_job_id: &str) -> Result<CronJobRun, RenderError> {
+        let request = self
+            .client
+            .post(self.url(&format!("/cron-jobs/{}/runs", cron_job_id)));
+        self.send(request).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_cron_job_run() {
+        let sample = r#"
+            {
+                "id": "crn-run-1",
+                "status": "pending",
+                "startedAt": null,
+                "triggeredBy": "api"
+            }
+        "#;
+        let run: CronJobRun = serde_json::from_str(sample).unwrap();
+        assert_eq!(run.status, CronRunStatus::Pending);
+        assert_eq!(run.triggered_by.as_deref(), Some("api"));
+    }
+}
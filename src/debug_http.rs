@@ -0,0 +1,104 @@
+use render_deploy::RequestEvent;
+use reqwest::header::{
+    HeaderMap, HeaderName, AUTHORIZATION, COOKIE, PROXY_AUTHORIZATION, SET_COOKIE,
+};
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Headers whose values are credentials
+const SECRET_HEADERS: [HeaderName; 4] = [AUTHORIZATION, PROXY_AUTHORIZATION, COOKIE, SET_COOKIE];
+
+/// Print an api request for `--debug-http` on stderr, curl style, `>` for what we sent and `<`
+/// for what came back
+pub fn print(event: &RequestEvent) {
+    let lines = match event {
+        RequestEvent::Sent {
+            method,
+            url,
+            status,
+            elapsed,
+            request_headers,
+            response_headers,
+        } => {
+            let mut lines = vec![format!("> {} {}", method, url)];
+            lines.extend(headers(request_headers).map(|header| format!("> {}", header)));
+            match status {
+                Some(status) => lines.push(format!("< {} in {}ms", status, elapsed.as_millis())),
+                None => lines.push(format!("< no response after {}ms", elapsed.as_millis())),
+            }
+            if let Some(response_headers) = response_headers {
+                lines.extend(headers(response_headers).map(|header| format!("< {}", header)));
+            }
+            lines
+        }
+        RequestEvent::Retrying {
+            attempt,
+            delay,
+            error,
+            ..
+        } => vec![format!(
+            "* retry {} in {}ms: {}",
+            attempt,
+            delay.as_millis(),
+            error
+        )],
+        RequestEvent::Unparseable { body, .. } => vec![match dump(body) {
+            Ok(path) => format!(
+                "* the body wasn't the json we expected, saved to {}",
+                path.display()
+            ),
+            Err(e) => format!(
+                "* the body wasn't the json we expected, unable to save it: {}",
+                e
+            ),
+        }],
+    };
+    eprintln!("{}", lines.join("\n"));
+}
+
+/// `name: value` lines with credentials redacted
+fn headers(headers: &HeaderMap) -> impl Iterator<Item = String> + '_ {
+    headers.iter().map(|(name, value)| {
+        let value = if SECRET_HEADERS.contains(name) {
+            "[redacted]"
+        } else {
+            value.to_str().unwrap_or("[binary]")
+        };
+        format!("{}: {}", name, value)
+    })
+}
+
+/// Save a response body to a temp file rather than the terminal, they can be whole html pages
+fn dump(body: &str) -> std::io::Result<PathBuf> {
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis();
+    let path = env::temp_dir().join(format!(
+        "render-deploy-response-{}-{}.txt",
+        std::process::id(),
+        millis
+    ));
+    fs::write(&path, body)?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    #[test]
+    fn redact_credentials() {
+        let mut map = HeaderMap::new();
+        map.insert(AUTHORIZATION, HeaderValue::from_static("Bearer rnd_secret"));
+        map.insert("ratelimit-remaining", HeaderValue::from_static("399"));
+        let lines: Vec<String> = headers(&map).collect();
+        assert_eq!(
+            lines,
+            ["authorization: [redacted]", "ratelimit-remaining: 399"]
+        );
+    }
+}
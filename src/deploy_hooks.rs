@@ -0,0 +1,147 @@
+use crate::error::RenderError;
+use crate::models::{ClearCache, TriggerDeployRequest};
+use reqwest::{Client, ClientBuilder, Url};
+use serde::Deserialize;
+use std::time::Duration;
+
+/// A service's Deploy Hook, the secret url from its settings that deploys it without an api key
+///
+/// ```no_run
+/// use render_deploy::{DeployHook, RenderError, TriggerDeployRequest};
+///
+/// # async fn deploy() -> Result<(), RenderError> {
+/// let hook = DeployHook::new("https://api.render.com/deploy/srv-xxxxx?key=yyyyy")?;
+/// let deploy_id = hook.trigger(&TriggerDeployRequest::default()).await?;
+/// println!("{} {}", hook.service_id(), deploy_id);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct DeployHook {
+    url: Url,
+    service_id: String,
+    client: Client,
+}
+
+#[derive(Deserialize)]
+struct HookResponse {
+    deploy: HookDeploy,
+}
+
+#[derive(Deserialize)]
+struct HookDeploy {
+    id: String,
+}
+
+impl DeployHook {
+    pub fn new(url: &str) -> Result<Self, RenderError> {
+        Self::from_builder(url, Client::builder())
+    }
+
+    /// Like [`RenderClient::from_builder`](crate::RenderClient::from_builder), for extra root
+    /// certificates or a proxy
+    pub fn from_builder(url: &str, builder: ClientBuilder) -> Result<Self, RenderError> {
+        // the url is a secret so it's left out of errors
+        let invalid = || {
+            RenderError::Config(
+                "Invalid deploy hook url, it looks like https://api.render.com/deploy/srv-...?key=..."
+                    .into(),
+            )
+        };
+        let url = Url::parse(url).map_err(|_| invalid())?;
+        if !url.query_pairs().any(|(name, _)| name == "key") {
+            return Err(invalid());
+        }
+        let service_id = url
+            .path_segments()
+            .and_then(|mut segments| segments.next_back())
+            .filter(|id| id.starts_with("srv-") || id.starts_with("crn-"))
+            .ok_or_else(invalid)?
+            .to_string();
+        let client = builder
+            .user_agent("render-deploy: https://github.com/reconbot/render-deploy")
+            .timeout(Duration::from_secs(30))
+            .build()?;
+        Ok(DeployHook {
+            url,
+            service_id,
+            client,
+        })
+    }
+
+    /// The service the hook deploys
+    pub fn service_id(&self) -> &str {
+        &self.service_id
+    }
+
+    /// The hook's url with the deploy's options as query params, hooks take `ref` for a commit,
+    /// `imgURL` for an image and `clearCache` like the api
+    pub fn url(&self, request: &TriggerDeployRequest) -> Url {
+        let mut url = self.url.clone();
+        {
+            let mut query = url.query_pairs_mut();
+            if let Some(commit) = &request.commit_id {
+                query.append_pair("ref", commit);
+            }
+            if let Some(image_url) = &request.image_url {
+                query.append_pair("imgURL", image_url);
+            }
+            if request.clear_cache == Some(ClearCache::Clear) {
+                query.append_pair("clearCache", "clear");
+            }
+        }
+        url
+    }
+
+    /// Start a deploy, returning its id. Unlike api calls it isn't retried, a retry could
+    /// deploy twice.
+    pub async fn trigger(&self, request: &TriggerDeployRequest) -> Result<String, RenderError> {
+        let response = self
+            .client
+            .post(self.url(request))
+            .send()
+            .await
+            .map_err(|e| e.without_url())?;
+        let status = response.status();
+        let body = response.text().await.map_err(|e| e.without_url())?;
+        if !status.is_success() {
+            return Err(RenderError::Api {
+                status,
+                body,
+                retry_after: None,
+                request: None,
+            });
+        }
+        let response: HookResponse =
+            serde_json::from_str(&body).map_err(|error| RenderError::Json { error, body })?;
+        Ok(response.deploy.id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hook_urls() {
+        let hook = DeployHook::new("https://api.render.com/deploy/srv-123?key=abc").unwrap();
+        assert_eq!(hook.service_id(), "srv-123");
+        assert_eq!(
+            hook.url(&TriggerDeployRequest::default()).as_str(),
+            "https://api.render.com/deploy/srv-123?key=abc"
+        );
+        let request = TriggerDeployRequest {
+            commit_id: Some("b2be9cf".into()),
+            clear_cache: Some(ClearCache::Clear),
+            ..Default::default()
+        };
+        assert_eq!(
+            hook.url(&request).as_str(),
+            "https://api.render.com/deploy/srv-123?key=abc&ref=b2be9cf&clearCache=clear"
+        );
+
+        assert!(DeployHook::new("https://api.render.com/deploy/srv-123").is_err());
+        assert!(DeployHook::new("https://api.render.com/deploy?key=abc").is_err());
+        assert!(DeployHook::new("srv-123").is_err());
+    }
+}
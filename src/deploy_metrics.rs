@@ -0,0 +1,175 @@
+use render_deploy::{Deploy, DeployStatus, RenderError, Service};
+use std::fmt::Write as _;
+use std::time::SystemTime;
+use tokio::net::UdpSocket;
+
+/// Push how a deploy ended to a prometheus pushgateway, grouped by service so each one keeps
+/// its last deploy
+pub async fn push(
+    http: &reqwest::Client,
+    gateway: &str,
+    service: &Service,
+    deploy: &Deploy,
+) -> Result<(), RenderError> {
+    let url = format!(
+        "{}/metrics/job/render-deploy/service_id/{}",
+        gateway.trim_end_matches('/'),
+        service.id
+    );
+    let response = http
+        .put(url)
+        .body(exposition(service, deploy, SystemTime::now()))
+        .send()
+        .await?;
+    let status = response.status();
+    if !status.is_success() {
+        return Err(RenderError::Api {
+            status,
+            body: response.text().await?,
+            retry_after: None,
+            request: None,
+        });
+    }
+    Ok(())
+}
+
+/// Send how a deploy ended to statsd at `address` (`host:port`), tagged the dogstatsd way which
+/// telegraf and the prometheus statsd exporter understand too
+pub async fn send_statsd(
+    address: &str,
+    service: &Service,
+    deploy: &Deploy,
+) -> Result<(), RenderError> {
+    let socket = UdpSocket::bind("0.0.0.0:0").await.map_err(|e| {
+        RenderError::Config(format!("Unable to send metrics to {}: {}", address, e))
+    })?;
+    socket
+        .send_to(statsd(service, deploy).as_bytes(), address)
+        .await
+        .map_err(|e| {
+            RenderError::Config(format!("Unable to send metrics to {}: {}", address, e))
+        })?;
+    Ok(())
+}
+
+/// The prometheus text format, timestamped so the time of the last deploy can be graphed
+fn exposition(service: &Service, deploy: &Deploy, now: SystemTime) -> String {
+    let labels = format!(
+        "service=\"{}\",service_id=\"{}\",status=\"{}\"",
+        escape(&service.name),
+        service.id,
+        status(&deploy.status)
+    );
+    let mut metrics = String::new();
+    if let Some(duration) = deploy.duration() {
+        let _ = writeln!(
+            metrics,
+            "# TYPE deploy_duration_seconds gauge\ndeploy_duration_seconds{{{}}} {}",
+            labels,
+            duration.as_secs()
+        );
+    }
+    let _ = writeln!(
+        metrics,
+        "# TYPE deploy_result gauge\ndeploy_result{{{}}} {}",
+        labels,
+        u8::from(deploy.status == DeployStatus::Live)
+    );
+    let finished = now
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default();
+    let _ = writeln!(
+        metrics,
+        "# TYPE deploy_timestamp_seconds gauge\ndeploy_timestamp_seconds{{{}}} {}",
+        labels,
+        finished.as_secs()
+    );
+    metrics
+}
+
+/// A gauge for the duration and result, and a counter for how often we deploy
+fn statsd(service: &Service, deploy: &Deploy) -> String {
+    // dogstatsd tags can't have commas or pipes in them
+    let name: String = service
+        .name
+        .chars()
+        .map(|c| if matches!(c, ',' | '|' | '#') { '_' } else { c })
+        .collect();
+    let tags = format!(
+        "#service:{},service_id:{},status:{}",
+        name,
+        service.id,
+        status(&deploy.status)
+    );
+    let mut metrics = format!("deploys:1|c|{}\n", tags);
+    if let Some(duration) = deploy.duration() {
+        let _ = writeln!(
+            metrics,
+            "deploy_duration_seconds:{}|g|{}",
+            duration.as_secs(),
+            tags
+        );
+    }
+    let _ = writeln!(
+        metrics,
+        "deploy_result:{}|g|{}",
+        u8::from(deploy.status == DeployStatus::Live),
+        tags
+    );
+    metrics
+}
+
+/// `build_failed` rather than `Build Failed`
+fn status(status: &DeployStatus) -> String {
+    serde_json::to_value(status)
+        .ok()
+        .and_then(|value| value.as_str().map(String::from))
+        .unwrap_or_else(|| status.to_string())
+}
+
+fn escape(label: &str) -> String {
+    label
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures;
+    use std::time::Duration;
+
+    fn deploy(status: DeployStatus) -> (Service, Deploy) {
+        (
+            fixtures::service("srv-1", "api \"prod\""),
+            fixtures::deploy("dep-1", status),
+        )
+    }
+
+    #[test]
+    fn prometheus_metrics() {
+        let (service, deploy) = deploy(DeployStatus::Live);
+        let now = SystemTime::UNIX_EPOCH + Duration::from_secs(1728872378);
+        assert_eq!(
+            exposition(&service, &deploy, now),
+            "# TYPE deploy_duration_seconds gauge\n\
+             deploy_duration_seconds{service=\"api \\\"prod\\\"\",service_id=\"srv-1\",status=\"live\"} 123\n\
+             # TYPE deploy_result gauge\n\
+             deploy_result{service=\"api \\\"prod\\\"\",service_id=\"srv-1\",status=\"live\"} 1\n\
+             # TYPE deploy_timestamp_seconds gauge\n\
+             deploy_timestamp_seconds{service=\"api \\\"prod\\\"\",service_id=\"srv-1\",status=\"live\"} 1728872378\n"
+        );
+    }
+
+    #[test]
+    fn statsd_metrics() {
+        let (service, deploy) = deploy(DeployStatus::BuildFailed);
+        assert_eq!(
+            statsd(&service, &deploy),
+            "deploys:1|c|#service:api \"prod\",service_id:srv-1,status:build_failed\n\
+             deploy_duration_seconds:123|g|#service:api \"prod\",service_id:srv-1,status:build_failed\n\
+             deploy_result:0|g|#service:api \"prod\",service_id:srv-1,status:build_failed\n"
+        );
+    }
+}
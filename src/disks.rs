@@ -0,0 +1,46 @@
+use crate::client::RenderClient;
+use crate::error::RenderError;
+use serde::{Deserialize, Serialize};
+
+/// A persistent disk, a service can have one mounted and keeps what's written there across
+/// deploys
+#[derive(PartialEq, Deserialize, Serialize, Debug, Clone)]
+pub struct Disk {
+    pub id: String,
+    pub name: String,
+    #[serde(rename = "sizeGB")]
+    pub size_gb: u32,
+    #[serde(rename = "mountPath")]
+    pub mount_path: String,
+    #[serde(rename = "serviceId", default)]
+    pub service_id: Option<String>,
+}
+
+impl RenderClient {
+    pub async fn get_disk(&self, disk_id: &str) -> Result<Disk, RenderError> {
+        self.get(&format!("/disks/{}", disk_id), &[]).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_disk() {
+        let disk: Disk = serde_json::from_str(
+            r#"{
+                "id": "dsk-1",
+                "name": "data",
+                "sizeGB": 10,
+                "mountPath": "/var/data",
+                "serviceId": "srv-1",
+                "createdAt": "2024-10-14T02:17:35Z",
+                "updatedAt": "2024-10-14T02:17:35Z"
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(disk.size_gb, 10);
+        assert_eq!(disk.mount_path, "/var/data");
+    }
+}
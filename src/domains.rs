@@ -0,0 +1,150 @@
+use crate::client::RenderClient;
+use crate::error::RenderError;
+use crate::pagination::Pages;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+#[derive(PartialEq, Deserialize, Serialize, Debug, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum DomainType {
+    Apex,
+    Subdomain,
+}
+
+#[derive(PartialEq, Deserialize, Serialize, Debug, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum VerificationStatus {
+    Verified,
+    Unverified,
+}
+
+impl fmt::Display for VerificationStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let status_str = match self {
+            VerificationStatus::Verified => "Verified",
+            VerificationStatus::Unverified => "Unverified",
+        };
+        write!(f, "{}", status_str)
+    }
+}
+
+/// A domain pointed at a service, render issues its certificate once it's verified
+#[derive(PartialEq, Deserialize, Serialize, Debug, Clone)]
+pub struct CustomDomain {
+    pub id: String,
+    pub name: String,
+    #[serde(rename = "domainType")]
+    pub domain_type: DomainType,
+    #[serde(rename = "verificationStatus")]
+    pub verification_status: VerificationStatus,
+    /// the domain this one redirects to or from, like `www.example.com` for `example.com`
+    #[serde(rename = "redirectForName", default)]
+    pub redirect_for_name: Option<String>,
+    #[serde(rename = "createdAt")]
+    pub created_at: String,
+}
+
+#[derive(PartialEq, Deserialize, Debug, Clone)]
+pub struct ListCustomDomainsResponse {
+    pub cursor: String,
+    #[serde(rename = "customDomain")]
+    pub custom_domain: CustomDomain,
+}
+
+#[derive(Serialize)]
+struct AddCustomDomainRequest<'a> {
+    name: &'a str,
+}
+
+impl RenderClient {
+    pub fn list_custom_domains(&self, service_id: &str) -> Pages<'_, ListCustomDomainsResponse> {
+        Pages::new(
+            self,
+            format!("/services/{}/custom-domains", service_id),
+            vec![],
+        )
+    }
+
+    /// Add a domain to a service. Adding an apex domain also adds its `www.` subdomain, so
+    /// this returns every domain that was added.
+    pub async fn add_custom_domain(
+        &self,
+        service_id: &str,
+        name: &str,
+    ) -> Result<Vec<CustomDomain>, RenderError> {
+        let request = self
+            .client
+            .post(self.url(&format!("/services/{}/custom-domains", service_id)))
+            .json(&AddCustomDomainRequest { name });
+        self.send(request).await
+    }
+
+    /// `domain` is the domain's id or name
+    pub async fn delete_custom_domain(
+        &self,
+        service_id: &str,
+        domain: &str,
+    ) -> Result<(), RenderError> {
+        let request = self.client.delete(self.url(&format!(
+            "/services/{}/custom-domains/{}",
+            service_id, domain
+        )));
+        self.send(request).await
+    }
+
+    /// Ask render to check the domain's DNS again instead of waiting for it to
+    pub async fn verify_custom_domain(
+        &self,
+        service_id: &str,
+        domain: &str,
+    ) -> Result<(), RenderError> {
+        let request = self.client.post(self.url(&format!(
+            "/services/{}/custom-domains/{}/verify",
+            service_id, domain
+        )));
+        self.send::<serde_json::Value>(request).await?;
+        Ok(())
+    }
+
+    pub async fn get_custom_domain(
+        &self,
+        service_id: &str,
+        domain: &str,
+    ) -> Result<CustomDomain, RenderError> {
+        self.get(
+            &format!("/services/{}/custom-domains/{}", service_id, domain),
+            &[],
+        )
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_custom_domains() {
+        let sample = r#"
+            [
+                {
+                    "cursor": "c1",
+                    "customDomain": {
+                        "id": "cdm-1",
+                        "name": "www.example.com",
+                        "domainType": "subdomain",
+                        "publicSuffix": "com",
+                        "redirectForName": "example.com",
+                        "verificationStatus": "unverified",
+                        "createdAt": "2024-10-14T02:17:35Z",
+                        "server": { "id": "srv-1", "name": "api" }
+                    }
+                }
+            ]
+        "#;
+        let domains: Vec<ListCustomDomainsResponse> = serde_json::from_str(sample).unwrap();
+        let domain = &domains[0].custom_domain;
+        assert_eq!(domain.domain_type, DomainType::Subdomain);
+        assert_eq!(domain.verification_status, VerificationStatus::Unverified);
+    }
+}
@@ -0,0 +1,124 @@
+use render_deploy::RenderError;
+use std::fs;
+use std::path::Path;
+
+/// Read `KEY=value` pairs from a .env file, in the order they're written
+pub fn load(path: &Path) -> Result<Vec<(String, String)>, RenderError> {
+    let contents = fs::read_to_string(path)
+        .map_err(|e| RenderError::Config(format!("Unable to read {}: {}", path.display(), e)))?;
+    parse(&contents)
+        .map_err(|e| RenderError::Config(format!("Invalid .env file {}: {}", path.display(), e)))
+}
+
+/// Parse the common subset of .env files, comments, blank lines, `export` prefixes and single or
+/// double quoted values
+pub fn parse(contents: &str) -> Result<Vec<(String, String)>, String> {
+    let mut pairs = vec![];
+    for (number, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line = line.strip_prefix("export ").unwrap_or(line);
+        let (key, value) = line
+            .split_once('=')
+            .ok_or_else(|| format!("line {} isn't KEY=value", number + 1))?;
+        let key = key.trim();
+        if key.is_empty() || key.contains(char::is_whitespace) {
+            return Err(format!("line {} has an invalid key {:?}", number + 1, key));
+        }
+        pairs.push((key.to_string(), unquote(value.trim())));
+    }
+    Ok(pairs)
+}
+
+fn unquote(value: &str) -> String {
+    if let Some(inner) = value.strip_prefix('"').and_then(|v| v.strip_suffix('"')) {
+        let mut unescaped = String::new();
+        let mut chars = inner.chars();
+        while let Some(c) = chars.next() {
+            match (c, chars.clone().next()) {
+                ('\\', Some('n')) => {
+                    unescaped.push('\n');
+                    chars.next();
+                }
+                ('\\', Some(escaped @ ('"' | '\\'))) => {
+                    unescaped.push(escaped);
+                    chars.next();
+                }
+                _ => unescaped.push(c),
+            }
+        }
+        return unescaped;
+    }
+    if let Some(inner) = value.strip_prefix('\'').and_then(|v| v.strip_suffix('\'')) {
+        return inner.to_string();
+    }
+    // unquoted values can have a trailing comment
+    match value.find(" #") {
+        Some(comment) => value[..comment].trim_end().to_string(),
+        None => value.to_string(),
+    }
+}
+
+/// A `KEY=value` line, quoting the value when it wouldn't survive being read back as is
+pub fn format_line(key: &str, value: &str) -> String {
+    let plain = !value.is_empty()
+        && !value.contains(|c: char| c.is_whitespace() || matches!(c, '"' | '\'' | '#' | '\\'));
+    if plain {
+        return format!("{}={}", key, value);
+    }
+    let escaped = value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n");
+    format!("{}=\"{}\"", key, escaped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_dotenv() {
+        let pairs = parse(
+            r#"
+# database
+DATABASE_URL=postgres://db/app
+export NODE_ENV=production
+GREETING="hello \"world\"\nbye"
+RAW='single $quoted'
+PORT=3000 # the port
+EMPTY=
+"#,
+        )
+        .unwrap();
+        assert_eq!(
+            pairs,
+            vec![
+                ("DATABASE_URL".into(), "postgres://db/app".into()),
+                ("NODE_ENV".into(), "production".into()),
+                ("GREETING".into(), "hello \"world\"\nbye".into()),
+                ("RAW".into(), "single $quoted".into()),
+                ("PORT".into(), "3000".into()),
+                ("EMPTY".into(), "".into()),
+            ]
+        );
+        assert!(parse("NOT A PAIR").is_err());
+    }
+
+    #[test]
+    fn format_round_trips() {
+        for value in [
+            "plain",
+            "two words",
+            "quote \" and \\ slash",
+            "multi\nline",
+            "",
+        ] {
+            let line = format_line("KEY", value);
+            assert_eq!(parse(&line).unwrap(), vec![("KEY".into(), value.into())]);
+        }
+        assert_eq!(format_line("PORT", "3000"), "PORT=3000");
+    }
+}
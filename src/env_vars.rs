@@ -0,0 +1,74 @@
+use crate::client::RenderClient;
+use crate::error::RenderError;
+use crate::pagination::Pages;
+use serde::{Deserialize, Serialize};
+
+/// An environment variable of a service
+#[derive(PartialEq, Deserialize, Serialize, Debug, Clone)]
+pub struct EnvVar {
+    pub key: String,
+    pub value: String,
+}
+
+#[derive(PartialEq, Deserialize, Debug, Clone)]
+pub struct ListEnvVarsResponse {
+    pub cursor: String,
+    #[serde(rename = "envVar")]
+    pub env_var: EnvVar,
+}
+
+#[derive(Serialize)]
+struct EnvVarValue<'a> {
+    value: &'a str,
+}
+
+impl RenderClient {
+    /// Every environment variable of a service
+    pub fn list_env_vars(&self, service_id: &str) -> Pages<'_, ListEnvVarsResponse> {
+        Pages::new(self, format!("/services/{}/env-vars", service_id), vec![])
+    }
+
+    pub async fn get_env_var(&self, service_id: &str, key: &str) -> Result<EnvVar, RenderError> {
+        self.get(&format!("/services/{}/env-vars/{}", service_id, key), &[])
+            .await
+    }
+
+    /// Add or update one environment variable, leaving the others alone. Like the dashboard
+    /// this doesn't deploy the change.
+    pub async fn set_env_var(
+        &self,
+        service_id: &str,
+        key: &str,
+        value: &str,
+    ) -> Result<EnvVar, RenderError> {
+        let request = self
+            .client
+            .put(self.url(&format!("/services/{}/env-vars/{}", service_id, key)))
+            .json(&EnvVarValue { value });
+        self.send(request).await
+    }
+
+    pub async fn delete_env_var(&self, service_id: &str, key: &str) -> Result<(), RenderError> {
+        let request = self
+            .client
+            .delete(self.url(&format!("/services/{}/env-vars/{}", service_id, key)));
+        self.send(request).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_env_vars() {
+        let sample = r#"
+            [
+                { "cursor": "c1", "envVar": { "key": "DATABASE_URL", "value": "postgres://db" } }
+            ]
+        "#;
+        let env_vars: Vec<ListEnvVarsResponse> = serde_json::from_str(sample).unwrap();
+        assert_eq!(env_vars[0].env_var.key, "DATABASE_URL");
+        assert_eq!(env_vars[0].env_var.value, "postgres://db");
+    }
+}
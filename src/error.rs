@@ -0,0 +1,211 @@
+use crate::events::Event;
+use crate::jobs::{Job, JobStatus};
+use crate::models::{format_duration, Deploy, DeployStatus};
+use reqwest::StatusCode;
+use std::time::Duration;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum RenderError {
+    /// The request could not be sent or the response could not be read
+    #[error("Request Error: {0}")]
+    Http(#[from] reqwest::Error),
+    /// The api responded with a non success status
+    #[error("{}", describe_api_error(*status, body, request.as_deref()))]
+    Api {
+        status: StatusCode,
+        body: String,
+        /// how long render asked us to wait before trying again
+        retry_after: Option<Duration>,
+        /// the render api request that failed, eg `GET https://api.render.com/v1/services/srv-...`,
+        /// missing for other apis like github's
+        request: Option<String>,
+    },
+    /// The response body wasn't the json we expected
+    #[error("Unable to parse json {error}\n{body}")]
+    Json {
+        #[source]
+        error: serde_json::Error,
+        body: String,
+    },
+    /// The api key can't be used as a header value
+    #[error("Invalid api key")]
+    InvalidApiKey,
+    /// Missing or invalid options
+    #[error("{0}")]
+    Config(String),
+    #[error("Cannot find a service named {name}{}", did_you_mean(candidates))]
+    ServiceNotFound {
+        name: String,
+        /// services with similar names
+        candidates: Vec<String>,
+    },
+    /// More than one service has this exact name, likely in different workspaces
+    #[error("More than one service is named {name}:\n  {}", candidates.join("\n  "))]
+    AmbiguousService {
+        name: String,
+        candidates: Vec<String>,
+    },
+    /// The deploy stopped without going live
+    #[error("Deploy {} has stopped: {}", .0.id, .0.status)]
+    DeployFailed(Box<Deploy>),
+    /// We gave up waiting, the deploy may still go live
+    #[error("Deploy {deploy_id} timed out after {}", format_duration(*.elapsed))]
+    Timeout {
+        deploy_id: String,
+        elapsed: Duration,
+    },
+    /// A `--pre-deploy-cmd` or `--post-deploy-cmd` failed
+    #[error("{command} failed with {status}")]
+    CommandFailed { command: String, status: String },
+    /// We stopped waiting when interrupted, the deploy carries on
+    #[error("Stopped waiting on Deploy {deploy_id}, it carries on without us")]
+    Interrupted { deploy_id: String },
+    /// The service's instances failed to come back after a restart
+    #[error("Service {} failed to start after restarting", .0.service_id)]
+    RestartFailed(Box<Event>),
+    /// The job finished without succeeding
+    #[error("Job {} has stopped: {}", .0.id, .0.status)]
+    JobFailed(Box<Job>),
+    /// We gave up waiting on a job, it may still finish
+    #[error("Job {job_id} timed out after {}", format_duration(*.elapsed))]
+    JobTimeout { job_id: String, elapsed: Duration },
+    /// We gave up waiting on a service or database to restart, scale, recover etc, it may
+    /// still get there
+    #[error("Gave up waiting on {service_id} after {}", format_duration(*.elapsed))]
+    ServiceTimeout {
+        service_id: String,
+        elapsed: Duration,
+    },
+}
+
+fn did_you_mean(candidates: &[String]) -> String {
+    if candidates.is_empty() {
+        String::new()
+    } else {
+        format!(", did you mean:\n  {}", candidates.join("\n  "))
+    }
+}
+
+/// What to do about the render api refusing a request, for the statuses that usually mean a
+/// problem with the api key or the service rather than with render
+fn describe_api_error(status: StatusCode, body: &str, request: Option<&str>) -> String {
+    let Some(request) = request else {
+        return format!("Request Error: {} {}", status, body);
+    };
+    match status {
+        StatusCode::UNAUTHORIZED => format!(
+            "Render refused the api key for {}, it's mistyped, revoked or expired. Check \
+             RENDER_API_KEY or --api-key, `render-deploy auth whoami` shows who a key belongs to.",
+            request
+        ),
+        StatusCode::FORBIDDEN => format!(
+            "The api key isn't allowed {}, its user may not be a member of the workspace. \
+             `render-deploy auth whoami` lists the workspaces the key can reach.",
+            request
+        ),
+        StatusCode::NOT_FOUND => format!(
+            "Render found nothing for {}, it may have been deleted or be in a workspace the api \
+             key can't reach",
+            request
+        ),
+        _ => format!("Request Error: {} {} {}", request, status, body),
+    }
+}
+
+impl RenderError {
+    /// Worth trying the request again, the network blipped, we were rate limited or render is
+    /// having a bad time
+    pub fn is_transient(&self) -> bool {
+        match self {
+            RenderError::Http(e) => e.is_timeout() || e.is_connect() || e.is_request(),
+            RenderError::Api { status, .. } => {
+                *status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+            }
+            _ => false,
+        }
+    }
+
+    /// Process exit code to use when this error ends the cli
+    ///
+    /// | code | meaning                                                  |
+    /// |------|----------------------------------------------------------|
+    /// | 0    | the deploy is live (or the command succeeded)            |
+    /// | 1    | usage or configuration error                             |
+    /// | 2    | the deploy (or job, or a deploy command) failed          |
+    /// | 3    | timed out waiting, the deploy (or job) may still finish  |
+    /// | 4    | the deploy (or job) was canceled                         |
+    /// | 5    | api, network or authentication error                     |
+    /// | 130  | interrupted while waiting, the deploy carries on         |
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            RenderError::Config(_)
+            | RenderError::ServiceNotFound { .. }
+            | RenderError::AmbiguousService { .. } => 1,
+            RenderError::DeployFailed(deploy) if deploy.status == DeployStatus::Canceled => 4,
+            RenderError::JobFailed(job) if job.status == JobStatus::Canceled => 4,
+            RenderError::DeployFailed(_)
+            | RenderError::RestartFailed(_)
+            | RenderError::JobFailed(_)
+            | RenderError::CommandFailed { .. } => 2,
+            RenderError::Timeout { .. }
+            | RenderError::JobTimeout { .. }
+            | RenderError::ServiceTimeout { .. } => 3,
+            RenderError::Http(_)
+            | RenderError::Api { .. }
+            | RenderError::Json { .. }
+            | RenderError::InvalidApiKey => 5,
+            RenderError::Interrupted { .. } => 130,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::deploy;
+
+    fn failed(status: DeployStatus) -> RenderError {
+        RenderError::DeployFailed(Box::new(deploy("dep-1", status)))
+    }
+
+    #[test]
+    fn exit_codes() {
+        assert_eq!(RenderError::Config("".into()).exit_code(), 1);
+        assert_eq!(failed(DeployStatus::BuildFailed).exit_code(), 2);
+        assert_eq!(failed(DeployStatus::Canceled).exit_code(), 4);
+        let timeout = RenderError::Timeout {
+            deploy_id: "dep-cs67ufi3esus73b74a70".into(),
+            elapsed: Duration::from_secs(600),
+        };
+        assert_eq!(timeout.exit_code(), 3);
+        assert_eq!(RenderError::InvalidApiKey.exit_code(), 5);
+        let interrupted = RenderError::Interrupted {
+            deploy_id: "dep-cs67ufi3esus73b74a70".into(),
+        };
+        assert_eq!(interrupted.exit_code(), 130);
+    }
+
+    #[test]
+    fn api_errors_say_what_to_do() {
+        let error = |status, request: Option<&str>| {
+            RenderError::Api {
+                status,
+                body: r#"{"message":"unauthorized"}"#.into(),
+                retry_after: None,
+                request: request.map(String::from),
+            }
+            .to_string()
+        };
+        let request = Some("GET https://api.render.com/v1/services/srv-1");
+        assert!(error(StatusCode::UNAUTHORIZED, request).contains("RENDER_API_KEY"));
+        assert!(error(StatusCode::FORBIDDEN, request).contains("workspace"));
+        assert!(error(StatusCode::NOT_FOUND, request).contains("deleted"));
+        assert!(error(StatusCode::NOT_FOUND, request).contains("/services/srv-1"));
+        // github's 401s aren't about our api key
+        assert_eq!(
+            error(StatusCode::UNAUTHORIZED, None),
+            r#"Request Error: 401 Unauthorized {"message":"unauthorized"}"#
+        );
+    }
+}
@@ -0,0 +1,193 @@
+use crate::client::{RenderClient, WaitOptions};
+use crate::error::RenderError;
+use crate::pagination::Pages;
+use futures::TryStreamExt;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use tokio::time::{sleep, Instant};
+
+#[derive(PartialEq, Deserialize, Serialize, Debug, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum EventType {
+    BuildStarted,
+    BuildEnded,
+    DeployStarted,
+    DeployEnded,
+    PreDeployStarted,
+    PreDeployEnded,
+    ImagePullFailed,
+    /// an instance came up and passed its health check
+    ServerAvailable,
+    ServerFailed,
+    ServerHardwareFailure,
+    ServerRestarted,
+    ServerUnhealthy,
+    InstanceCountChanged,
+    AutoscalingStarted,
+    AutoscalingEnded,
+    AutoscalingConfigChanged,
+    PlanChanged,
+    ServiceSuspended,
+    ServiceResumed,
+    MaintenanceStarted,
+    MaintenanceEnded,
+    CronJobRunStarted,
+    /// details has the `cronJobRunId` and its `status`
+    CronJobRunEnded,
+    JobRunEnded,
+    /// one of the many events we don't know about
+    #[serde(other)]
+    Unknown,
+}
+
+impl fmt::Display for EventType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let type_str = match self {
+            EventType::BuildStarted => "Build Started",
+            EventType::BuildEnded => "Build Ended",
+            EventType::DeployStarted => "Deploy Started",
+            EventType::DeployEnded => "Deploy Ended",
+            EventType::PreDeployStarted => "Pre-Deploy Started",
+            EventType::PreDeployEnded => "Pre-Deploy Ended",
+            EventType::ImagePullFailed => "Image Pull Failed",
+            EventType::ServerAvailable => "Server Available",
+            EventType::ServerFailed => "Server Failed",
+            EventType::ServerHardwareFailure => "Server Hardware Failure",
+            EventType::ServerRestarted => "Server Restarted",
+            EventType::ServerUnhealthy => "Server Unhealthy",
+            EventType::InstanceCountChanged => "Instance Count Changed",
+            EventType::AutoscalingStarted => "Autoscaling Started",
+            EventType::AutoscalingEnded => "Autoscaling Ended",
+            EventType::AutoscalingConfigChanged => "Autoscaling Changed",
+            EventType::PlanChanged => "Plan Changed",
+            EventType::ServiceSuspended => "Suspended",
+            EventType::ServiceResumed => "Resumed",
+            EventType::MaintenanceStarted => "Maintenance Started",
+            EventType::MaintenanceEnded => "Maintenance Ended",
+            EventType::CronJobRunStarted => "Cron Run Started",
+            EventType::CronJobRunEnded => "Cron Run Ended",
+            EventType::JobRunEnded => "Job Ended",
+            EventType::Unknown => "Other",
+        };
+        write!(f, "{}", type_str)
+    }
+}
+
+/// Something that happened to a service, from `GET /v1/services/{id}/events`
+#[derive(PartialEq, Deserialize, Serialize, Debug, Clone)]
+pub struct Event {
+    pub id: String,
+    /// RFC3339
+    pub timestamp: String,
+    #[serde(rename = "serviceId")]
+    pub service_id: String,
+    #[serde(rename = "type")]
+    pub event_type: EventType,
+    /// varies with the type
+    #[serde(default)]
+    pub details: serde_json::Value,
+}
+
+#[derive(PartialEq, Deserialize, Debug, Clone)]
+pub struct ListEventsResponse {
+    pub cursor: String,
+    pub event: Event,
+}
+
+impl RenderClient {
+    /// A service's events, newest first, optionally only those since an RFC3339 `start_time`
+    pub fn list_events(
+        &self,
+        service_id: &str,
+        start_time: Option<&str>,
+    ) -> Pages<'_, ListEventsResponse> {
+        let query = start_time
+            .map(|start_time| vec![("startTime".to_string(), start_time.to_string())])
+            .unwrap_or_default();
+        Pages::new(self, format!("/services/{}/events", service_id), query)
+    }
+
+    /// Restart every instance of a service without building or deploying anything
+    pub async fn restart_service(&self, service_id: &str) -> Result<(), RenderError> {
+        let request = self
+            .client
+            .post(self.url(&format!("/services/{}/restart", service_id)));
+        self.send::<serde_json::Value>(request).await?;
+        Ok(())
+    }
+
+    /// Poll a service's events until an instance is available again after a restart at the
+    /// RFC3339 `since`
+    pub async fn wait_for_restart(
+        &self,
+        service_id: &str,
+        since: &str,
+        options: &WaitOptions,
+    ) -> Result<Event, RenderError> {
+        let start = Instant::now();
+        loop {
+            if start.elapsed() > options.timeout {
+                return Err(RenderError::ServiceTimeout {
+                    service_id: service_id.to_string(),
+                    elapsed: start.elapsed(),
+                });
+            }
+            sleep(options.poll_interval).await;
+            let events: Vec<Event> = match self
+                .list_events(service_id, Some(since))
+                .try_collect()
+                .await
+            {
+                Ok(events) => events,
+                Err(e) if e.is_transient() => continue,
+                Err(e) => return Err(e),
+            };
+            // oldest first so a failure that was recovered from doesn't end the wait
+            for event in events.into_iter().rev() {
+                match event.event_type {
+                    EventType::ServerAvailable => return Ok(event),
+                    EventType::ServerFailed => {
+                        return Err(RenderError::RestartFailed(Box::new(event)))
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_events() {
+        let sample = r#"
+            [
+                {
+                    "cursor": "c2",
+                    "event": {
+                        "id": "evt-2",
+                        "timestamp": "2024-10-14T02:19:38Z",
+                        "serviceId": "srv-1",
+                        "type": "server_available",
+                        "details": {}
+                    }
+                },
+                {
+                    "cursor": "c1",
+                    "event": {
+                        "id": "evt-1",
+                        "timestamp": "2024-10-14T02:17:35Z",
+                        "serviceId": "srv-1",
+                        "type": "disk_created",
+                        "details": { "diskId": "dsk-1" }
+                    }
+                }
+            ]
+        "#;
+        let events: Vec<ListEventsResponse> = serde_json::from_str(sample).unwrap();
+        assert_eq!(events[0].event.event_type, EventType::ServerAvailable);
+        assert_eq!(events[1].event.event_type, EventType::Unknown);
+    }
+}
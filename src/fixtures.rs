@@ -0,0 +1,40 @@
+//! Services and deploys for tests. It's built into the library's tests and the cli's, so it
+//! names the library the way the cli does.
+
+use render_deploy::{CommitInfo, Deploy, DeployStatus, Service, ServiceType};
+
+/// A web service built from this repo's main branch
+pub fn service(id: &str, name: &str) -> Service {
+    Service {
+        id: id.into(),
+        name: name.into(),
+        service_type: ServiceType::WebService,
+        owner_id: "tea-1".into(),
+        branch: Some("main".into()),
+        dashboard_url: format!("https://dashboard.render.com/web/{}", id),
+        auto_deploy: false,
+        suspended: false,
+        repo: Some("https://github.com/reconbot/render-deploy".into()),
+        image_path: None,
+        updated_at: "2024-10-14T02:17:35Z".into(),
+        created_at: "2024-10-14T02:17:35Z".into(),
+    }
+}
+
+/// A deploy of a commit that finished as `status` after 2m3s
+pub fn deploy(id: &str, status: DeployStatus) -> Deploy {
+    Deploy {
+        id: id.into(),
+        commit: Some(CommitInfo {
+            id: "b2be9cf9e3188d00f58ef18a5904528993faeaa2".into(),
+            message: "fix the build\n\nfor real this time".into(),
+            created_at: "2024-10-11T20:02:45Z".into(),
+        }),
+        image: None,
+        trigger: Some("api".into()),
+        status,
+        created_at: "2024-10-14T02:17:35Z".into(),
+        updated_at: "2024-10-14T02:19:38Z".into(),
+        finished_at: Some("2024-10-14T02:19:38Z".into()),
+    }
+}
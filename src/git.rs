@@ -0,0 +1,157 @@
+use std::process::Command;
+
+/// The commit at the head of `branch` in a remote `repo`, using whatever git credentials are
+/// around. `None` if git isn't installed or can't reach the repo.
+pub fn remote_head(repo: &str, branch: &str) -> Option<String> {
+    let output = Command::new("git")
+        .args(["ls-remote", "--exit-code", repo])
+        .arg(format!("refs/heads/{}", branch))
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    parse_ls_remote(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Whether `commit` names a commit in the local repo, like `HEAD`, `HEAD~1` or `@`, rather than
+/// a sha
+pub fn is_local_rev(commit: &str) -> bool {
+    commit == "@" || commit.starts_with("@{") || commit.starts_with("HEAD")
+}
+
+/// The sha of a commit in the local repo
+pub fn resolve(rev: &str) -> Option<String> {
+    let output = Command::new("git")
+        .args(["rev-parse", "--verify", "--quiet"])
+        .arg(format!("{}^{{commit}}", rev))
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Whether `commit` is on a branch of the local remote that points at `repo`, as of the last
+/// fetch. `None` if there's no git repo here or none of its remotes are `repo`.
+pub fn is_pushed(commit: &str, repo: &str) -> Option<bool> {
+    let remotes = Command::new("git").args(["remote", "-v"]).output().ok()?;
+    let remotes = String::from_utf8_lossy(&remotes.stdout);
+    let remote = remotes.lines().find_map(|line| {
+        let mut fields = line.split_whitespace();
+        let (name, url) = (fields.next()?, fields.next()?);
+        (same_repo(url, repo)).then(|| name.to_string())
+    })?;
+    let output = Command::new("git")
+        .args(["branch", "--remotes", "--contains", commit])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return Some(false);
+    }
+    let branches = String::from_utf8_lossy(&output.stdout);
+    let prefix = format!("{}/", remote);
+    Some(
+        branches
+            .lines()
+            .any(|branch| branch.trim().starts_with(&prefix)),
+    )
+}
+
+/// `<short sha> <subject>` for each commit after `from` up to `to` in the local repo, newest
+/// first. `None` if either commit isn't here.
+pub fn log(from: &str, to: &str) -> Option<Vec<String>> {
+    let output = Command::new("git")
+        .args(["log", "--format=%h %s"])
+        .arg(format!("{}..{}", from, to))
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(str::to_string)
+            .collect(),
+    )
+}
+
+/// The url of the local repo's `origin` remote
+pub fn origin_url() -> Option<String> {
+    let output = Command::new("git")
+        .args(["remote", "get-url", "origin"])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Whether two remote urls are the same repo, `git@github.com:o/r.git` is
+/// `https://github.com/o/r`
+pub fn same_repo(a: &str, b: &str) -> bool {
+    fn normalize(url: &str) -> String {
+        let url = url.trim().to_lowercase();
+        let url = url.split_once("://").map_or(url.as_str(), |(_, rest)| rest);
+        // drop any user, then turn scp style `host:path` into `host/path`
+        let url = url.rsplit_once('@').map_or(url, |(_, rest)| rest);
+        url.replacen(':', "/", 1)
+            .trim_end_matches('/')
+            .trim_end_matches(".git")
+            .to_string()
+    }
+    normalize(a) == normalize(b)
+}
+
+fn parse_ls_remote(output: &str) -> Option<String> {
+    output
+        .lines()
+        .next()?
+        .split_whitespace()
+        .next()
+        .map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_ls_remote_output() {
+        let output = "b2be9cf9e3188d00f58ef18a5904528993faeaa2\trefs/heads/main\n";
+        assert_eq!(
+            parse_ls_remote(output).as_deref(),
+            Some("b2be9cf9e3188d00f58ef18a5904528993faeaa2")
+        );
+        assert_eq!(parse_ls_remote(""), None);
+    }
+
+    #[test]
+    fn same_repo_across_url_styles() {
+        let repo = "https://github.com/reconbot/render-deploy";
+        assert!(same_repo("git@github.com:reconbot/render-deploy.git", repo));
+        assert!(same_repo(
+            "https://github.com/reconbot/render-deploy.git",
+            repo
+        ));
+        assert!(same_repo(
+            "ssh://git@github.com/reconbot/render-deploy",
+            repo
+        ));
+        assert!(!same_repo(
+            "git@github.com:reconbot/render-deploy-action.git",
+            repo
+        ));
+    }
+
+    #[test]
+    fn local_revs() {
+        assert!(is_local_rev("HEAD"));
+        assert!(is_local_rev("HEAD~2"));
+        assert!(is_local_rev("@"));
+        assert!(!is_local_rev("b2be9cf9e3188d00f58ef18a5904528993faeaa2"));
+    }
+}
@@ -0,0 +1,315 @@
+use render_deploy::RenderError;
+use reqwest::{header, StatusCode, Url};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use std::env;
+
+const GITHUB_API_URL: &str = "https://api.github.com";
+const GITLAB_API_URL: &str = "https://gitlab.com/api/v4";
+
+/// Where a service's repo lives, from its url
+#[derive(Debug, PartialEq, Eq)]
+pub enum GitHost {
+    /// `owner/repo` on github.com
+    GitHub(String),
+    /// `group/project` (maybe with subgroups) on gitlab.com
+    GitLab(String),
+}
+
+impl GitHost {
+    /// A page showing the commits after `from` up to `to`
+    pub fn compare_url(&self, from: &str, to: &str) -> String {
+        match self {
+            GitHost::GitHub(path) => {
+                format!("https://github.com/{}/compare/{}...{}", path, from, to)
+            }
+            GitHost::GitLab(path) => {
+                format!("https://gitlab.com/{}/-/compare/{}...{}", path, from, to)
+            }
+        }
+    }
+
+    pub fn parse(repo: &str) -> Option<GitHost> {
+        let url = Url::parse(repo).ok()?;
+        let path = url
+            .path()
+            .trim_matches('/')
+            .trim_end_matches(".git")
+            .to_string();
+        if path.is_empty() {
+            return None;
+        }
+        match url.host_str()? {
+            "github.com" => Some(GitHost::GitHub(path)),
+            "gitlab.com" => Some(GitHost::GitLab(path)),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct Comparison {
+    status: String,
+}
+
+#[derive(Deserialize)]
+struct Ref {
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct GitHubCommit {
+    sha: String,
+}
+
+#[derive(Deserialize)]
+struct GitHubComparison {
+    commits: Vec<GitHubComparedCommit>,
+}
+
+#[derive(Deserialize)]
+struct GitHubComparedCommit {
+    sha: String,
+    commit: GitHubCommitMessage,
+}
+
+#[derive(Deserialize)]
+struct GitHubCommitMessage {
+    message: String,
+}
+
+#[derive(Deserialize)]
+struct GitLabComparison {
+    commits: Vec<GitLabComparedCommit>,
+}
+
+#[derive(Deserialize)]
+struct GitLabComparedCommit {
+    short_id: String,
+    title: String,
+}
+
+#[derive(Deserialize)]
+struct GitLabBranch {
+    commit: GitLabCommit,
+}
+
+#[derive(Deserialize)]
+struct GitLabCommit {
+    id: String,
+}
+
+/// Fail unless `commit` exists in `repo` and is on `branch`, asking github or gitlab with
+/// `token`, or `GITHUB_TOKEN`/`GITLAB_TOKEN` when it's not given. Public repos don't need one.
+pub async fn verify_commit(
+    http: &reqwest::Client,
+    repo: &str,
+    branch: &str,
+    commit: &str,
+    token: Option<String>,
+) -> Result<(), RenderError> {
+    let host = GitHost::parse(repo).ok_or_else(|| {
+        RenderError::Config(format!(
+            "Can only verify commits in github.com and gitlab.com repos, not {}",
+            repo
+        ))
+    })?;
+    let on_branch = match &host {
+        GitHost::GitHub(path) => {
+            let api_url = env::var("GITHUB_API_URL").unwrap_or_else(|_| GITHUB_API_URL.into());
+            let token = token.or_else(|| env::var("GITHUB_TOKEN").ok());
+            let request = http
+                .get(format!(
+                    "{}/repos/{}/compare/{}...{}",
+                    api_url, path, branch, commit
+                ))
+                .header(header::ACCEPT, "application/vnd.github+json");
+            // the commit is on the branch when the branch already contains everything in it
+            let comparison = get::<Comparison>(request, token.as_deref()).await?;
+            comparison
+                .map(|comparison| matches!(comparison.status.as_str(), "identical" | "behind"))
+        }
+        GitHost::GitLab(path) => {
+            let api_url = env::var("CI_API_V4_URL").unwrap_or_else(|_| GITLAB_API_URL.into());
+            let token = token.or_else(|| env::var("GITLAB_TOKEN").ok());
+            let request = http
+                .get(format!(
+                    "{}/projects/{}/repository/commits/{}/refs",
+                    api_url,
+                    path.replace('/', "%2F"),
+                    commit
+                ))
+                .query(&[("type", "branch"), ("per_page", "100")]);
+            get::<Vec<Ref>>(request, token.as_deref())
+                .await?
+                .map(|refs| refs.iter().any(|branch_ref| branch_ref.name == branch))
+        }
+    };
+    match on_branch {
+        Some(true) => Ok(()),
+        Some(false) => Err(RenderError::Config(format!(
+            "Commit {} isn't on the {} branch of {}",
+            commit, branch, repo
+        ))),
+        None => Err(RenderError::Config(format!(
+            "Cannot find commit {} or the {} branch in {}, has it been pushed? A private repo \
+             needs a token in GITHUB_TOKEN, GITLAB_TOKEN or the profile's git_token_env",
+            commit, branch, repo
+        ))),
+    }
+}
+
+/// The commit at the head of `branch` in `repo`, `None` if there's no such branch
+pub async fn branch_head(
+    http: &reqwest::Client,
+    repo: &str,
+    branch: &str,
+    token: Option<String>,
+) -> Result<Option<String>, RenderError> {
+    let host = GitHost::parse(repo).ok_or_else(|| {
+        RenderError::Config(format!(
+            "Unable to find the {} branch in {}, git can't reach it and it's not on github.com \
+             or gitlab.com",
+            branch, repo
+        ))
+    })?;
+    match &host {
+        GitHost::GitHub(path) => {
+            let api_url = env::var("GITHUB_API_URL").unwrap_or_else(|_| GITHUB_API_URL.into());
+            let token = token.or_else(|| env::var("GITHUB_TOKEN").ok());
+            let request = http
+                .get(format!("{}/repos/{}/commits/{}", api_url, path, branch))
+                .header(header::ACCEPT, "application/vnd.github+json");
+            let commit = get::<GitHubCommit>(request, token.as_deref()).await?;
+            Ok(commit.map(|commit| commit.sha))
+        }
+        GitHost::GitLab(path) => {
+            let api_url = env::var("CI_API_V4_URL").unwrap_or_else(|_| GITLAB_API_URL.into());
+            let token = token.or_else(|| env::var("GITLAB_TOKEN").ok());
+            let request = http.get(format!(
+                "{}/projects/{}/repository/branches/{}",
+                api_url,
+                path.replace('/', "%2F"),
+                branch.replace('/', "%2F")
+            ));
+            let branch = get::<GitLabBranch>(request, token.as_deref()).await?;
+            Ok(branch.map(|branch| branch.commit.id))
+        }
+    }
+}
+
+/// `<short sha> <subject>` for each commit after `from` up to `to`, newest first like `git log`.
+/// `None` if the repo doesn't have one of them.
+pub async fn commits_between(
+    http: &reqwest::Client,
+    host: &GitHost,
+    from: &str,
+    to: &str,
+    token: Option<String>,
+) -> Result<Option<Vec<String>>, RenderError> {
+    let mut commits = match host {
+        GitHost::GitHub(path) => {
+            let api_url = env::var("GITHUB_API_URL").unwrap_or_else(|_| GITHUB_API_URL.into());
+            let token = token.or_else(|| env::var("GITHUB_TOKEN").ok());
+            let request = http
+                .get(format!(
+                    "{}/repos/{}/compare/{}...{}",
+                    api_url, path, from, to
+                ))
+                .header(header::ACCEPT, "application/vnd.github+json");
+            let Some(comparison) = get::<GitHubComparison>(request, token.as_deref()).await? else {
+                return Ok(None);
+            };
+            comparison
+                .commits
+                .into_iter()
+                .map(|commit| {
+                    let subject = commit.commit.message.lines().next().unwrap_or_default();
+                    format!("{} {}", &commit.sha[..commit.sha.len().min(7)], subject)
+                })
+                .collect::<Vec<_>>()
+        }
+        GitHost::GitLab(path) => {
+            let api_url = env::var("CI_API_V4_URL").unwrap_or_else(|_| GITLAB_API_URL.into());
+            let token = token.or_else(|| env::var("GITLAB_TOKEN").ok());
+            let request = http
+                .get(format!(
+                    "{}/projects/{}/repository/compare",
+                    api_url,
+                    path.replace('/', "%2F")
+                ))
+                .query(&[("from", from), ("to", to)]);
+            let Some(comparison) = get::<GitLabComparison>(request, token.as_deref()).await? else {
+                return Ok(None);
+            };
+            comparison
+                .commits
+                .into_iter()
+                .map(|commit| format!("{} {}", commit.short_id, commit.title))
+                .collect()
+        }
+    };
+    // both list the oldest first
+    commits.reverse();
+    Ok(Some(commits))
+}
+
+/// `None` when the repo, branch or commit can't be found
+async fn get<T: DeserializeOwned>(
+    request: reqwest::RequestBuilder,
+    token: Option<&str>,
+) -> Result<Option<T>, RenderError> {
+    let request = match token {
+        Some(token) => request.bearer_auth(token),
+        None => request,
+    };
+    let response = request.send().await?;
+    let status = response.status();
+    let body = response.text().await?;
+    // github says 422 when there's no such commit to compare with
+    if status == StatusCode::NOT_FOUND || status == StatusCode::UNPROCESSABLE_ENTITY {
+        return Ok(None);
+    }
+    if !status.is_success() {
+        return Err(RenderError::Api {
+            status,
+            body,
+            retry_after: None,
+            request: None,
+        });
+    }
+    serde_json::from_str(&body)
+        .map(Some)
+        .map_err(|error| RenderError::Json { error, body })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_repo_urls() {
+        assert_eq!(
+            GitHost::parse("https://github.com/reconbot/render-deploy"),
+            Some(GitHost::GitHub("reconbot/render-deploy".into()))
+        );
+        assert_eq!(
+            GitHost::parse("https://gitlab.com/group/sub/project.git"),
+            Some(GitHost::GitLab("group/sub/project".into()))
+        );
+        assert_eq!(GitHost::parse("https://bitbucket.org/o/r"), None);
+        assert_eq!(GitHost::parse("https://github.com/"), None);
+    }
+
+    #[test]
+    fn compare_urls() {
+        assert_eq!(
+            GitHost::GitHub("o/r".into()).compare_url("abc", "def"),
+            "https://github.com/o/r/compare/abc...def"
+        );
+        assert_eq!(
+            GitHost::GitLab("g/p".into()).compare_url("abc", "def"),
+            "https://gitlab.com/g/p/-/compare/abc...def"
+        );
+    }
+}
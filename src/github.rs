@@ -0,0 +1,239 @@
+use render_deploy::{deploy_url, format_duration, Deploy, RenderError, Service};
+use reqwest::header;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::env;
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::path::Path;
+
+const GITHUB_API_URL: &str = "https://api.github.com";
+
+/// Whether we're running as a step of a github actions job
+pub fn is_actions() -> bool {
+    env::var("GITHUB_ACTIONS").is_ok_and(|value| value == "true")
+}
+
+/// Write a deploy to the step's outputs and job summary when running in github actions
+pub fn report(service: &Service, deploy: &Deploy) -> io::Result<()> {
+    if !is_actions() {
+        return Ok(());
+    }
+    if let Some(path) = env::var_os("GITHUB_OUTPUT") {
+        append(Path::new(&path), &outputs(service, deploy))?;
+    }
+    summarize(service, deploy)
+}
+
+/// Add a deploy to the job summary when running in github actions, for when there are too many
+/// deploys for the step's outputs to describe
+pub fn summarize(service: &Service, deploy: &Deploy) -> io::Result<()> {
+    if !is_actions() {
+        return Ok(());
+    }
+    match env::var_os("GITHUB_STEP_SUMMARY") {
+        Some(path) => append(Path::new(&path), &summary(service, deploy)),
+        None => Ok(()),
+    }
+}
+
+fn append(path: &Path, contents: &str) -> io::Result<()> {
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    file.write_all(contents.as_bytes())
+}
+
+/// `key=value` lines for `$GITHUB_OUTPUT`
+fn outputs(service: &Service, deploy: &Deploy) -> String {
+    // the api's snake_case status is easier to compare against in a workflow
+    let status = serde_json::to_value(&deploy.status)
+        .ok()
+        .and_then(|status| status.as_str().map(String::from))
+        .unwrap_or_default();
+    let duration = deploy
+        .duration()
+        .map(|duration| duration.as_secs().to_string())
+        .unwrap_or_default();
+    format!(
+        "deploy-id={id}\ndeploy-url={url}\nstatus={status}\nduration={duration}\n",
+        id = deploy.id,
+        url = deploy_url(service, deploy),
+        status = status,
+        duration = duration
+    )
+}
+
+/// Markdown for `$GITHUB_STEP_SUMMARY`
+fn summary(service: &Service, deploy: &Deploy) -> String {
+    let mut summary = format!(
+        "### {name} {status}\n\n| | |\n| --- | --- |\n| Deploy | [{id}]({url}) |\n",
+        name = service.name,
+        status = deploy.status,
+        id = deploy.id,
+        url = deploy_url(service, deploy)
+    );
+    let deployed = match &deploy.commit {
+        Some(commit) => format!(
+            "`{sha}` {message}",
+            sha = commit.id.get(..7).unwrap_or(&commit.id),
+            message = commit.message.lines().next().unwrap_or_default()
+        ),
+        None => deploy.describe(),
+    };
+    summary.push_str(&format!(
+        "| Deployed | {} |\n",
+        deployed.replace('|', "\\|")
+    ));
+    summary.push_str(&format!("| Started | {} |\n", deploy.created_at));
+    if let Some(finished_at) = &deploy.finished_at {
+        summary.push_str(&format!("| Finished | {} |\n", finished_at));
+    }
+    if let Some(duration) = deploy.duration() {
+        summary.push_str(&format!("| Duration | {} |\n", format_duration(duration)));
+    }
+    summary.push('\n');
+    summary
+}
+
+/// Where a github deployment is at, see
+/// <https://docs.github.com/en/rest/deployments/statuses>
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DeploymentState {
+    InProgress,
+    Success,
+    Failure,
+    /// we stopped watching before the deploy finished
+    Error,
+}
+
+/// A github deployment mirroring a render deploy so it shows up on the repo's environments
+pub struct Deployment {
+    client: reqwest::Client,
+    token: String,
+    statuses_url: String,
+}
+
+#[derive(Deserialize)]
+struct CreatedDeployment {
+    statuses_url: String,
+}
+
+impl Deployment {
+    /// Create a deployment of `git_ref` to `environment` in the repo from `GITHUB_REPOSITORY`,
+    /// authenticated with `GITHUB_TOKEN`
+    pub async fn create(
+        client: &reqwest::Client,
+        environment: &str,
+        git_ref: Option<&str>,
+        description: &str,
+    ) -> Result<Deployment, RenderError> {
+        let var = |name: &str| {
+            env::var(name).map_err(|_| {
+                RenderError::Config(format!("{} is needed for --github-deployment", name))
+            })
+        };
+        let token = var("GITHUB_TOKEN")?;
+        let repository = var("GITHUB_REPOSITORY")?;
+        let api_url = env::var("GITHUB_API_URL").unwrap_or_else(|_| GITHUB_API_URL.into());
+        let git_ref = match git_ref {
+            Some(git_ref) => git_ref.to_string(),
+            None => var("GITHUB_SHA")?,
+        };
+
+        let request = client
+            .post(format!("{}/repos/{}/deployments", api_url, repository))
+            .json(&json!({
+                "ref": git_ref,
+                "environment": environment,
+                "description": description,
+                "auto_merge": false,
+                // render already built the commit, don't wait on the repo's checks
+                "required_contexts": [],
+            }));
+        let created: CreatedDeployment = send(request, &token).await?;
+        Ok(Deployment {
+            client: client.clone(),
+            token,
+            statuses_url: created.statuses_url,
+        })
+    }
+
+    pub async fn set_state(
+        &self,
+        state: DeploymentState,
+        log_url: &str,
+        description: &str,
+    ) -> Result<(), RenderError> {
+        let request = self.client.post(&self.statuses_url).json(&json!({
+            "state": state,
+            "log_url": log_url,
+            "description": description,
+        }));
+        send::<serde_json::Value>(request, &self.token).await?;
+        Ok(())
+    }
+}
+
+async fn send<T: DeserializeOwned>(
+    request: reqwest::RequestBuilder,
+    token: &str,
+) -> Result<T, RenderError> {
+    let response = request
+        .bearer_auth(token)
+        .header(header::ACCEPT, "application/vnd.github+json")
+        .send()
+        .await?;
+    let status = response.status();
+    let body = response.text().await?;
+    if !status.is_success() {
+        return Err(RenderError::Api {
+            status,
+            body,
+            retry_after: None,
+            request: None,
+        });
+    }
+    serde_json::from_str(&body).map_err(|error| RenderError::Json { error, body })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures;
+    use render_deploy::{CommitInfo, DeployStatus};
+
+    fn service() -> Service {
+        fixtures::service("srv-1", "api")
+    }
+
+    fn deploy() -> Deploy {
+        let deploy = fixtures::deploy("dep-1", DeployStatus::Live);
+        Deploy {
+            commit: Some(CommitInfo {
+                message: "fix the | pipe\n\nlonger description".into(),
+                ..deploy.commit.unwrap()
+            }),
+            ..deploy
+        }
+    }
+
+    #[test]
+    fn outputs_for_a_deploy() {
+        assert_eq!(
+            outputs(&service(), &deploy()),
+            "deploy-id=dep-1\ndeploy-url=https://dashboard.render.com/web/srv-1/deploys/dep-1\nstatus=live\nduration=123\n"
+        );
+    }
+
+    #[test]
+    fn summary_for_a_deploy() {
+        let summary = summary(&service(), &deploy());
+        assert!(summary.starts_with("### api Live\n"));
+        assert!(summary.contains(
+            "| Deploy | [dep-1](https://dashboard.render.com/web/srv-1/deploys/dep-1) |\n"
+        ));
+        assert!(summary.contains("| Deployed | `b2be9cf` fix the \\| pipe |\n"));
+        assert!(summary.contains("| Duration | 2m3s |\n"));
+    }
+}
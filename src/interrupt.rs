@@ -0,0 +1,136 @@
+use crate::cli::OnInterrupt;
+use crate::commands::confirm;
+use std::future;
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Once;
+use tokio::sync::{Notify, OnceCell};
+
+/// What to do about deploys being waited on when we're interrupted, decided once however many
+/// deploys there are
+#[derive(Debug, Default)]
+pub struct Interrupt {
+    choice: OnceCell<OnInterrupt>,
+}
+
+impl Interrupt {
+    /// `--on-interrupt`, or ask at a terminal, or detach. Being interrupted again while asking
+    /// exits right away, leaving the deploys be.
+    pub async fn choose(&self, on_interrupt: Option<OnInterrupt>) -> OnInterrupt {
+        *self
+            .choice
+            .get_or_init(|| async {
+                if let Some(on_interrupt) = on_interrupt {
+                    return on_interrupt;
+                }
+                // reading the answer blocks, keep it off the runtime so we still hear signals
+                let asking = tokio::task::spawn_blocking(|| {
+                    confirm("Interrupted, cancel the deploy? Otherwise it carries on")
+                });
+                tokio::select! {
+                    answer = asking => match answer {
+                        Ok(Ok(true)) => OnInterrupt::Cancel,
+                        _ => OnInterrupt::Detach,
+                    },
+                    // the runtime would wait on the blocked read before exiting
+                    _ = signalled() => process::exit(130),
+                }
+            })
+            .await
+    }
+
+    /// Whether we've been interrupted while waiting on a deploy
+    pub fn interrupted(&self) -> bool {
+        self.choice.initialized()
+    }
+}
+
+/// How many waits are listening for signals right now
+static WAITING: AtomicUsize = AtomicUsize::new(0);
+/// Wakes the waits listening when a signal comes in
+static SIGNALLED: Notify = Notify::const_new();
+
+/// Resolves on Ctrl-C, or SIGTERM like CI sends when a job is canceled
+pub async fn signalled() {
+    listen();
+    WAITING.fetch_add(1, Ordering::SeqCst);
+    let _waiting = Waiting;
+    SIGNALLED.notified().await;
+}
+
+/// No longer listening once the wait is over, however it ended
+struct Waiting;
+
+impl Drop for Waiting {
+    fn drop(&mut self) {
+        WAITING.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Start handling signals the first time a wait needs to. Tokio keeps handling them for the rest
+/// of the process once it has, so a signal with no wait to hear it exits the way it would have
+/// without us, whether that's during --post-deploy-cmd or releasing a lock.
+fn listen() {
+    static LISTENING: Once = Once::new();
+    LISTENING.call_once(|| {
+        tokio::spawn(async {
+            let mut signals = Signals::new();
+            loop {
+                let exit_code = signals.next().await;
+                if WAITING.load(Ordering::SeqCst) == 0 {
+                    process::exit(exit_code);
+                }
+                SIGNALLED.notify_waiters();
+            }
+        });
+    });
+}
+
+/// Ctrl-C and SIGTERM, listened for once so none are missed between them. Not being able to
+/// listen for a signal means never being interrupted, not being interrupted right away.
+struct Signals {
+    #[cfg(unix)]
+    interrupt: Option<tokio::signal::unix::Signal>,
+    #[cfg(unix)]
+    terminate: Option<tokio::signal::unix::Signal>,
+}
+
+impl Signals {
+    fn new() -> Signals {
+        #[cfg(unix)]
+        {
+            use tokio::signal::unix::{signal, SignalKind};
+            Signals {
+                interrupt: signal(SignalKind::interrupt()).ok(),
+                terminate: signal(SignalKind::terminate()).ok(),
+            }
+        }
+        #[cfg(not(unix))]
+        Signals {}
+    }
+
+    /// The next signal, as the exit code it would have ended the process with
+    #[cfg(unix)]
+    async fn next(&mut self) -> i32 {
+        async fn recv(signal: &mut Option<tokio::signal::unix::Signal>) {
+            match signal {
+                Some(signal) => {
+                    signal.recv().await;
+                }
+                None => future::pending().await,
+            }
+        }
+        tokio::select! {
+            _ = recv(&mut self.interrupt) => 130,
+            _ = recv(&mut self.terminate) => 143,
+        }
+    }
+
+    #[cfg(not(unix))]
+    async fn next(&mut self) -> i32 {
+        if tokio::signal::ctrl_c().await.is_err() {
+            future::pending::<()>().await;
+        }
+        130
+    }
+}
@@ -0,0 +1,184 @@
+use crate::client::{RenderClient, WaitOptions};
+use crate::error::RenderError;
+use crate::pagination::Pages;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::time::Duration;
+use tokio::time::{sleep, Instant};
+
+#[derive(PartialEq, Deserialize, Serialize, Debug, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    Pending,
+    Running,
+    Succeeded,
+    Failed,
+    Canceled,
+}
+
+impl JobStatus {
+    pub fn is_finished(&self) -> bool {
+        matches!(
+            self,
+            JobStatus::Succeeded | JobStatus::Failed | JobStatus::Canceled
+        )
+    }
+}
+
+impl fmt::Display for JobStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let status_str = match self {
+            JobStatus::Pending => "Pending",
+            JobStatus::Running => "Running",
+            JobStatus::Succeeded => "Succeeded",
+            JobStatus::Failed => "Failed",
+            JobStatus::Canceled => "Canceled",
+        };
+        write!(f, "{}", status_str)
+    }
+}
+
+/// A one off command run with a service's build and environment, like a migration
+#[derive(PartialEq, Deserialize, Serialize, Debug, Clone)]
+pub struct Job {
+    pub id: String,
+    #[serde(rename = "serviceId")]
+    pub service_id: String,
+    #[serde(rename = "startCommand")]
+    pub start_command: String,
+    #[serde(rename = "planId")]
+    pub plan_id: String,
+    /// render leaves this out until the job is picked up
+    #[serde(default = "pending")]
+    pub status: JobStatus,
+    #[serde(rename = "createdAt")]
+    pub created_at: String,
+    #[serde(rename = "startedAt", default)]
+    pub started_at: Option<String>,
+    #[serde(rename = "finishedAt", default)]
+    pub finished_at: Option<String>,
+}
+
+fn pending() -> JobStatus {
+    JobStatus::Pending
+}
+
+impl Job {
+    /// How long the job ran, `None` until it finishes
+    pub fn duration(&self) -> Option<Duration> {
+        let started_at = humantime::parse_rfc3339(self.started_at.as_deref()?).ok()?;
+        let finished_at = humantime::parse_rfc3339(self.finished_at.as_deref()?).ok()?;
+        finished_at.duration_since(started_at).ok()
+    }
+}
+
+#[derive(PartialEq, Deserialize, Debug, Clone)]
+pub struct ListJobsResponse {
+    pub cursor: String,
+    pub job: Job,
+}
+
+/// Body of `POST /v1/services/{id}/jobs`
+#[derive(Serialize, Debug, Default, Clone)]
+pub struct RunJobRequest {
+    #[serde(rename = "startCommand")]
+    pub start_command: String,
+    /// instance type to run on, defaults to the service's
+    #[serde(rename = "planId", skip_serializing_if = "Option::is_none")]
+    pub plan_id: Option<String>,
+}
+
+impl RenderClient {
+    /// Start a one off job, it doesn't wait for the job to run
+    pub async fn run_job(
+        &self,
+        service_id: &str,
+        request: &RunJobRequest,
+    ) -> Result<Job, RenderError> {
+        let request = self
+            .client
+            .post(self.url(&format!("/services/{}/jobs", service_id)))
+            .json(request);
+        self.send(request).await
+    }
+
+    /// Every job run on a service, newest first, fetched a page at a time as you iterate
+    pub fn job_history(&self, service_id: &str) -> Pages<'_, ListJobsResponse> {
+        Pages::new(self, format!("/services/{}/jobs", service_id), vec![])
+    }
+
+    pub async fn get_job(&self, service_id: &str, job_id: &str) -> Result<Job, RenderError> {
+        self.get(&format!("/services/{}/jobs/{}", service_id, job_id), &[])
+            .await
+    }
+
+    /// Poll a job until it succeeds, calling `on_poll` with every state we see. It's an error
+    /// for the job to fail, be canceled or still be running after `options.timeout`.
+    pub async fn wait_for_job<F>(
+        &self,
+        service_id: &str,
+        job_id: &str,
+        options: &WaitOptions,
+        mut on_poll: F,
+    ) -> Result<Job, RenderError>
+    where
+        F: FnMut(&Job),
+    {
+        let start = Instant::now();
+        loop {
+            if start.elapsed() > options.timeout {
+                return Err(RenderError::JobTimeout {
+                    job_id: job_id.to_string(),
+                    elapsed: start.elapsed(),
+                });
+            }
+            sleep(options.poll_interval).await;
+            let job = match self.get_job(service_id, job_id).await {
+                Ok(job) => job,
+                Err(e) if e.is_transient() => continue,
+                Err(e) => return Err(e),
+            };
+            on_poll(&job);
+            match job.status {
+                JobStatus::Succeeded => return Ok(job),
+                JobStatus::Failed | JobStatus::Canceled => {
+                    return Err(RenderError::JobFailed(Box::new(job)))
+                }
+                JobStatus::Pending | JobStatus::Running => {}
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_job() {
+        let sample = r#"
+            {
+                "id": "job-cs67ufi3esus73b74a70",
+                "serviceId": "srv-cs67ufi3esus73b74a70",
+                "startCommand": "rake db:migrate",
+                "planId": "plan-srv-006",
+                "createdAt": "2024-10-14T02:17:35.868638Z"
+            }
+        "#;
+        let job: Job = serde_json::from_str(sample).unwrap();
+        assert_eq!(job.status, JobStatus::Pending);
+        assert!(job.started_at.is_none());
+        assert!(job.duration().is_none());
+
+        let finished = Job {
+            status: JobStatus::Succeeded,
+            started_at: Some("2024-10-14T02:18:00Z".into()),
+            finished_at: Some("2024-10-14T02:18:42Z".into()),
+            ..job
+        };
+        assert_eq!(finished.duration(), Some(Duration::from_secs(42)));
+
+        let status: JobStatus = serde_json::from_str(r#""succeeded""#).unwrap();
+        assert!(status.is_finished());
+    }
+}
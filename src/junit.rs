@@ -0,0 +1,146 @@
+use render_deploy::RenderError;
+use std::fmt::Write as _;
+use std::fs;
+use std::future::Future;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// A deploy, or a command run around one, as a test case
+#[derive(Debug, Clone, PartialEq)]
+struct TestCase {
+    /// the service's name, test report uis group cases by it
+    classname: String,
+    name: String,
+    time: Duration,
+    failure: Option<String>,
+}
+
+/// What `--junit-output` writes, filled in as deploys and their commands finish so Jenkins and
+/// GitLab can show them like test results
+#[derive(Debug, Default)]
+pub struct Report {
+    cases: Mutex<Vec<TestCase>>,
+}
+
+impl Report {
+    pub fn record(&self, service: &str, name: &str, time: Duration, failure: Option<String>) {
+        self.cases.lock().unwrap().push(TestCase {
+            classname: service.to_string(),
+            name: name.to_string(),
+            time,
+            failure,
+        });
+    }
+
+    /// Run `step`, recording how long it took and how it failed
+    pub async fn time<T>(
+        &self,
+        service: &str,
+        name: &str,
+        step: impl Future<Output = Result<T, RenderError>>,
+    ) -> Result<T, RenderError> {
+        let started = Instant::now();
+        let result = step.await;
+        let failure = result.as_ref().err().map(ToString::to_string);
+        self.record(service, name, started.elapsed(), failure);
+        result
+    }
+
+    /// Write the report to `path`. When the deploy failed before any case did, say why as a case
+    /// of its own so the report doesn't look like a pass.
+    pub fn write(&self, path: &Path, result: &Result<(), RenderError>) -> Result<(), RenderError> {
+        let mut cases = self.cases.lock().unwrap().clone();
+        if let Err(e) = result {
+            if cases.iter().all(|case| case.failure.is_none()) {
+                cases.push(TestCase {
+                    classname: "render-deploy".into(),
+                    name: "deploy".into(),
+                    time: Duration::ZERO,
+                    failure: Some(e.to_string()),
+                });
+            }
+        }
+        fs::write(path, to_xml(&cases))
+            .map_err(|e| RenderError::Config(format!("Unable to write {}: {}", path.display(), e)))
+    }
+}
+
+fn to_xml(cases: &[TestCase]) -> String {
+    let failures = cases.iter().filter(|case| case.failure.is_some()).count();
+    let time: Duration = cases.iter().map(|case| case.time).sum();
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    let _ = writeln!(
+        xml,
+        "<testsuites tests=\"{tests}\" failures=\"{failures}\" time=\"{time:.3}\">\n  \
+         <testsuite name=\"render-deploy\" tests=\"{tests}\" failures=\"{failures}\" \
+         time=\"{time:.3}\">",
+        tests = cases.len(),
+        failures = failures,
+        time = time.as_secs_f64()
+    );
+    for case in cases {
+        let _ = write!(
+            xml,
+            "    <testcase classname=\"{}\" name=\"{}\" time=\"{:.3}\"",
+            escape(&case.classname),
+            escape(&case.name),
+            case.time.as_secs_f64()
+        );
+        match &case.failure {
+            Some(failure) => {
+                let _ = writeln!(
+                    xml,
+                    ">\n      <failure message=\"{message}\">{message}</failure>\n    </testcase>",
+                    message = escape(failure)
+                );
+            }
+            None => xml.push_str("/>\n"),
+        }
+    }
+    xml.push_str("  </testsuite>\n</testsuites>\n");
+    xml
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn write_test_cases() {
+        let cases = vec![
+            TestCase {
+                classname: "api".into(),
+                name: "deploy dep-1".into(),
+                time: Duration::from_millis(61_500),
+                failure: None,
+            },
+            TestCase {
+                classname: "api".into(),
+                name: "post-deploy-cmd".into(),
+                time: Duration::from_secs(2),
+                failure: Some("curl -f \"$URL\" failed <exit 22>".into()),
+            },
+        ];
+        assert_eq!(
+            to_xml(&cases),
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<testsuites tests="2" failures="1" time="63.500">
+  <testsuite name="render-deploy" tests="2" failures="1" time="63.500">
+    <testcase classname="api" name="deploy dep-1" time="61.500"/>
+    <testcase classname="api" name="post-deploy-cmd" time="2.000">
+      <failure message="curl -f &quot;$URL&quot; failed &lt;exit 22&gt;">curl -f &quot;$URL&quot; failed &lt;exit 22&gt;</failure>
+    </testcase>
+  </testsuite>
+</testsuites>
+"#
+        );
+    }
+}
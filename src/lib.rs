@@ -0,0 +1,76 @@
+//! A small client for the parts of the [render.com api](https://api-docs.render.com/) needed to
+//! trigger deploys and wait for them to go live.
+//!
+//! ```no_run
+//! use render_deploy::{RenderClient, RenderError, TriggerDeployRequest};
+//!
+//! # async fn deploy() -> Result<(), RenderError> {
+//! let client = RenderClient::new("rnd_xxxxx")?;
+//! let service = client.find_service("my-service").await?;
+//! let deploy = client
+//!     .trigger_deploy(&service.id, &TriggerDeployRequest::default())
+//!     .await?;
+//! println!("{} {}", deploy.id, deploy.status);
+//! # Ok(())
+//! # }
+//! ```
+
+// so the test fixtures shared with the cli can name the library
+#[cfg(test)]
+extern crate self as render_deploy;
+
+pub mod api_key;
+pub mod autoscaling;
+pub mod client;
+pub mod cron;
+pub mod deploy_hooks;
+pub mod disks;
+pub mod domains;
+pub mod env_vars;
+pub mod error;
+pub mod events;
+#[cfg(test)]
+mod fixtures;
+pub mod jobs;
+pub mod logs;
+pub mod metrics;
+pub mod models;
+pub mod observer;
+pub mod owners;
+pub mod pagination;
+pub mod postgres;
+pub mod projects;
+pub mod retry;
+pub mod secret_files;
+pub mod services;
+pub mod static_sites;
+
+pub use api_key::ApiKey;
+pub use autoscaling::{Autoscaling, AutoscalingCriteria, AutoscalingTarget};
+pub use client::{RenderClient, WaitOptions};
+pub use cron::{CronJobRun, CronRunStatus};
+pub use deploy_hooks::DeployHook;
+pub use disks::Disk;
+pub use domains::{CustomDomain, DomainType, VerificationStatus};
+pub use env_vars::EnvVar;
+pub use error::RenderError;
+pub use events::{Event, EventType};
+pub use jobs::{Job, JobStatus, RunJobRequest};
+pub use logs::{LogEntry, LogTail, LogsQuery};
+pub use metrics::{MetricKind, MetricPoint, MetricSeries, MetricsQuery};
+pub use models::*;
+pub use observer::RequestEvent;
+pub use owners::{Owner, OwnerType, User};
+pub use pagination::Pages;
+pub use postgres::{
+    ConnectionInfo, Postgres, PostgresExport, PostgresStatus, RecoverRequest, RecoveryInfo,
+    RecoveryStatus,
+};
+pub use projects::{Environment, Project};
+pub use retry::RetryPolicy;
+pub use secret_files::SecretFile;
+pub use services::{
+    CreateServiceRequest, CreateServiceResponse, EnvSpecificDetails, ServiceDetailsRequest,
+    ServiceSettings, UpdateServiceRequest,
+};
+pub use static_sites::{AddRouteRequest, HeaderRule, Route, RouteType};
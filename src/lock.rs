@@ -0,0 +1,179 @@
+use crate::output::Output;
+use render_deploy::{RenderClient, RenderError, Service};
+use reqwest::StatusCode;
+use std::env;
+use std::time::{Duration, SystemTime};
+
+/// The env var on a service that says who's deploying it
+pub const LOCK_KEY: &str = "RENDER_DEPLOY_LOCK";
+/// A lock this old was left behind by a deploy that died before letting go
+const STALE_AFTER: Duration = Duration::from_secs(2 * 60 * 60);
+/// How often to look again when waiting for a lock
+const RETRY_INTERVAL: Duration = Duration::from_secs(10);
+
+/// `--lock`, an env var on the service holding when and by whom it was taken. Render has no
+/// compare and swap so two deploys taking it in the same instant can both win, reading it back
+/// narrows that to the odd race rather than every overlapping pipeline. Anything else in the
+/// env var counts as held so a lock we can't read is never written over.
+pub struct DeployLock {
+    service_id: String,
+    value: String,
+}
+
+impl DeployLock {
+    /// Take the lock on `service`, waiting up to `wait` for whoever holds it
+    pub async fn acquire(
+        client: &RenderClient,
+        output: &Output,
+        service: &Service,
+        wait: Option<Duration>,
+    ) -> Result<DeployLock, RenderError> {
+        let started = SystemTime::now();
+        loop {
+            match held_by(client, &service.id).await? {
+                Some(Held::Unknown(value)) => {
+                    return Err(RenderError::Config(format!(
+                        "{} is locked by {}={:?}, remove it from the service if that deploy is gone",
+                        service.name, LOCK_KEY, value
+                    )));
+                }
+                Some(Held::By(since, holder)) if !is_stale(since) => {
+                    let left = wait
+                        .map(|wait| wait.saturating_sub(started.elapsed().unwrap_or_default()))
+                        .unwrap_or_default();
+                    if left.is_zero() {
+                        return Err(RenderError::Config(format!(
+                            "{} is being deployed by {} since {}, remove {} from the service if \
+                             that deploy is gone",
+                            service.name,
+                            holder,
+                            output.time(&humantime::format_rfc3339_seconds(since).to_string()),
+                            LOCK_KEY
+                        )));
+                    }
+                    output.info(format!(
+                        "Waiting for {} to finish deploying {}",
+                        holder, service.name
+                    ));
+                    tokio::time::sleep(left.min(RETRY_INTERVAL)).await;
+                    continue;
+                }
+                Some(Held::By(since, holder)) => output.info(format!(
+                    "Warning: Taking over the lock {} left on {} at {}",
+                    holder,
+                    service.name,
+                    output.time(&humantime::format_rfc3339_seconds(since).to_string())
+                )),
+                None => {}
+            }
+            let value = format!(
+                "{} {}",
+                humantime::format_rfc3339_seconds(SystemTime::now()),
+                holder()
+            );
+            client.set_env_var(&service.id, LOCK_KEY, &value).await?;
+            // whoever wrote last holds it
+            if client.get_env_var(&service.id, LOCK_KEY).await?.value == value {
+                output.info(format!("Locked {}", service.name));
+                return Ok(DeployLock {
+                    service_id: service.id.clone(),
+                    value,
+                });
+            }
+        }
+    }
+
+    /// Hold on to the lock after we stopped waiting, the deploy is still going
+    pub fn keep(self, output: &Output) {
+        output.info(format!(
+            "Warning: Leaving {} on {} while the deploy finishes, remove it once it has",
+            LOCK_KEY, self.service_id
+        ));
+    }
+
+    /// Let go of the lock, unless someone has taken it over since
+    pub async fn release(self, client: &RenderClient, output: &Output) {
+        let released = async {
+            if client.get_env_var(&self.service_id, LOCK_KEY).await?.value == self.value {
+                client.delete_env_var(&self.service_id, LOCK_KEY).await?;
+            }
+            Ok::<_, RenderError>(())
+        }
+        .await;
+        if let Err(e) = released {
+            output.info(format!(
+                "Warning: Unable to remove {} from {}: {}",
+                LOCK_KEY, self.service_id, e
+            ));
+        }
+    }
+}
+
+/// Who holds the lock
+#[derive(Debug, PartialEq)]
+enum Held {
+    /// since when and by whom
+    By(SystemTime, String),
+    /// a value we can't read, we can't tell it's stale either
+    Unknown(String),
+}
+
+/// Who holds the lock, if anyone does
+async fn held_by(client: &RenderClient, service_id: &str) -> Result<Option<Held>, RenderError> {
+    match client.get_env_var(service_id, LOCK_KEY).await {
+        Ok(lock) => Ok(Some(parse(&lock.value))),
+        Err(RenderError::Api {
+            status: StatusCode::NOT_FOUND,
+            ..
+        }) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+fn parse(value: &str) -> Held {
+    value
+        .split_once(' ')
+        .and_then(|(since, holder)| {
+            let since = humantime::parse_rfc3339(since).ok()?;
+            Some(Held::By(since, holder.to_string()))
+        })
+        .unwrap_or_else(|| Held::Unknown(value.to_string()))
+}
+
+fn is_stale(since: SystemTime) -> bool {
+    since.elapsed().is_ok_and(|age| age > STALE_AFTER)
+}
+
+/// Who's deploying, a link to the workflow run in github actions, otherwise the user and host
+fn holder() -> String {
+    if let (Ok(server), Ok(repository), Ok(run_id)) = (
+        env::var("GITHUB_SERVER_URL"),
+        env::var("GITHUB_REPOSITORY"),
+        env::var("GITHUB_RUN_ID"),
+    ) {
+        return format!("{}/{}/actions/runs/{}", server, repository, run_id);
+    }
+    let user = env::var("USER").unwrap_or_else(|_| "someone".into());
+    let host = env::var("HOSTNAME")
+        .ok()
+        .or_else(|| std::fs::read_to_string("/etc/hostname").ok())
+        .map(|host| host.trim().to_string())
+        .unwrap_or_else(|| "somewhere".into());
+    format!("{}@{} (pid {})", user, host, std::process::id())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_lock_values() {
+        let Held::By(since, holder) = parse("2024-10-14T02:17:35Z francis@laptop (pid 42)") else {
+            panic!("lock wasn't parsed");
+        };
+        assert_eq!(holder, "francis@laptop (pid 42)");
+        assert!(is_stale(since));
+        assert!(!is_stale(SystemTime::now()));
+        assert_eq!(parse("garbage"), Held::Unknown("garbage".into()));
+    }
+}
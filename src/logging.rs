@@ -0,0 +1,85 @@
+use clap::ValueEnum;
+use render_deploy::{Deploy, DeployStatus, RequestEvent, Service};
+use std::io;
+use tracing::Level;
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogFormat {
+    /// a line of key=value pairs per event
+    Text,
+    /// a json object per event
+    Json,
+}
+
+/// Log api requests, retries and deploy status changes to stderr as they happen. Without this
+/// the events go nowhere.
+pub fn init(format: LogFormat) {
+    let subscriber = tracing_subscriber::fmt()
+        .with_writer(io::stderr)
+        .with_max_level(Level::INFO)
+        .with_target(false)
+        .with_ansi(false);
+    match format {
+        LogFormat::Text => subscriber.init(),
+        // fields at the top level rather than under "fields", it's what aggregators expect
+        LogFormat::Json => subscriber.json().flatten_event(true).init(),
+    }
+}
+
+pub fn request(event: &RequestEvent) {
+    match event {
+        RequestEvent::Sent {
+            method,
+            url,
+            status,
+            elapsed,
+            ..
+        } => tracing::info!(
+            method = %method,
+            url = %url,
+            status = status.map(|status| status.as_u16()),
+            elapsed_ms = elapsed.as_millis() as u64,
+            "api request"
+        ),
+        RequestEvent::Retrying {
+            method,
+            url,
+            attempt,
+            delay,
+            error,
+        } => tracing::warn!(
+            method = %method,
+            url = %url,
+            attempt,
+            delay_ms = delay.as_millis() as u64,
+            error = %error,
+            "retrying api request"
+        ),
+        RequestEvent::Unparseable { method, url, body } => tracing::warn!(
+            method = %method,
+            url = %url,
+            body_bytes = body.len(),
+            "unparseable api response"
+        ),
+    }
+}
+
+/// A deploy we started or saw change, `previous` is missing for a new one
+pub fn deploy_status(service: &Service, deploy: &Deploy, previous: Option<&DeployStatus>) {
+    tracing::info!(
+        service = %service.name,
+        service_id = %service.id,
+        deploy_id = %deploy.id,
+        status = status_name(&deploy.status),
+        previous_status = previous.map(status_name),
+        "deploy status"
+    );
+}
+
+/// The api's name for a status, `build_in_progress` rather than `Build In Progress`
+fn status_name(status: &DeployStatus) -> String {
+    serde_json::to_value(status)
+        .ok()
+        .and_then(|name| name.as_str().map(String::from))
+        .unwrap_or_default()
+}
@@ -0,0 +1,214 @@
+use crate::client::RenderClient;
+use crate::error::RenderError;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+#[derive(PartialEq, Deserialize, Serialize, Debug, Clone)]
+pub struct LogLabel {
+    pub name: String,
+    pub value: String,
+}
+
+#[derive(PartialEq, Deserialize, Serialize, Debug, Clone)]
+pub struct LogEntry {
+    pub id: String,
+    pub message: String,
+    pub timestamp: String,
+    #[serde(default)]
+    pub labels: Vec<LogLabel>,
+}
+
+impl LogEntry {
+    /// Value of a label like `type` (`build`, `app`, `request`) or `instance`
+    pub fn label(&self, name: &str) -> Option<&str> {
+        self.labels
+            .iter()
+            .find(|label| label.name == name)
+            .map(|label| label.value.as_str())
+    }
+}
+
+#[derive(PartialEq, Deserialize, Debug, Clone)]
+pub struct LogsResponse {
+    #[serde(rename = "hasMore")]
+    pub has_more: bool,
+    #[serde(rename = "nextStartTime")]
+    pub next_start_time: Option<String>,
+    #[serde(rename = "nextEndTime")]
+    pub next_end_time: Option<String>,
+    pub logs: Vec<LogEntry>,
+}
+
+/// Query for `GET /v1/logs`, render requires the owner of the resources
+#[derive(Debug, Clone, Default)]
+pub struct LogsQuery {
+    pub owner_id: String,
+    /// service ids to fetch logs for
+    pub resources: Vec<String>,
+    /// RFC3339 timestamps
+    pub start_time: Option<String>,
+    pub end_time: Option<String>,
+    /// oldest first instead of newest first
+    pub forward: bool,
+    pub limit: Option<u32>,
+}
+
+impl LogsQuery {
+    fn to_query(&self) -> Vec<(String, String)> {
+        let mut query = vec![("ownerId".to_string(), self.owner_id.clone())];
+        for resource in &self.resources {
+            query.push(("resource".into(), resource.clone()));
+        }
+        if let Some(start_time) = &self.start_time {
+            query.push(("startTime".into(), start_time.clone()));
+        }
+        if let Some(end_time) = &self.end_time {
+            query.push(("endTime".into(), end_time.clone()));
+        }
+        let direction = if self.forward { "forward" } else { "backward" };
+        query.push(("direction".into(), direction.into()));
+        if let Some(limit) = self.limit {
+            query.push(("limit".into(), limit.to_string()));
+        }
+        query
+    }
+}
+
+impl RenderClient {
+    pub async fn list_logs(&self, query: &LogsQuery) -> Result<LogsResponse, RenderError> {
+        self.get("/logs", &query.to_query()).await
+    }
+
+    /// Follow the logs of some services from `start_time` onwards
+    pub fn tail_logs(&self, query: LogsQuery) -> LogTail<'_> {
+        LogTail {
+            client: self,
+            query: LogsQuery {
+                forward: true,
+                end_time: None,
+                ..query
+            },
+            seen: HashSet::new(),
+        }
+    }
+}
+
+/// Polls the logs endpoint for lines newer than the last one we've seen. Render's timestamps
+/// aren't unique so we restart each poll at the newest timestamp and drop the ids we've
+/// already returned. Only ids from that timestamp can come back, so only they're kept.
+pub struct LogTail<'a> {
+    client: &'a RenderClient,
+    query: LogsQuery,
+    seen: HashSet<String>,
+}
+
+impl LogTail<'_> {
+    /// Don't return these entries again, and start after them. Useful when you've already
+    /// fetched some recent logs and want to follow from there.
+    pub fn skip<'e>(&mut self, entries: impl IntoIterator<Item = &'e LogEntry>) {
+        for entry in entries {
+            if self.query.start_time.as_deref() < Some(entry.timestamp.as_str()) {
+                self.restart_at(&entry.timestamp);
+            }
+            if self.query.start_time.as_deref() == Some(entry.timestamp.as_str()) {
+                self.seen.insert(entry.id.clone());
+            }
+        }
+    }
+
+    /// Every log line since the last poll, oldest first. When a page fails the next poll starts
+    /// over from where this one did, so lines from the pages before it aren't lost.
+    pub async fn poll(&mut self) -> Result<Vec<LogEntry>, RenderError> {
+        let (start_time, seen) = (self.query.start_time.clone(), self.seen.clone());
+        let polled = self.poll_pages().await;
+        if polled.is_err() {
+            self.query.start_time = start_time;
+            self.seen = seen;
+        }
+        polled
+    }
+
+    async fn poll_pages(&mut self) -> Result<Vec<LogEntry>, RenderError> {
+        let mut entries = vec![];
+        loop {
+            let page = self.client.list_logs(&self.query).await?;
+            for entry in page.logs {
+                if self.seen.contains(&entry.id) {
+                    continue;
+                }
+                self.restart_at(&entry.timestamp);
+                self.seen.insert(entry.id.clone());
+                entries.push(entry);
+            }
+            match page.next_start_time {
+                Some(next_start_time) if page.has_more => self.restart_at(&next_start_time),
+                _ => return Ok(entries),
+            }
+        }
+    }
+
+    /// Start the next poll at `timestamp`, forgetting the ids from before it
+    fn restart_at(&mut self, timestamp: &str) {
+        if self.query.start_time.as_deref() != Some(timestamp) {
+            self.query.start_time = Some(timestamp.to_string());
+            self.seen.clear();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_logs() {
+        let sample = r#"
+            {
+                "hasMore": false,
+                "nextStartTime": null,
+                "nextEndTime": null,
+                "logs": [
+                    {
+                        "id": "8e4ee6f0-5a7b-4d3e-9c0e-3a6b0e1d4b1c",
+                        "message": "==> Running build command 'cargo build --release'...",
+                        "timestamp": "2024-10-14T02:17:40.123Z",
+                        "labels": [
+                            { "name": "resource", "value": "srv-cs67ufi3esus73b74a70" },
+                            { "name": "type", "value": "build" }
+                        ]
+                    }
+                ]
+            }
+        "#;
+        let response: LogsResponse = serde_json::from_str(sample).unwrap();
+        assert!(!response.has_more);
+        assert_eq!(response.logs.len(), 1);
+        assert_eq!(response.logs[0].label("type"), Some("build"));
+        assert_eq!(response.logs[0].label("instance"), None);
+    }
+
+    #[test]
+    fn only_keep_ids_at_the_start_time() {
+        let entry = |id: &str, timestamp: &str| LogEntry {
+            id: id.into(),
+            message: "hi".into(),
+            timestamp: timestamp.into(),
+            labels: vec![],
+        };
+        let client = RenderClient::new("rnd_test").unwrap();
+        let mut tail = client.tail_logs(LogsQuery::default());
+        tail.skip(&[
+            entry("l1", "2024-10-14T02:17:40Z"),
+            entry("l3", "2024-10-14T02:17:41Z"),
+            entry("l2", "2024-10-14T02:17:40Z"),
+            entry("l4", "2024-10-14T02:17:41Z"),
+        ]);
+        assert_eq!(
+            tail.query.start_time.as_deref(),
+            Some("2024-10-14T02:17:41Z")
+        );
+        assert_eq!(tail.seen, HashSet::from(["l3".into(), "l4".into()]));
+        tail.restart_at("2024-10-14T02:17:42Z");
+        assert!(tail.seen.is_empty());
+    }
+}
@@ -1,406 +1,297 @@
-use clap::Parser;
-use reqwest::{blocking::Client, header};
-use serde;
-use serde::Deserialize;
-use serde_json;
-use std::fmt;
-use std::process::exit;
-use std::thread::sleep;
-use std::time::{Duration, Instant};
-
-/// Simple program to greet a person
-#[derive(Parser, Debug, Clone)]
-#[command(version, about = " I needed a cli for render.com and I wanted to play with rust so it's a rust cli for triggering deploys on render.com", long_about = None)]
-struct Config {
-    /// name of your service
-    name: String,
-    /// optional commit to deploy (otherwise head of the default branch)
-    commit: Option<String>,
-    /// Wait for the deploy to finish or fail
-    #[arg(short, long)]
-    wait: bool,
-
-    #[arg(short, long, env("RENDER_API_KEY"))]
-    api_key: String,
-
-    /// wait for deploy timeout in seconds, doesn't cancel the deploy just exits
-    #[arg(short, long, default_value="600", value_parser = parse_duration)]
-    timeout: Duration,
-}
-
-fn parse_duration(arg: &str) -> Result<Duration, std::num::ParseIntError> {
-    let seconds = arg.parse::<u64>()?;
-    Ok(Duration::from_secs(seconds))
-}
-
-fn deserialize_yes_no<'de, D>(deserializer: D) -> Result<bool, D::Error>
-where
-    D: serde::de::Deserializer<'de>,
-{
-    let s: &str = Deserialize::deserialize(deserializer)?;
-
-    match s {
-        "yes" => Ok(true),
-        "no" => Ok(false),
-        _ => Err(serde::de::Error::unknown_variant(s, &["yes", "no"])),
+use cache::ServiceCache;
+use clap::parser::ValueSource;
+use clap::{CommandFactory, FromArgMatches};
+use cli::{AuthArgs, AuthCommands, Cli, Commands};
+use commands::Context;
+use config::Profile;
+use interrupt::Interrupt;
+use junit::Report;
+use otel::Trace;
+use output::{ColorChoice, Output, Verbosity};
+use render_deploy::{ApiKey, DeployHook, RenderClient, RenderError, RequestEvent};
+use std::fs;
+use std::process::ExitCode;
+use std::time::Duration;
+
+mod blueprint;
+mod cache;
+mod cli;
+mod commands;
+mod config;
+mod credentials;
+mod debug_http;
+mod deploy_metrics;
+mod dotenv;
+#[cfg(test)]
+mod fixtures;
+mod git;
+mod git_host;
+mod github;
+mod interrupt;
+mod junit;
+mod lock;
+mod logging;
+mod notify;
+mod otel;
+mod output;
+mod progress;
+mod release;
+mod timeline;
+mod webhooks;
+
+async fn run(cli: &Cli, api_key_source: Option<ValueSource>) -> Result<(), RenderError> {
+    if let Some(format) = cli.log_format {
+        logging::init(format);
     }
-}
-
-#[derive(PartialEq, Deserialize, Debug, Clone)]
-struct Service {
-    id: String,
-    name: String,
-    branch: String,
-    #[serde(rename = "dashboardUrl")]
-    dashboard_url: String,
-    #[serde(rename = "autoDeploy", deserialize_with = "deserialize_yes_no")]
-    auto_deploy: bool,
-    repo: String,
-    #[serde(rename = "updatedAt")]
-    updated_at: String,
-    #[serde(rename = "createdAt")]
-    created_at: String,
-}
-
-#[derive(PartialEq, Deserialize, Debug, Clone)]
-struct ListServiceResponse {
-    cursor: String,
-    service: Service,
-}
-
-fn http_client(config: &Config) -> Client {
-    let mut headers = header::HeaderMap::new();
-    let bearer = format!("Bearer {}", config.api_key);
-    headers.insert(
-        header::AUTHORIZATION,
-        header::HeaderValue::from_str(&bearer).expect("valid api key"),
-    );
-    headers.insert(
-        header::ACCEPT,
-        header::HeaderValue::from_static("application/json"),
-    );
-
-    Client::builder()
-        .user_agent("render-deploy: https://github.com/reconbot/render-deploy")
-        .default_headers(headers)
-        .timeout(Duration::from_secs(30))
-        .gzip(true)
-        .build()
-        .expect("http client could be built")
-}
-
-fn list_service(client: &Client, config: &Config) -> Option<Service> {
-    let response = client
-        .get("https://api.render.com/v1/services")
-        .query(&[("name", config.name.clone()), ("limit", "1".to_string())])
-        .send()
-        .expect("Could not build request");
-    if !response.status().is_success() {
-        println!(
-            "Request Error: {:?} {:?}",
-            response.status(),
-            response.text().unwrap_or("Unknown Error".into())
-        );
-        exit(1);
+    if let Commands::Man(args) = &cli.command {
+        return commands::man::run(args);
     }
-    let body = response.text().expect("unable to read response body");
-
-    let services: Vec<ListServiceResponse> = match serde_json::from_str(&body) {
-        Ok(services) => services,
-        Err(e) => {
-            println!("Unable to parse json {:?}", e);
-            println!("{}", body);
-            exit(1);
-        }
+    let profile = config::load_profile(cli.profile.as_deref())?;
+    let profile_name = cli.profile.as_deref().unwrap_or(config::DEFAULT_PROFILE);
+    let output = output(cli);
+    if let Commands::Auth(AuthArgs {
+        command: AuthCommands::Logout,
+    }) = &cli.command
+    {
+        return commands::auth::logout(&output, profile_name);
+    }
+    let api_key = match (api_key(cli, api_key_source, &profile)?, &cli.command) {
+        (Some(api_key), _) => Some(api_key),
+        // logging in is for saving a new key, not reusing the saved one
+        (
+            None,
+            Commands::Auth(AuthArgs {
+                command: AuthCommands::Login,
+            }),
+        ) => Some(commands::auth::prompt()?),
+        (None, _) => credentials::load(profile_name),
     };
-    services.into_iter().next().map(|resp| resp.service)
-}
-
-#[derive(PartialEq, Deserialize, Debug, Clone)]
-struct CommitInfo {
-    id: String,
-    message: String,
-    #[serde(rename = "createdAt")]
-    created_at: String,
-}
-
-#[derive(PartialEq, Deserialize, Debug, Clone)]
-#[serde(rename_all = "snake_case")]
-enum DeployStatus {
-    Created,
-    BuildInProgress,
-    UpdateInProgress,
-    Live,
-    Deactivated,
-    BuildFailed,
-    UpdateFailed,
-    Canceled,
-    PreDeployInProgress,
-    PreDeployFailed,
-}
-
-impl fmt::Display for DeployStatus {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let status_str = match self {
-            DeployStatus::Created => "Created",
-            DeployStatus::BuildInProgress => "Build In Progress",
-            DeployStatus::UpdateInProgress => "Update In Progress",
-            DeployStatus::Live => "Live",
-            DeployStatus::Deactivated => "Deactivated",
-            DeployStatus::BuildFailed => "Build Failed",
-            DeployStatus::UpdateFailed => "Update Failed",
-            DeployStatus::Canceled => "Canceled",
-            DeployStatus::PreDeployInProgress => "Pre-Deploy In Progress",
-            DeployStatus::PreDeployFailed => "Pre-Deploy Failed",
-        };
-        write!(f, "{}", status_str)
+    let hook = match &cli.command {
+        Commands::Deploy(args) => args.hook_url.clone().or_else(|| profile.deploy_hook_url()),
+        _ => None,
     }
-}
-
-#[derive(PartialEq, Deserialize, Debug, Clone)]
-struct Deploy {
-    id: String,
-    commit: CommitInfo,
-    status: DeployStatus,
-    #[serde(rename = "createdAt")]
-    created_at: String,
-    #[serde(rename = "updatedAt")]
-    updated_at: String,
-    #[serde(rename = "finishedAt")]
-    finished_at: Option<String>,
-}
-
-fn trigger_deploy(client: &Client, service: &Service, config: &Config) -> Result<Deploy, String> {
-    // todo json post commitId if present
-    let response = client
-        .post(format!(
-            "https://api.render.com/v1/services/{}/deploys",
-            service.id
-        ))
-        .send()
-        .expect("Could not build request trigger_deploy");
-    if !response.status().is_success() {
-        println!(
-            "Request Error: {:?} {:?}",
-            response.status(),
-            response.text().unwrap_or("Unknown Error".into())
-        );
-        exit(1);
+    .map(|url| DeployHook::from_builder(&url, http_client(cli)?))
+    .transpose()?;
+    // a deploy hook is enough to start a deploy, everything else needs the api
+    if let (Commands::Deploy(args), Some(hook), None) = (&cli.command, &hook, &api_key) {
+        return commands::deploy::run_hook(&output, &profile, hook, args).await;
     }
-    let body = response.text().expect("unable to read response body");
-
-    let deploy: Deploy = match serde_json::from_str(&body) {
-        Ok(deploy) => deploy,
-        Err(e) => {
-            return Result::Err(format!("Unable to parse json {:?} {}", e, body));
-        }
-    };
-    Result::Ok(deploy)
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn parse_deploy() {
-        let sample = r#"
-            {
-                "id": "dep-cs67ufi3esus73b74a70",
-                "commit": {
-                    "id": "b2be9cf9e3188d00f58ef18a5904528993faeaa2",
-                    "message": "render uses sigterm and disable aws healthcheck",
-                    "createdAt": "2024-10-11T20:02:45Z"
-                },
-                "status": "build_in_progress",
-                "trigger": "api",
-                "createdAt": "2024-10-14T02:17:35.868638Z",
-                "updatedAt": "2024-10-14T02:17:35.868638Z",
-                "finishedAt": null
+    let api_key = api_key.ok_or_else(|| {
+        RenderError::Config(
+            "An api key is required, set RENDER_API_KEY, pass --api-key or run auth login".into(),
+        )
+    })?;
+    let mut client = RenderClient::from_builder(api_key.clone(), http_client(cli)?)?;
+    if let Some(base_url) = &cli.api_base_url {
+        reqwest::Url::parse(base_url).map_err(|e| {
+            RenderError::Config(format!("Invalid --api-base-url {}: {}", base_url, e))
+        })?;
+        client = client.with_base_url(base_url);
+    }
+    if output.is_verbose() || cli.log_format.is_some() || cli.debug_http {
+        let debug_http = cli.debug_http;
+        let output = output.clone();
+        client = client.with_observer(move |event| {
+            logging::request(event);
+            output.verbose(describe_request(event));
+            if debug_http {
+                debug_http::print(event);
             }
-        "#;
-        let deploy: Deploy = serde_json::from_str(sample).unwrap();
-        assert_eq!(deploy.id, "dep-cs67ufi3esus73b74a70");
-        assert_eq!(deploy.commit.id, "b2be9cf9e3188d00f58ef18a5904528993faeaa2");
-        assert_eq!(deploy.status, DeployStatus::BuildInProgress);
-        assert_eq!(deploy.finished_at, None);
+        });
     }
-}
-
-fn deploy_url(service: &Service, deploy: &Deploy) -> String {
-    format!(
-        "https://dashboard.render.com/web/{service_id}/deploys/{deploy_id}",
-        deploy_id = deploy.id,
-        service_id = service.id
-    )
-}
+    if let Some(owner) = cli.owner.as_deref().or(profile.owner.as_deref()) {
+        let owner = commands::owners::find_owner(&client, owner).await?;
+        client = client.with_owner(&owner.id);
+    }
+    let cache = ServiceCache::new(
+        cli.refresh_cache,
+        &api_key,
+        cli.api_base_url.as_deref().unwrap_or_default(),
+        client.owner_id(),
+    );
+    let ctx = Context {
+        client,
+        http: http_client(cli)?
+            .user_agent("render-deploy: https://github.com/reconbot/render-deploy")
+            .timeout(OUTSIDE_TIMEOUT)
+            .build()?,
+        cache,
+        interrupt: Interrupt::default(),
+        junit: Report::default(),
+        trace: Trace::from_env(),
+        output,
+        fuzzy: cli.fuzzy,
+        service_id: cli.service_id.clone(),
+        profile,
+    };
 
-#[derive(PartialEq, Deserialize, Debug, Clone)]
-struct ListDeploysResponse {
-    cursor: String,
-    deploy: Deploy,
+    match &cli.command {
+        Commands::Deploy(args) => commands::deploy::run(&ctx, args, hook.as_ref()).await,
+        Commands::Cancel(args) => commands::cancel::run(&ctx, args).await,
+        Commands::Rollback(args) => commands::rollback::run(&ctx, args).await,
+        Commands::Restart(args) => commands::restart::run(&ctx, args).await,
+        Commands::Suspend(args) => commands::suspend::run(&ctx, args).await,
+        Commands::Resume(args) => commands::resume::run(&ctx, args).await,
+        Commands::Scale(args) => commands::scale::run(&ctx, args).await,
+        Commands::Autoscale(args) => commands::autoscale::run(&ctx, args).await,
+        Commands::Job(args) => commands::job::run(&ctx, args).await,
+        Commands::Cron(args) => commands::cron::run(&ctx, args).await,
+        Commands::Domains(args) => commands::domains::run(&ctx, args).await,
+        Commands::Routes(args) => commands::routes::run(&ctx, args).await,
+        Commands::Headers(args) => commands::headers::run(&ctx, args).await,
+        Commands::Postgres(args) => commands::postgres::run(&ctx, args).await,
+        Commands::Logs(args) => commands::logs::run(&ctx, args).await,
+        Commands::Status(args) => commands::status::run(&ctx, args).await,
+        Commands::Watch(args) => commands::watch::run(&ctx, args).await,
+        Commands::List => commands::list::run(&ctx).await,
+        Commands::Owners => commands::owners::run(&ctx).await,
+        Commands::Auth(args) => commands::auth::run(&ctx, args, profile_name, &api_key).await,
+        Commands::Projects(args) => commands::projects::run(&ctx, args).await,
+        Commands::Environments(args) => commands::environments::run(&ctx, args).await,
+        Commands::Deploys(args) => commands::deploys::run(&ctx, args).await,
+        Commands::Stats(args) => commands::stats::run(&ctx, args).await,
+        Commands::Events(args) => commands::events::run(&ctx, args).await,
+        Commands::Metrics(args) => commands::metrics::run(&ctx, args).await,
+        Commands::Dashboard(args) => commands::dashboard::run(&ctx, args).await,
+        Commands::Release(args) => commands::release::run(&ctx, args).await,
+        Commands::Env(args) => commands::env::run(&ctx, args).await,
+        Commands::SecretFiles(args) => commands::secret_files::run(&ctx, args).await,
+        Commands::Service(args) => commands::service::run(&ctx, args).await,
+        Commands::Preview(args) => commands::preview::run(&ctx, args).await,
+        Commands::Apply(args) => commands::apply::run(&ctx, args).await,
+        Commands::Blueprint(args) => commands::blueprint::run(&ctx, args).await,
+        Commands::Listen(args) => commands::listen::run(&ctx, args).await,
+        Commands::Man(_) => unreachable!("handled before the client is needed"),
+    }
 }
 
-fn latest_deploy(client: &Client, service: &Service) -> Option<Deploy> {
-    let response = client
-        .get(format!(
-            "https://api.render.com/v1/services/{}/deploys",
-            service.id
-        ))
-        .query(&[("limit", "1".to_string())])
-        .send()
-        .expect("Could not build request latest_deploy");
-    if !response.status().is_success() {
-        println!(
-            "Request Error: {:?} {:?}",
-            response.status(),
-            response.text().unwrap_or("Unknown Error".into())
-        );
-        exit(1);
+/// The api key from `--api-key`, `--api-key-file`, the profile's env var or RENDER_API_KEY, in
+/// that order
+fn api_key(
+    cli: &Cli,
+    source: Option<ValueSource>,
+    profile: &Profile,
+) -> Result<Option<ApiKey>, RenderError> {
+    let api_key = match (source, &cli.api_key_file, profile.api_key()) {
+        (Some(ValueSource::CommandLine), _, _) => cli.api_key.clone(),
+        (_, Some(path), _) => Some(credentials::read_file(path)?),
+        (_, None, Some(profile_key)) => Some(ApiKey::new(profile_key)),
+        (_, None, None) => cli.api_key.clone(),
+    };
+    match &api_key {
+        Some(key) if key.expose() == "-" => credentials::read_stdin().map(Some),
+        _ => Ok(api_key),
     }
-    let body = response.text().expect("unable to read response body");
+}
 
-    let deploys: Vec<ListDeploysResponse> = match serde_json::from_str(&body) {
-        Ok(services) => services,
-        Err(e) => {
-            println!("Unable to parse json {:?}", e);
-            println!("{}", body);
-            exit(1);
-        }
+fn output(cli: &Cli) -> Output {
+    let color = if cli.no_color {
+        ColorChoice::Never
+    } else {
+        cli.color
     };
-    deploys.into_iter().next().map(|resp| resp.deploy)
+    let verbosity = match (cli.quiet, cli.verbose) {
+        (true, _) => Verbosity::Quiet,
+        (_, true) => Verbosity::Verbose,
+        _ => Verbosity::Normal,
+    };
+    Output::new(cli.output, color, verbosity)
+        .with_template(cli.format_template.clone())
+        .with_columns(cli.columns.clone())
+        .with_utc(cli.utc)
 }
 
-fn get_deploy(client: &Client, service: &Service, deploy_id: &String) -> Option<Deploy> {
-    let response = client
-        .get(format!(
-            "https://api.render.com/v1/services/{service_id}/deploys/{deploy_id}",
-            service_id = service.id,
-            deploy_id = deploy_id
-        ))
-        .query(&[("limit", "1".to_string())])
-        .send()
-        .expect("Could not build request latest_deploy");
-    if !response.status().is_success() {
-        println!(
-            "Request Error: {:?} {:?}",
-            response.status(),
-            response.text().unwrap_or("Unknown Error".into())
-        );
-        exit(1);
-    }
-    let body = response.text().expect("unable to read response body");
-
-    let deploy: Deploy = match serde_json::from_str(&body) {
-        Ok(services) => services,
-        Err(e) => {
-            println!("Unable to parse json {:?}", e);
-            println!("{}", body);
-            exit(1);
+/// A line for `--verbose` about an api request
+fn describe_request(event: &RequestEvent) -> String {
+    match event {
+        RequestEvent::Sent {
+            method,
+            url,
+            status: Some(status),
+            elapsed,
+            ..
+        } => format!("{} {} {} in {}ms", method, url, status, elapsed.as_millis()),
+        RequestEvent::Sent {
+            method,
+            url,
+            status: None,
+            elapsed,
+            ..
+        } => format!("{} {} failed after {}ms", method, url, elapsed.as_millis()),
+        RequestEvent::Retrying {
+            method,
+            url,
+            attempt,
+            delay,
+            error,
+        } => format!(
+            "Retrying {} {} in {}ms (retry {}): {}",
+            method,
+            url,
+            delay.as_millis(),
+            attempt,
+            error
+        ),
+        RequestEvent::Unparseable { method, url, .. } => {
+            format!("{} {} didn't send the json we expected", method, url)
         }
-    };
-    Some(deploy)
+    }
 }
 
-fn main() {
-    let config = Config::parse();
-    let client = http_client(&config);
-    // get the service
-    let service = match list_service(&client, &config) {
-        None => {
-            println!("Cannot find a service named {}", config.name);
-            exit(1);
+/// How long requests to anything but render get, a webhook that never answers shouldn't hold up
+/// a deploy that's already finished
+const OUTSIDE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The http client settings from the global options, proxies come from the usual env vars
+fn http_client(cli: &Cli) -> Result<reqwest::ClientBuilder, RenderError> {
+    let mut builder = reqwest::Client::builder();
+    if let Some(path) = &cli.cacert {
+        let pem = fs::read(path).map_err(|e| {
+            RenderError::Config(format!("Unable to read {}: {}", path.display(), e))
+        })?;
+        let certs = reqwest::Certificate::from_pem_bundle(&pem).map_err(|e| {
+            RenderError::Config(format!("Invalid certificates in {}: {}", path.display(), e))
+        })?;
+        if certs.is_empty() {
+            return Err(RenderError::Config(format!(
+                "No certificates found in {}",
+                path.display()
+            )));
+        }
+        for cert in certs {
+            builder = builder.add_root_certificate(cert);
         }
-        Some(service) => service,
-    };
-    println!(
-        "Found {name} {dashboard}",
-        name = service.name,
-        dashboard = service.dashboard_url
-    );
-    if service.auto_deploy {
-        println!("Warning: AutoDeploy is true");
     }
+    Ok(builder)
+}
 
-    if config.commit.is_some() {
-        println!(
-            "Deploying {repo} #{commit}",
-            repo = service.repo,
-            commit = config.commit.clone().unwrap()
-        );
+fn clap_exit(e: clap::Error) -> ExitCode {
+    let _ = e.print();
+    if e.use_stderr() {
+        ExitCode::from(1)
     } else {
-        println!(
-            "Deploying {repo} #{branch}",
-            repo = service.repo,
-            branch = service.branch
-        );
-    }
-    print!("\n");
-
-    let previous_deploy = latest_deploy(&client, &service);
-    if previous_deploy.is_some() {
-        let deploy = previous_deploy.unwrap();
-        println!(
-            "Previous Deploy {commit} - {message}",
-            commit = deploy.commit.id,
-            message = deploy.commit.message
-        );
-        println!(
-            "Status: {status} on {finished_at}",
-            status = deploy.status,
-            finished_at = deploy.finished_at.unwrap_or("".into())
-        );
-        print!("\n");
+        ExitCode::SUCCESS
     }
+}
 
-    // trigger deploy
-    let deploy = trigger_deploy(&client, &service, &config).unwrap();
-    println!(
-        "Created Deploy #{commit} - {message}",
-        commit = deploy.commit.id,
-        message = deploy.commit.message
-    );
-    println!("{}", deploy_url(&service, &deploy));
-    println!("Status: {status}", status = deploy.status);
-
-    // if error error
-
-    if config.wait {
-        let start = Instant::now();
-        loop {
-            if start.elapsed() > config.timeout {
-                println!("Deploy timed out");
-                exit(1);
-            }
-            sleep(Duration::from_secs(5));
-            let deploy = get_deploy(&client, &service, &deploy.id).unwrap();
-            println!("Status: {status}", status = deploy.status);
-            match deploy.status {
-                DeployStatus::Live => {
-                    println!(
-                        "Deploy is live on {} in {} seconds",
-                        deploy.finished_at.unwrap_or("unknown".into()),
-                        start.elapsed().as_secs()
-                    );
-                    break;
-                }
-                DeployStatus::BuildInProgress
-                | DeployStatus::UpdateInProgress
-                | DeployStatus::PreDeployInProgress
-                | DeployStatus::Created => (),
-                DeployStatus::BuildFailed
-                | DeployStatus::UpdateFailed
-                | DeployStatus::Canceled
-                | DeployStatus::Deactivated
-                | DeployStatus::PreDeployFailed => {
-                    println!(
-                        "Deploy has Stopped {}",
-                        deploy.finished_at.unwrap_or("unknown".into())
-                    );
-                    break;
-                }
-            }
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> ExitCode {
+    // clap exits 2 on usage errors but 2 means the deploy failed, so use 1 like other config
+    // errors
+    let matches = match Cli::command().try_get_matches() {
+        Ok(matches) => matches,
+        Err(e) => return clap_exit(e),
+    };
+    let cli = match Cli::from_arg_matches(&matches) {
+        Ok(cli) => cli,
+        Err(e) => return clap_exit(e),
+    };
+    match run(&cli, matches.value_source("api_key")).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            tracing::error!(error = %e, exit_code = e.exit_code(), "failed");
+            eprintln!("{}", e);
+            ExitCode::from(e.exit_code())
         }
     }
-    exit(0);
 }
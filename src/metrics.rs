@@ -0,0 +1,122 @@
+use crate::client::RenderClient;
+use crate::error::RenderError;
+use serde::{Deserialize, Serialize};
+
+/// Which of render's metrics endpoints to read
+#[derive(PartialEq, Serialize, Debug, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum MetricKind {
+    /// cpu time used, in cpus
+    Cpu,
+    /// memory in use, in bytes
+    Memory,
+}
+
+impl MetricKind {
+    fn path(&self) -> &'static str {
+        match self {
+            MetricKind::Cpu => "/metrics/cpu",
+            MetricKind::Memory => "/metrics/memory",
+        }
+    }
+}
+
+#[derive(PartialEq, Deserialize, Serialize, Debug, Clone)]
+pub struct MetricLabel {
+    pub field: String,
+    pub value: String,
+}
+
+#[derive(PartialEq, Deserialize, Serialize, Debug, Clone)]
+pub struct MetricPoint {
+    /// RFC3339
+    pub timestamp: String,
+    pub value: f64,
+}
+
+/// One line on a graph, usually one instance of a service
+#[derive(PartialEq, Deserialize, Serialize, Debug, Clone)]
+pub struct MetricSeries {
+    #[serde(default)]
+    pub labels: Vec<MetricLabel>,
+    pub unit: String,
+    pub values: Vec<MetricPoint>,
+}
+
+impl MetricSeries {
+    /// Value of a label like `instance` or `service`
+    pub fn label(&self, field: &str) -> Option<&str> {
+        self.labels
+            .iter()
+            .find(|label| label.field == field)
+            .map(|label| label.value.as_str())
+    }
+}
+
+/// Query for render's `GET /v1/metrics/*` endpoints
+#[derive(Debug, Clone, Default)]
+pub struct MetricsQuery {
+    /// service ids to fetch metrics for
+    pub resources: Vec<String>,
+    /// RFC3339 timestamps
+    pub start_time: Option<String>,
+    pub end_time: Option<String>,
+    /// seconds between points
+    pub resolution_seconds: Option<u64>,
+}
+
+impl MetricsQuery {
+    fn to_query(&self) -> Vec<(String, String)> {
+        let mut query = vec![];
+        for resource in &self.resources {
+            query.push(("resource".to_string(), resource.clone()));
+        }
+        if let Some(start_time) = &self.start_time {
+            query.push(("startTime".into(), start_time.clone()));
+        }
+        if let Some(end_time) = &self.end_time {
+            query.push(("endTime".into(), end_time.clone()));
+        }
+        if let Some(resolution_seconds) = self.resolution_seconds {
+            query.push(("resolutionSeconds".into(), resolution_seconds.to_string()));
+        }
+        query
+    }
+}
+
+impl RenderClient {
+    pub async fn metrics(
+        &self,
+        kind: MetricKind,
+        query: &MetricsQuery,
+    ) -> Result<Vec<MetricSeries>, RenderError> {
+        self.get(kind.path(), &query.to_query()).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_metrics() {
+        let sample = r#"
+            [
+                {
+                    "labels": [
+                        { "field": "resource", "value": "srv-1" },
+                        { "field": "instance", "value": "srv-1-abcde" }
+                    ],
+                    "unit": "cpu",
+                    "values": [
+                        { "timestamp": "2024-10-14T02:17:00Z", "value": 0.021 },
+                        { "timestamp": "2024-10-14T02:18:00Z", "value": 0.5 }
+                    ]
+                }
+            ]
+        "#;
+        let series: Vec<MetricSeries> = serde_json::from_str(sample).unwrap();
+        assert_eq!(series[0].label("instance"), Some("srv-1-abcde"));
+        assert_eq!(series[0].values[1].value, 0.5);
+    }
+}
@@ -0,0 +1,507 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::time::Duration;
+
+fn deserialize_yes_no<'de, D>(deserializer: D) -> Result<bool, D::Error>
+where
+    D: serde::de::Deserializer<'de>,
+{
+    let s: &str = Deserialize::deserialize(deserializer)?;
+
+    match s {
+        "yes" => Ok(true),
+        "no" => Ok(false),
+        _ => Err(serde::de::Error::unknown_variant(s, &["yes", "no"])),
+    }
+}
+
+pub(crate) fn serialize_yes_no<S>(value: &bool, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::ser::Serializer,
+{
+    serializer.serialize_str(if *value { "yes" } else { "no" })
+}
+
+fn deserialize_suspended<'de, D>(deserializer: D) -> Result<bool, D::Error>
+where
+    D: serde::de::Deserializer<'de>,
+{
+    let s: &str = Deserialize::deserialize(deserializer)?;
+
+    match s {
+        "suspended" => Ok(true),
+        "not_suspended" => Ok(false),
+        _ => Err(serde::de::Error::unknown_variant(
+            s,
+            &["suspended", "not_suspended"],
+        )),
+    }
+}
+
+fn serialize_suspended<S>(value: &bool, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::ser::Serializer,
+{
+    serializer.serialize_str(if *value { "suspended" } else { "not_suspended" })
+}
+
+#[derive(PartialEq, Deserialize, Serialize, Debug, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum ServiceType {
+    StaticSite,
+    WebService,
+    PrivateService,
+    BackgroundWorker,
+    CronJob,
+    /// a type render added after we were written
+    #[serde(other)]
+    Unknown,
+}
+
+impl fmt::Display for ServiceType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let type_str = match self {
+            ServiceType::StaticSite => "static site",
+            ServiceType::WebService => "web service",
+            ServiceType::PrivateService => "private service",
+            ServiceType::BackgroundWorker => "background worker",
+            ServiceType::CronJob => "cron job",
+            ServiceType::Unknown => "unknown",
+        };
+        write!(f, "{}", type_str)
+    }
+}
+
+#[derive(PartialEq, Deserialize, Serialize, Debug, Clone)]
+pub struct Service {
+    pub id: String,
+    pub name: String,
+    #[serde(rename = "type")]
+    pub service_type: ServiceType,
+    #[serde(rename = "ownerId")]
+    pub owner_id: String,
+    /// services deployed from an image don't have a repo or branch
+    #[serde(default)]
+    pub branch: Option<String>,
+    #[serde(rename = "dashboardUrl")]
+    pub dashboard_url: String,
+    #[serde(
+        rename = "autoDeploy",
+        deserialize_with = "deserialize_yes_no",
+        serialize_with = "serialize_yes_no"
+    )]
+    pub auto_deploy: bool,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_suspended",
+        serialize_with = "serialize_suspended"
+    )]
+    pub suspended: bool,
+    #[serde(default)]
+    pub repo: Option<String>,
+    /// the registry image an image backed service deploys, eg `docker.io/library/nginx:latest`
+    #[serde(rename = "imagePath", default)]
+    pub image_path: Option<String>,
+    #[serde(rename = "updatedAt")]
+    pub updated_at: String,
+    #[serde(rename = "createdAt")]
+    pub created_at: String,
+}
+
+impl Service {
+    /// Where the service deploys from, `repo #branch` or the image
+    pub fn source(&self) -> String {
+        match (&self.repo, &self.branch, &self.image_path) {
+            (Some(repo), Some(branch), _) => format!("{} #{}", repo, branch),
+            (Some(repo), None, _) => repo.clone(),
+            (None, _, Some(image_path)) => image_path.clone(),
+            (None, _, None) => String::new(),
+        }
+    }
+}
+
+#[derive(PartialEq, Deserialize, Debug, Clone)]
+pub struct ListServiceResponse {
+    pub cursor: String,
+    pub service: Service,
+}
+
+#[derive(PartialEq, Deserialize, Serialize, Debug, Clone)]
+pub struct CommitInfo {
+    pub id: String,
+    pub message: String,
+    #[serde(rename = "createdAt")]
+    pub created_at: String,
+}
+
+/// The image an image backed deploy is running
+#[derive(PartialEq, Deserialize, Serialize, Debug, Clone)]
+pub struct ImageInfo {
+    #[serde(rename = "ref")]
+    pub image_ref: String,
+    /// digest the ref resolved to
+    pub sha: Option<String>,
+    #[serde(rename = "registryCredential")]
+    pub registry_credential: Option<String>,
+}
+
+#[derive(PartialEq, Deserialize, Serialize, Debug, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum DeployStatus {
+    Created,
+    BuildInProgress,
+    UpdateInProgress,
+    Live,
+    Deactivated,
+    BuildFailed,
+    UpdateFailed,
+    Canceled,
+    PreDeployInProgress,
+    PreDeployFailed,
+    /// a status render added after we were written
+    #[serde(other)]
+    Unknown,
+}
+
+impl DeployStatus {
+    /// The deploy hasn't finished yet
+    pub fn is_in_progress(&self) -> bool {
+        matches!(
+            self,
+            DeployStatus::Created
+                | DeployStatus::BuildInProgress
+                | DeployStatus::UpdateInProgress
+                | DeployStatus::PreDeployInProgress
+        )
+    }
+
+    /// The deploy finished without going live
+    pub fn is_failed(&self) -> bool {
+        matches!(
+            self,
+            DeployStatus::BuildFailed
+                | DeployStatus::UpdateFailed
+                | DeployStatus::Canceled
+                | DeployStatus::Deactivated
+                | DeployStatus::PreDeployFailed
+        )
+    }
+}
+
+impl fmt::Display for DeployStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let status_str = match self {
+            DeployStatus::Created => "Created",
+            DeployStatus::BuildInProgress => "Build In Progress",
+            DeployStatus::UpdateInProgress => "Update In Progress",
+            DeployStatus::Live => "Live",
+            DeployStatus::Deactivated => "Deactivated",
+            DeployStatus::BuildFailed => "Build Failed",
+            DeployStatus::UpdateFailed => "Update Failed",
+            DeployStatus::Canceled => "Canceled",
+            DeployStatus::PreDeployInProgress => "Pre-Deploy In Progress",
+            DeployStatus::PreDeployFailed => "Pre-Deploy Failed",
+            DeployStatus::Unknown => "Unknown",
+        };
+        write!(f, "{}", status_str)
+    }
+}
+
+#[derive(PartialEq, Deserialize, Serialize, Debug, Clone)]
+pub struct Deploy {
+    pub id: String,
+    /// the commit of a deploy built from a repo
+    #[serde(default)]
+    pub commit: Option<CommitInfo>,
+    /// the image of a deploy pulled from a registry
+    #[serde(default)]
+    pub image: Option<ImageInfo>,
+    /// what started the deploy, eg `api`, `new_commit`, `manual`, `deploy_hook`
+    #[serde(default)]
+    pub trigger: Option<String>,
+    pub status: DeployStatus,
+    #[serde(rename = "createdAt")]
+    pub created_at: String,
+    /// static site deploys can leave this and `finishedAt` out
+    #[serde(rename = "updatedAt", default)]
+    pub updated_at: String,
+    #[serde(rename = "finishedAt", default)]
+    pub finished_at: Option<String>,
+}
+
+impl Deploy {
+    /// What was deployed, `#sha - message` for a commit or the image ref. Cron jobs and some
+    /// static sites deploy without either so we fall back to the deploy id and what triggered
+    /// it.
+    pub fn describe(&self) -> String {
+        match (&self.commit, &self.image, &self.trigger) {
+            (Some(commit), _, _) => format!("#{} - {}", commit.id, commit.message),
+            (None, Some(image), _) => match &image.sha {
+                Some(sha) => format!("{} ({})", image.image_ref, sha),
+                None => image.image_ref.clone(),
+            },
+            (None, None, Some(trigger)) => format!("{} (trigger: {})", self.id, trigger),
+            (None, None, None) => self.id.clone(),
+        }
+    }
+
+    /// How long the deploy took, `None` until it finishes
+    pub fn duration(&self) -> Option<Duration> {
+        let created_at = humantime::parse_rfc3339(&self.created_at).ok()?;
+        let finished_at = humantime::parse_rfc3339(self.finished_at.as_deref()?).ok()?;
+        finished_at.duration_since(created_at).ok()
+    }
+}
+
+/// A duration to the second like `4m12s` or `1h30m`, the way durations are given to `--timeout`
+pub fn format_duration(duration: Duration) -> String {
+    let mut seconds = duration.as_secs();
+    if seconds == 0 {
+        return "0s".into();
+    }
+    let mut formatted = String::new();
+    for (length, unit) in [(86_400, "d"), (3_600, "h"), (60, "m"), (1, "s")] {
+        if seconds >= length {
+            formatted.push_str(&format!("{}{}", seconds / length, unit));
+            seconds %= length;
+        }
+    }
+    formatted
+}
+
+#[derive(PartialEq, Deserialize, Debug, Clone)]
+pub struct ListDeploysResponse {
+    pub cursor: String,
+    pub deploy: Deploy,
+}
+
+#[derive(PartialEq, Serialize, Debug, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum ClearCache {
+    Clear,
+    DoNotClear,
+}
+
+/// Body of `POST /v1/services/{id}/deploys`
+#[derive(Serialize, Debug, Default, Clone)]
+pub struct TriggerDeployRequest {
+    /// deploy a specific commit instead of the head of the service's branch
+    #[serde(rename = "commitId", skip_serializing_if = "Option::is_none")]
+    pub commit_id: Option<String>,
+    /// deploy an image backed service from this image instead of its configured one
+    #[serde(rename = "imageUrl", skip_serializing_if = "Option::is_none")]
+    pub image_url: Option<String>,
+    /// clear the build cache before building, render doesn't by default
+    #[serde(rename = "clearCache", skip_serializing_if = "Option::is_none")]
+    pub clear_cache: Option<ClearCache>,
+}
+
+/// An image reference without its tag or digest, `docker.io/library/nginx:1.27` becomes
+/// `docker.io/library/nginx`
+pub fn image_repository(image: &str) -> &str {
+    let image = image.split('@').next().unwrap_or(image);
+    let name_start = image.rfind('/').map(|slash| slash + 1).unwrap_or(0);
+    match image[name_start..].rfind(':') {
+        Some(colon) => &image[..name_start + colon],
+        None => image,
+    }
+}
+
+/// One running copy of a service
+#[derive(PartialEq, Deserialize, Serialize, Debug, Clone)]
+pub struct Instance {
+    pub id: String,
+    #[serde(rename = "createdAt")]
+    pub created_at: String,
+}
+
+/// Body of `POST /v1/services/{id}/scale`
+#[derive(Serialize, Debug, Clone)]
+pub struct ScaleRequest {
+    #[serde(rename = "numInstances")]
+    pub num_instances: u32,
+}
+
+/// Link to a deploy in the render dashboard, the path differs for web services, static sites,
+/// cron jobs etc so we build on the service's dashboard url
+pub fn deploy_url(service: &Service, deploy: &Deploy) -> String {
+    format!(
+        "{dashboard_url}/deploys/{deploy_id}",
+        dashboard_url = service.dashboard_url.trim_end_matches('/'),
+        deploy_id = deploy.id
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_durations() {
+        assert_eq!(format_duration(Duration::from_millis(252_900)), "4m12s");
+        assert_eq!(format_duration(Duration::from_secs(5_400)), "1h30m");
+        assert_eq!(format_duration(Duration::from_secs(90_061)), "1d1h1m1s");
+        assert_eq!(format_duration(Duration::from_secs(45)), "45s");
+        assert_eq!(format_duration(Duration::ZERO), "0s");
+    }
+
+    #[test]
+    fn parse_service() {
+        let sample = r#"
+            {
+                "id": "crn-cs67ufi3esus73b74a70",
+                "name": "nightly-report",
+                "type": "cron_job",
+                "ownerId": "tea-cs67ufi3esus73b74a70",
+                "branch": "main",
+                "dashboardUrl": "https://dashboard.render.com/cron/crn-cs67ufi3esus73b74a70",
+                "autoDeploy": "no",
+                "suspended": "suspended",
+                "repo": "https://github.com/reconbot/render-deploy",
+                "updatedAt": "2024-10-14T02:17:35.868638Z",
+                "createdAt": "2024-10-14T02:17:35.868638Z"
+            }
+        "#;
+        let service: Service = serde_json::from_str(sample).unwrap();
+        assert_eq!(service.service_type, ServiceType::CronJob);
+        assert!(!service.auto_deploy);
+        assert!(service.suspended);
+
+        let unknown: ServiceType = serde_json::from_str(r#""keyvalue""#).unwrap();
+        assert_eq!(unknown, ServiceType::Unknown);
+    }
+
+    #[test]
+    fn parse_deploy() {
+        let sample = r#"
+            {
+                "id": "dep-cs67ufi3esus73b74a70",
+                "commit": {
+                    "id": "b2be9cf9e3188d00f58ef18a5904528993faeaa2",
+                    "message": "render uses sigterm and disable aws healthcheck",
+                    "createdAt": "2024-10-11T20:02:45Z"
+                },
+                "status": "build_in_progress",
+                "trigger": "api",
+                "createdAt": "2024-10-14T02:17:35.868638Z",
+                "updatedAt": "2024-10-14T02:17:35.868638Z",
+                "finishedAt": null
+            }
+        "#;
+        let deploy: Deploy = serde_json::from_str(sample).unwrap();
+        assert_eq!(deploy.id, "dep-cs67ufi3esus73b74a70");
+        assert_eq!(deploy.duration(), None);
+        assert_eq!(
+            deploy.commit.unwrap().id,
+            "b2be9cf9e3188d00f58ef18a5904528993faeaa2"
+        );
+        assert_eq!(deploy.status, DeployStatus::BuildInProgress);
+        assert_eq!(deploy.finished_at, None);
+    }
+
+    #[test]
+    fn parse_image_deploy() {
+        let sample = r#"
+            {
+                "id": "dep-cs67ufi3esus73b74a70",
+                "image": {
+                    "ref": "docker.io/library/nginx:1.27",
+                    "sha": "sha256:28402db69fec7c17e179ea87882667f1e054391138f77ffaf0c3eb388efc3ffb",
+                    "registryCredential": null
+                },
+                "status": "live",
+                "trigger": "api",
+                "createdAt": "2024-10-14T02:17:35.868638Z",
+                "updatedAt": "2024-10-14T02:19:35.868638Z",
+                "finishedAt": "2024-10-14T02:19:35.868638Z"
+            }
+        "#;
+        let deploy: Deploy = serde_json::from_str(sample).unwrap();
+        assert_eq!(deploy.commit, None);
+        assert_eq!(
+            deploy.image.unwrap().image_ref,
+            "docker.io/library/nginx:1.27"
+        );
+    }
+
+    #[test]
+    fn parse_deploy_without_commit() {
+        let sample = r#"
+            {
+                "id": "dep-cs67ufi3esus73b74a70",
+                "status": "live",
+                "trigger": "manual",
+                "createdAt": "2024-10-14T02:17:35.868638Z",
+                "updatedAt": "2024-10-14T02:19:35.868638Z",
+                "finishedAt": "2024-10-14T02:19:35.868638Z"
+            }
+        "#;
+        let deploy: Deploy = serde_json::from_str(sample).unwrap();
+        assert_eq!(deploy.commit, None);
+        assert_eq!(deploy.image, None);
+        assert_eq!(
+            deploy.describe(),
+            "dep-cs67ufi3esus73b74a70 (trigger: manual)"
+        );
+        assert_eq!(deploy.duration(), Some(Duration::from_secs(120)));
+    }
+
+    #[test]
+    fn parse_static_site_deploy() {
+        let sample = r#"
+            {
+                "id": "dep-cs67ufi3esus73b74a70",
+                "status": "publishing",
+                "createdAt": "2024-10-14T02:17:35.868638Z"
+            }
+        "#;
+        let deploy: Deploy = serde_json::from_str(sample).unwrap();
+        assert_eq!(deploy.status, DeployStatus::Unknown);
+        assert_eq!(deploy.finished_at, None);
+        assert_eq!(deploy.describe(), "dep-cs67ufi3esus73b74a70");
+    }
+
+    #[test]
+    fn image_repository_strips_tags_and_digests() {
+        assert_eq!(
+            image_repository("docker.io/library/nginx:1.27"),
+            "docker.io/library/nginx"
+        );
+        assert_eq!(
+            image_repository("ghcr.io/reconbot/app@sha256:28402db69fec"),
+            "ghcr.io/reconbot/app"
+        );
+        assert_eq!(image_repository("localhost:5000/app"), "localhost:5000/app");
+        assert_eq!(image_repository("nginx"), "nginx");
+    }
+
+    #[test]
+    fn serialize_trigger_deploy_request() {
+        let request = TriggerDeployRequest {
+            commit_id: Some("b2be9cf9e3188d00f58ef18a5904528993faeaa2".into()),
+            ..TriggerDeployRequest::default()
+        };
+        assert_eq!(
+            serde_json::to_string(&request).unwrap(),
+            r#"{"commitId":"b2be9cf9e3188d00f58ef18a5904528993faeaa2"}"#
+        );
+        let request = TriggerDeployRequest {
+            clear_cache: Some(ClearCache::Clear),
+            ..TriggerDeployRequest::default()
+        };
+        assert_eq!(
+            serde_json::to_string(&request).unwrap(),
+            r#"{"clearCache":"clear"}"#
+        );
+        let request = TriggerDeployRequest::default();
+        assert_eq!(serde_json::to_string(&request).unwrap(), "{}");
+    }
+
+    #[test]
+    fn deploy_status_is_finished() {
+        assert!(DeployStatus::Created.is_in_progress());
+        assert!(!DeployStatus::Live.is_in_progress());
+        assert!(!DeployStatus::Live.is_failed());
+        assert!(DeployStatus::PreDeployFailed.is_failed());
+    }
+}
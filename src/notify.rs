@@ -0,0 +1,116 @@
+use render_deploy::{deploy_url, format_duration, Deploy, RenderError, Service};
+use serde_json::{json, Value};
+
+/// Post how a deploy ended to a slack or discord webhook. The webhook's url is its secret so
+/// it's left out of errors.
+pub async fn notify(
+    http: &reqwest::Client,
+    webhook: &str,
+    service: &Service,
+    deploy: &Deploy,
+) -> Result<(), RenderError> {
+    let response = http
+        .post(webhook)
+        .json(&payload(webhook, &message(service, deploy)))
+        .send()
+        .await
+        .map_err(|e| e.without_url())?;
+    let status = response.status();
+    if !status.is_success() {
+        return Err(RenderError::Api {
+            status,
+            body: response.text().await.map_err(|e| e.without_url())?,
+            retry_after: None,
+            request: None,
+        });
+    }
+    Ok(())
+}
+
+/// `api is Live: #b2be9cf fix the build (2m3s) https://dashboard.render.com/...`
+fn message(service: &Service, deploy: &Deploy) -> String {
+    let deployed = match &deploy.commit {
+        Some(commit) => format!(
+            "#{sha} {message}",
+            sha = commit.id.get(..7).unwrap_or(&commit.id),
+            message = commit.message.lines().next().unwrap_or_default()
+        ),
+        None => deploy.describe(),
+    };
+    let duration = deploy
+        .duration()
+        .map(|duration| format!(" ({})", format_duration(duration)))
+        .unwrap_or_default();
+    format!(
+        "{name} is {status}: {deployed}{duration} {url}",
+        name = service.name,
+        status = deploy.status,
+        deployed = deployed,
+        duration = duration,
+        url = deploy_url(service, deploy)
+    )
+}
+
+/// Discord webhooks want `content`, slack and most things modeled on it want `text`
+fn payload(webhook: &str, message: &str) -> Value {
+    let discord = reqwest::Url::parse(webhook)
+        .ok()
+        .and_then(|url| url.host_str().map(String::from))
+        .is_some_and(|host| host == "discord.com" || host.ends_with(".discord.com"));
+    if discord {
+        json!({ "content": message })
+    } else {
+        json!({ "text": message })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{deploy, service};
+    use render_deploy::DeployStatus;
+
+    #[test]
+    fn message_for_a_failed_deploy() {
+        assert_eq!(
+            message(
+                &service("srv-1", "api"),
+                &deploy("dep-1", DeployStatus::BuildFailed)
+            ),
+            "api is Build Failed: #b2be9cf fix the build (2m3s) https://dashboard.render.com/web/srv-1/deploys/dep-1"
+        );
+    }
+
+    #[tokio::test]
+    async fn errors_leave_out_the_webhook() {
+        // nothing listens on a port we just let go of
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let webhook = format!("http://127.0.0.1:{}/services/T0/B0/secret", port);
+        let deploy = deploy("dep-1", DeployStatus::Live);
+        let error = notify(
+            &reqwest::Client::new(),
+            &webhook,
+            &service("srv-1", "api"),
+            &deploy,
+        )
+        .await
+        .unwrap_err();
+        assert!(!error.to_string().contains("secret"), "{}", error);
+    }
+
+    #[test]
+    fn payload_for_discord_or_slack() {
+        assert_eq!(
+            payload("https://discord.com/api/webhooks/1/abc", "hi"),
+            json!({ "content": "hi" })
+        );
+        assert_eq!(
+            payload("https://hooks.slack.com/services/T0/B0/abc", "hi"),
+            json!({ "text": "hi" })
+        );
+    }
+}
@@ -0,0 +1,62 @@
+use crate::error::RenderError;
+use reqwest::header::HeaderMap;
+use reqwest::{Method, StatusCode, Url};
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// What happened to an api request, handed to the function given to
+/// [`RenderClient::with_observer`](crate::RenderClient::with_observer)
+#[derive(Debug)]
+pub enum RequestEvent<'a> {
+    /// An attempt at a request finished, `status` and `response_headers` are missing when there
+    /// was no response
+    Sent {
+        method: &'a Method,
+        url: &'a Url,
+        status: Option<StatusCode>,
+        elapsed: Duration,
+        /// includes the client's `authorization` header, redact it before printing these
+        request_headers: &'a HeaderMap,
+        response_headers: Option<&'a HeaderMap>,
+    },
+    /// A successful response's body wasn't the json we expected
+    Unparseable {
+        method: &'a Method,
+        url: &'a Url,
+        body: &'a str,
+    },
+    /// An attempt failed and the request will be tried again after `delay`
+    Retrying {
+        method: &'a Method,
+        url: &'a Url,
+        /// the retry about to be made, starting at 1
+        attempt: u32,
+        delay: Duration,
+        error: &'a RenderError,
+    },
+}
+
+type ObserverFn = dyn Fn(&RequestEvent) + Send + Sync;
+
+/// Wraps the observer function so [`RenderClient`](crate::RenderClient) can stay `Debug`
+#[derive(Clone, Default)]
+pub(crate) struct Observer(Option<Arc<ObserverFn>>);
+
+impl Observer {
+    pub(crate) fn new(observer: impl Fn(&RequestEvent) + Send + Sync + 'static) -> Self {
+        Observer(Some(Arc::new(observer)))
+    }
+
+    pub(crate) fn observe(&self, event: RequestEvent) {
+        if let Some(observer) = &self.0 {
+            observer(&event);
+        }
+    }
+}
+
+impl fmt::Debug for Observer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(if self.0.is_some() { "Observer" } else { "None" })
+    }
+}
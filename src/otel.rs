@@ -0,0 +1,407 @@
+use render_deploy::{Deploy, DeployStatus, RenderError, Service};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::env;
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+/// A span of the deploy, from Render's timestamps where it has them
+#[derive(Debug, Clone)]
+struct Span {
+    id: String,
+    name: String,
+    start: SystemTime,
+    end: SystemTime,
+    attributes: Vec<(&'static str, String)>,
+    error: Option<String>,
+}
+
+/// Where to send the trace, from the standard `OTEL_*` env vars
+#[derive(Debug, PartialEq)]
+struct Exporter {
+    endpoint: String,
+    headers: Vec<(String, String)>,
+    resource: Vec<(String, String)>,
+}
+
+/// The deploy as an OTLP trace, a span for each phase under one for the whole command. Only
+/// collected when `OTEL_EXPORTER_OTLP_ENDPOINT` or `OTEL_EXPORTER_OTLP_TRACES_ENDPOINT` is set,
+/// and joins the CI job's trace when `TRACEPARENT` is.
+#[derive(Debug)]
+pub struct Trace {
+    exporter: Option<Exporter>,
+    trace_id: String,
+    root_id: String,
+    parent_id: Option<String>,
+    started: SystemTime,
+    spans: Mutex<Vec<Span>>,
+    /// when each deploy we're waiting on started updating, by deploy id
+    updating: Mutex<HashMap<String, SystemTime>>,
+}
+
+impl Trace {
+    pub fn from_env() -> Trace {
+        let var = |name: &str| env::var(name).ok().filter(|value| !value.is_empty());
+        let parent = var("TRACEPARENT").and_then(|parent| parse_traceparent(&parent));
+        let (trace_id, parent_id) = match parent {
+            Some((trace_id, parent_id)) => (trace_id, Some(parent_id)),
+            None => (random_id(16), None),
+        };
+        Trace {
+            exporter: exporter(var),
+            trace_id,
+            root_id: random_id(8),
+            parent_id,
+            started: SystemTime::now(),
+            spans: Mutex::default(),
+            updating: Mutex::default(),
+        }
+    }
+
+    fn record(&self, span: Span) {
+        if self.exporter.is_some() {
+            self.spans.lock().unwrap().push(span);
+        }
+    }
+
+    /// Run `step` as a span
+    pub async fn time<T>(
+        &self,
+        name: &str,
+        service: Option<&Service>,
+        step: impl Future<Output = Result<T, RenderError>>,
+    ) -> Result<T, RenderError> {
+        let start = SystemTime::now();
+        let result = step.await;
+        self.record(Span {
+            id: random_id(8),
+            name: name.to_string(),
+            start,
+            end: SystemTime::now(),
+            attributes: service.map(attributes).unwrap_or_default(),
+            error: result.as_ref().err().map(ToString::to_string),
+        });
+        result
+    }
+
+    /// Note when a deploy moves from building to updating, Render only keeps when it was
+    /// created and finished
+    pub fn status_changed(&self, deploy: &Deploy) {
+        if deploy.status == DeployStatus::UpdateInProgress {
+            let at = humantime::parse_rfc3339(&deploy.updated_at).unwrap_or(SystemTime::now());
+            self.updating.lock().unwrap().insert(deploy.id.clone(), at);
+        }
+    }
+
+    /// The build, update and live spans of a deploy we've stopped waiting on
+    pub fn deploy_finished(
+        &self,
+        service: &Service,
+        deploy: &Deploy,
+        waited: &Result<Deploy, RenderError>,
+    ) {
+        let Ok(created) = humantime::parse_rfc3339(&deploy.created_at) else {
+            return;
+        };
+        let finished = deploy
+            .finished_at
+            .as_deref()
+            .and_then(|finished| humantime::parse_rfc3339(finished).ok());
+        let updating = self.updating.lock().unwrap().get(&deploy.id).copied();
+        let end = finished.unwrap_or(SystemTime::now());
+        let error = waited.as_ref().err().map(ToString::to_string);
+        let mut attributes = attributes(service);
+        attributes.push(("render.deploy.id", deploy.id.clone()));
+        attributes.push(("render.deploy.status", deploy.status.to_string()));
+        if let Some(commit) = &deploy.commit {
+            attributes.push(("vcs.ref.head.revision", commit.id.clone()));
+        }
+        let span = |name: &str, start, end, error: Option<String>| Span {
+            id: random_id(8),
+            name: name.to_string(),
+            start,
+            end,
+            attributes: attributes.clone(),
+            error,
+        };
+        // it failed in whichever phase it got to
+        match updating {
+            Some(updating) => {
+                self.record(span("build", created, updating, None));
+                self.record(span("update", updating, end, error));
+            }
+            None => self.record(span("build", created, end, error)),
+        }
+        if let (Some(finished), Ok(_)) = (finished, waited) {
+            self.record(span("live", finished, SystemTime::now(), None));
+        }
+    }
+
+    /// Send the trace, ending the span for the whole command with `result`
+    pub async fn export(
+        &self,
+        http: &reqwest::Client,
+        result: &Result<(), RenderError>,
+    ) -> Result<(), RenderError> {
+        let Some(exporter) = &self.exporter else {
+            return Ok(());
+        };
+        let root = Span {
+            id: self.root_id.clone(),
+            name: "render-deploy deploy".into(),
+            start: self.started,
+            end: SystemTime::now(),
+            attributes: vec![],
+            error: result.as_ref().err().map(ToString::to_string),
+        };
+        let spans = self.spans.lock().unwrap().clone();
+        let body = self.to_otlp(exporter, &root, &spans);
+        let mut request = http.post(&exporter.endpoint).json(&body);
+        for (name, value) in &exporter.headers {
+            request = request.header(name, value);
+        }
+        let response = request.send().await?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(RenderError::Api {
+                status,
+                body: response.text().await?,
+                retry_after: None,
+                request: None,
+            });
+        }
+        Ok(())
+    }
+
+    /// OTLP's json encoding, https://opentelemetry.io/docs/specs/otlp/#json-protobuf-encoding
+    fn to_otlp(&self, exporter: &Exporter, root: &Span, spans: &[Span]) -> Value {
+        let span = |span: &Span, parent_id: Option<&str>| {
+            let mut value = json!({
+                "traceId": self.trace_id,
+                "spanId": span.id,
+                "name": span.name,
+                // internal
+                "kind": 1,
+                "startTimeUnixNano": nanos(span.start),
+                "endTimeUnixNano": nanos(span.end),
+                "attributes": span.attributes.iter().map(|(key, value)| {
+                    json!({ "key": key, "value": { "stringValue": value } })
+                }).collect::<Vec<_>>(),
+                "status": match &span.error {
+                    Some(message) => json!({ "code": 2, "message": message }),
+                    None => json!({ "code": 1 }),
+                },
+            });
+            if let Some(parent_id) = parent_id {
+                value["parentSpanId"] = json!(parent_id);
+            }
+            value
+        };
+        let mut all = vec![span(root, self.parent_id.as_deref())];
+        all.extend(spans.iter().map(|child| span(child, Some(&self.root_id))));
+        let resource: Vec<Value> = exporter
+            .resource
+            .iter()
+            .map(|(key, value)| json!({ "key": key, "value": { "stringValue": value } }))
+            .collect();
+        json!({
+            "resourceSpans": [{
+                "resource": { "attributes": resource },
+                "scopeSpans": [{
+                    "scope": { "name": "render-deploy", "version": env!("CARGO_PKG_VERSION") },
+                    "spans": all,
+                }],
+            }],
+        })
+    }
+}
+
+fn attributes(service: &Service) -> Vec<(&'static str, String)> {
+    vec![
+        ("render.service.id", service.id.clone()),
+        ("render.service.name", service.name.clone()),
+    ]
+}
+
+/// The exporter the `OTEL_*` vars read by `var` describe, `None` when they don't or turn it off
+fn exporter(var: impl Fn(&str) -> Option<String>) -> Option<Exporter> {
+    if var("OTEL_SDK_DISABLED").is_some_and(|disabled| disabled.eq_ignore_ascii_case("true"))
+        || var("OTEL_TRACES_EXPORTER").is_some_and(|exporter| exporter == "none")
+    {
+        return None;
+    }
+    let endpoint = match var("OTEL_EXPORTER_OTLP_TRACES_ENDPOINT") {
+        Some(endpoint) => endpoint,
+        None => format!(
+            "{}/v1/traces",
+            var("OTEL_EXPORTER_OTLP_ENDPOINT")?.trim_end_matches('/')
+        ),
+    };
+    let headers = [
+        var("OTEL_EXPORTER_OTLP_HEADERS"),
+        var("OTEL_EXPORTER_OTLP_TRACES_HEADERS"),
+    ];
+    let attributes = var("OTEL_RESOURCE_ATTRIBUTES")
+        .map(|attributes| pairs(&attributes))
+        .unwrap_or_default();
+    // OTEL_SERVICE_NAME wins over service.name in OTEL_RESOURCE_ATTRIBUTES
+    let service_name = var("OTEL_SERVICE_NAME")
+        .or_else(|| {
+            attributes
+                .iter()
+                .find(|(key, _)| key == "service.name")
+                .map(|(_, value)| value.clone())
+        })
+        .unwrap_or_else(|| "render-deploy".into());
+    let mut resource = vec![("service.name".to_string(), service_name)];
+    resource.extend(
+        attributes
+            .into_iter()
+            .filter(|(key, _)| key != "service.name"),
+    );
+    Some(Exporter {
+        endpoint,
+        headers: headers.iter().flatten().flat_map(|h| pairs(h)).collect(),
+        resource,
+    })
+}
+
+/// `key1=value1,key2=value2` with the values percent encoded
+fn pairs(list: &str) -> Vec<(String, String)> {
+    list.split(',')
+        .filter_map(|pair| pair.split_once('='))
+        .map(|(key, value)| (key.trim().to_string(), percent_decode(value.trim())))
+        .collect()
+}
+
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// The trace and span ids of a w3c `traceparent`, `00-<trace id>-<span id>-<flags>`
+fn parse_traceparent(traceparent: &str) -> Option<(String, String)> {
+    let mut parts = traceparent.trim().split('-');
+    let (_version, trace_id, span_id) = (parts.next()?, parts.next()?, parts.next()?);
+    let is_id = |id: &str, len| {
+        id.len() == len
+            && id.bytes().all(|b| b.is_ascii_hexdigit())
+            && id.bytes().any(|b| b != b'0')
+    };
+    (is_id(trace_id, 32) && is_id(span_id, 16))
+        .then(|| (trace_id.to_lowercase(), span_id.to_lowercase()))
+}
+
+fn random_id(bytes: usize) -> String {
+    let mut id = vec![0; bytes];
+    // a clock based id still makes a usable trace if there's no randomness to be had
+    if getrandom::getrandom(&mut id).is_err() {
+        let now = nanos(SystemTime::now()).parse::<u128>().unwrap_or_default();
+        for (byte, clock) in id.iter_mut().zip(now.to_le_bytes().iter().cycle()) {
+            *byte = *clock;
+        }
+    }
+    id.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn nanos(time: SystemTime) -> String {
+    time.duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_traceparents() {
+        assert_eq!(
+            parse_traceparent("00-4BF92F3577B34DA6A3CE929D0E0E4736-00f067aa0ba902b7-01"),
+            Some((
+                "4bf92f3577b34da6a3ce929d0e0e4736".into(),
+                "00f067aa0ba902b7".into()
+            ))
+        );
+        assert_eq!(
+            parse_traceparent("00-00000000000000000000000000000000-00f067aa0ba902b7-01"),
+            None
+        );
+        assert_eq!(parse_traceparent("garbage"), None);
+    }
+
+    #[test]
+    fn exporter_from_env() {
+        let vars = |vars: &'static [(&'static str, &'static str)]| {
+            move |name: &str| {
+                vars.iter()
+                    .find(|(var, _)| *var == name)
+                    .map(|(_, value)| value.to_string())
+            }
+        };
+        assert_eq!(exporter(vars(&[])), None);
+        assert_eq!(
+            exporter(vars(&[
+                ("OTEL_EXPORTER_OTLP_ENDPOINT", "http://collector:4318/"),
+                (
+                    "OTEL_EXPORTER_OTLP_HEADERS",
+                    "authorization=Bearer%20abc, x-team=ops"
+                ),
+                ("OTEL_RESOURCE_ATTRIBUTES", "deployment.environment=prod"),
+            ])),
+            Some(Exporter {
+                endpoint: "http://collector:4318/v1/traces".into(),
+                headers: vec![
+                    ("authorization".into(), "Bearer abc".into()),
+                    ("x-team".into(), "ops".into())
+                ],
+                resource: vec![
+                    ("service.name".into(), "render-deploy".into()),
+                    ("deployment.environment".into(), "prod".into())
+                ],
+            })
+        );
+        assert_eq!(
+            exporter(vars(&[
+                ("OTEL_EXPORTER_OTLP_ENDPOINT", "http://collector:4318"),
+                (
+                    "OTEL_EXPORTER_OTLP_TRACES_ENDPOINT",
+                    "http://traces:4318/v1/traces"
+                ),
+                ("OTEL_SERVICE_NAME", "deploys"),
+            ]))
+            .map(|exporter| (exporter.endpoint, exporter.resource)),
+            Some((
+                "http://traces:4318/v1/traces".into(),
+                vec![("service.name".into(), "deploys".into())]
+            ))
+        );
+        assert_eq!(
+            exporter(vars(&[
+                ("OTEL_EXPORTER_OTLP_ENDPOINT", "http://collector:4318"),
+                ("OTEL_SDK_DISABLED", "true"),
+            ])),
+            None
+        );
+    }
+}
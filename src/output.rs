@@ -0,0 +1,615 @@
+use chrono::{DateTime, Local, TimeDelta, TimeZone, Utc};
+use clap::ValueEnum;
+use render_deploy::{DeployStatus, RenderError};
+use serde::Serialize;
+use serde_json::Value;
+use std::env;
+use std::fmt::Display;
+use std::io::{self, IsTerminal, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+const GREEN: &str = "\x1b[32m";
+const RED: &str = "\x1b[31m";
+const YELLOW: &str = "\x1b[33m";
+const RESET: &str = "\x1b[0m";
+
+#[derive(ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// human readable progress on stdout
+    #[default]
+    Text,
+    /// a single json document on stdout, progress goes to stderr
+    Json,
+    /// a json line on stdout for each status change while waiting, then the result as one line,
+    /// progress goes to stderr
+    Ndjson,
+    /// the result as yaml on stdout, progress goes to stderr
+    Yaml,
+    /// lists lined up in columns on stdout, pick them with --columns, anything else as a table
+    /// of its fields. Progress goes to stderr.
+    Table,
+    /// lists as csv on stdout for spreadsheets, pick the columns with --columns, anything else
+    /// as rows of its fields. Progress goes to stderr.
+    Csv,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ColorChoice {
+    /// when progress goes to a terminal and NO_COLOR isn't set
+    #[default]
+    Auto,
+    /// eg for CI logs, which aren't terminals but show colors
+    Always,
+    Never,
+}
+
+/// How much progress to print
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Verbosity {
+    /// only what a command came to, eg the id of the deploy it started
+    Quiet,
+    #[default]
+    Normal,
+    /// every api request too
+    Verbose,
+}
+
+/// Where the cli writes things. In text mode everything goes to stdout, otherwise stdout is
+/// reserved for the machine readable result and the chatter moves to stderr.
+#[derive(Debug, Clone)]
+pub struct Output {
+    format: OutputFormat,
+    color: bool,
+    verbosity: Verbosity,
+    /// `--format-template` for results
+    template: Option<Arc<str>>,
+    /// `--columns` to keep from tables
+    columns: Option<Arc<[String]>>,
+    /// whether a table has printed the result already, for `--output table`
+    tabled: Arc<AtomicBool>,
+    /// `--utc`, print times as they came from the api
+    utc: bool,
+}
+
+impl Output {
+    pub fn new(format: OutputFormat, color: ColorChoice, verbosity: Verbosity) -> Self {
+        let mut output = Output {
+            format,
+            color: false,
+            verbosity,
+            template: None,
+            columns: None,
+            tabled: Arc::default(),
+            utc: false,
+        };
+        output.color = match color {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            // https://no-color.org, set to anything but empty
+            ColorChoice::Auto => {
+                env::var_os("NO_COLOR").is_none_or(|value| value.is_empty()) && output.is_terminal()
+            }
+        };
+        output
+    }
+
+    /// Print results through `template` rather than as json. Progress moves to stderr like it
+    /// does for json so stdout is only the results.
+    pub fn with_template(mut self, template: Option<String>) -> Self {
+        if template.is_some() && self.format == OutputFormat::Text {
+            self.format = OutputFormat::Json;
+        }
+        self.template = template.map(Arc::from);
+        self
+    }
+
+    /// Only print these columns of tables, by header
+    pub fn with_columns(mut self, columns: Vec<String>) -> Self {
+        if !columns.is_empty() {
+            self.columns = Some(columns.into());
+        }
+        self
+    }
+
+    pub fn with_utc(mut self, utc: bool) -> Self {
+        self.utc = utc;
+        self
+    }
+
+    pub fn format(&self) -> OutputFormat {
+        self.format
+    }
+
+    pub fn is_quiet(&self) -> bool {
+        self.verbosity == Verbosity::Quiet
+    }
+
+    pub fn is_verbose(&self) -> bool {
+        self.verbosity == Verbosity::Verbose
+    }
+
+    /// Human readable progress, left out with `--quiet`
+    pub fn info(&self, message: impl Display) {
+        if !self.is_quiet() {
+            self.summary(message);
+        }
+    }
+
+    /// What a command came to, printed like progress but even with `--quiet`
+    pub fn summary(&self, message: impl Display) {
+        match self.format {
+            OutputFormat::Text => println!("{}", message),
+            _ => eprintln!("{}", message),
+        }
+    }
+
+    /// Only printed with `--quiet`, standing in for the progress that would have said it
+    pub fn brief(&self, message: impl Display) {
+        if self.is_quiet() {
+            self.summary(message);
+        }
+    }
+
+    /// Details for debugging, only with `--verbose`. Always on stderr so they can't end up mixed
+    /// into results.
+    pub fn verbose(&self, message: impl Display) {
+        if self.is_verbose() {
+            eprintln!("{}", message);
+        }
+    }
+
+    /// One item of a stream of results, `text` in text mode or a line of json
+    pub fn item<T: Serialize>(&self, value: &T, text: impl Display) -> Result<(), RenderError> {
+        match self.format {
+            OutputFormat::Text | OutputFormat::Table | OutputFormat::Csv
+                if self.template.is_none() =>
+            {
+                println!("{}", text);
+                Ok(())
+            }
+            OutputFormat::Yaml if self.template.is_none() => {
+                print!("---\n{}", to_yaml(value)?);
+                Ok(())
+            }
+            _ => {
+                let json = match &self.template {
+                    Some(template) => render_template(template, &to_value(value)?)?,
+                    None => serde_json::to_string(value)
+                        .map_err(|e| RenderError::Config(format!("Unable to write json {}", e)))?,
+                };
+                println!("{}", json);
+                Ok(())
+            }
+        }
+    }
+
+    /// Something that happened while waiting, a line of json with `--output ndjson`
+    pub fn event<T: Serialize>(&self, value: &T) {
+        if self.format == OutputFormat::Ndjson {
+            if let Ok(json) = serde_json::to_string(value) {
+                println!("{}", json);
+            }
+        }
+    }
+
+    /// A deploy status for progress and tables, green when it's live, red when it failed and
+    /// yellow while it's in progress
+    pub fn status(&self, status: &DeployStatus) -> String {
+        // deactivated deploys went live and were replaced, that's not a failure
+        let color = if *status == DeployStatus::Deactivated {
+            return status.to_string();
+        } else if status.is_in_progress() {
+            YELLOW
+        } else if status.is_failed() {
+            RED
+        } else if *status == DeployStatus::Live {
+            GREEN
+        } else {
+            return status.to_string();
+        };
+        if self.color {
+            format!("{}{}{}", color, status, RESET)
+        } else {
+            status.to_string()
+        }
+    }
+
+    /// An RFC3339 time from the api for people, in the local timezone and how long ago it was.
+    /// Left as it is with `--utc`, in csv for spreadsheets to parse, or if it doesn't parse.
+    pub fn time(&self, timestamp: &str) -> String {
+        if self.utc || self.format == OutputFormat::Csv {
+            return timestamp.to_string();
+        }
+        match DateTime::parse_from_rfc3339(timestamp) {
+            Ok(time) => format_time(&time.with_timezone(&Local), Utc::now(), "local"),
+            Err(_) => timestamp.to_string(),
+        }
+    }
+
+    /// Whether progress goes to a terminal, where a line can be redrawn in place
+    pub fn is_terminal(&self) -> bool {
+        match self.format {
+            OutputFormat::Text => io::stdout().is_terminal(),
+            _ => io::stderr().is_terminal(),
+        }
+    }
+
+    /// Replace the line progress is on, an empty one clears it. Only for terminals.
+    pub fn redraw(&self, line: &str) {
+        // \r back to the start, then erase whatever the last line left past the new one
+        let line = format!("\r{}\x1b[K", line);
+        let _ = match self.format {
+            OutputFormat::Text => io::stdout()
+                .write_all(line.as_bytes())
+                .and(io::stdout().flush()),
+            _ => io::stderr().write_all(line.as_bytes()),
+        };
+    }
+
+    /// Rows of results lined up under a header, only printed in text, table and csv modes, pair
+    /// it with [`Output::result`] for the machine readable versions
+    pub fn table(&self, header: &[&str], rows: &[Vec<String>]) {
+        let print = match self.format {
+            OutputFormat::Text | OutputFormat::Table => format_table,
+            OutputFormat::Csv => format_csv,
+            _ => return,
+        };
+        self.tabled.store(true, Ordering::Relaxed);
+        let Some(columns) = &self.columns else {
+            print!("{}", print(header, rows));
+            return;
+        };
+        let mut picked = vec![];
+        for column in columns.iter() {
+            match header
+                .iter()
+                .position(|name| name.eq_ignore_ascii_case(column))
+            {
+                Some(index) => picked.push(index),
+                None => eprintln!(
+                    "There's no {} column, the columns are {}",
+                    column,
+                    header.join(", ")
+                ),
+            }
+        }
+        let header: Vec<&str> = picked.iter().map(|&index| header[index]).collect();
+        let rows: Vec<Vec<String>> = rows
+            .iter()
+            .map(|row| picked.iter().map(|&index| row[index].clone()).collect())
+            .collect();
+        print!("{}", print(&header, &rows));
+    }
+
+    /// The result of a command, only printed when a machine readable format was asked for
+    pub fn result<T: Serialize>(&self, value: &T) -> Result<(), RenderError> {
+        if let Some(template) = &self.template {
+            // a line for each item of a list, like docker --format
+            match to_value(value)? {
+                Value::Array(items) => {
+                    for item in items {
+                        println!("{}", render_template(template, &item)?);
+                    }
+                }
+                value => println!("{}", render_template(template, &value)?),
+            }
+            return Ok(());
+        }
+        match self.format {
+            OutputFormat::Text => Ok(()),
+            OutputFormat::Json => {
+                let json = serde_json::to_string_pretty(value)
+                    .map_err(|e| RenderError::Config(format!("Unable to write json {}", e)))?;
+                println!("{}", json);
+                Ok(())
+            }
+            OutputFormat::Ndjson => self.item(value, ""),
+            OutputFormat::Yaml => {
+                print!("{}", to_yaml(value)?);
+                Ok(())
+            }
+            // lists have printed their table already
+            OutputFormat::Table | OutputFormat::Csv if self.tabled.load(Ordering::Relaxed) => {
+                Ok(())
+            }
+            OutputFormat::Table | OutputFormat::Csv => {
+                let mut rows = vec![];
+                flatten("", &to_value(value)?, &mut rows);
+                self.table(&["FIELD", "VALUE"], &rows);
+                Ok(())
+            }
+        }
+    }
+}
+
+/// `2025-03-02 14:03 local, 12 minutes ago`
+fn format_time<Tz: TimeZone>(time: &DateTime<Tz>, now: DateTime<Utc>, zone: &str) -> String
+where
+    Tz::Offset: Display,
+{
+    format!(
+        "{} {}, {}",
+        time.format("%Y-%m-%d %H:%M"),
+        zone,
+        relative(now.signed_duration_since(time))
+    )
+}
+
+/// `12 minutes ago`, or `in 5 minutes` for times that haven't come yet
+fn relative(age: TimeDelta) -> String {
+    let seconds = age.num_seconds().unsigned_abs();
+    if seconds < 60 {
+        return "just now".into();
+    }
+    let (count, unit) = [
+        (365 * 24 * 60 * 60, "year"),
+        (30 * 24 * 60 * 60, "month"),
+        (24 * 60 * 60, "day"),
+        (60 * 60, "hour"),
+        (60, "minute"),
+    ]
+    .into_iter()
+    .find(|(length, _)| seconds >= *length)
+    .map(|(length, unit)| (seconds / length, unit))
+    .unwrap_or((seconds / 60, "minute"));
+    let plural = if count == 1 { "" } else { "s" };
+    if age < TimeDelta::zero() {
+        format!("in {} {}{}", count, unit, plural)
+    } else {
+        format!("{} {}{} ago", count, unit, plural)
+    }
+}
+
+fn to_yaml<T: Serialize>(value: &T) -> Result<String, RenderError> {
+    serde_yaml::to_string(value)
+        .map_err(|e| RenderError::Config(format!("Unable to write yaml {}", e)))
+}
+
+/// A `[path, value]` row for every field of a result, `deploy.commit.id` style
+fn flatten(path: &str, value: &Value, rows: &mut Vec<Vec<String>>) {
+    let child = |key: &dyn Display| match path {
+        "" => key.to_string(),
+        _ => format!("{}.{}", path, key),
+    };
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map {
+                flatten(&child(key), value, rows);
+            }
+        }
+        Value::Array(items) => {
+            for (index, value) in items.iter().enumerate() {
+                flatten(&child(&index), value, rows);
+            }
+        }
+        Value::Null => {}
+        Value::String(text) => rows.push(vec![path.to_string(), text.clone()]),
+        other => rows.push(vec![path.to_string(), other.to_string()]),
+    }
+}
+
+fn to_value<T: Serialize>(value: &T) -> Result<Value, RenderError> {
+    serde_json::to_value(value)
+        .map_err(|e| RenderError::Config(format!("Unable to write json {}", e)))
+}
+
+/// Fill in `{{.deploy.id}}` style fields of a `--format-template` from a result, strings as they
+/// are and anything else as json. `{{.}}` is the whole result.
+pub fn render_template(template: &str, value: &Value) -> Result<String, RenderError> {
+    let mut rendered = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        let end = rest[start..].find("}}").ok_or_else(|| {
+            RenderError::Config(format!("The template has an unclosed {{{{ in {}", template))
+        })? + start;
+        rendered.push_str(&rest[..start]);
+        let path = rest[start + 2..end].trim().trim_start_matches('.');
+        let mut field = value;
+        for key in path.split('.').filter(|key| !key.is_empty()) {
+            let found = match field {
+                Value::Object(map) => map.get(key),
+                Value::Array(items) => key.parse::<usize>().ok().and_then(|i| items.get(i)),
+                _ => None,
+            };
+            field = found.ok_or_else(|| {
+                let keys = match value {
+                    Value::Object(map) => map.keys().cloned().collect::<Vec<_>>().join(", "),
+                    _ => String::new(),
+                };
+                RenderError::Config(format!("The result has no .{}, it has {}", path, keys))
+            })?;
+        }
+        match field {
+            Value::String(text) => rendered.push_str(text),
+            Value::Null => {}
+            other => rendered.push_str(&other.to_string()),
+        }
+        rest = &rest[end + 2..];
+    }
+    rendered.push_str(rest);
+    Ok(rendered)
+}
+
+/// Columns padded to line up, for when a table has to be printed whatever the output format
+pub fn format_table(header: &[&str], rows: &[Vec<String>]) -> String {
+    let mut widths: Vec<usize> = header.iter().map(|column| column.len()).collect();
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(visible_width(cell));
+        }
+    }
+    let header = header.iter().map(|column| column.to_string()).collect();
+    let mut table = String::new();
+    for row in std::iter::once(&header).chain(rows) {
+        let cells: Vec<String> = row
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{}{}", cell, " ".repeat(width - visible_width(cell))))
+            .collect();
+        table.push_str(cells.join("  ").trim_end());
+        table.push('\n');
+    }
+    table
+}
+
+/// Rows as csv under a header row, quoting cells that need it and leaving out colors
+pub fn format_csv(header: &[&str], rows: &[Vec<String>]) -> String {
+    let header = header.iter().map(|column| column.to_string()).collect();
+    let mut csv = String::new();
+    for row in std::iter::once(&header).chain(rows) {
+        let cells: Vec<String> = row.iter().map(|cell| csv_cell(cell)).collect();
+        csv.push_str(&cells.join(","));
+        // rfc 4180 line endings
+        csv.push_str("\r\n");
+    }
+    csv
+}
+
+fn csv_cell(cell: &str) -> String {
+    let cell = strip_colors(cell);
+    if cell.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", cell.replace('"', "\"\""))
+    } else {
+        cell
+    }
+}
+
+fn strip_colors(text: &str) -> String {
+    let mut stripped = String::with_capacity(text.len());
+    let mut in_escape = false;
+    for c in text.chars() {
+        match (in_escape, c) {
+            (false, '\x1b') => in_escape = true,
+            (false, _) => stripped.push(c),
+            (true, 'm') => in_escape = false,
+            (true, _) => {}
+        }
+    }
+    stripped
+}
+
+/// How many columns text takes up, leaving out color codes
+fn visible_width(text: &str) -> usize {
+    let mut width = 0;
+    let mut in_escape = false;
+    for c in text.chars() {
+        match (in_escape, c) {
+            (false, '\x1b') => in_escape = true,
+            (false, _) => width += 1,
+            (true, 'm') => in_escape = false,
+            (true, _) => {}
+        }
+    }
+    width
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_templates() {
+        let result = serde_json::json!({
+            "deploy": {"id": "dep-1", "status": "live", "finishedAt": null},
+            "services": [{"name": "api"}],
+            "count": 2,
+        });
+        let render = |template| render_template(template, &result).unwrap();
+        assert_eq!(render("{{.deploy.id}} {{ .deploy.status }}"), "dep-1 live");
+        assert_eq!(render("{{.services.0.name}} x{{.count}}"), "api x2");
+        assert_eq!(render("[{{.deploy.finishedAt}}]"), "[]");
+        assert_eq!(render("{{.services}}"), r#"[{"name":"api"}]"#);
+        assert!(render_template("{{.status}}", &result).is_err());
+        assert!(render_template("{{.deploy", &result).is_err());
+    }
+
+    #[test]
+    fn flatten_results() {
+        let result = serde_json::json!({
+            "deploy": {"id": "dep-1", "finishedAt": null},
+            "services": ["api", "worker"],
+            "count": 2,
+        });
+        let mut rows = vec![];
+        flatten("", &result, &mut rows);
+        assert_eq!(
+            rows,
+            vec![
+                vec!["count".to_string(), "2".to_string()],
+                vec!["deploy.id".to_string(), "dep-1".to_string()],
+                vec!["services.0".to_string(), "api".to_string()],
+                vec!["services.1".to_string(), "worker".to_string()],
+            ]
+        );
+    }
+
+    #[test]
+    fn format_table_pads_columns() {
+        let rows = vec![
+            vec!["api".to_string(), "srv-1".to_string(), "live".to_string()],
+            vec![
+                "worker-long".to_string(),
+                "srv-2".to_string(),
+                "-".to_string(),
+            ],
+        ];
+        assert_eq!(
+            format_table(&["NAME", "ID", "STATUS"], &rows),
+            "NAME         ID     STATUS\napi          srv-1  live\nworker-long  srv-2  -\n"
+        );
+    }
+
+    #[test]
+    fn format_csv_quotes_cells() {
+        let rows = vec![
+            vec!["api".to_string(), "fix \"things\", again".to_string()],
+            vec!["worker".to_string(), "\x1b[32mLive\x1b[0m".to_string()],
+        ];
+        assert_eq!(
+            format_csv(&["NAME", "DETAILS"], &rows),
+            "NAME,DETAILS\r\napi,\"fix \"\"things\"\", again\"\r\nworker,Live\r\n"
+        );
+    }
+
+    #[test]
+    fn format_times() {
+        let now = DateTime::parse_from_rfc3339("2025-03-02T22:15:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let time = DateTime::parse_from_rfc3339("2025-03-02T14:03:00-08:00").unwrap();
+        assert_eq!(
+            format_time(&time, now, "local"),
+            "2025-03-02 14:03 local, 12 minutes ago"
+        );
+        assert_eq!(relative(TimeDelta::seconds(59)), "just now");
+        assert_eq!(relative(TimeDelta::seconds(60 * 60)), "1 hour ago");
+        assert_eq!(relative(TimeDelta::days(45)), "1 month ago");
+        assert_eq!(relative(TimeDelta::days(800)), "2 years ago");
+        assert_eq!(relative(TimeDelta::seconds(-300)), "in 5 minutes");
+    }
+
+    #[test]
+    fn format_table_ignores_colors() {
+        let output = Output {
+            format: OutputFormat::Text,
+            color: true,
+            verbosity: Verbosity::Normal,
+            template: None,
+            columns: None,
+            tabled: Arc::default(),
+            utc: false,
+        };
+        let rows = vec![
+            vec!["api".to_string(), output.status(&DeployStatus::Live)],
+            vec![
+                "worker".to_string(),
+                output.status(&DeployStatus::BuildFailed),
+            ],
+        ];
+        assert_eq!(
+            format_table(&["NAME", "STATUS"], &rows),
+            "NAME    STATUS\napi     \x1b[32mLive\x1b[0m\nworker  \x1b[31mBuild Failed\x1b[0m\n"
+        );
+    }
+}
@@ -0,0 +1,104 @@
+use crate::client::RenderClient;
+use crate::error::RenderError;
+use crate::pagination::Pages;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+#[derive(PartialEq, Deserialize, Serialize, Debug, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum OwnerType {
+    User,
+    Team,
+}
+
+impl fmt::Display for OwnerType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let type_str = match self {
+            OwnerType::User => "User",
+            OwnerType::Team => "Team",
+        };
+        write!(f, "{}", type_str)
+    }
+}
+
+/// A user or team workspace that services belong to
+#[derive(PartialEq, Deserialize, Serialize, Debug, Clone)]
+pub struct Owner {
+    /// `usr-...` or `tea-...`
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub email: Option<String>,
+    #[serde(rename = "type")]
+    pub owner_type: OwnerType,
+}
+
+/// The person an api key belongs to, keys reach every workspace they're in
+#[derive(PartialEq, Deserialize, Serialize, Debug, Clone)]
+pub struct User {
+    pub name: String,
+    pub email: String,
+}
+
+#[derive(PartialEq, Deserialize, Debug, Clone)]
+pub struct ListOwnersResponse {
+    pub cursor: String,
+    pub owner: Owner,
+}
+
+impl RenderClient {
+    /// Every user and team workspace the api key can reach
+    pub fn list_owners(&self) -> Pages<'_, ListOwnersResponse> {
+        Pages::new(self, "/owners".to_string(), vec![])
+    }
+
+    pub async fn get_owner(&self, owner_id: &str) -> Result<Owner, RenderError> {
+        self.get(&format!("/owners/{}", owner_id), &[]).await
+    }
+
+    /// Who the api key belongs to
+    pub async fn get_user(&self) -> Result<User, RenderError> {
+        self.get("/users", &[]).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_owners() {
+        let sample = r#"
+            [
+                {
+                    "cursor": "c1",
+                    "owner": {
+                        "id": "usr-cs67ufi3esus73b74a70",
+                        "name": "Francis",
+                        "email": "francis@example.com",
+                        "type": "user"
+                    }
+                },
+                {
+                    "cursor": "c2",
+                    "owner": {
+                        "id": "tea-cs67ufi3esus73b74a71",
+                        "name": "Platform",
+                        "email": "platform@example.com",
+                        "type": "team"
+                    }
+                }
+            ]
+        "#;
+        let owners: Vec<ListOwnersResponse> = serde_json::from_str(sample).unwrap();
+        assert_eq!(owners[0].owner.owner_type, OwnerType::User);
+        assert_eq!(owners[1].owner.name, "Platform");
+    }
+
+    #[test]
+    fn parse_user() {
+        let sample = r#"{"name": "Francis", "email": "francis@example.com"}"#;
+        let user: User = serde_json::from_str(sample).unwrap();
+        assert_eq!(user.email, "francis@example.com");
+    }
+}
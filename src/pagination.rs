@@ -0,0 +1,228 @@
+use crate::client::RenderClient;
+use crate::domains::{CustomDomain, ListCustomDomainsResponse};
+use crate::env_vars::{EnvVar, ListEnvVarsResponse};
+use crate::error::RenderError;
+use crate::events::{Event, ListEventsResponse};
+use crate::jobs::{Job, ListJobsResponse};
+use crate::models::{Deploy, ListDeploysResponse, ListServiceResponse, Service};
+use crate::owners::{ListOwnersResponse, Owner};
+use crate::postgres::{ListPostgresResponse, Postgres};
+use crate::projects::{Environment, ListEnvironmentsResponse, ListProjectsResponse, Project};
+use crate::secret_files::{ListSecretFilesResponse, SecretFile};
+use crate::static_sites::{HeaderRule, ListHeadersResponse, ListRoutesResponse, Route};
+use futures::stream::{self, BoxStream, Stream, StreamExt, TryStreamExt};
+use serde::de::DeserializeOwned;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// render's maximum page size
+const PAGE_SIZE: u32 = 100;
+
+/// An entry in one of render's list responses, every list endpoint wraps its items as
+/// `[{ "cursor": "...", "<item>": {...} }]`
+pub trait ListItem: DeserializeOwned {
+    type Item;
+    fn cursor(&self) -> &str;
+    fn into_item(self) -> Self::Item;
+}
+
+impl ListItem for ListServiceResponse {
+    type Item = Service;
+    fn cursor(&self) -> &str {
+        &self.cursor
+    }
+    fn into_item(self) -> Service {
+        self.service
+    }
+}
+
+impl ListItem for ListDeploysResponse {
+    type Item = Deploy;
+    fn cursor(&self) -> &str {
+        &self.cursor
+    }
+    fn into_item(self) -> Deploy {
+        self.deploy
+    }
+}
+
+impl ListItem for ListCustomDomainsResponse {
+    type Item = CustomDomain;
+    fn cursor(&self) -> &str {
+        &self.cursor
+    }
+    fn into_item(self) -> CustomDomain {
+        self.custom_domain
+    }
+}
+
+impl ListItem for ListEnvVarsResponse {
+    type Item = EnvVar;
+    fn cursor(&self) -> &str {
+        &self.cursor
+    }
+    fn into_item(self) -> EnvVar {
+        self.env_var
+    }
+}
+
+impl ListItem for ListEventsResponse {
+    type Item = Event;
+    fn cursor(&self) -> &str {
+        &self.cursor
+    }
+    fn into_item(self) -> Event {
+        self.event
+    }
+}
+
+impl ListItem for ListJobsResponse {
+    type Item = Job;
+    fn cursor(&self) -> &str {
+        &self.cursor
+    }
+    fn into_item(self) -> Job {
+        self.job
+    }
+}
+
+impl ListItem for ListOwnersResponse {
+    type Item = Owner;
+    fn cursor(&self) -> &str {
+        &self.cursor
+    }
+    fn into_item(self) -> Owner {
+        self.owner
+    }
+}
+
+impl ListItem for ListPostgresResponse {
+    type Item = Postgres;
+    fn cursor(&self) -> &str {
+        &self.cursor
+    }
+    fn into_item(self) -> Postgres {
+        self.postgres
+    }
+}
+
+impl ListItem for ListProjectsResponse {
+    type Item = Project;
+    fn cursor(&self) -> &str {
+        &self.cursor
+    }
+    fn into_item(self) -> Project {
+        self.project
+    }
+}
+
+impl ListItem for ListEnvironmentsResponse {
+    type Item = Environment;
+    fn cursor(&self) -> &str {
+        &self.cursor
+    }
+    fn into_item(self) -> Environment {
+        self.environment
+    }
+}
+
+impl ListItem for ListRoutesResponse {
+    type Item = Route;
+    fn cursor(&self) -> &str {
+        &self.cursor
+    }
+    fn into_item(self) -> Route {
+        self.route
+    }
+}
+
+impl ListItem for ListHeadersResponse {
+    type Item = HeaderRule;
+    fn cursor(&self) -> &str {
+        &self.cursor
+    }
+    fn into_item(self) -> HeaderRule {
+        self.headers
+    }
+}
+
+impl ListItem for ListSecretFilesResponse {
+    type Item = SecretFile;
+    fn cursor(&self) -> &str {
+        &self.cursor
+    }
+    fn into_item(self) -> SecretFile {
+        self.secret_file
+    }
+}
+
+/// Walks a list endpoint one page at a time, following the cursor of the last item until a page
+/// comes back short. It's a [`Stream`] of items, only fetching the next page once the items
+/// before it have been read.
+pub struct Pages<'a, R: ListItem> {
+    items: BoxStream<'a, Result<R::Item, RenderError>>,
+}
+
+/// Where we are in a list endpoint
+struct Cursor<'a> {
+    client: &'a RenderClient,
+    path: String,
+    query: Vec<(String, String)>,
+    cursor: Option<String>,
+    done: bool,
+}
+
+impl Cursor<'_> {
+    /// The next page and where to continue from, `None` once we've read the last page
+    async fn next_page<R: ListItem>(mut self) -> Result<Option<(Vec<R>, Self)>, RenderError> {
+        if self.done {
+            return Ok(None);
+        }
+        let mut query = self.query.clone();
+        query.push(("limit".into(), PAGE_SIZE.to_string()));
+        if let Some(cursor) = &self.cursor {
+            query.push(("cursor".into(), cursor.clone()));
+        }
+        let page: Vec<R> = self.client.get(&self.path, &query).await?;
+        if page.len() < PAGE_SIZE as usize {
+            self.done = true;
+        }
+        self.cursor = page.last().map(|entry| entry.cursor().to_string());
+        if self.cursor.is_none() {
+            self.done = true;
+        }
+        Ok(Some((page, self)))
+    }
+}
+
+impl<'a, R: ListItem + Send + 'a> Pages<'a, R>
+where
+    R::Item: Send,
+{
+    pub(crate) fn new(
+        client: &'a RenderClient,
+        path: String,
+        query: Vec<(String, String)>,
+    ) -> Self {
+        let cursor = Cursor {
+            client,
+            path,
+            query,
+            cursor: None,
+            done: false,
+        };
+        let pages = stream::try_unfold(cursor, Cursor::next_page::<R>)
+            .map_ok(|page| stream::iter(page.into_iter().map(|entry| Ok(entry.into_item()))));
+        Pages {
+            items: pages.try_flatten().boxed(),
+        }
+    }
+}
+
+impl<R: ListItem> Stream for Pages<'_, R> {
+    type Item = Result<R::Item, RenderError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.items.poll_next_unpin(cx)
+    }
+}
@@ -0,0 +1,202 @@
+use crate::output::Output;
+use futures::StreamExt;
+use render_deploy::{format_duration, Deploy, DeployStatus, RenderClient};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+use tokio::time::sleep;
+
+const SPINNER: [char; 10] = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+/// How often the spinner and elapsed time are redrawn
+const SPIN_INTERVAL: Duration = Duration::from_millis(100);
+/// How many successful deploys to take the typical time from
+const TYPICAL_DEPLOYS: usize = 5;
+/// How far back to look for them, past failed and canceled deploys
+const HISTORY_LIMIT: usize = 20;
+
+/// Progress while waiting on a deploy. At a terminal it's a spinner with the time so far and how
+/// long deploys usually take, redrawn in place, and a line for each new status. Anywhere else
+/// every check is printed as a line so CI logs keep a heartbeat.
+pub struct Progress {
+    output: Output,
+    live: bool,
+    started: SystemTime,
+    /// how long the service's deploys usually take
+    typical: Option<Duration>,
+    status: Mutex<DeployStatus>,
+}
+
+impl Progress {
+    /// Progress on one of a service's deploys, starting from the status already shown. `live` is
+    /// for whether anything else will be printing while we wait, logs would end up tangled with
+    /// the redrawn line.
+    pub fn new(output: Output, live: bool, deploy: &Deploy, typical: Option<Duration>) -> Self {
+        Progress {
+            live: live && output.is_terminal() && !output.is_quiet(),
+            output,
+            // from when the deploy started rather than us, we may have attached to it part way
+            started: humantime::parse_rfc3339_weak(&deploy.created_at)
+                .unwrap_or_else(|_| SystemTime::now()),
+            typical,
+            status: Mutex::new(deploy.status.clone()),
+        }
+    }
+
+    /// A check on the deploy
+    pub fn update(&self, deploy: &Deploy) {
+        if !self.live {
+            self.output
+                .info(format!("Status: {}", self.output.status(&deploy.status)));
+            return;
+        }
+        let mut status = self.status.lock().expect("status");
+        if *status != deploy.status {
+            self.output.redraw("");
+            self.output
+                .info(format!("Status: {}", self.output.status(&deploy.status)));
+            *status = deploy.status.clone();
+        }
+    }
+
+    /// Take the line away when [`Progress::spin`] was stopped without `stop` being set
+    pub fn clear(&self) {
+        if self.live {
+            self.output.redraw("");
+        }
+    }
+
+    /// Redraw the line until `stop` is set, then clear it
+    pub async fn spin(&self, stop: &AtomicBool) {
+        if !self.live {
+            return;
+        }
+        let mut frame = 0;
+        while !stop.load(Ordering::Relaxed) {
+            let line = progress_line(
+                frame,
+                &self.output.status(&self.status.lock().expect("status")),
+                self.started.elapsed().unwrap_or_default(),
+                self.typical,
+            );
+            self.output.redraw(&line);
+            frame += 1;
+            sleep(SPIN_INTERVAL).await;
+        }
+        self.output.redraw("");
+    }
+}
+
+/// The median time the service's last few successful deploys took, leaving out `deploy_id`.
+/// It's only for estimates so not being able to tell isn't an error.
+pub async fn typical_deploy_duration(
+    client: &RenderClient,
+    service_id: &str,
+    deploy_id: &str,
+) -> Option<Duration> {
+    let mut history = client.deploy_history(service_id).take(HISTORY_LIMIT);
+    let mut durations = vec![];
+    while let Some(Ok(deploy)) = history.next().await {
+        // deactivated deploys were live until they were replaced
+        let went_live = matches!(
+            deploy.status,
+            DeployStatus::Live | DeployStatus::Deactivated
+        );
+        if deploy.id != deploy_id && went_live {
+            durations.extend(deploy.duration());
+            if durations.len() == TYPICAL_DEPLOYS {
+                break;
+            }
+        }
+    }
+    median(durations)
+}
+
+fn median(mut durations: Vec<Duration>) -> Option<Duration> {
+    durations.sort();
+    let middle = durations.len() / 2;
+    match durations.len() {
+        0 => None,
+        len if len % 2 == 0 => Some((durations[middle - 1] + durations[middle]) / 2),
+        _ => Some(durations[middle]),
+    }
+}
+
+/// A `--timeout` with room for a deploy that takes twice as long as usual, in whole minutes
+pub fn suggested_timeout(typical: Duration) -> Duration {
+    Duration::from_secs((typical.as_secs() * 2).div_ceil(60) * 60)
+}
+
+fn progress_line(
+    frame: usize,
+    status: &str,
+    elapsed: Duration,
+    typical: Option<Duration>,
+) -> String {
+    let spinner = SPINNER[frame % SPINNER.len()];
+    match typical {
+        Some(typical) => format!(
+            "{} {}, typically ~{}, elapsed {}",
+            spinner,
+            status,
+            format_duration(typical),
+            format_duration(elapsed)
+        ),
+        None => format!(
+            "{} {}, elapsed {}",
+            spinner,
+            status,
+            format_duration(elapsed)
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn progress_lines() {
+        let status = "Build In Progress";
+        assert_eq!(
+            progress_line(0, status, Duration::from_secs(65), None),
+            "⠋ Build In Progress, elapsed 1m5s"
+        );
+        assert_eq!(
+            progress_line(
+                11,
+                status,
+                Duration::from_millis(130_500),
+                Some(Duration::from_secs(270))
+            ),
+            "⠙ Build In Progress, typically ~4m30s, elapsed 2m10s"
+        );
+    }
+
+    #[test]
+    fn typical_durations() {
+        let minutes = |minutes: &[u64]| {
+            minutes
+                .iter()
+                .map(|m| Duration::from_secs(m * 60))
+                .collect()
+        };
+        assert_eq!(median(vec![]), None);
+        assert_eq!(
+            median(minutes(&[9, 3, 4])),
+            Some(Duration::from_secs(4 * 60))
+        );
+        assert_eq!(
+            median(minutes(&[5, 3, 4, 30])),
+            Some(Duration::from_secs(270))
+        );
+
+        assert_eq!(
+            suggested_timeout(Duration::from_secs(270)),
+            Duration::from_secs(9 * 60)
+        );
+        assert_eq!(
+            suggested_timeout(Duration::from_secs(301)),
+            Duration::from_secs(11 * 60)
+        );
+    }
+}
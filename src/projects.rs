@@ -0,0 +1,123 @@
+use crate::client::{pick_service, RenderClient};
+use crate::error::RenderError;
+use crate::models::{ListServiceResponse, Service};
+use crate::pagination::Pages;
+use futures::TryStreamExt;
+use serde::{Deserialize, Serialize};
+
+/// A group of environments, eg an app's staging and production
+#[derive(PartialEq, Deserialize, Serialize, Debug, Clone)]
+pub struct Project {
+    /// `prj-...`
+    pub id: String,
+    pub name: String,
+    #[serde(rename = "ownerId")]
+    pub owner_id: String,
+    #[serde(rename = "environmentIds", default)]
+    pub environment_ids: Vec<String>,
+}
+
+/// The services and databases for one stage of a project
+#[derive(PartialEq, Deserialize, Serialize, Debug, Clone)]
+pub struct Environment {
+    /// `evm-...`
+    pub id: String,
+    pub name: String,
+    #[serde(rename = "projectId")]
+    pub project_id: String,
+    #[serde(rename = "serviceIds", default)]
+    pub service_ids: Vec<String>,
+    #[serde(rename = "databasesIds", default)]
+    pub database_ids: Vec<String>,
+    /// `protected` environments only let admins change them
+    #[serde(rename = "protectedStatus", default)]
+    pub protected_status: Option<String>,
+}
+
+impl Environment {
+    pub fn is_protected(&self) -> bool {
+        self.protected_status.as_deref() == Some("protected")
+    }
+}
+
+#[derive(PartialEq, Deserialize, Debug, Clone)]
+pub struct ListProjectsResponse {
+    pub cursor: String,
+    pub project: Project,
+}
+
+#[derive(PartialEq, Deserialize, Debug, Clone)]
+pub struct ListEnvironmentsResponse {
+    pub cursor: String,
+    pub environment: Environment,
+}
+
+impl RenderClient {
+    /// Every project the api key can reach, only the workspace's if
+    /// [`RenderClient::with_owner`] picked one
+    pub fn list_projects(&self) -> Pages<'_, ListProjectsResponse> {
+        Pages::new(self, "/projects".to_string(), self.owner_query())
+    }
+
+    pub async fn get_project(&self, project_id: &str) -> Result<Project, RenderError> {
+        self.get(&format!("/projects/{}", project_id), &[]).await
+    }
+
+    pub fn list_environments(&self, project_id: &str) -> Pages<'_, ListEnvironmentsResponse> {
+        Pages::new(
+            self,
+            "/environments".to_string(),
+            vec![("projectId".into(), project_id.into())],
+        )
+    }
+
+    /// Find the service named exactly `name` in one environment
+    pub async fn find_service_in_environment(
+        &self,
+        environment_id: &str,
+        name: &str,
+    ) -> Result<Service, RenderError> {
+        let services = Pages::<ListServiceResponse>::new(
+            self,
+            "/services".to_string(),
+            vec![
+                ("environmentId".into(), environment_id.into()),
+                ("name".into(), name.into()),
+            ],
+        )
+        .try_collect()
+        .await?;
+        pick_service(name, services)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_environments() {
+        let sample = r#"
+            [
+                {
+                    "cursor": "c1",
+                    "environment": {
+                        "id": "evm-cs67ufi3esus73b74a70",
+                        "name": "production",
+                        "projectId": "prj-cs67ufi3esus73b74a70",
+                        "serviceIds": ["srv-1", "srv-2"],
+                        "databasesIds": [],
+                        "redisIds": [],
+                        "envGroupIds": [],
+                        "protectedStatus": "protected",
+                        "networkIsolationEnabled": false
+                    }
+                }
+            ]
+        "#;
+        let environments: Vec<ListEnvironmentsResponse> = serde_json::from_str(sample).unwrap();
+        let environment = &environments[0].environment;
+        assert_eq!(environment.service_ids.len(), 2);
+        assert!(environment.is_protected());
+    }
+}
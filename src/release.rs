@@ -0,0 +1,159 @@
+use render_deploy::RenderError;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+/// Where `release` looks for steps when it isn't given a file
+pub const DEFAULT_RELEASE_FILE: &str = "render-release.toml";
+
+/// A release file, the services to deploy together and the order to deploy them in
+///
+/// ```toml
+/// [steps.migrate]
+/// service = "app-migrate"
+///
+/// [steps.api]
+/// service = "app-web"
+/// after = ["migrate"]
+///
+/// [steps.worker]
+/// service = "app-worker"
+/// after = ["api"]
+/// ```
+#[derive(Deserialize, Debug, Default)]
+#[serde(deny_unknown_fields)]
+pub struct ReleaseFile {
+    #[serde(default)]
+    pub steps: BTreeMap<String, Step>,
+}
+
+#[derive(Deserialize, Debug, Default, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Step {
+    /// name or id of the service, defaults to the step's name
+    pub service: Option<String>,
+    /// steps that have to be live before this one deploys
+    #[serde(default)]
+    pub after: Vec<String>,
+    /// commit to deploy instead of the head of the service's branch
+    pub commit: Option<String>,
+}
+
+impl Step {
+    pub fn service<'a>(&'a self, name: &'a str) -> &'a str {
+        self.service.as_deref().unwrap_or(name)
+    }
+}
+
+pub fn load(path: &Path) -> Result<ReleaseFile, RenderError> {
+    let contents = fs::read_to_string(path)
+        .map_err(|e| RenderError::Config(format!("Unable to read {}: {}", path.display(), e)))?;
+    toml::from_str(&contents)
+        .map_err(|e| RenderError::Config(format!("Invalid release file {}: {}", path.display(), e)))
+}
+
+impl ReleaseFile {
+    /// The steps grouped into stages, every step comes after the stages holding the steps it
+    /// depends on so each stage can deploy all at once
+    pub fn stages(&self) -> Result<Vec<Vec<(&str, &Step)>>, RenderError> {
+        for (name, step) in &self.steps {
+            if let Some(missing) = step
+                .after
+                .iter()
+                .find(|after| !self.steps.contains_key(*after))
+            {
+                return Err(RenderError::Config(format!(
+                    "Step {} comes after {} which isn't a step",
+                    name, missing
+                )));
+            }
+        }
+
+        let mut remaining: Vec<(&str, &Step)> = self
+            .steps
+            .iter()
+            .map(|(name, step)| (name.as_str(), step))
+            .collect();
+        let mut done: Vec<&str> = vec![];
+        let mut stages = vec![];
+        while !remaining.is_empty() {
+            let (ready, blocked): (Vec<_>, Vec<_>) = remaining
+                .into_iter()
+                .partition(|(_, step)| step.after.iter().all(|after| done.contains(&&**after)));
+            if ready.is_empty() {
+                let names: Vec<&str> = blocked.iter().map(|(name, _)| *name).collect();
+                return Err(RenderError::Config(format!(
+                    "Steps {} depend on each other",
+                    names.join(", ")
+                )));
+            }
+            done.extend(ready.iter().map(|(name, _)| *name));
+            stages.push(ready);
+            remaining = blocked;
+        }
+        Ok(stages)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stage_names(release: &ReleaseFile) -> Result<Vec<Vec<&str>>, RenderError> {
+        Ok(release
+            .stages()?
+            .into_iter()
+            .map(|stage| stage.into_iter().map(|(name, _)| name).collect())
+            .collect())
+    }
+
+    #[test]
+    fn stages_follow_dependencies() {
+        let release: ReleaseFile = toml::from_str(
+            r#"
+            [steps.worker]
+            after = ["api"]
+            [steps.api]
+            service = "app-web"
+            after = ["migrate"]
+            [steps.cron]
+            after = ["migrate"]
+            [steps.migrate]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            stage_names(&release).unwrap(),
+            vec![vec!["migrate"], vec!["api", "cron"], vec!["worker"]]
+        );
+        assert_eq!(release.steps["api"].service("api"), "app-web");
+        assert_eq!(release.steps["cron"].service("cron"), "cron");
+    }
+
+    #[test]
+    fn stages_reject_cycles_and_unknown_steps() {
+        let release: ReleaseFile = toml::from_str(
+            r#"
+            [steps.api]
+            after = ["worker"]
+            [steps.worker]
+            after = ["api"]
+            "#,
+        )
+        .unwrap();
+        assert!(matches!(
+            stage_names(&release),
+            Err(RenderError::Config(message)) if message == "Steps api, worker depend on each other"
+        ));
+
+        let release: ReleaseFile = toml::from_str(
+            r#"
+            [steps.api]
+            after = ["migrate"]
+            "#,
+        )
+        .unwrap();
+        assert!(stage_names(&release).is_err());
+    }
+}
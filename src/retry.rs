@@ -0,0 +1,173 @@
+use crate::error::RenderError;
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use reqwest::{Method, StatusCode};
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+
+/// How hard to retry requests that fail for reasons that might go away
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// retries after the first attempt, 0 disables retrying
+    pub max_retries: u32,
+    /// delay before the first retry, doubled for each one after that
+    pub base_delay: Duration,
+    /// cap on the backoff, a `Retry-After` from render is honored even if it's longer
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_retries: 4,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn none() -> Self {
+        RetryPolicy {
+            max_retries: 0,
+            ..RetryPolicy::default()
+        }
+    }
+
+    /// Whether attempt number `attempt` (starting at 0) of a `method` request that failed with
+    /// `error` should be retried. Requests that change things are only retried when render
+    /// definitely didn't act on them, so we never trigger two deploys.
+    pub fn should_retry(&self, method: &Method, error: &RenderError, attempt: u32) -> bool {
+        if attempt >= self.max_retries || !error.is_transient() {
+            return false;
+        }
+        if method == Method::GET || method == Method::HEAD {
+            return true;
+        }
+        match error {
+            RenderError::Http(e) => e.is_connect(),
+            RenderError::Api { status, .. } => *status == StatusCode::TOO_MANY_REQUESTS,
+            _ => false,
+        }
+    }
+
+    /// How long to sleep before retry number `attempt`, exponential with jitter
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let exponential = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.max_delay);
+        // half the delay plus a random amount up to the other half so a bunch of clients
+        // retrying at once spread out
+        let half = exponential / 2;
+        half + half.mul_f64(random_fraction())
+    }
+
+    /// The delay before retrying `error`, render's `Retry-After` if it sent one
+    pub fn delay(&self, error: &RenderError, attempt: u32) -> Duration {
+        match error {
+            RenderError::Api {
+                retry_after: Some(retry_after),
+                ..
+            } => *retry_after,
+            _ => self.backoff(attempt),
+        }
+    }
+}
+
+/// Seconds until the rate limit window resets, sent on every render response
+const RATELIMIT_RESET: &str = "ratelimit-reset";
+
+/// How long render wants us to back off, from a `Retry-After` header in seconds (we don't
+/// bother with the http date form) or failing that the `Ratelimit-Reset` header of a 429
+pub fn parse_retry_after(status: StatusCode, headers: &HeaderMap) -> Option<Duration> {
+    let seconds = |name| -> Option<Duration> {
+        headers
+            .get(name)?
+            .to_str()
+            .ok()?
+            .trim()
+            .parse::<u64>()
+            .ok()
+            .map(Duration::from_secs)
+    };
+    seconds(RETRY_AFTER.as_str()).or_else(|| {
+        if status == StatusCode::TOO_MANY_REQUESTS {
+            seconds(RATELIMIT_RESET)
+        } else {
+            None
+        }
+    })
+}
+
+/// Good enough randomness for jitter without pulling in a rng
+fn random_fraction() -> f64 {
+    let random = RandomState::new().build_hasher().finish();
+    (random >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    fn api_error(status: StatusCode) -> RenderError {
+        RenderError::Api {
+            status,
+            body: "".into(),
+            retry_after: None,
+            request: None,
+        }
+    }
+
+    #[test]
+    fn backoff_grows_and_is_capped() {
+        let policy = RetryPolicy::default();
+        for attempt in 0..10 {
+            let delay = policy.backoff(attempt);
+            let exponential = (policy.base_delay * 2u32.pow(attempt)).min(policy.max_delay);
+            assert!(delay >= exponential / 2, "{:?} {:?}", delay, exponential);
+            assert!(delay <= exponential);
+        }
+    }
+
+    #[test]
+    fn only_retries_posts_render_rejected() {
+        let policy = RetryPolicy::default();
+        let unavailable = api_error(StatusCode::SERVICE_UNAVAILABLE);
+        let rate_limited = api_error(StatusCode::TOO_MANY_REQUESTS);
+        assert!(policy.should_retry(&Method::GET, &unavailable, 0));
+        assert!(!policy.should_retry(&Method::POST, &unavailable, 0));
+        assert!(policy.should_retry(&Method::POST, &rate_limited, 0));
+        assert!(!policy.should_retry(&Method::GET, &rate_limited, policy.max_retries));
+        assert!(!policy.should_retry(&Method::GET, &api_error(StatusCode::NOT_FOUND), 0));
+    }
+
+    #[test]
+    fn parses_retry_after_seconds() {
+        let status = StatusCode::SERVICE_UNAVAILABLE;
+        let mut headers = HeaderMap::new();
+        assert_eq!(parse_retry_after(status, &headers), None);
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("12"));
+        assert_eq!(
+            parse_retry_after(status, &headers),
+            Some(Duration::from_secs(12))
+        );
+        headers.insert(
+            RETRY_AFTER,
+            HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"),
+        );
+        assert_eq!(parse_retry_after(status, &headers), None);
+    }
+
+    #[test]
+    fn falls_back_to_ratelimit_reset_when_rate_limited() {
+        let mut headers = HeaderMap::new();
+        headers.insert(RATELIMIT_RESET, HeaderValue::from_static("30"));
+        assert_eq!(
+            parse_retry_after(StatusCode::TOO_MANY_REQUESTS, &headers),
+            Some(Duration::from_secs(30))
+        );
+        assert_eq!(parse_retry_after(StatusCode::OK, &headers), None);
+    }
+}
@@ -0,0 +1,81 @@
+use crate::client::RenderClient;
+use crate::error::RenderError;
+use crate::pagination::Pages;
+use serde::{Deserialize, Serialize};
+
+/// A file render mounts in the service at `/etc/secrets/<name>`, for credentials that don't fit
+/// in an environment variable
+#[derive(PartialEq, Deserialize, Serialize, Debug, Clone)]
+pub struct SecretFile {
+    pub name: String,
+    pub content: String,
+}
+
+#[derive(PartialEq, Deserialize, Debug, Clone)]
+pub struct ListSecretFilesResponse {
+    pub cursor: String,
+    #[serde(rename = "secretFile")]
+    pub secret_file: SecretFile,
+}
+
+#[derive(Serialize)]
+struct SecretFileContent<'a> {
+    content: &'a str,
+}
+
+impl RenderClient {
+    /// Every secret file of a service
+    pub fn list_secret_files(&self, service_id: &str) -> Pages<'_, ListSecretFilesResponse> {
+        Pages::new(
+            self,
+            format!("/services/{}/secret-files", service_id),
+            vec![],
+        )
+    }
+
+    /// Add or replace one secret file, leaving the others alone. Like env vars this doesn't
+    /// deploy the change.
+    pub async fn put_secret_file(
+        &self,
+        service_id: &str,
+        name: &str,
+        content: &str,
+    ) -> Result<SecretFile, RenderError> {
+        let request = self
+            .client
+            .put(self.url(&format!("/services/{}/secret-files/{}", service_id, name)))
+            .json(&SecretFileContent { content });
+        self.send(request).await
+    }
+
+    pub async fn delete_secret_file(
+        &self,
+        service_id: &str,
+        name: &str,
+    ) -> Result<(), RenderError> {
+        let request = self
+            .client
+            .delete(self.url(&format!("/services/{}/secret-files/{}", service_id, name)));
+        self.send(request).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_secret_files() {
+        let sample = r#"
+            [
+                { "cursor": "c1", "secretFile": { "name": "gcp.json", "content": "{\"type\": \"service_account\"}" } }
+            ]
+        "#;
+        let files: Vec<ListSecretFilesResponse> = serde_json::from_str(sample).unwrap();
+        assert_eq!(files[0].secret_file.name, "gcp.json");
+        assert_eq!(
+            files[0].secret_file.content,
+            r#"{"type": "service_account"}"#
+        );
+    }
+}
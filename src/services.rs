@@ -0,0 +1,335 @@
+use crate::client::RenderClient;
+use crate::env_vars::EnvVar;
+use crate::error::RenderError;
+use crate::models::{serialize_yes_no, Service, ServiceType};
+use serde::{Deserialize, Serialize};
+
+/// Body of `POST /v1/services`
+#[derive(Serialize, Debug, Clone)]
+pub struct CreateServiceRequest {
+    #[serde(rename = "type")]
+    pub service_type: ServiceType,
+    pub name: String,
+    /// the workspace the service goes in, `usr-...` or `tea-...`
+    #[serde(rename = "ownerId")]
+    pub owner_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub repo: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub branch: Option<String>,
+    #[serde(rename = "autoDeploy", serialize_with = "serialize_yes_no")]
+    pub auto_deploy: bool,
+    #[serde(rename = "envVars", skip_serializing_if = "Vec::is_empty")]
+    pub env_vars: Vec<EnvVar>,
+    #[serde(rename = "serviceDetails")]
+    pub service_details: ServiceDetailsRequest,
+}
+
+/// The type specific half of a [`CreateServiceRequest`], render rejects fields that don't apply
+/// to the type so leave them `None`
+#[derive(Serialize, Debug, Default, Clone)]
+pub struct ServiceDetailsRequest {
+    /// eg `node`, `python` or `docker`, not used by static sites
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub runtime: Option<String>,
+    /// eg `starter` or `standard`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub plan: Option<String>,
+    /// eg `oregon` or `frankfurt`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub region: Option<String>,
+    #[serde(rename = "numInstances", skip_serializing_if = "Option::is_none")]
+    pub num_instances: Option<u32>,
+    /// build and start commands for services with a runtime
+    #[serde(rename = "envSpecificDetails", skip_serializing_if = "Option::is_none")]
+    pub env_specific_details: Option<EnvSpecificDetails>,
+    /// static sites only
+    #[serde(rename = "buildCommand", skip_serializing_if = "Option::is_none")]
+    pub build_command: Option<String>,
+    /// static sites only, the directory to publish
+    #[serde(rename = "publishPath", skip_serializing_if = "Option::is_none")]
+    pub publish_path: Option<String>,
+}
+
+#[derive(Serialize, Debug, Default, Clone)]
+pub struct EnvSpecificDetails {
+    #[serde(rename = "buildCommand", skip_serializing_if = "Option::is_none")]
+    pub build_command: Option<String>,
+    #[serde(rename = "startCommand", skip_serializing_if = "Option::is_none")]
+    pub start_command: Option<String>,
+}
+
+#[derive(PartialEq, Deserialize, Serialize, Debug, Clone)]
+pub struct CreateServiceResponse {
+    pub service: Service,
+    /// the first deploy, render starts one straight away for services built from a repo
+    #[serde(rename = "deployId", default)]
+    pub deploy_id: Option<String>,
+}
+
+/// How a service is built and run, the parts `service update` can change and enough to create
+/// a copy of it
+#[derive(PartialEq, Serialize, Debug, Clone, Default)]
+pub struct ServiceSettings {
+    pub branch: Option<String>,
+    #[serde(rename = "buildCommand")]
+    pub build_command: Option<String>,
+    /// static sites don't have one
+    #[serde(rename = "startCommand")]
+    pub start_command: Option<String>,
+    pub plan: Option<String>,
+    /// static sites don't have one
+    pub runtime: Option<String>,
+    pub region: Option<String>,
+    /// static sites only
+    #[serde(rename = "publishPath")]
+    pub publish_path: Option<String>,
+    /// cron jobs only
+    pub schedule: Option<String>,
+    #[serde(rename = "numInstances")]
+    pub num_instances: Option<u32>,
+    /// the persistent disk mounted in the service, see [`RenderClient::get_disk`]
+    #[serde(rename = "diskId")]
+    pub disk_id: Option<String>,
+}
+
+/// Just enough of a service to read its settings, static sites keep their build command in
+/// `serviceDetails` and everything else in `envSpecificDetails`
+#[derive(Deserialize)]
+struct ServiceWithSettings {
+    #[serde(default)]
+    branch: Option<String>,
+    #[serde(rename = "serviceDetails", default)]
+    service_details: Option<SettingsDetails>,
+}
+
+#[derive(Deserialize, Default)]
+struct SettingsDetails {
+    #[serde(default)]
+    plan: Option<String>,
+    #[serde(default)]
+    runtime: Option<String>,
+    #[serde(default)]
+    region: Option<String>,
+    #[serde(rename = "publishPath", default)]
+    publish_path: Option<String>,
+    #[serde(rename = "buildCommand", default)]
+    build_command: Option<String>,
+    #[serde(default)]
+    schedule: Option<String>,
+    #[serde(rename = "numInstances", default)]
+    num_instances: Option<u32>,
+    #[serde(default)]
+    disk: Option<DiskId>,
+    #[serde(rename = "envSpecificDetails", default)]
+    env_specific_details: Option<SettingsEnvSpecificDetails>,
+}
+
+#[derive(Deserialize)]
+struct DiskId {
+    id: String,
+}
+
+#[derive(Deserialize, Default)]
+struct SettingsEnvSpecificDetails {
+    #[serde(rename = "buildCommand", default)]
+    build_command: Option<String>,
+    #[serde(rename = "startCommand", default)]
+    start_command: Option<String>,
+}
+
+impl From<ServiceWithSettings> for ServiceSettings {
+    fn from(service: ServiceWithSettings) -> Self {
+        let details = service.service_details.unwrap_or_default();
+        let env_specific = details.env_specific_details.unwrap_or_default();
+        ServiceSettings {
+            branch: service.branch,
+            build_command: env_specific.build_command.or(details.build_command),
+            start_command: env_specific.start_command,
+            plan: details.plan,
+            runtime: details.runtime,
+            region: details.region,
+            publish_path: details.publish_path,
+            schedule: details.schedule,
+            num_instances: details.num_instances,
+            disk_id: details.disk.map(|disk| disk.id),
+        }
+    }
+}
+
+/// Body of `PATCH /v1/services/{id}`, only what's given is changed
+#[derive(Serialize, Debug, Default, Clone)]
+pub struct UpdateServiceRequest {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub branch: Option<String>,
+    /// deploy on every push to the branch
+    #[serde(
+        rename = "autoDeploy",
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "serialize_optional_yes_no"
+    )]
+    pub auto_deploy: Option<bool>,
+    #[serde(rename = "serviceDetails", skip_serializing_if = "Option::is_none")]
+    pub service_details: Option<ServiceDetailsRequest>,
+}
+
+fn serialize_optional_yes_no<S>(value: &Option<bool>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::ser::Serializer,
+{
+    match value {
+        Some(value) => serialize_yes_no(value, serializer),
+        None => serializer.serialize_none(),
+    }
+}
+
+impl RenderClient {
+    pub async fn get_service_settings(
+        &self,
+        service_id: &str,
+    ) -> Result<ServiceSettings, RenderError> {
+        let service: ServiceWithSettings =
+            self.get(&format!("/services/{}", service_id), &[]).await?;
+        Ok(service.into())
+    }
+
+    pub async fn update_service(
+        &self,
+        service_id: &str,
+        request: &UpdateServiceRequest,
+    ) -> Result<Service, RenderError> {
+        let request = self
+            .client
+            .patch(self.url(&format!("/services/{}", service_id)))
+            .json(request);
+        self.send(request).await
+    }
+
+    /// Turn deploying on every push to the service's branch on or off
+    pub async fn set_auto_deploy(
+        &self,
+        service_id: &str,
+        auto_deploy: bool,
+    ) -> Result<Service, RenderError> {
+        let request = UpdateServiceRequest {
+            auto_deploy: Some(auto_deploy),
+            ..Default::default()
+        };
+        self.update_service(service_id, &request).await
+    }
+
+    /// Delete a service along with its deploys, logs and disks, there's no undoing it
+    pub async fn delete_service(&self, service_id: &str) -> Result<(), RenderError> {
+        let request = self
+            .client
+            .delete(self.url(&format!("/services/{}", service_id)));
+        self.send(request).await
+    }
+
+    pub async fn create_service(
+        &self,
+        request: &CreateServiceRequest,
+    ) -> Result<CreateServiceResponse, RenderError> {
+        let request = self.client.post(self.url("/services")).json(request);
+        self.send(request).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn parse_settings() {
+        let web: ServiceWithSettings = serde_json::from_str(
+            r#"{
+                "branch": "main",
+                "serviceDetails": {
+                    "plan": "starter",
+                    "runtime": "node",
+                    "region": "oregon",
+                    "numInstances": 2,
+                    "disk": { "id": "dsk-1", "name": "data" },
+                    "envSpecificDetails": { "buildCommand": "npm ci", "startCommand": "npm start" }
+                }
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            ServiceSettings::from(web),
+            ServiceSettings {
+                branch: Some("main".into()),
+                build_command: Some("npm ci".into()),
+                start_command: Some("npm start".into()),
+                plan: Some("starter".into()),
+                runtime: Some("node".into()),
+                region: Some("oregon".into()),
+                publish_path: None,
+                schedule: None,
+                num_instances: Some(2),
+                disk_id: Some("dsk-1".into()),
+            }
+        );
+
+        let site: ServiceWithSettings = serde_json::from_str(
+            r#"{ "branch": "main", "serviceDetails": { "buildCommand": "npm run build" } }"#,
+        )
+        .unwrap();
+        let site = ServiceSettings::from(site);
+        assert_eq!(site.build_command.as_deref(), Some("npm run build"));
+        assert_eq!(site.start_command, None);
+    }
+
+    #[test]
+    fn update_request_body() {
+        let request = UpdateServiceRequest {
+            auto_deploy: Some(false),
+            ..Default::default()
+        };
+        assert_eq!(
+            serde_json::to_value(&request).unwrap(),
+            json!({ "autoDeploy": "no" })
+        );
+    }
+
+    #[test]
+    fn create_request_body() {
+        let request = CreateServiceRequest {
+            service_type: ServiceType::WebService,
+            name: "api-pr-42".into(),
+            owner_id: "tea-1".into(),
+            repo: Some("https://github.com/reconbot/render-deploy".into()),
+            branch: Some("pr-42".into()),
+            auto_deploy: false,
+            env_vars: vec![EnvVar {
+                key: "LOG_LEVEL".into(),
+                value: "debug".into(),
+            }],
+            service_details: ServiceDetailsRequest {
+                runtime: Some("node".into()),
+                env_specific_details: Some(EnvSpecificDetails {
+                    build_command: Some("npm ci".into()),
+                    start_command: Some("npm start".into()),
+                }),
+                ..Default::default()
+            },
+        };
+        assert_eq!(
+            serde_json::to_value(&request).unwrap(),
+            json!({
+                "type": "web_service",
+                "name": "api-pr-42",
+                "ownerId": "tea-1",
+                "repo": "https://github.com/reconbot/render-deploy",
+                "branch": "pr-42",
+                "autoDeploy": "no",
+                "envVars": [{ "key": "LOG_LEVEL", "value": "debug" }],
+                "serviceDetails": {
+                    "runtime": "node",
+                    "envSpecificDetails": { "buildCommand": "npm ci", "startCommand": "npm start" }
+                }
+            })
+        );
+    }
+}
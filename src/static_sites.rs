@@ -0,0 +1,171 @@
+use crate::client::RenderClient;
+use crate::error::RenderError;
+use crate::pagination::Pages;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+#[derive(PartialEq, Deserialize, Serialize, Debug, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum RouteType {
+    /// send the browser to the destination
+    Redirect,
+    /// serve the destination from the source's url
+    Rewrite,
+}
+
+impl fmt::Display for RouteType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let type_str = match self {
+            RouteType::Redirect => "redirect",
+            RouteType::Rewrite => "rewrite",
+        };
+        write!(f, "{}", type_str)
+    }
+}
+
+/// A redirect or rewrite rule of a static site
+#[derive(PartialEq, Deserialize, Serialize, Debug, Clone)]
+pub struct Route {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub route_type: RouteType,
+    pub source: String,
+    pub destination: String,
+    /// rules are tried lowest priority first
+    pub priority: u32,
+}
+
+#[derive(PartialEq, Deserialize, Debug, Clone)]
+pub struct ListRoutesResponse {
+    pub cursor: String,
+    pub route: Route,
+}
+
+/// Body of `POST /v1/services/{id}/routes`
+#[derive(Serialize, Debug, Clone)]
+pub struct AddRouteRequest {
+    #[serde(rename = "type")]
+    pub route_type: RouteType,
+    pub source: String,
+    pub destination: String,
+    /// after every other route when left out
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub priority: Option<u32>,
+}
+
+/// A response header a static site adds to every path matching `path`
+#[derive(PartialEq, Deserialize, Serialize, Debug, Clone)]
+pub struct HeaderRule {
+    pub id: String,
+    pub path: String,
+    pub name: String,
+    pub value: String,
+}
+
+#[derive(PartialEq, Deserialize, Debug, Clone)]
+pub struct ListHeadersResponse {
+    pub cursor: String,
+    pub headers: HeaderRule,
+}
+
+#[derive(Serialize)]
+struct AddHeaderRequest<'a> {
+    path: &'a str,
+    name: &'a str,
+    value: &'a str,
+}
+
+impl RenderClient {
+    pub fn list_routes(&self, service_id: &str) -> Pages<'_, ListRoutesResponse> {
+        Pages::new(self, format!("/services/{}/routes", service_id), vec![])
+    }
+
+    pub async fn add_route(
+        &self,
+        service_id: &str,
+        route: &AddRouteRequest,
+    ) -> Result<Route, RenderError> {
+        let request = self
+            .client
+            .post(self.url(&format!("/services/{}/routes", service_id)))
+            .json(route);
+        self.send(request).await
+    }
+
+    pub async fn delete_route(&self, service_id: &str, route_id: &str) -> Result<(), RenderError> {
+        let request = self
+            .client
+            .delete(self.url(&format!("/services/{}/routes/{}", service_id, route_id)));
+        self.send(request).await
+    }
+
+    pub fn list_header_rules(&self, service_id: &str) -> Pages<'_, ListHeadersResponse> {
+        Pages::new(self, format!("/services/{}/headers", service_id), vec![])
+    }
+
+    pub async fn add_header_rule(
+        &self,
+        service_id: &str,
+        path: &str,
+        name: &str,
+        value: &str,
+    ) -> Result<HeaderRule, RenderError> {
+        let request = self
+            .client
+            .post(self.url(&format!("/services/{}/headers", service_id)))
+            .json(&AddHeaderRequest { path, name, value });
+        self.send(request).await
+    }
+
+    pub async fn delete_header_rule(
+        &self,
+        service_id: &str,
+        header_id: &str,
+    ) -> Result<(), RenderError> {
+        let request = self
+            .client
+            .delete(self.url(&format!("/services/{}/headers/{}", service_id, header_id)));
+        self.send(request).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_routes_and_headers() {
+        let routes = r#"
+            [
+                {
+                    "cursor": "c1",
+                    "route": {
+                        "id": "rdr-1",
+                        "type": "rewrite",
+                        "source": "/*",
+                        "destination": "/index.html",
+                        "priority": 0
+                    }
+                }
+            ]
+        "#;
+        let routes: Vec<ListRoutesResponse> = serde_json::from_str(routes).unwrap();
+        assert_eq!(routes[0].route.route_type, RouteType::Rewrite);
+
+        let headers = r#"
+            [
+                {
+                    "cursor": "c1",
+                    "headers": {
+                        "id": "hdr-1",
+                        "path": "/assets/*",
+                        "name": "Cache-Control",
+                        "value": "public, max-age=31536000"
+                    }
+                }
+            ]
+        "#;
+        let headers: Vec<ListHeadersResponse> = serde_json::from_str(headers).unwrap();
+        assert_eq!(headers[0].headers.name, "Cache-Control");
+    }
+}
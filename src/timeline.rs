@@ -0,0 +1,154 @@
+use futures::TryStreamExt;
+use render_deploy::{format_duration, Deploy, Event, EventType, RenderClient, RenderError};
+use std::time::{Duration, SystemTime};
+
+/// How long after a deploy finishes Render might still log its last events
+const EVENT_LAG: Duration = Duration::from_secs(60);
+
+/// How long part of a deploy took
+#[derive(Debug, PartialEq)]
+pub struct Phase {
+    pub name: &'static str,
+    pub duration: Duration,
+}
+
+/// How long a finished deploy spent queued, building, running its pre-deploy command and
+/// updating, from the service's events
+pub async fn phases(
+    client: &RenderClient,
+    service_id: &str,
+    deploy: &Deploy,
+) -> Result<Vec<Phase>, RenderError> {
+    let events: Vec<Event> = client
+        .list_events(service_id, Some(&deploy.created_at))
+        .try_collect()
+        .await?;
+    Ok(from_events(deploy, &events))
+}
+
+/// `queued 4s, build 3m12s, update 48s`
+pub fn describe(phases: &[Phase]) -> String {
+    phases
+        .iter()
+        .map(|phase| format!("{} {}", phase.name, format_duration(phase.duration)))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn from_events(deploy: &Deploy, events: &[Event]) -> Vec<Phase> {
+    let parse = |timestamp: &str| humantime::parse_rfc3339(timestamp).ok();
+    let (Some(created), Some(finished)) = (
+        parse(&deploy.created_at),
+        deploy.finished_at.as_deref().and_then(parse),
+    ) else {
+        return vec![];
+    };
+    // the service's other deploys can only be told apart by time when there's no deploy id
+    let ours: Vec<(EventType, SystemTime)> = events
+        .iter()
+        .filter(|event| {
+            event.details["deployId"]
+                .as_str()
+                .is_none_or(|id| id == deploy.id)
+        })
+        .filter_map(|event| Some((event.event_type, parse(&event.timestamp)?)))
+        .filter(|(_, at)| created <= *at && *at <= finished + EVENT_LAG)
+        .collect();
+    let first = |wanted: EventType| {
+        ours.iter()
+            .filter(|(event_type, _)| *event_type == wanted)
+            .map(|(_, at)| *at)
+            .min()
+    };
+    let build = (first(EventType::BuildStarted), first(EventType::BuildEnded));
+    let pre_deploy = (
+        first(EventType::PreDeployStarted),
+        first(EventType::PreDeployEnded),
+    );
+    let updated = first(EventType::DeployEnded).unwrap_or(finished);
+
+    let mut phases = vec![];
+    let mut phase = |name, start: Option<SystemTime>, end: Option<SystemTime>| {
+        if let Some(duration) = start
+            .zip(end)
+            .and_then(|(start, end)| end.duration_since(start).ok())
+        {
+            phases.push(Phase { name, duration });
+        }
+    };
+    phase("queued", Some(created), build.0.or(pre_deploy.0));
+    phase("build", build.0, build.1);
+    phase("pre-deploy", pre_deploy.0, pre_deploy.1);
+    // image deploys go straight to updating
+    let built = match deploy.image {
+        Some(_) => Some(created),
+        None => build.1,
+    };
+    phase("update", pre_deploy.1.or(built), Some(updated));
+    phases
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures;
+    use render_deploy::DeployStatus;
+    use serde_json::json;
+
+    fn event(event_type: EventType, timestamp: &str, deploy_id: Option<&str>) -> Event {
+        Event {
+            id: "evt-1".into(),
+            timestamp: timestamp.into(),
+            service_id: "srv-1".into(),
+            event_type,
+            details: deploy_id
+                .map(|id| json!({ "deployId": id }))
+                .unwrap_or(json!({})),
+        }
+    }
+
+    #[test]
+    fn phases_from_events() {
+        let deploy = Deploy {
+            updated_at: "2024-10-14T02:22:00Z".into(),
+            finished_at: Some("2024-10-14T02:22:00Z".into()),
+            ..fixtures::deploy("dep-1", DeployStatus::Live)
+        };
+        let events = vec![
+            event(
+                EventType::DeployEnded,
+                "2024-10-14T02:22:01Z",
+                Some("dep-1"),
+            ),
+            event(
+                EventType::PreDeployEnded,
+                "2024-10-14T02:21:10Z",
+                Some("dep-1"),
+            ),
+            event(
+                EventType::PreDeployStarted,
+                "2024-10-14T02:20:50Z",
+                Some("dep-1"),
+            ),
+            event(EventType::BuildEnded, "2024-10-14T02:20:47Z", None),
+            // someone else's
+            event(
+                EventType::BuildStarted,
+                "2024-10-14T02:17:36Z",
+                Some("dep-0"),
+            ),
+            event(EventType::BuildStarted, "2024-10-14T02:17:39Z", None),
+            event(
+                EventType::DeployStarted,
+                "2024-10-14T02:17:35Z",
+                Some("dep-1"),
+            ),
+        ];
+        let phases = from_events(&deploy, &events);
+        assert_eq!(
+            describe(&phases),
+            "queued 4s, build 3m8s, pre-deploy 20s, update 51s"
+        );
+        assert_eq!(from_events(&deploy, &[]), vec![]);
+    }
+}
@@ -0,0 +1,239 @@
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::Sha256;
+use std::collections::HashMap;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+
+/// Deliveries older or newer than this are refused, so a captured one can't be replayed later,
+/// and [`Deliveries`] refuses one replayed sooner
+const TOLERANCE: Duration = Duration::from_secs(5 * 60);
+/// Render's payloads are small, anything bigger isn't from render
+const MAX_BODY: usize = 1024 * 1024;
+
+/// A webhook delivery from render
+///
+/// ```json
+/// { "type": "deploy_ended", "timestamp": "2024-10-14T02:19:38Z",
+///   "data": { "id": "evt-1", "serviceId": "srv-1", "serviceName": "api", "status": "succeeded" } }
+/// ```
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct WebhookEvent {
+    #[serde(rename = "type")]
+    pub event_type: String,
+    pub timestamp: String,
+    #[serde(default)]
+    pub data: Value,
+}
+
+impl WebhookEvent {
+    pub fn service_id(&self) -> Option<&str> {
+        self.data.get("serviceId").and_then(Value::as_str)
+    }
+
+    pub fn service_name(&self) -> Option<&str> {
+        self.data.get("serviceName").and_then(Value::as_str)
+    }
+}
+
+/// Check a delivery's `webhook-signature` header, render signs them the
+/// [Standard Webhooks](https://www.standardwebhooks.com/) way with the `whsec_...` secret from
+/// the dashboard
+pub fn verify(
+    secret: &str,
+    id: &str,
+    timestamp: &str,
+    signatures: &str,
+    body: &[u8],
+    now: SystemTime,
+) -> Result<(), String> {
+    let sent = timestamp
+        .parse::<u64>()
+        .map(|seconds| UNIX_EPOCH + Duration::from_secs(seconds))
+        .map_err(|_| format!("webhook-timestamp {} isn't a unix timestamp", timestamp))?;
+    let skew = now.duration_since(sent).unwrap_or_else(|e| e.duration());
+    if skew > TOLERANCE {
+        return Err(format!(
+            "webhook-timestamp {} is too far from now",
+            timestamp
+        ));
+    }
+
+    let key = STANDARD
+        .decode(secret.strip_prefix("whsec_").unwrap_or(secret))
+        .map_err(|_| {
+            "the webhook secret should be the whsec_... one from the dashboard".to_string()
+        })?;
+    let signed = [id.as_bytes(), b".", timestamp.as_bytes(), b".", body].concat();
+    // there can be several, space separated, while a secret is being rotated
+    for signature in signatures.split(' ') {
+        let Some(signature) = signature.strip_prefix("v1,") else {
+            continue;
+        };
+        let Ok(signature) = STANDARD.decode(signature) else {
+            continue;
+        };
+        let mut mac = Hmac::<Sha256>::new_from_slice(&key).expect("hmac takes keys of any size");
+        mac.update(&signed);
+        if mac.verify_slice(&signature).is_ok() {
+            return Ok(());
+        }
+    }
+    Err("webhook-signature doesn't match, is the secret right?".into())
+}
+
+/// The `webhook-id`s of deliveries we've taken, kept for as long as [`verify`] would take them
+/// again
+#[derive(Debug, Default)]
+pub struct Deliveries {
+    seen: HashMap<String, SystemTime>,
+}
+
+impl Deliveries {
+    /// Whether this is the first we've seen of delivery `id`. A delivery can be timestamped up to
+    /// [`TOLERANCE`] either side of when it came, so its id is remembered for twice that.
+    pub fn first(&mut self, id: &str, now: SystemTime) -> bool {
+        self.seen.retain(|_, seen| {
+            now.duration_since(*seen)
+                .map_or(true, |age| age <= 2 * TOLERANCE)
+        });
+        self.seen.insert(id.to_string(), now).is_none()
+    }
+}
+
+/// An http request, only as much of http/1.1 as webhook deliveries use
+#[derive(Debug)]
+pub struct Request {
+    pub method: String,
+    pub path: String,
+    /// names lowercased
+    pub headers: HashMap<String, String>,
+    pub body: Vec<u8>,
+}
+
+impl Request {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(name).map(String::as_str)
+    }
+}
+
+pub async fn read_request(stream: &mut TcpStream) -> Result<Request, String> {
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+    reader
+        .read_line(&mut line)
+        .await
+        .map_err(|e| e.to_string())?;
+    let mut parts = line.split_whitespace();
+    let (Some(method), Some(path)) = (parts.next(), parts.next()) else {
+        return Err(format!("bad request line {:?}", line.trim_end()));
+    };
+    let (method, path) = (method.to_string(), path.to_string());
+
+    let mut headers = HashMap::new();
+    loop {
+        line.clear();
+        reader
+            .read_line(&mut line)
+            .await
+            .map_err(|e| e.to_string())?;
+        let header = line.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            headers.insert(name.trim().to_lowercase(), value.trim().to_string());
+        }
+    }
+
+    let length: usize = match headers.get("content-length") {
+        Some(length) => length
+            .parse()
+            .map_err(|_| format!("bad content-length {}", length))?,
+        None => 0,
+    };
+    if length > MAX_BODY {
+        return Err(format!("{} bytes is too big for a webhook", length));
+    }
+    let mut body = vec![0; length];
+    reader
+        .read_exact(&mut body)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(Request {
+        method,
+        path,
+        headers,
+        body,
+    })
+}
+
+pub async fn respond(stream: &mut TcpStream, status: u16, reason: &str) -> std::io::Result<()> {
+    let response = format!(
+        "HTTP/1.1 {} {}\r\ncontent-type: text/plain\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+        status,
+        reason,
+        reason.len(),
+        reason
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // from the Standard Webhooks test vectors
+    const SECRET: &str = "whsec_MfKQ9r8GKYqrTwjUPD8ILPZIo2LaLaSw";
+    const ID: &str = "msg_p5jXN8AQM9LWM0D4loKWxJek";
+    const TIMESTAMP: &str = "1614265330";
+    const BODY: &[u8] = br#"{"test": 2432232314}"#;
+    const SIGNATURE: &str = "v1,g0hM9SsE+OTPJTGt/tmIKtSyZlE3uFJELVlNIOLJ1OE=";
+
+    fn at(seconds: u64) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(seconds)
+    }
+
+    #[test]
+    fn verify_signatures() {
+        let now = at(1614265330 + 10);
+        assert_eq!(verify(SECRET, ID, TIMESTAMP, SIGNATURE, BODY, now), Ok(()));
+        // a rotated secret's signature alongside
+        let both = format!("v1,bm90IGl0 {}", SIGNATURE);
+        assert_eq!(verify(SECRET, ID, TIMESTAMP, &both, BODY, now), Ok(()));
+        assert!(verify(SECRET, ID, TIMESTAMP, SIGNATURE, b"{}", now).is_err());
+        assert!(verify(SECRET, "msg_other", TIMESTAMP, SIGNATURE, BODY, now).is_err());
+        assert!(verify(SECRET, ID, TIMESTAMP, SIGNATURE, BODY, at(1614265330 + 600)).is_err());
+        assert!(verify(SECRET, ID, TIMESTAMP, SIGNATURE, BODY, at(1614265330 - 600)).is_err());
+    }
+
+    #[test]
+    fn refuse_replays() {
+        let mut deliveries = Deliveries::default();
+        assert!(deliveries.first(ID, at(1000)));
+        assert!(!deliveries.first(ID, at(1000 + 60)));
+        assert!(deliveries.first("msg_other", at(1000 + 60)));
+        // long after verify would have refused it anyway
+        assert!(deliveries.first(ID, at(1000 + 60 + 2 * TOLERANCE.as_secs() + 1)));
+    }
+
+    #[test]
+    fn parse_event() {
+        let event: WebhookEvent = serde_json::from_str(
+            r#"{
+                "type": "deploy_ended",
+                "timestamp": "2024-10-14T02:19:38Z",
+                "data": { "id": "evt-1", "serviceId": "srv-1", "serviceName": "api", "status": "succeeded" }
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(event.event_type, "deploy_ended");
+        assert_eq!(event.service_id(), Some("srv-1"));
+        assert_eq!(event.service_name(), Some("api"));
+    }
+}
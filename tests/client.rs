@@ -0,0 +1,577 @@
+use futures::TryStreamExt;
+use render_deploy::{
+    ClearCache, DeployHook, DeployStatus, LogsQuery, RenderClient, RenderError, RequestEvent,
+    RetryPolicy, TriggerDeployRequest, WaitOptions,
+};
+use serde_json::{json, Value};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use wiremock::matchers::{body_json, header, method, path, query_param};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+fn service(id: &str, name: &str) -> Value {
+    json!({
+        "id": id,
+        "name": name,
+        "type": "web_service",
+        "ownerId": "tea-cs67ufi3esus73b74a70",
+        "branch": "main",
+        "dashboardUrl": format!("https://dashboard.render.com/web/{}", id),
+        "autoDeploy": "no",
+        "repo": "https://github.com/reconbot/render-deploy",
+        "updatedAt": "2024-10-14T02:17:35.868638Z",
+        "createdAt": "2024-10-14T02:17:35.868638Z"
+    })
+}
+
+fn deploy(id: &str, status: &str) -> Value {
+    json!({
+        "id": id,
+        "commit": {
+            "id": "b2be9cf9e3188d00f58ef18a5904528993faeaa2",
+            "message": "render uses sigterm",
+            "createdAt": "2024-10-11T20:02:45Z"
+        },
+        "status": status,
+        "trigger": "api",
+        "createdAt": "2024-10-14T02:17:35.868638Z",
+        "updatedAt": "2024-10-14T02:17:35.868638Z",
+        "finishedAt": null
+    })
+}
+
+fn client(server: &MockServer) -> RenderClient {
+    RenderClient::new("rnd_test")
+        .unwrap()
+        .with_base_url(&server.uri())
+        .with_retry(RetryPolicy {
+            max_retries: 2,
+            base_delay: Duration::from_millis(1),
+            max_delay: Duration::from_millis(5),
+        })
+}
+
+fn quick_wait(timeout: Duration) -> WaitOptions {
+    WaitOptions {
+        timeout,
+        poll_interval: Duration::from_millis(5),
+    }
+}
+
+#[tokio::test]
+async fn find_service_picks_the_exact_name() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/services"))
+        .and(query_param("name", "api"))
+        .and(header("authorization", "Bearer rnd_test"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+            { "cursor": "c1", "service": service("srv-2", "api-staging") },
+            { "cursor": "c2", "service": service("srv-1", "api") },
+        ])))
+        .mount(&server)
+        .await;
+
+    let service = client(&server).find_service("api").await.unwrap();
+    assert_eq!(service.id, "srv-1");
+}
+
+#[tokio::test]
+async fn find_service_only_looks_in_the_owners_workspace() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/services"))
+        .and(query_param("name", "api"))
+        .and(query_param("ownerId", "tea-cs67ufi3esus73b74a70"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+            { "cursor": "c1", "service": service("srv-1", "api") },
+        ])))
+        .mount(&server)
+        .await;
+
+    let service = client(&server)
+        .with_owner("tea-cs67ufi3esus73b74a70")
+        .find_service("api")
+        .await
+        .unwrap();
+    assert_eq!(service.id, "srv-1");
+}
+
+#[tokio::test]
+async fn find_service_lists_similar_names_when_not_found() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/services"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+            { "cursor": "c1", "service": service("srv-2", "api-staging") },
+        ])))
+        .mount(&server)
+        .await;
+
+    match client(&server).find_service("api").await {
+        Err(RenderError::ServiceNotFound { candidates, .. }) => {
+            assert_eq!(candidates, vec!["api-staging (srv-2)"])
+        }
+        other => panic!("expected not found {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn deploy_history_follows_cursors() {
+    let server = MockServer::start().await;
+    let first_page: Vec<Value> = (0..100)
+        .map(|n| {
+            json!({
+                "cursor": format!("c{}", n),
+                "deploy": deploy(&format!("dep-{}", n), "deactivated"),
+            })
+        })
+        .collect();
+    Mock::given(method("GET"))
+        .and(path("/services/srv-1/deploys"))
+        .and(query_param("cursor", "c99"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+            { "cursor": "c100", "deploy": deploy("dep-100", "deactivated") },
+        ])))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/services/srv-1/deploys"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(first_page))
+        .up_to_n_times(1)
+        .mount(&server)
+        .await;
+
+    let client = client(&server);
+    let deploys: Vec<_> = client.deploy_history("srv-1").try_collect().await.unwrap();
+    assert_eq!(deploys.len(), 101);
+    assert_eq!(deploys[100].id, "dep-100");
+}
+
+#[tokio::test]
+async fn trigger_deploy_sends_the_request() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/services/srv-1/deploys"))
+        .and(body_json(
+            json!({ "commitId": "b2be9cf", "clearCache": "clear" }),
+        ))
+        .respond_with(ResponseTemplate::new(201).set_body_json(deploy("dep-1", "created")))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let request = TriggerDeployRequest {
+        commit_id: Some("b2be9cf".into()),
+        clear_cache: Some(ClearCache::Clear),
+        ..TriggerDeployRequest::default()
+    };
+    let deploy = client(&server)
+        .trigger_deploy("srv-1", &request)
+        .await
+        .unwrap();
+    assert_eq!(deploy.id, "dep-1");
+    assert_eq!(deploy.status, DeployStatus::Created);
+}
+
+#[tokio::test]
+async fn trigger_deploy_is_not_retried_after_a_server_error() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/services/srv-1/deploys"))
+        .respond_with(ResponseTemplate::new(503))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let result = client(&server)
+        .trigger_deploy("srv-1", &TriggerDeployRequest::default())
+        .await;
+    assert!(matches!(result, Err(RenderError::Api { status, .. }) if status == 503));
+}
+
+#[tokio::test]
+async fn deploy_hook_passes_the_commit_as_ref() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/deploy/srv-1"))
+        .and(query_param("key", "secret"))
+        .and(query_param("ref", "b2be9cf"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_json(json!({ "deploy": { "id": "dep-1" } })),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let hook = DeployHook::new(&format!("{}/deploy/srv-1?key=secret", server.uri())).unwrap();
+    let request = TriggerDeployRequest {
+        commit_id: Some("b2be9cf".into()),
+        ..TriggerDeployRequest::default()
+    };
+    assert_eq!(hook.trigger(&request).await.unwrap(), "dep-1");
+}
+
+#[tokio::test]
+async fn wait_for_deploy_follows_the_status_until_live() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/services/srv-1/deploys/dep-1"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_json(deploy("dep-1", "build_in_progress")),
+        )
+        .up_to_n_times(2)
+        .mount(&server)
+        .await;
+    // a flaky poll in the middle doesn't end the wait
+    Mock::given(method("GET"))
+        .and(path("/services/srv-1/deploys/dep-1"))
+        .respond_with(ResponseTemplate::new(502))
+        .up_to_n_times(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/services/srv-1/deploys/dep-1"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_json(deploy("dep-1", "update_in_progress")),
+        )
+        .up_to_n_times(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/services/srv-1/deploys/dep-1"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(deploy("dep-1", "live")))
+        .mount(&server)
+        .await;
+
+    let mut seen = vec![];
+    let deploy = client(&server)
+        .wait_for_deploy(
+            "srv-1",
+            "dep-1",
+            &quick_wait(Duration::from_secs(5)),
+            |deploy| seen.push(deploy.status.clone()),
+        )
+        .await
+        .unwrap();
+    assert_eq!(deploy.status, DeployStatus::Live);
+    assert_eq!(
+        seen,
+        vec![
+            DeployStatus::BuildInProgress,
+            DeployStatus::BuildInProgress,
+            DeployStatus::UpdateInProgress,
+            DeployStatus::Live
+        ]
+    );
+}
+
+#[tokio::test]
+async fn wait_for_deploy_fails_when_the_build_does() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/services/srv-1/deploys/dep-1"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(deploy("dep-1", "build_failed")))
+        .mount(&server)
+        .await;
+
+    let result = client(&server)
+        .wait_for_deploy(
+            "srv-1",
+            "dep-1",
+            &quick_wait(Duration::from_secs(5)),
+            |_| {},
+        )
+        .await;
+    match result {
+        Err(error @ RenderError::DeployFailed(_)) => assert_eq!(error.exit_code(), 2),
+        other => panic!("expected a failed deploy {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn wait_for_deploy_times_out() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/services/srv-1/deploys/dep-1"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_json(deploy("dep-1", "build_in_progress")),
+        )
+        .mount(&server)
+        .await;
+
+    let result = client(&server)
+        .wait_for_deploy(
+            "srv-1",
+            "dep-1",
+            &quick_wait(Duration::from_millis(50)),
+            |_| {},
+        )
+        .await;
+    match result {
+        Err(RenderError::Timeout { deploy_id, .. }) => assert_eq!(deploy_id, "dep-1"),
+        other => panic!("expected a timeout {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn observers_see_requests_and_retries() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/services/srv-1"))
+        .respond_with(ResponseTemplate::new(503))
+        .up_to_n_times(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/services/srv-1"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(service("srv-1", "api")))
+        .mount(&server)
+        .await;
+
+    let seen = Arc::new(Mutex::new(vec![]));
+    let client = client(&server).with_observer({
+        let seen = seen.clone();
+        move |event| {
+            let event = match event {
+                RequestEvent::Sent {
+                    method,
+                    url,
+                    status,
+                    ..
+                } => format!("{} {} {:?}", method, url.path(), status.map(|s| s.as_u16())),
+                RequestEvent::Retrying { attempt, .. } => format!("retry {}", attempt),
+                RequestEvent::Unparseable { .. } => "unparseable".into(),
+            };
+            seen.lock().unwrap().push(event);
+        }
+    });
+    client.get_service("srv-1").await.unwrap();
+    assert_eq!(
+        *seen.lock().unwrap(),
+        [
+            "GET /services/srv-1 Some(503)",
+            "retry 1",
+            "GET /services/srv-1 Some(200)"
+        ]
+    );
+}
+
+#[tokio::test]
+async fn the_api_key_is_redacted_from_debug_output() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/services/srv-1"))
+        .and(header("authorization", "Bearer rnd_test"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(service("srv-1", "api")))
+        .mount(&server)
+        .await;
+
+    let seen = Arc::new(Mutex::new(vec![]));
+    let client = client(&server).with_observer({
+        let seen = seen.clone();
+        move |event| seen.lock().unwrap().push(format!("{:?}", event))
+    });
+    client.get_service("srv-1").await.unwrap();
+    let seen = seen.lock().unwrap();
+    assert!(seen[0].contains("authorization"));
+    for debug in seen.iter().chain([&format!("{:?}", client)]) {
+        assert!(!debug.contains("rnd_test"), "{}", debug);
+    }
+}
+
+#[tokio::test]
+async fn api_errors_keep_the_response_body() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/services/srv-missing"))
+        .respond_with(ResponseTemplate::new(404).set_body_string(r#"{"message":"not found"}"#))
+        .mount(&server)
+        .await;
+
+    match client(&server).get_service("srv-missing").await {
+        Err(RenderError::Api { status, body, .. }) => {
+            assert_eq!(status, 404);
+            assert_eq!(body, r#"{"message":"not found"}"#);
+        }
+        other => panic!("expected an api error {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn unexpected_json_keeps_the_response_body() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/services/srv-1"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"id":"srv-1"}"#))
+        .mount(&server)
+        .await;
+
+    match client(&server).get_service("srv-1").await {
+        Err(RenderError::Json { body, .. }) => assert_eq!(body, r#"{"id":"srv-1"}"#),
+        other => panic!("expected a json error {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn env_vars_are_set_and_deleted_one_at_a_time() {
+    let server = MockServer::start().await;
+    Mock::given(method("PUT"))
+        .and(path("/services/srv-1/env-vars/NODE_ENV"))
+        .and(body_json(json!({ "value": "production" })))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(json!({ "key": "NODE_ENV", "value": "production" })),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("DELETE"))
+        .and(path("/services/srv-1/env-vars/DEBUG"))
+        .respond_with(ResponseTemplate::new(204))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let client = client(&server);
+    let env_var = client
+        .set_env_var("srv-1", "NODE_ENV", "production")
+        .await
+        .unwrap();
+    assert_eq!(env_var.value, "production");
+    client.delete_env_var("srv-1", "DEBUG").await.unwrap();
+}
+
+#[tokio::test]
+async fn wait_for_restart_waits_for_an_available_server() {
+    let server = MockServer::start().await;
+    let event = |id: &str, event_type: &str| {
+        json!({
+            "cursor": id,
+            "event": {
+                "id": id,
+                "timestamp": "2024-10-14T02:17:40Z",
+                "serviceId": "srv-1",
+                "type": event_type,
+                "details": {}
+            }
+        })
+    };
+    Mock::given(method("GET"))
+        .and(path("/services/srv-1/events"))
+        .and(query_param("startTime", "2024-10-14T02:17:35Z"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_json(json!([event("evt-1", "server_restarted")])),
+        )
+        .up_to_n_times(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/services/srv-1/events"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+            event("evt-2", "server_available"),
+            event("evt-1", "server_restarted"),
+        ])))
+        .mount(&server)
+        .await;
+
+    let event = client(&server)
+        .wait_for_restart(
+            "srv-1",
+            "2024-10-14T02:17:35Z",
+            &quick_wait(Duration::from_secs(5)),
+        )
+        .await
+        .unwrap();
+    assert_eq!(event.id, "evt-2");
+}
+
+#[tokio::test]
+async fn wait_for_job_fails_when_the_job_does() {
+    let server = MockServer::start().await;
+    let job = |status: &str| {
+        json!({
+            "id": "job-1",
+            "serviceId": "srv-1",
+            "startCommand": "rake db:migrate",
+            "planId": "plan-srv-006",
+            "status": status,
+            "createdAt": "2024-10-14T02:17:35Z",
+            "startedAt": "2024-10-14T02:17:40Z",
+            "finishedAt": null
+        })
+    };
+    Mock::given(method("GET"))
+        .and(path("/services/srv-1/jobs/job-1"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(job("running")))
+        .up_to_n_times(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/services/srv-1/jobs/job-1"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(job("failed")))
+        .mount(&server)
+        .await;
+
+    let result = client(&server)
+        .wait_for_job(
+            "srv-1",
+            "job-1",
+            &quick_wait(Duration::from_secs(5)),
+            |_| {},
+        )
+        .await;
+    match result {
+        Err(error @ RenderError::JobFailed(_)) => assert_eq!(error.exit_code(), 2),
+        other => panic!("expected a failed job {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn tail_logs_keeps_lines_from_a_poll_that_failed() {
+    let server = MockServer::start().await;
+    let log = |id: &str, timestamp: &str| json!({ "id": id, "message": id, "timestamp": timestamp, "labels": [] });
+    // the second page fails for longer than the client retries
+    Mock::given(method("GET"))
+        .and(path("/logs"))
+        .and(query_param("startTime", "2024-10-14T02:17:42Z"))
+        .respond_with(ResponseTemplate::new(503))
+        .up_to_n_times(3)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/logs"))
+        .and(query_param("startTime", "2024-10-14T02:17:42Z"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "hasMore": false,
+            "logs": [log("l2", "2024-10-14T02:17:42Z")],
+        })))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/logs"))
+        .and(query_param("startTime", "2024-10-14T02:17:40Z"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "hasMore": true,
+            "nextStartTime": "2024-10-14T02:17:42Z",
+            "logs": [log("l1", "2024-10-14T02:17:41Z")],
+        })))
+        .mount(&server)
+        .await;
+
+    let client = client(&server);
+    let mut tail = client.tail_logs(LogsQuery {
+        owner_id: "tea-1".into(),
+        start_time: Some("2024-10-14T02:17:40Z".into()),
+        ..LogsQuery::default()
+    });
+    assert!(tail.poll().await.is_err());
+    let ids: Vec<String> = tail
+        .poll()
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|entry| entry.id)
+        .collect();
+    assert_eq!(ids, ["l1", "l2"]);
+}